@@ -30,6 +30,9 @@ pub struct Config {
     pub peers: PeersConfig,
     /// Configuration for peer sessions.
     pub sessions: SessionsConfig,
+    /// Configuration for serving requests of peers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub network: NetworkConfig,
     /// Configuration for static files.
     #[cfg_attr(feature = "serde", serde(default))]
     pub static_files: StaticFilesConfig,
@@ -424,6 +427,26 @@ pub struct StaticFilesConfig {
     pub blocks_per_file: BlocksPerFileConfig,
 }
 
+/// Limits for serving `eth` requests of peers.
+///
+/// Command line arguments take precedence, unset limits fall back to the defaults.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NetworkConfig {
+    /// Max number of headers to serve in one response.
+    pub max_headers_serve: Option<usize>,
+    /// Max number of block bodies to serve in one response.
+    pub max_bodies_serve: Option<usize>,
+    /// Max number of blocks to serve receipts of in one response.
+    pub max_receipts_serve: Option<usize>,
+    /// Max accumulated byte size of headers, bodies, receipts or node data to serve in one
+    /// response.
+    pub eth_response_soft_limit: Option<usize>,
+    /// Max number of response bytes to serve to a single peer per second.
+    pub max_serve_bytes_per_peer: Option<usize>,
+}
+
 /// Configuration for the number of blocks per file for each segment.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -1144,6 +1167,25 @@ connect_trusted_nodes_only = true
         assert!(conf.peers.trusted_nodes_only);
     }
 
+    #[test]
+    fn test_network_section() {
+        let s = r"#
+[network]
+max_headers_serve = 128
+eth_response_soft_limit = 65536
+max_serve_bytes_per_peer = 1048576
+#";
+        let conf: Config = toml::from_str(s).unwrap();
+        assert_eq!(conf.network.max_headers_serve, Some(128));
+        assert_eq!(conf.network.max_bodies_serve, None);
+        assert_eq!(conf.network.eth_response_soft_limit, Some(65536));
+        assert_eq!(conf.network.max_serve_bytes_per_peer, Some(1048576));
+
+        // unset limits aren't written
+        let s = toml::to_string(&Config::default()).unwrap();
+        assert!(!s.contains("max_headers_serve"), "{s}");
+    }
+
     #[test]
     fn test_can_support_dns_in_trusted_nodes() {
         let reth_toml = r#"
@@ -64,11 +64,7 @@ impl<T: PayloadTypes> PayloadTestContext<T> {
                 continue
             }
             // Resolve payload once its built
-            self.payload_builder
-                .resolve_kind(payload_id, PayloadKind::Earliest)
-                .await
-                .unwrap()
-                .unwrap();
+            self.payload_builder.try_resolve_kind(payload_id, PayloadKind::Earliest).await.unwrap();
             break;
         }
     }
@@ -163,6 +163,10 @@ impl EthBuiltPayload {
 impl<N: NodePrimitives> BuiltPayload for EthBuiltPayload<N> {
     type Primitives = N;
 
+    fn payload_id(&self) -> Option<PayloadId> {
+        Some(self.id)
+    }
+
     fn block(&self) -> &SealedBlock<N::Block> {
         &self.block
     }
@@ -112,6 +112,13 @@ impl BanList {
         self.banned_peers.contains_key(peer_id)
     }
 
+    /// Returns the timestamp until which the peer is banned.
+    ///
+    /// Returns `None` if the peer is not banned or banned indefinitely.
+    pub fn banned_peer_until(&self, peer_id: &PeerId) -> Option<Instant> {
+        self.banned_peers.get(peer_id).copied().flatten()
+    }
+
     /// Unbans the ip address
     pub fn unban_ip(&mut self, ip: &IpAddr) {
         self.banned_ips.remove(ip);
@@ -165,6 +172,13 @@ impl BanList {
             self.banned_ips.insert(ip, until);
         }
     }
+
+    /// Bans the ip indefinitely or until the given timeout, even if it's not a global IP.
+    ///
+    /// If the IP is already banned, the timeout will be updated to the new value.
+    pub fn force_ban_ip_with(&mut self, ip: IpAddr, until: Option<Instant>) {
+        self.banned_ips.insert(ip, until);
+    }
 }
 
 #[cfg(test)]
@@ -414,6 +414,14 @@ impl Discv4 {
         Ok(rx.await?)
     }
 
+    /// Returns a snapshot of all nodes currently in the routing table of the [`Discv4Service`].
+    pub async fn table_entries(&self) -> Result<Vec<TableEntry>, Discv4Error> {
+        let (tx, rx) = oneshot::channel();
+        let cmd = Discv4Command::TableEntries(tx);
+        self.to_service.send(cmd)?;
+        Ok(rx.await?)
+    }
+
     /// Terminates the spawned [`Discv4Service`].
     pub fn terminate(&self) {
         self.send_to_service(Discv4Command::Terminated);
@@ -679,6 +687,11 @@ impl Discv4Service {
         self.kbuckets.get_index(&key).is_some()
     }
 
+    /// Returns a snapshot of all nodes currently in the routing table.
+    pub fn table_entries(&self) -> Vec<TableEntry> {
+        self.kbuckets.iter_ref().map(|entry| TableEntry::from(entry.node.value)).collect()
+    }
+
     /// Bootstraps the local node to join the DHT.
     ///
     /// Bootstrapping is a multi-step operation that starts with a lookup of the local node's
@@ -1742,6 +1755,9 @@ impl Discv4Service {
                         let rx = self.update_stream();
                         let _ = tx.send(rx);
                     }
+                    Discv4Command::TableEntries(tx) => {
+                        let _ = tx.send(self.table_entries());
+                    }
                     Discv4Command::BanPeer(node_id) => self.ban_node(node_id),
                     Discv4Command::Remove(node_id) => {
                         self.remove_node(node_id);
@@ -2056,6 +2072,7 @@ enum Discv4Command {
     Lookup { node_id: Option<PeerId>, tx: Option<NodeRecordSender> },
     SetLookupInterval(Duration),
     Updates(OneshotSender<ReceiverStream<DiscoveryUpdate>>),
+    TableEntries(OneshotSender<Vec<TableEntry>>),
     Terminated,
 }
 
@@ -2296,6 +2313,33 @@ struct EnrRequestState {
     echo_hash: B256,
 }
 
+/// A snapshot of a node in the routing table of the [`Discv4Service`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TableEntry {
+    /// The node's record.
+    pub record: NodeRecord,
+    /// Timestamp of the last message received from the node.
+    pub last_seen: Instant,
+    /// The last ENR sequence number the node advertised.
+    pub enr_seq: Option<u64>,
+    /// The [`ForkId`] the node advertised in its ENR, if it was requested.
+    pub fork_id: Option<ForkId>,
+    /// Whether the node's endpoint is proven.
+    pub has_endpoint_proof: bool,
+}
+
+impl From<&NodeEntry> for TableEntry {
+    fn from(entry: &NodeEntry) -> Self {
+        Self {
+            record: entry.record,
+            last_seen: entry.last_seen,
+            enr_seq: entry.last_enr_seq,
+            fork_id: entry.fork_id,
+            has_endpoint_proof: entry.has_endpoint_proof,
+        }
+    }
+}
+
 /// Stored node info.
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct NodeEntry {
@@ -2815,6 +2859,26 @@ mod tests {
         let _ = discv4.lookup_self().await;
     }
 
+    #[tokio::test]
+    async fn test_table_entries() {
+        reth_tracing::init_test_tracing();
+
+        let config = Discv4Config::builder().build();
+        let (discv4, service) = create_discv4_with_config(config).await;
+        let _handle = service.spawn();
+
+        let record = rng_record(&mut rand_08::thread_rng());
+        discv4.add_node(record);
+
+        let entries = discv4.table_entries().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.record, record);
+        assert_eq!(entry.enr_seq, None);
+        assert_eq!(entry.fork_id, None);
+        assert!(!entry.has_endpoint_proof);
+    }
+
     #[tokio::test]
     async fn test_requests_timeout() {
         reth_tracing::init_test_tracing();
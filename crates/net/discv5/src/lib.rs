@@ -170,6 +170,25 @@ impl Discv5 {
         self.local_node_record.udp_port
     }
 
+    /// Returns a snapshot of all reachable nodes in the kbuckets.
+    ///
+    /// Each node is returned with the sequence number of its ENR and the [`ForkId`] it advertises,
+    /// if any.
+    pub fn table_entries(&self) -> Vec<(NodeRecord, u64, Option<ForkId>)> {
+        self.discv5
+            .table_entries_enr()
+            .into_iter()
+            .filter_map(|enr| {
+                let socket = match self.rlpx_ip_mode {
+                    IpMode::Ip6 => enr.udp6_socket().map(SocketAddr::V6),
+                    _ => enr.udp4_socket().map(SocketAddr::V4),
+                }?;
+                let node_record = self.try_into_reachable(&enr, socket).ok()?;
+                Some((node_record, enr.seq(), self.get_fork_id(&enr).ok()))
+            })
+            .collect()
+    }
+
     /// Spawns [`discv5::Discv5`]. Returns [`discv5::Discv5`] handle in reth compatible wrapper type
     /// [`Discv5`], a receiver of [`discv5::Event`]s from the underlying node, and the local
     /// [`Enr`](discv5::Enr) converted into the reth compatible [`NodeRecord`] type.
@@ -12,8 +12,10 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+pub mod natpmp;
 pub mod net_if;
 
+pub use natpmp::{NatPmpClient, NatPmpError, NatPmpPortMapper, UdpNatPmpClient};
 pub use net_if::{NetInterfaceError, DEFAULT_NET_IF_NAME};
 
 use std::{
@@ -46,6 +48,12 @@ pub enum NatResolver {
     Any,
     /// Resolve external IP via `UPnP`.
     Upnp,
+    /// Resolve external IP via NAT-PMP and map the node's ports on the gateway.
+    ///
+    /// Falls back to resolving the external IP via a network request if the gateway doesn't
+    /// support NAT-PMP. The gateway is only discovered on Linux, so the ports aren't mapped on
+    /// other platforms.
+    NatPmp,
     /// Resolve external IP via a network request.
     PublicIp,
     /// Use the given [`IpAddr`]
@@ -91,6 +99,7 @@ impl fmt::Display for NatResolver {
         match self {
             Self::Any => f.write_str("any"),
             Self::Upnp => f.write_str("upnp"),
+            Self::NatPmp => f.write_str("natpmp"),
             Self::PublicIp => f.write_str("publicip"),
             Self::ExternalIp(ip) => write!(f, "extip:{ip}"),
             Self::ExternalAddr(domain) => write!(f, "extaddr:{domain}"),
@@ -118,6 +127,7 @@ impl FromStr for NatResolver {
         let r = match s {
             "any" => Self::Any,
             "upnp" => Self::Upnp,
+            "natpmp" | "nat-pmp" => Self::NatPmp,
             "none" => Self::None,
             "publicip" | "public-ip" => Self::PublicIp,
             "netif" => Self::NetIf,
@@ -222,6 +232,10 @@ pub async fn external_ip() -> Option<IpAddr> {
 pub async fn external_addr_with(resolver: NatResolver) -> Option<IpAddr> {
     match resolver {
         NatResolver::Any | NatResolver::Upnp | NatResolver::PublicIp => resolve_external_ip().await,
+        NatResolver::NatPmp => match resolve_natpmp_ip().await {
+            Some(ip) => Some(ip),
+            None => resolve_external_ip().await,
+        },
         NatResolver::ExternalIp(ip) => Some(ip),
         NatResolver::NetIf => resolve_net_if_ip(DEFAULT_NET_IF_NAME)
             .inspect_err(|err| {
@@ -252,6 +266,23 @@ async fn resolve_external_ip() -> Option<IpAddr> {
         .map(|(ip, _)| ip)
 }
 
+async fn resolve_natpmp_ip() -> Option<IpAddr> {
+    let client = UdpNatPmpClient::from_default_gateway()
+        .inspect_err(|err| debug!(target: "net::nat", %err, "Failed to find NAT-PMP gateway"))
+        .ok()?;
+    client
+        .external_address()
+        .await
+        .inspect_err(|err| {
+            debug!(target: "net::nat",
+                %err,
+                gateway=%client.gateway(),
+                "Failed to resolve external IP via NAT-PMP");
+        })
+        .ok()
+        .map(IpAddr::V4)
+}
+
 async fn resolve_external_ip_url_res(url: &str) -> Result<IpAddr, ()> {
     resolve_external_ip_url(url).await.ok_or(())
 }
@@ -305,6 +336,9 @@ mod tests {
     fn test_from_str() {
         assert_eq!(NatResolver::Any, "any".parse().unwrap());
         assert_eq!(NatResolver::None, "none".parse().unwrap());
+        assert_eq!(NatResolver::NatPmp, "natpmp".parse().unwrap());
+        assert_eq!(NatResolver::NatPmp, "nat-pmp".parse().unwrap());
+        assert_eq!(NatResolver::NatPmp.to_string(), "natpmp");
 
         let ip = NatResolver::ExternalIp(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
         let s = "extip:0.0.0.0";
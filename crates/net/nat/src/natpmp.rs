@@ -0,0 +1,490 @@
+//! Port mapping via NAT-PMP.
+//!
+//! See also [RFC 6886](https://datatracker.ietf.org/doc/html/rfc6886)
+
+use futures_util::future::{self, Either};
+use std::{
+    future::Future,
+    io,
+    net::{Ipv4Addr, SocketAddrV4},
+    time::Duration,
+};
+use tokio::net::UdpSocket;
+use tracing::{debug, trace};
+
+/// The port NAT-PMP gateways listen on.
+pub const NAT_PMP_PORT: u16 = 5351;
+
+/// The lifetime requested for port mappings, as recommended by the RFC.
+pub const DEFAULT_MAPPING_LIFETIME: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// How long to wait before retrying after the port mappings could not be created.
+pub const MAPPING_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The NAT-PMP protocol version.
+const VERSION: u8 = 0;
+
+/// Opcode of the external address request.
+const OP_EXTERNAL_ADDRESS: u8 = 0;
+
+/// Offset added to the request opcode in responses.
+const RESPONSE_OPCODE_OFFSET: u8 = 128;
+
+/// The delay before the first retransmission of a request, doubled after every attempt.
+const INITIAL_RETRANSMISSION_DELAY: Duration = Duration::from_millis(250);
+
+/// Number of times a request is sent before giving up.
+const MAX_REQUEST_ATTEMPTS: u32 = 4;
+
+/// Errors that can occur when talking to a NAT-PMP gateway.
+#[derive(Debug, thiserror::Error)]
+pub enum NatPmpError {
+    /// No default gateway could be found.
+    #[error("no default gateway found")]
+    NoGateway,
+    /// Failed to send or receive a request.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The gateway didn't respond.
+    #[error("NAT-PMP request timed out")]
+    Timeout,
+    /// The gateway sent a malformed response.
+    #[error("invalid NAT-PMP response")]
+    InvalidResponse,
+    /// The gateway rejected the request.
+    #[error("NAT-PMP request failed with result code {0}")]
+    ResultCode(u16),
+}
+
+/// The transport protocol of a port mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MappingProtocol {
+    /// Map a UDP port, used for discovery.
+    Udp,
+    /// Map a TCP port, used for `RLPx` sessions.
+    Tcp,
+}
+
+impl MappingProtocol {
+    /// Returns the opcode of the mapping request for this protocol.
+    const fn opcode(&self) -> u8 {
+        match self {
+            Self::Udp => 1,
+            Self::Tcp => 2,
+        }
+    }
+}
+
+/// A port mapping granted by the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortMapping {
+    /// The protocol of the mapping.
+    pub protocol: MappingProtocol,
+    /// The local port.
+    pub internal_port: u16,
+    /// The port on the gateway's external address that is forwarded to the local port.
+    pub external_port: u16,
+    /// How long the mapping is valid for.
+    pub lifetime: Duration,
+}
+
+/// The interaction with a NAT-PMP gateway.
+pub trait NatPmpClient: Send + Sync {
+    /// Requests the external address of the gateway.
+    fn external_address(&self) -> impl Future<Output = Result<Ipv4Addr, NatPmpError>> + Send;
+
+    /// Requests a mapping of the given local port for the given lifetime.
+    ///
+    /// Requesting an existing mapping again renews it.
+    fn map_port(
+        &self,
+        protocol: MappingProtocol,
+        internal_port: u16,
+        lifetime: Duration,
+    ) -> impl Future<Output = Result<PortMapping, NatPmpError>> + Send;
+}
+
+/// A [`NatPmpClient`] that talks to the gateway over UDP.
+#[derive(Debug, Clone, Copy)]
+pub struct UdpNatPmpClient {
+    gateway: SocketAddrV4,
+}
+
+impl UdpNatPmpClient {
+    /// Creates a new client for the given gateway.
+    pub const fn new(gateway: Ipv4Addr) -> Self {
+        Self { gateway: SocketAddrV4::new(gateway, NAT_PMP_PORT) }
+    }
+
+    /// Creates a new client for the default gateway of this host.
+    pub fn from_default_gateway() -> Result<Self, NatPmpError> {
+        default_gateway().map(Self::new).ok_or(NatPmpError::NoGateway)
+    }
+
+    /// Returns the address of the gateway.
+    pub const fn gateway(&self) -> SocketAddrV4 {
+        self.gateway
+    }
+
+    /// Sends the request to the gateway and returns the response with the expected opcode.
+    ///
+    /// The request is retransmitted with an exponential backoff until a response is received.
+    async fn request(&self, request: &[u8], opcode: u8) -> Result<Vec<u8>, NatPmpError> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        socket.connect(self.gateway).await?;
+
+        let mut delay = INITIAL_RETRANSMISSION_DELAY;
+        let mut buf = [0u8; 16];
+        for _ in 0..MAX_REQUEST_ATTEMPTS {
+            socket.send(request).await?;
+            let Ok(res) = tokio::time::timeout(delay, socket.recv(&mut buf)).await else {
+                trace!(target: "net::nat", gateway=%self.gateway, ?delay, "NAT-PMP request timed out, retrying");
+                delay *= 2;
+                continue
+            };
+            let response = &buf[..res?];
+            check_response(response, opcode)?;
+            return Ok(response.to_vec())
+        }
+
+        Err(NatPmpError::Timeout)
+    }
+}
+
+impl NatPmpClient for UdpNatPmpClient {
+    async fn external_address(&self) -> Result<Ipv4Addr, NatPmpError> {
+        let response = self.request(&[VERSION, OP_EXTERNAL_ADDRESS], OP_EXTERNAL_ADDRESS).await?;
+        decode_external_address(&response)
+    }
+
+    async fn map_port(
+        &self,
+        protocol: MappingProtocol,
+        internal_port: u16,
+        lifetime: Duration,
+    ) -> Result<PortMapping, NatPmpError> {
+        let request = encode_mapping_request(protocol, internal_port, lifetime);
+        let response = self.request(&request, protocol.opcode()).await?;
+        decode_mapping(protocol, &response)
+    }
+}
+
+/// Checks the header of a response to a request with the given opcode.
+fn check_response(response: &[u8], opcode: u8) -> Result<(), NatPmpError> {
+    if response.len() < 4 ||
+        response[0] != VERSION ||
+        response[1] != RESPONSE_OPCODE_OFFSET + opcode
+    {
+        return Err(NatPmpError::InvalidResponse)
+    }
+    match u16::from_be_bytes([response[2], response[3]]) {
+        0 => Ok(()),
+        code => Err(NatPmpError::ResultCode(code)),
+    }
+}
+
+/// Decodes the external address from the response to an external address request.
+fn decode_external_address(response: &[u8]) -> Result<Ipv4Addr, NatPmpError> {
+    let octets: [u8; 4] =
+        response.get(8..12).and_then(|b| b.try_into().ok()).ok_or(NatPmpError::InvalidResponse)?;
+    Ok(Ipv4Addr::from(octets))
+}
+
+/// Encodes a request to map the given port.
+///
+/// A lifetime of zero requests the deletion of the mapping.
+fn encode_mapping_request(protocol: MappingProtocol, port: u16, lifetime: Duration) -> [u8; 12] {
+    let lifetime = u32::try_from(lifetime.as_secs()).unwrap_or(u32::MAX);
+    let mut request = [0u8; 12];
+    request[0] = VERSION;
+    request[1] = protocol.opcode();
+    request[4..6].copy_from_slice(&port.to_be_bytes());
+    // suggest the same external port, deletions must not suggest a port
+    if lifetime != 0 {
+        request[6..8].copy_from_slice(&port.to_be_bytes());
+    }
+    request[8..12].copy_from_slice(&lifetime.to_be_bytes());
+    request
+}
+
+/// Decodes the granted mapping from the response to a mapping request.
+fn decode_mapping(protocol: MappingProtocol, response: &[u8]) -> Result<PortMapping, NatPmpError> {
+    if response.len() < 16 {
+        return Err(NatPmpError::InvalidResponse)
+    }
+    Ok(PortMapping {
+        protocol,
+        internal_port: u16::from_be_bytes([response[8], response[9]]),
+        external_port: u16::from_be_bytes([response[10], response[11]]),
+        lifetime: Duration::from_secs(u64::from(u32::from_be_bytes([
+            response[12],
+            response[13],
+            response[14],
+            response[15],
+        ]))),
+    })
+}
+
+/// Returns the default gateway of this host, if it can be determined.
+///
+/// The gateway is read from `/proc/net/route`, so this always returns `None` on other platforms
+/// than Linux.
+pub fn default_gateway() -> Option<Ipv4Addr> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/net/route")
+            .ok()
+            .and_then(|routes| parse_default_gateway(&routes))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Parses the default gateway from the contents of `/proc/net/route`.
+#[cfg_attr(not(any(test, target_os = "linux")), expect(dead_code))]
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let destination = fields.nth(1)?;
+        let gateway = fields.next()?;
+        if destination != "00000000" {
+            return None
+        }
+        // addresses are printed as hex of the address in network byte order read as native u32
+        let gateway = u32::from_str_radix(gateway, 16).ok()?;
+        Some(Ipv4Addr::from(gateway.to_ne_bytes())).filter(|ip| !ip.is_unspecified())
+    })
+}
+
+/// Maps the ports of the node on the gateway and keeps renewing the mappings.
+#[derive(Debug)]
+pub struct NatPmpPortMapper<C> {
+    /// The client used to talk to the gateway.
+    client: C,
+    /// The ports to map.
+    ports: Vec<(MappingProtocol, u16)>,
+    /// The lifetime requested for the mappings.
+    lifetime: Duration,
+}
+
+impl<C: NatPmpClient> NatPmpPortMapper<C> {
+    /// Creates a new mapper for the given `RLPx` (TCP) and discovery (UDP) ports.
+    pub fn new(client: C, tcp_port: u16, udp_port: u16) -> Self {
+        Self {
+            client,
+            ports: vec![(MappingProtocol::Tcp, tcp_port), (MappingProtocol::Udp, udp_port)],
+            lifetime: DEFAULT_MAPPING_LIFETIME,
+        }
+    }
+
+    /// Sets the lifetime requested for the mappings.
+    pub const fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Requests all port mappings.
+    ///
+    /// Returns the mappings granted by the gateway.
+    pub async fn map_ports(&self) -> Result<Vec<PortMapping>, NatPmpError> {
+        let mut mappings = Vec::with_capacity(self.ports.len());
+        for (protocol, port) in &self.ports {
+            mappings.push(self.client.map_port(*protocol, *port, self.lifetime).await?);
+        }
+        Ok(mappings)
+    }
+
+    /// Creates or renews all port mappings.
+    ///
+    /// Returns how long to wait until the mappings should be renewed. This is half the shortest
+    /// granted lifetime as recommended by the RFC, or [`MAPPING_RETRY_INTERVAL`] if the mappings
+    /// could not be created.
+    pub async fn renew(&self) -> Duration {
+        match self.map_ports().await {
+            Ok(mappings) => {
+                debug!(target: "net::nat", ?mappings, "Created NAT-PMP port mappings");
+                mappings
+                    .iter()
+                    .map(|mapping| mapping.lifetime / 2)
+                    .min()
+                    .unwrap_or(self.lifetime / 2)
+                    .max(Duration::from_secs(1))
+            }
+            Err(err) => {
+                debug!(target: "net::nat", %err, "Failed to create NAT-PMP port mappings");
+                MAPPING_RETRY_INTERVAL
+            }
+        }
+    }
+
+    /// Releases all port mappings by requesting a lifetime of zero.
+    pub async fn release(&self) {
+        for (protocol, port) in &self.ports {
+            if let Err(err) = self.client.map_port(*protocol, *port, Duration::ZERO).await {
+                debug!(target: "net::nat", %err, ?protocol, port, "Failed to release NAT-PMP port mapping");
+            }
+        }
+    }
+
+    /// Keeps renewing the port mappings for as long as the returned future is polled.
+    pub async fn run(self) {
+        self.run_until(future::pending::<()>()).await
+    }
+
+    /// Keeps renewing the port mappings until the given shutdown future resolves, then releases
+    /// them.
+    pub async fn run_until<F: Future>(self, shutdown: F) {
+        let mut shutdown = std::pin::pin!(shutdown);
+        loop {
+            let renew = std::pin::pin!(async {
+                let renew_in = self.renew().await;
+                tokio::time::sleep(renew_in).await;
+            });
+            if let Either::Right(_) = future::select(renew, shutdown.as_mut()).await {
+                break
+            }
+        }
+        debug!(target: "net::nat", "Releasing NAT-PMP port mappings");
+        self.release().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// A gateway that grants every mapping with half the requested lifetime.
+    #[derive(Debug, Default, Clone)]
+    struct MockClient {
+        fail: Arc<AtomicBool>,
+        requests: Arc<AtomicUsize>,
+        released: Arc<AtomicUsize>,
+    }
+
+    impl NatPmpClient for MockClient {
+        async fn external_address(&self) -> Result<Ipv4Addr, NatPmpError> {
+            Ok(Ipv4Addr::new(203, 0, 113, 1))
+        }
+
+        async fn map_port(
+            &self,
+            protocol: MappingProtocol,
+            internal_port: u16,
+            lifetime: Duration,
+        ) -> Result<PortMapping, NatPmpError> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            if lifetime.is_zero() {
+                self.released.fetch_add(1, Ordering::Relaxed);
+            }
+            if self.fail.load(Ordering::Relaxed) {
+                return Err(NatPmpError::Timeout)
+            }
+            Ok(PortMapping {
+                protocol,
+                internal_port,
+                external_port: internal_port,
+                lifetime: lifetime / 2,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn renew_mappings() {
+        let client = MockClient::default();
+        let mapper = NatPmpPortMapper::new(client.clone(), 30303, 30304)
+            .with_lifetime(Duration::from_secs(3600));
+
+        let mappings = mapper.map_ports().await.unwrap();
+        assert_eq!(
+            mappings.iter().map(|m| (m.protocol, m.external_port)).collect::<Vec<_>>(),
+            vec![(MappingProtocol::Tcp, 30303), (MappingProtocol::Udp, 30304)]
+        );
+
+        // renewed after half of the granted lifetime
+        assert_eq!(mapper.renew().await, Duration::from_secs(900));
+        assert_eq!(client.requests.load(Ordering::Relaxed), 4);
+    }
+
+    #[tokio::test]
+    async fn retry_failed_mappings() {
+        let client = MockClient::default();
+        let mapper = NatPmpPortMapper::new(client.clone(), 30303, 30303);
+
+        client.fail.store(true, Ordering::Relaxed);
+        assert_eq!(mapper.renew().await, MAPPING_RETRY_INTERVAL);
+
+        client.fail.store(false, Ordering::Relaxed);
+        assert_eq!(mapper.renew().await, DEFAULT_MAPPING_LIFETIME / 4);
+    }
+
+    #[tokio::test]
+    async fn release_mappings_on_shutdown() {
+        let client = MockClient::default();
+        let mapper = NatPmpPortMapper::new(client.clone(), 30303, 30304);
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(mapper.run_until(rx));
+        tokio::task::yield_now().await;
+        assert_eq!(client.released.load(Ordering::Relaxed), 0);
+
+        drop(tx);
+        task.await.unwrap();
+        assert_eq!(client.requests.load(Ordering::Relaxed), 4);
+        assert_eq!(client.released.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn encode_decode_mapping() {
+        let request =
+            encode_mapping_request(MappingProtocol::Tcp, 30303, Duration::from_secs(7200));
+        assert_eq!(request, [0, 2, 0, 0, 0x76, 0x5f, 0x76, 0x5f, 0, 0, 0x1c, 0x20]);
+
+        // deletion
+        let request = encode_mapping_request(MappingProtocol::Udp, 30303, Duration::ZERO);
+        assert_eq!(request, [0, 1, 0, 0, 0x76, 0x5f, 0, 0, 0, 0, 0, 0]);
+
+        let response = [0, 130, 0, 0, 0, 0, 0, 1, 0x76, 0x5f, 0x76, 0x60, 0, 0, 0x0e, 0x10];
+        check_response(&response, MappingProtocol::Tcp.opcode()).unwrap();
+        assert_eq!(
+            decode_mapping(MappingProtocol::Tcp, &response).unwrap(),
+            PortMapping {
+                protocol: MappingProtocol::Tcp,
+                internal_port: 30303,
+                external_port: 30304,
+                lifetime: Duration::from_secs(3600),
+            }
+        );
+    }
+
+    #[test]
+    fn decode_external_address_response() {
+        let response = [0, 128, 0, 0, 0, 0, 0, 1, 203, 0, 113, 1];
+        check_response(&response, OP_EXTERNAL_ADDRESS).unwrap();
+        assert_eq!(decode_external_address(&response).unwrap(), Ipv4Addr::new(203, 0, 113, 1));
+
+        // unsupported opcode
+        let response = [0, 128, 0, 5, 0, 0, 0, 1, 0, 0, 0, 0];
+        assert!(matches!(
+            check_response(&response, OP_EXTERNAL_ADDRESS),
+            Err(NatPmpError::ResultCode(5))
+        ));
+        assert!(matches!(check_response(&response, 1), Err(NatPmpError::InvalidResponse)));
+    }
+
+    #[test]
+    fn parse_proc_net_route() {
+        let routes =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+                      eth0\t0000A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+                      eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n";
+        let expected = Ipv4Addr::from(0x0101A8C0u32.to_ne_bytes());
+        assert_eq!(parse_default_gateway(routes), Some(expected));
+        assert_eq!(parse_default_gateway("Iface\tDestination\tGateway\n"), None);
+    }
+}
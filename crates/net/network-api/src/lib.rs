@@ -35,12 +35,18 @@ pub use events::{
 };
 
 use reth_eth_wire_types::{
-    capability::Capabilities, Capability, DisconnectReason, EthVersion, NetworkPrimitives,
-    UnifiedStatus,
+    capability::Capabilities, BlockRangeUpdate, Capability, DisconnectReason, EthVersion,
+    NetworkPrimitives, UnifiedStatus,
 };
+use reth_ethereum_forks::ForkId;
 use reth_network_p2p::sync::NetworkSyncUpdater;
 use reth_network_peers::NodeRecord;
-use std::{future::Future, net::SocketAddr, sync::Arc, time::Instant};
+use std::{
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 /// The `PeerId` type.
 pub type PeerId = alloy_primitives::B512;
@@ -189,6 +195,18 @@ pub trait Peers: PeersInfo {
         peer_ids: Vec<PeerId>,
     ) -> impl Future<Output = Result<Vec<PeerInfo>, NetworkError>> + Send;
 
+    /// Returns a snapshot of the nodes known to the running discovery services.
+    ///
+    /// This includes the routing tables of discv4 and discv5 and the nodes resolved via DNS
+    /// discovery.
+    ///
+    /// The default implementation returns no nodes.
+    fn discovery_nodes(
+        &self,
+    ) -> impl Future<Output = Result<Vec<DiscoveryTableEntry>, NetworkError>> + Send {
+        async { Ok(Vec::new()) }
+    }
+
     /// Removes a peer from the peer set that corresponds to given kind.
     fn remove_peer(&self, peer: PeerId, kind: PeerKind);
 
@@ -198,6 +216,26 @@ pub trait Peers: PeersInfo {
     /// Disconnect an existing connection to the given peer using the provided reason
     fn disconnect_peer_with_reason(&self, peer: PeerId, reason: DisconnectReason);
 
+    /// Toggles whether only trusted peers are connected to or accepted.
+    ///
+    /// While enabled, sessions with non-trusted peers are disconnected, non-trusted peers are no
+    /// longer dialed and incoming connections from them are rejected. Discovery keeps running, so
+    /// the known peers are still available once this is disabled again.
+    fn set_trusted_only(&self, _trusted_only: bool) {}
+
+    /// Bans the given IP indefinitely or for the given duration.
+    ///
+    /// All sessions from the IP are disconnected, incoming connections from it are rejected and
+    /// peers with that IP are no longer dialed.
+    ///
+    /// Does nothing by default.
+    fn ban_ip(&self, _ip: IpAddr, _duration: Option<Duration>) {}
+
+    /// Lifts the ban of the given IP.
+    ///
+    /// Does nothing by default.
+    fn unban_ip(&self, _ip: IpAddr) {}
+
     /// Connect to the given peer. NOTE: if the maximum number of outbound sessions is reached,
     /// this won't do anything. See `reth_network::SessionManager::dial_outbound`.
     fn connect_peer(&self, peer: PeerId, tcp_addr: SocketAddr) {
@@ -246,8 +284,12 @@ pub struct PeerInfo {
     pub eth_version: EthVersion,
     /// The Status message the peer sent for the `eth` handshake
     pub status: Arc<UnifiedStatus>,
+    /// The range of blocks the peer last advertised, starting with `eth/69`.
+    pub block_range: Option<BlockRangeUpdate>,
     /// The timestamp when the session to that peer has been established.
     pub session_established: Instant,
+    /// The current timeout of requests sent to the peer, adapted to its response latency.
+    pub request_timeout: Duration,
     /// The peer's connection kind
     pub kind: PeerKind,
 }
@@ -282,9 +324,44 @@ impl std::fmt::Display for Direction {
     }
 }
 
+/// The discovery service a node is known through.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum DiscoverySource {
+    /// The node is in the discv4 routing table.
+    Discv4,
+    /// The node is in the discv5 routing table.
+    Discv5,
+    /// The node was resolved via DNS discovery.
+    Dns,
+}
+
+/// A node known to one of the discovery services.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct DiscoveryTableEntry {
+    /// The node's record, containing its id and address.
+    pub record: NodeRecord,
+    /// The sequence number of the node's ENR, if known.
+    pub enr_seq: Option<u64>,
+    /// The [`ForkId`] the node advertised, if known.
+    pub fork_id: Option<ForkId>,
+    /// Whether the advertised [`ForkId`] is compatible with the local chain.
+    ///
+    /// `None` if the node didn't advertise a [`ForkId`].
+    pub fork_id_valid: Option<bool>,
+    /// Unix timestamp in seconds of the last time the node was seen, if tracked.
+    pub last_seen: Option<u64>,
+    /// The discovery service the node is known through.
+    pub source: DiscoverySource,
+}
+
 /// The status of the network being ran by the local node.
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct NetworkStatus {
     /// The local node client version.
     pub client_version: String,
@@ -294,4 +371,31 @@ pub struct NetworkStatus {
     pub eth_protocol_info: EthProtocolInfo,
     /// The list of supported capabilities and their versions.
     pub capabilities: Vec<Capability>,
+    /// Whether only trusted peers are connected to or accepted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub trusted_nodes_only: bool,
+}
+
+impl NetworkStatus {
+    /// Creates a new [`NetworkStatus`] with trusted-nodes-only mode disabled.
+    pub const fn new(
+        client_version: String,
+        protocol_version: u64,
+        eth_protocol_info: EthProtocolInfo,
+        capabilities: Vec<Capability>,
+    ) -> Self {
+        Self {
+            client_version,
+            protocol_version,
+            eth_protocol_info,
+            capabilities,
+            trusted_nodes_only: false,
+        }
+    }
+
+    /// Sets whether only trusted peers are connected to or accepted.
+    pub const fn with_trusted_nodes_only(mut self, trusted_nodes_only: bool) -> Self {
+        self.trusted_nodes_only = trusted_nodes_only;
+        self
+    }
 }
@@ -4,7 +4,10 @@
 //! generic over it.
 
 use core::{fmt, marker::PhantomData};
-use std::net::{IpAddr, SocketAddr};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
 use crate::{
     events::{NetworkPeersEvents, PeerEventStream},
@@ -82,6 +85,7 @@ where
                 head: Default::default(),
             },
             capabilities: vec![],
+            trusted_nodes_only: false,
         })
     }
 
@@ -153,6 +157,10 @@ where
 
     fn disconnect_peer_with_reason(&self, _peer: PeerId, _reason: DisconnectReason) {}
 
+    fn ban_ip(&self, _ip: IpAddr, _duration: Option<Duration>) {}
+
+    fn unban_ip(&self, _ip: IpAddr) {}
+
     fn connect_peer_kind(
         &self,
         _peer: PeerId,
@@ -11,7 +11,7 @@ use reth_net_banlist::{BanList, IpFilter};
 use reth_network_peers::{NodeRecord, TrustedPeer};
 use tracing::info;
 
-use crate::{BackoffKind, ReputationChangeWeights};
+use crate::{peers::persisted::PersistedPeer, BackoffKind, ReputationChangeWeights};
 
 /// Maximum number of available slots for outbound sessions.
 pub const DEFAULT_MAX_COUNT_PEERS_OUTBOUND: u32 = 100;
@@ -147,6 +147,11 @@ pub struct PeersConfig {
     /// Basic nodes to connect to.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub basic_nodes: HashSet<NodeRecord>,
+    /// Reputation, ban and fork id of known peers from a previous run.
+    ///
+    /// This is restored for the matching [`Self::basic_nodes`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub persisted_peers: Vec<PersistedPeer>,
     /// How long to ban bad peers.
     #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
     pub ban_duration: Duration,
@@ -172,6 +177,12 @@ pub struct PeersConfig {
     /// IPs within the specified CIDR ranges will be allowed.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub ip_filter: IpFilter,
+    /// Limits how many peers from the same IPv4 subnet can be connected at once, given as the
+    /// subnet prefix length and the max number of peers, e.g. `(24, 2)`.
+    ///
+    /// This applies to incoming sessions and outbound dial candidates alike. Trusted and static
+    /// peers are exempt from this limit.
+    pub max_peers_per_ip_subnet: Option<(u8, usize)>,
 }
 
 impl Default for PeersConfig {
@@ -188,9 +199,11 @@ impl Default for PeersConfig {
             trusted_nodes_only: false,
             trusted_nodes_resolution_interval: Duration::from_secs(60 * 60),
             basic_nodes: Default::default(),
+            persisted_peers: Default::default(),
             max_backoff_count: 5,
             incoming_ip_throttle_duration: INBOUND_IP_THROTTLE_DURATION,
             ip_filter: IpFilter::default(),
+            max_peers_per_ip_subnet: None,
         }
     }
 }
@@ -266,6 +279,13 @@ impl PeersConfig {
         self
     }
 
+    /// Nodes available at launch, together with their state from a previous run.
+    pub fn with_persisted_peers(mut self, peers: Vec<PersistedPeer>) -> Self {
+        self.basic_nodes = peers.iter().map(|peer| peer.record).collect();
+        self.persisted_peers = peers;
+        self
+    }
+
     /// Configures the max allowed backoff count.
     pub const fn with_max_backoff_count(mut self, max_backoff_count: u8) -> Self {
         self.max_backoff_count = max_backoff_count;
@@ -293,6 +313,9 @@ impl PeersConfig {
     }
 
     /// Read from file nodes available at launch. Ignored if None.
+    ///
+    /// With the `serde` feature this also restores the persisted state of the peers, see
+    /// [`PersistedPeer`]. Otherwise the file is expected to only contain [`NodeRecord`]s.
     pub fn with_basic_nodes_from_file(
         self,
         optional_file: Option<impl AsRef<Path>>,
@@ -304,8 +327,17 @@ impl PeersConfig {
             Err(e) => Err(e)?,
         };
         info!(target: "net::peers", file = %file_path.as_ref().display(), "Loading saved peers");
-        let nodes: HashSet<NodeRecord> = serde_json::from_reader(reader)?;
-        Ok(self.with_basic_nodes(nodes))
+        #[cfg(feature = "serde")]
+        {
+            let entries: Vec<crate::peers::persisted::PeersFileEntry> =
+                serde_json::from_reader(reader)?;
+            Ok(self.with_persisted_peers(entries.into_iter().map(Into::into).collect()))
+        }
+        #[cfg(not(feature = "serde"))]
+        {
+            let nodes: HashSet<NodeRecord> = serde_json::from_reader(reader)?;
+            Ok(self.with_basic_nodes(nodes))
+        }
     }
 
     /// Configure the IP filter for restricting network connections to specific IP ranges.
@@ -314,6 +346,12 @@ impl PeersConfig {
         self
     }
 
+    /// Limits the number of connected peers per IPv4 subnet with the given prefix length.
+    pub const fn with_max_peers_per_ip_subnet(mut self, prefix_len: u8, max_peers: usize) -> Self {
+        self.max_peers_per_ip_subnet = Some((prefix_len, max_peers));
+        self
+    }
+
     /// Returns settings for testing
     #[cfg(any(test, feature = "test-utils"))]
     pub fn test() -> Self {
@@ -0,0 +1,146 @@
+//! Peer state that is persisted across restarts.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy_eip2124::ForkId;
+use reth_network_peers::NodeRecord;
+
+use crate::{Reputation, DEFAULT_REPUTATION};
+
+/// A known peer as written to the peers file.
+///
+/// Besides the [`NodeRecord`] this keeps the peer's reputation, ban and last announced
+/// [`ForkId`], so that these are not lost on restart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PersistedPeer {
+    /// The node record of the peer.
+    pub record: NodeRecord,
+    /// The reputation of the peer.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reputation: Reputation,
+    /// Unix timestamp in seconds until which the peer is banned, if it is.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub banned_until: Option<u64>,
+    /// The [`ForkId`] the peer last announced.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub fork_id: Option<ForkId>,
+}
+
+impl PersistedPeer {
+    /// Returns how long the peer is still banned for, relative to `now`, at most `ban_duration`.
+    ///
+    /// Returns `None` if the peer is not banned or the ban already expired. A ban that ends too far
+    /// in the future to be represented lasts `ban_duration`.
+    pub fn remaining_ban(&self, now: SystemTime, ban_duration: Duration) -> Option<Duration> {
+        let Some(banned_until) = UNIX_EPOCH.checked_add(Duration::from_secs(self.banned_until?))
+        else {
+            return Some(ban_duration)
+        };
+        banned_until
+            .duration_since(now)
+            .ok()
+            .filter(|remaining| !remaining.is_zero())
+            .map(|remaining| remaining.min(ban_duration))
+    }
+}
+
+impl From<NodeRecord> for PersistedPeer {
+    fn from(record: NodeRecord) -> Self {
+        Self { record, reputation: DEFAULT_REPUTATION, banned_until: None, fork_id: None }
+    }
+}
+
+/// An entry of the peers file.
+///
+/// Files written by older versions only contain plain [`NodeRecord`]s, which are still accepted.
+#[cfg(feature = "serde")]
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub(crate) enum PeersFileEntry {
+    /// A peer with its persisted state.
+    Peer(PersistedPeer),
+    /// A bare node record.
+    Record(NodeRecord),
+}
+
+#[cfg(feature = "serde")]
+impl From<PeersFileEntry> for PersistedPeer {
+    fn from(entry: PeersFileEntry) -> Self {
+        match entry {
+            PeersFileEntry::Peer(peer) => peer,
+            PeersFileEntry::Record(record) => record.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_eip2124::ForkHash;
+    use reth_network_peers::PeerId;
+
+    fn record() -> NodeRecord {
+        NodeRecord::new("10.3.58.6:30303".parse().unwrap(), PeerId::new([1; 64]))
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn persisted_peer_roundtrip() {
+        let peer = PersistedPeer {
+            record: record(),
+            reputation: -1024,
+            banned_until: Some(1_700_000_000),
+            fork_id: Some(ForkId { hash: ForkHash([0xfc, 0x64, 0xec, 0x04]), next: 1150000 }),
+        };
+        let json = serde_json::to_string(&peer).unwrap();
+        assert_eq!(serde_json::from_str::<PersistedPeer>(&json).unwrap(), peer);
+
+        let peer = PersistedPeer::from(record());
+        let json = serde_json::to_string(&peer).unwrap();
+        assert!(!json.contains("banned_until"));
+        assert_eq!(serde_json::from_str::<PersistedPeer>(&json).unwrap(), peer);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn load_legacy_peers_file() {
+        let record = record();
+        let peer = PersistedPeer { reputation: -1024, ..record.into() };
+        let json = format!(
+            "[{}, {}]",
+            serde_json::to_string(&record).unwrap(),
+            serde_json::to_string(&peer).unwrap()
+        );
+
+        let entries: Vec<PeersFileEntry> = serde_json::from_str(&json).unwrap();
+        let peers = entries.into_iter().map(PersistedPeer::from).collect::<Vec<_>>();
+        assert_eq!(peers, vec![record.into(), peer]);
+    }
+
+    #[test]
+    fn remaining_ban() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let ban_duration = Duration::from_secs(120);
+        let mut peer = PersistedPeer::from(record());
+        assert_eq!(peer.remaining_ban(now, ban_duration), None);
+
+        peer.banned_until = Some(900);
+        assert_eq!(peer.remaining_ban(now, ban_duration), None);
+
+        peer.banned_until = Some(1_060);
+        assert_eq!(peer.remaining_ban(now, ban_duration), Some(Duration::from_secs(60)));
+
+        // bans never outlast the ban duration
+        peer.banned_until = Some(10_000);
+        assert_eq!(peer.remaining_ban(now, ban_duration), Some(ban_duration));
+    }
+
+    #[test]
+    fn remaining_ban_out_of_range() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let ban_duration = Duration::from_secs(120);
+        let peer = PersistedPeer { banned_until: Some(u64::MAX), ..record().into() };
+        assert_eq!(peer.remaining_ban(now, ban_duration), Some(ban_duration));
+    }
+}
@@ -1,13 +1,20 @@
 //! Configuration types for peer sessions manager.
 
 use crate::peers::config::{DEFAULT_MAX_COUNT_PEERS_INBOUND, DEFAULT_MAX_COUNT_PEERS_OUTBOUND};
-use std::time::Duration;
+use std::{num::NonZeroU32, time::Duration};
 
 /// Default request timeout for a single request.
 ///
 /// This represents the amount of time we wait for a response until we consider it timed out.
 pub const INITIAL_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
 
+/// Default lower bound of the adaptive request timeout.
+pub const DEFAULT_MIN_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default number of standard deviations of the observed response latency that are added to the
+/// average latency to derive the request timeout.
+pub const DEFAULT_REQUEST_TIMEOUT_DEVIATION_FACTOR: u32 = 4;
+
 /// Default timeout after which a pending session attempt is considered failed.
 pub const PENDING_SESSION_TIMEOUT: Duration = Duration::from_secs(20);
 
@@ -16,6 +23,15 @@ pub const PENDING_SESSION_TIMEOUT: Duration = Duration::from_secs(20);
 /// This is the time a peer has to answer a response.
 pub const PROTOCOL_BREACH_REQUEST_TIMEOUT: Duration = Duration::from_secs(2 * 60);
 
+/// Default number of messages per second a peer may send before its messages are throttled.
+pub const DEFAULT_MESSAGES_PER_SECOND: NonZeroU32 = NonZeroU32::new(500).unwrap();
+
+/// Default number of messages a peer may send in a single burst.
+pub const DEFAULT_MESSAGE_BURST: NonZeroU32 = NonZeroU32::new(1_000).unwrap();
+
+/// Default duration a peer may exceed the message rate limit before it is disconnected.
+pub const DEFAULT_MAX_THROTTLED_DURATION: Duration = Duration::from_secs(10);
+
 /// The default maximum number of peers.
 const DEFAULT_MAX_PEERS: usize =
     DEFAULT_MAX_COUNT_PEERS_OUTBOUND as usize + DEFAULT_MAX_COUNT_PEERS_INBOUND as usize;
@@ -42,6 +58,8 @@ pub struct SessionsConfig {
     /// The maximum initial time we wait for a response from the peer before we timeout a request
     /// _internally_.
     pub initial_internal_request_timeout: Duration,
+    /// Bounds of the request timeout that is adapted to the observed response latency of a peer.
+    pub request_timeout: RequestTimeoutConfig,
     /// The amount of time we continue to wait for a response from the peer, even if we timed it
     /// out internally (`initial_internal_request_timeout`). Timeouts are not penalized but the
     /// session directly, however if a peer fails to respond at all (within
@@ -50,6 +68,10 @@ pub struct SessionsConfig {
     pub protocol_breach_request_timeout: Duration,
     /// The timeout after which a pending session attempt is considered failed.
     pub pending_session_timeout: Duration,
+    /// Rate limit for messages received from a peer.
+    ///
+    /// By default, messages are not rate limited.
+    pub message_rate_limit: Option<SessionRateLimit>,
 }
 
 impl Default for SessionsConfig {
@@ -67,8 +89,10 @@ impl Default for SessionsConfig {
             session_event_buffer: DEFAULT_SESSION_EVENT_BUFFER_SIZE,
             limits: Default::default(),
             initial_internal_request_timeout: INITIAL_REQUEST_TIMEOUT,
+            request_timeout: Default::default(),
             protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
             pending_session_timeout: PENDING_SESSION_TIMEOUT,
+            message_rate_limit: None,
         }
     }
 }
@@ -84,6 +108,18 @@ impl SessionsConfig {
         self
     }
 
+    /// Sets the bounds of the adaptive request timeout.
+    pub const fn with_request_timeout(mut self, request_timeout: RequestTimeoutConfig) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Sets the rate limit for messages received from a peer.
+    pub const fn with_message_rate_limit(mut self, rate_limit: SessionRateLimit) -> Self {
+        self.message_rate_limit = Some(rate_limit);
+        self
+    }
+
     /// Helper function to set the buffer size for the bounded communication channel between the
     /// manager and its sessions for events emitted by the sessions.
     ///
@@ -144,6 +180,104 @@ impl SessionLimits {
     }
 }
 
+/// Configuration of the request timeout that is adapted to the response latency of a peer.
+///
+/// The timeout is the average latency plus `deviation_factor` times its standard deviation,
+/// clamped to `min_timeout..=max_timeout`. The standard deviation is at least 1/8 of the average
+/// latency, so peers with stable latency keep some headroom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RequestTimeoutConfig {
+    /// The lowest timeout a request can be assigned.
+    pub min_timeout: Duration,
+    /// The highest timeout a request can be assigned.
+    ///
+    /// Defaults to [`SessionsConfig::initial_internal_request_timeout`], the timeout of requests
+    /// before the first response of a peer.
+    pub max_timeout: Option<Duration>,
+    /// Number of standard deviations of the response latency added to the average latency.
+    pub deviation_factor: u32,
+}
+
+impl Default for RequestTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            min_timeout: DEFAULT_MIN_REQUEST_TIMEOUT,
+            max_timeout: None,
+            deviation_factor: DEFAULT_REQUEST_TIMEOUT_DEVIATION_FACTOR,
+        }
+    }
+}
+
+impl RequestTimeoutConfig {
+    /// Sets the lowest timeout a request can be assigned.
+    pub const fn with_min_timeout(mut self, timeout: Duration) -> Self {
+        self.min_timeout = timeout;
+        self
+    }
+
+    /// Sets the highest timeout a request can be assigned.
+    pub const fn with_max_timeout(mut self, timeout: Duration) -> Self {
+        self.max_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the number of standard deviations of the response latency added to the average.
+    pub const fn with_deviation_factor(mut self, deviation_factor: u32) -> Self {
+        self.deviation_factor = deviation_factor;
+        self
+    }
+}
+
+/// Token bucket rate limit for messages received from a peer.
+///
+/// Responses to our own requests are not rate limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SessionRateLimit {
+    /// Number of messages per second a peer may send on average.
+    pub messages_per_second: NonZeroU32,
+    /// Maximum number of messages a peer may send in a single burst.
+    pub burst: NonZeroU32,
+    /// How long a peer may exceed the rate limit before the session is disconnected.
+    ///
+    /// A peer exceeds the rate limit from the first throttled message until it has not sent any
+    /// messages for long enough to fully refill its burst.
+    pub max_throttled_duration: Duration,
+}
+
+impl Default for SessionRateLimit {
+    fn default() -> Self {
+        Self {
+            messages_per_second: DEFAULT_MESSAGES_PER_SECOND,
+            burst: DEFAULT_MESSAGE_BURST,
+            max_throttled_duration: DEFAULT_MAX_THROTTLED_DURATION,
+        }
+    }
+}
+
+impl SessionRateLimit {
+    /// Sets the number of messages per second a peer may send on average.
+    pub const fn with_messages_per_second(mut self, messages_per_second: NonZeroU32) -> Self {
+        self.messages_per_second = messages_per_second;
+        self
+    }
+
+    /// Sets the maximum number of messages a peer may send in a single burst.
+    pub const fn with_burst(mut self, burst: NonZeroU32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Sets how long a peer may exceed the rate limit before the session is disconnected.
+    pub const fn with_max_throttled_duration(mut self, duration: Duration) -> Self {
+        self.max_throttled_duration = duration;
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,4 +292,15 @@ mod tests {
         let expected_ration = config.session_event_buffer / (DEFAULT_MAX_PEERS * 2);
         assert_eq!(default_ration, expected_ration);
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn reject_zero_rate_limit() {
+        let rate_limit: SessionRateLimit =
+            serde_json::from_str(r#"{"messages_per_second":1,"burst":1}"#).unwrap();
+        assert_eq!(rate_limit.burst, NonZeroU32::MIN);
+
+        assert!(serde_json::from_str::<SessionRateLimit>(r#"{"burst":0}"#).is_err());
+        assert!(serde_json::from_str::<SessionRateLimit>(r#"{"messages_per_second":0}"#).is_err());
+    }
 }
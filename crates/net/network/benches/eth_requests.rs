@@ -0,0 +1,64 @@
+#![allow(missing_docs)]
+use alloy_consensus::Header;
+use criterion::*;
+use reth_eth_wire::GetReceipts;
+use reth_network::{
+    eth_requests::{EthRequestHandler, EthRequestHandlerConfig},
+    EthNetworkPrimitives,
+};
+use reth_network_api::test_utils::PeersHandle;
+use reth_provider::test_utils::MockEthProvider;
+use tokio::sync::mpsc;
+
+criterion_group!(
+    name = eth_requests_benches;
+    config = Criterion::default();
+    targets = receipts69_bench
+);
+
+/// Number of receipts in every block.
+const RECEIPTS_PER_BLOCK: u64 = 200;
+
+pub fn receipts69_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Serve Receipts69");
+    for blocks in [16u64, 256, 1024] {
+        let client = MockEthProvider::default();
+        let headers =
+            (1..=blocks).map(|number| Header { number, ..Default::default() }).collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+        client.extend_receipts(headers.iter().map(|header| {
+            let receipts = (0..RECEIPTS_PER_BLOCK)
+                .map(|i| reth_ethereum_primitives::Receipt {
+                    cumulative_gas_used: i,
+                    ..Default::default()
+                })
+                .collect();
+            (header.number, receipts)
+        }));
+        let request = GetReceipts(headers.iter().map(|header| header.hash_slow()).collect());
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig {
+                soft_response_limit: usize::MAX,
+                max_receipts_per_response: usize::MAX,
+                // measure the storage lookups of every request
+                header_cache_bytes: 0,
+                receipts_cache_bytes: 0,
+                ..Default::default()
+            },
+        );
+
+        group.throughput(Throughput::Elements(blocks));
+        group.bench_function(BenchmarkId::from_parameter(blocks), |b| {
+            b.iter(|| black_box(handler.receipts69_response(&request)))
+        });
+    }
+    group.finish();
+}
+
+criterion_main!(eth_requests_benches);
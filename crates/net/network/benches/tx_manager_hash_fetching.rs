@@ -11,8 +11,8 @@ use reth_network::{
         Testnet,
     },
     transactions::{
-        fetcher::TransactionFetcher, TransactionFetcherConfig, TransactionPropagationMode::Max,
-        TransactionsManagerConfig,
+        fetcher::TransactionFetcher, TransactionBroadcastPolicy, TransactionFetcherConfig,
+        TransactionPropagationMode::Max, TransactionsManagerConfig,
     },
 };
 use reth_network_peers::PeerId;
@@ -74,7 +74,7 @@ pub fn tx_fetch_bench(c: &mut Criterion) {
                 tokio::task::block_in_place(|| {
                     tokio::runtime::Handle::current().block_on(async {
                         let tx_manager_config = TransactionsManagerConfig {
-                            propagation_mode: Max(0),
+                            broadcast_policy: TransactionBroadcastPolicy::new(Max(0)),
                             transaction_fetcher_config: TransactionFetcherConfig {
                                 max_inflight_requests: 1,
                                 ..Default::default()
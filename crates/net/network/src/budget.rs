@@ -8,6 +8,12 @@ pub const DEFAULT_BUDGET_TRY_DRAIN_STREAM: u32 = 10;
 /// Default is 2 iterations.
 pub const DEFAULT_BUDGET_TRY_DRAIN_DOWNLOADERS: u32 = 2;
 
+/// Default budget to try and handle queued requests in the
+/// [`EthRequestHandler`](crate::eth_requests::EthRequestHandler).
+///
+/// Default is 10 requests. Handling a request only spawns the task that serves it.
+pub const DEFAULT_BUDGET_TRY_DRAIN_ETH_REQUESTS: u32 = DEFAULT_BUDGET_TRY_DRAIN_STREAM;
+
 /// Default budget to try and drain [`Swarm`](crate::swarm::Swarm).
 ///
 /// Default is 10 [`SwarmEvent`](crate::swarm::SwarmEvent)s.
@@ -1,7 +1,7 @@
 //! Builder support for configuring the entire setup.
 
 use crate::{
-    eth_requests::EthRequestHandler,
+    eth_requests::{EthRequestHandler, EthRequestHandlerConfig},
     transactions::{
         config::{StrictEthAnnouncementFilter, TransactionPropagationKind},
         policy::NetworkPolicies,
@@ -61,12 +61,22 @@ impl<Tx, Eth, N: NetworkPrimitives> NetworkBuilder<Tx, Eth, N> {
     pub fn request_handler<Client>(
         self,
         client: Client,
+    ) -> NetworkBuilder<Tx, EthRequestHandler<Client, N>, N> {
+        self.request_handler_with_config(client, EthRequestHandlerConfig::default())
+    }
+
+    /// Creates a new [`EthRequestHandler`] with the given [`EthRequestHandlerConfig`] and wires it
+    /// to the network.
+    pub fn request_handler_with_config<Client>(
+        self,
+        client: Client,
+        config: EthRequestHandlerConfig,
     ) -> NetworkBuilder<Tx, EthRequestHandler<Client, N>, N> {
         let Self { mut network, transactions, .. } = self;
         let (tx, rx) = mpsc::channel(ETH_REQUEST_CHANNEL_CAPACITY);
         network.set_eth_request_handler(tx);
         let peers = network.handle().peers_handle().clone();
-        let request_handler = EthRequestHandler::new(client, peers, rx);
+        let request_handler = EthRequestHandler::new(client, peers, rx, config);
         NetworkBuilder { network, request_handler, transactions }
     }
 
@@ -1,6 +1,7 @@
 //! Network cache support
 
 use alloy_primitives::map::DefaultHashBuilder;
+use alloy_rlp::Encodable;
 use core::hash::BuildHasher;
 use derive_more::{Deref, DerefMut};
 use itertools::Itertools;
@@ -187,6 +188,85 @@ where
     }
 }
 
+impl<K, V> LruMap<K, V, ByEncodedLength>
+where
+    K: Hash + PartialEq,
+    V: Encodable,
+{
+    /// Returns a new cache that holds values with a total encoded length of at most `max_bytes`,
+    /// with default hash builder.
+    pub fn new_by_encoded_length(max_bytes: usize) -> Self {
+        Self(schnellru::LruMap::with_hasher(ByEncodedLength::new(max_bytes), Default::default()))
+    }
+}
+
+/// Limiter for [`LruMap`] that limits the total RLP encoded length of the values.
+///
+/// Values that are larger than the limit on their own aren't inserted.
+#[derive(Debug, Clone, Copy)]
+pub struct ByEncodedLength {
+    max_bytes: usize,
+    bytes: usize,
+}
+
+impl ByEncodedLength {
+    /// Returns a new limiter with the given maximum total encoded length.
+    pub const fn new(max_bytes: usize) -> Self {
+        Self { max_bytes, bytes: 0 }
+    }
+
+    /// Returns the total encoded length of the values in the map.
+    pub const fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl<K, V: Encodable> Limiter<K, V> for ByEncodedLength {
+    type KeyToInsert<'a> = K;
+    type LinkType = u32;
+
+    fn is_over_the_limit(&self, _length: usize) -> bool {
+        self.bytes > self.max_bytes
+    }
+
+    fn on_insert(&mut self, _length: usize, key: K, value: V) -> Option<(K, V)> {
+        let len = value.length();
+        if len > self.max_bytes {
+            return None
+        }
+        self.bytes += len;
+        Some((key, value))
+    }
+
+    fn on_replace(
+        &mut self,
+        _length: usize,
+        _old_key: &mut K,
+        _new_key: K,
+        old_value: &mut V,
+        new_value: &mut V,
+    ) -> bool {
+        let new_len = new_value.length();
+        if new_len > self.max_bytes {
+            return false
+        }
+        self.bytes = self.bytes - old_value.length() + new_len;
+        true
+    }
+
+    fn on_removed(&mut self, _key: &mut K, value: &mut V) {
+        self.bytes -= value.length();
+    }
+
+    fn on_cleared(&mut self) {
+        self.bytes = 0;
+    }
+
+    fn on_grow(&mut self, _new_memory_usage: usize) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -370,4 +450,25 @@ mod test {
         assert!(!is_new); // should not be new
         assert_eq!(evicted, None); // should not evict anything
     }
+
+    #[test]
+    fn evict_by_encoded_length() {
+        let mut cache = LruMap::<u8, Vec<u8>, ByEncodedLength>::new_by_encoded_length(10);
+
+        // a 4 byte list encodes to 5 bytes
+        assert!(cache.insert(1, vec![1; 4]));
+        assert!(cache.insert(2, vec![2; 4]));
+        assert_eq!(cache.limiter().bytes(), 10);
+
+        assert!(cache.insert(3, vec![3; 4]));
+        assert!(cache.peek(&1).is_none());
+        assert_eq!(cache.limiter().bytes(), 10);
+
+        // values larger than the limit aren't cached
+        assert!(!cache.insert(4, vec![4; 20]));
+        assert!(cache.peek(&4).is_none());
+
+        cache.remove(&2);
+        assert_eq!(cache.limiter().bytes(), 5);
+    }
 }
@@ -361,7 +361,7 @@ impl<N: NetworkPrimitives> NetworkConfigBuilder<N> {
 
     /// Configures the propagation mode for the transaction manager.
     pub const fn transaction_propagation_mode(mut self, mode: TransactionPropagationMode) -> Self {
-        self.transactions_manager_config.propagation_mode = mode;
+        self.transactions_manager_config.broadcast_policy.propagation_mode = mode;
         self
     }
 
@@ -12,7 +12,7 @@ use reth_dns_discovery::{
     DnsDiscoveryConfig, DnsDiscoveryHandle, DnsDiscoveryService, DnsNodeRecordUpdate, DnsResolver,
 };
 use reth_ethereum_forks::{EnrForkIdEntry, ForkId};
-use reth_network_api::{DiscoveredEvent, DiscoveryEvent};
+use reth_network_api::{DiscoveredEvent, DiscoveryEvent, DiscoverySource, DiscoveryTableEntry};
 use reth_network_peers::{NodeRecord, PeerId};
 use reth_network_types::PeerAddr;
 use secp256k1::SecretKey;
@@ -22,6 +22,7 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{ready, Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
 };
 use tokio::{sync::mpsc, task::JoinHandle};
 use tokio_stream::{wrappers::ReceiverStream, Stream};
@@ -42,6 +43,8 @@ pub struct Discovery {
     ///
     /// These nodes can be ephemeral and are updated via the discovery protocol.
     discovered_nodes: LruMap<PeerId, PeerAddr>,
+    /// All nodes resolved via DNS discovery.
+    dns_nodes: LruMap<PeerId, DiscoveryTableEntry>,
     /// Local ENR of the discovery v4 service (discv5 ENR has same [`PeerId`]).
     local_enr: NodeRecord,
     /// Handler to interact with the Discovery v4 service
@@ -133,6 +136,7 @@ impl Discovery {
             discv5,
             discv5_updates,
             discovered_nodes: LruMap::new(DEFAULT_MAX_CAPACITY_DISCOVERED_PEERS_CACHE),
+            dns_nodes: LruMap::new(DEFAULT_MAX_CAPACITY_DISCOVERED_PEERS_CACHE),
             queued_events: Default::default(),
             _dns_disc_service,
             _dns_discovery,
@@ -214,6 +218,27 @@ impl Discovery {
         Ok(())
     }
 
+    /// Tracks a node resolved via DNS discovery.
+    fn on_dns_node(&mut self, update: &DnsNodeRecordUpdate) {
+        let last_seen = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        self.dns_nodes.insert(
+            update.node_record.id,
+            DiscoveryTableEntry {
+                record: update.node_record,
+                enr_seq: Some(update.enr.seq()),
+                fork_id: update.fork_id,
+                fork_id_valid: None,
+                last_seen: Some(last_seen),
+                source: DiscoverySource::Dns,
+            },
+        );
+    }
+
+    /// Returns all nodes resolved via DNS discovery.
+    pub(crate) fn dns_nodes(&self) -> impl Iterator<Item = &DiscoveryTableEntry> + '_ {
+        self.dns_nodes.iter().map(|(_, node)| node)
+    }
+
     /// Processes an incoming [`NodeRecord`] update from a discovery service
     fn on_node_record_update(&mut self, record: NodeRecord, fork_id: Option<ForkId>) {
         let peer_id = record.id;
@@ -291,6 +316,7 @@ impl Discovery {
                         "failed adding node discovered by dns to discv5"
                     );
                 }
+                self.on_dns_node(&update);
                 self.on_node_record_update(update.node_record, update.fork_id);
             }
 
@@ -320,6 +346,7 @@ impl Discovery {
 
         Self {
             discovered_nodes: LruMap::new(0),
+            dns_nodes: LruMap::new(0),
             local_enr: NodeRecord {
                 address: IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
                 tcp_port: 0,
@@ -1,13 +1,17 @@
 //! Blocks/Headers management for the p2p network.
 
 use crate::{
-    budget::DEFAULT_BUDGET_TRY_DRAIN_DOWNLOADERS, metered_poll_nested_stream_with_budget,
+    budget::DEFAULT_BUDGET_TRY_DRAIN_ETH_REQUESTS,
+    cache::{ByEncodedLength, LruMap},
+    metered_poll_nested_stream_with_budget,
     metrics::EthRequestHandlerMetrics,
 };
-use alloy_consensus::{BlockHeader, ReceiptWithBloom};
+use alloy_consensus::{BlockHeader, ReceiptWithBloom, EMPTY_ROOT_HASH};
 use alloy_eips::BlockHashOrNumber;
+use alloy_primitives::{Bytes, B256};
 use alloy_rlp::Encodable;
-use futures::StreamExt;
+use futures::{stream::FuturesUnordered, FutureExt, Stream, StreamExt};
+use parking_lot::Mutex;
 use reth_eth_wire::{
     BlockBodies, BlockHeaders, EthNetworkPrimitives, GetBlockBodies, GetBlockHeaders, GetNodeData,
     GetReceipts, GetReceipts70, HeadersDirection, NetworkPrimitives, NodeData, Receipts,
@@ -16,16 +20,27 @@ use reth_eth_wire::{
 use reth_network_api::test_utils::PeersHandle;
 use reth_network_p2p::error::RequestResult;
 use reth_network_peers::PeerId;
+use reth_network_types::ReputationChangeKind;
 use reth_primitives_traits::Block;
-use reth_storage_api::{BlockReader, HeaderProvider};
+use reth_storage_api::{
+    errors::provider::ProviderResult, BlockReader, HeaderProvider, StateProviderFactory,
+};
 use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    fmt,
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
-use tokio::sync::{mpsc::Receiver, oneshot};
+use tokio::{
+    sync::{mpsc::Receiver, oneshot},
+    task::JoinHandle,
+    time::Instant,
+};
 use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, trace, warn};
 
 // Limits: <https://github.com/ethereum/go-ethereum/blob/b0d44338bbcefee044f1f635a84487cbbd8f0538/eth/protocols/eth/handler.go#L34-L56>
 
@@ -34,6 +49,12 @@ use tokio_stream::wrappers::ReceiverStream;
 /// Used to limit lookups.
 pub const MAX_RECEIPTS_SERVE: usize = 1024;
 
+/// Maximum number of individual receipts to serve in a single response.
+///
+/// Unlike [`MAX_RECEIPTS_SERVE`], which limits the number of blocks, this counts the receipts of
+/// all blocks, so that a few blocks with many receipts can't produce huge responses.
+pub const MAX_RECEIPTS_PER_RESPONSE: usize = 16 * 1024;
+
 /// Maximum number of block headers to serve.
 ///
 /// Used to limit lookups.
@@ -45,23 +66,304 @@ pub const MAX_HEADERS_SERVE: usize = 1024;
 /// `SOFT_RESPONSE_LIMIT`.
 pub const MAX_BODIES_SERVE: usize = 1024;
 
+/// Maximum number of node data entries to serve.
+///
+/// Used to limit lookups.
+pub const MAX_NODE_DATA_SERVE: usize = 384;
+
 /// Maximum size of replies to data retrievals: 2MB
 pub const SOFT_RESPONSE_LIMIT: usize = 2 * 1024 * 1024;
 
+/// Lowest configurable maximum size of replies to data retrievals: 64KB
+pub const MIN_SOFT_RESPONSE_LIMIT: usize = 64 * 1024;
+
+/// Default total encoded size of cached headers: 4MB
+pub const DEFAULT_HEADER_CACHE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Default total encoded size of cached block bodies: 16MB
+pub const DEFAULT_BODY_CACHE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default total encoded size of cached receipts: 12MB
+pub const DEFAULT_RECEIPTS_CACHE_BYTES: usize = 12 * 1024 * 1024;
+
+/// Default number of requests that are served concurrently.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 64;
+
+/// Default number of requests of a single peer that are served concurrently.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS_PER_PEER: usize = 8;
+
+/// Default number of queued requests above which expensive requests aren't served.
+pub const DEFAULT_SHED_REQUESTS_QUEUE_DEPTH: usize = 128;
+
+/// Number of blocks above which a receipts request isn't served while the queue is backed up.
+const LARGE_RECEIPTS_REQUEST_BLOCKS: usize = 64;
+
+/// Limits for serving eth requests, see [`EthRequestHandler`].
+///
+/// Defaults to [`MAX_RECEIPTS_SERVE`], [`MAX_RECEIPTS_PER_RESPONSE`], [`MAX_HEADERS_SERVE`],
+/// [`MAX_BODIES_SERVE`], [`SOFT_RESPONSE_LIMIT`], [`DEFAULT_HEADER_CACHE_BYTES`],
+/// [`DEFAULT_BODY_CACHE_BYTES`], [`DEFAULT_RECEIPTS_CACHE_BYTES`],
+/// [`DEFAULT_MAX_CONCURRENT_REQUESTS`], [`DEFAULT_MAX_CONCURRENT_REQUESTS_PER_PEER`] and
+/// [`DEFAULT_SHED_REQUESTS_QUEUE_DEPTH`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthRequestHandlerConfig {
+    /// Maximum number of blocks to serve receipts for.
+    pub max_receipts: usize,
+    /// Maximum number of individual receipts to serve in a single response, across all blocks.
+    pub max_receipts_per_response: usize,
+    /// Maximum number of block headers to serve.
+    pub max_headers: usize,
+    /// Maximum number of block bodies to serve.
+    pub max_bodies: usize,
+    /// Maximum size of replies to data retrievals, in bytes.
+    ///
+    /// Should be at least [`MIN_SOFT_RESPONSE_LIMIT`].
+    pub soft_response_limit: usize,
+    /// Total encoded size of recently served headers to cache, in bytes.
+    pub header_cache_bytes: usize,
+    /// Total encoded size of recently served block bodies to cache, in bytes.
+    pub body_cache_bytes: usize,
+    /// Total encoded size of recently served `eth/68` receipts to cache, in bytes.
+    pub receipts_cache_bytes: usize,
+    /// Maximum number of requests that are served concurrently.
+    ///
+    /// Further requests are left queued until a request is served.
+    pub max_concurrent_requests: usize,
+    /// Maximum number of requests of a single peer that are served concurrently.
+    ///
+    /// Further requests of the peer are answered with empty responses and counted as rejected.
+    pub max_concurrent_requests_per_peer: usize,
+    /// Number of queued requests above which node data and large receipts requests are answered
+    /// with empty responses, so that headers and bodies keep being served when the queue backs up.
+    pub shed_requests_queue_depth: usize,
+    /// Maximum number of response bytes served to a single peer per second.
+    ///
+    /// Responses are truncated to the remaining budget of the peer, further requests of the peer
+    /// are answered with empty responses once the budget is used up. Disabled by default.
+    pub max_bytes_per_peer_per_second: Option<usize>,
+}
+
+impl Default for EthRequestHandlerConfig {
+    fn default() -> Self {
+        Self {
+            max_receipts: MAX_RECEIPTS_SERVE,
+            max_receipts_per_response: MAX_RECEIPTS_PER_RESPONSE,
+            max_headers: MAX_HEADERS_SERVE,
+            max_bodies: MAX_BODIES_SERVE,
+            soft_response_limit: SOFT_RESPONSE_LIMIT,
+            header_cache_bytes: DEFAULT_HEADER_CACHE_BYTES,
+            body_cache_bytes: DEFAULT_BODY_CACHE_BYTES,
+            receipts_cache_bytes: DEFAULT_RECEIPTS_CACHE_BYTES,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            max_concurrent_requests_per_peer: DEFAULT_MAX_CONCURRENT_REQUESTS_PER_PEER,
+            shed_requests_queue_depth: DEFAULT_SHED_REQUESTS_QUEUE_DEPTH,
+            max_bytes_per_peer_per_second: None,
+        }
+    }
+}
+
+impl EthRequestHandlerConfig {
+    /// Checks that all limits are non-zero and that the soft response limit is at least
+    /// [`MIN_SOFT_RESPONSE_LIMIT`].
+    pub fn validate(&self) -> Result<(), EthRequestHandlerConfigError> {
+        let limits = [
+            ("max_receipts", self.max_receipts),
+            ("max_receipts_per_response", self.max_receipts_per_response),
+            ("max_headers", self.max_headers),
+            ("max_bodies", self.max_bodies),
+            ("max_concurrent_requests", self.max_concurrent_requests),
+            ("max_concurrent_requests_per_peer", self.max_concurrent_requests_per_peer),
+        ];
+        if let Some((name, _)) = limits.into_iter().find(|(_, limit)| *limit == 0) {
+            return Err(EthRequestHandlerConfigError::ZeroLimit(name))
+        }
+        if self.max_bytes_per_peer_per_second == Some(0) {
+            return Err(EthRequestHandlerConfigError::ZeroLimit("max_bytes_per_peer_per_second"))
+        }
+        if self.soft_response_limit < MIN_SOFT_RESPONSE_LIMIT {
+            return Err(EthRequestHandlerConfigError::SoftResponseLimitTooLow(
+                self.soft_response_limit,
+            ))
+        }
+        Ok(())
+    }
+}
+
+/// Error returned for an invalid [`EthRequestHandlerConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum EthRequestHandlerConfigError {
+    /// The limit with the given name is zero.
+    #[error("{0} must be greater than zero")]
+    ZeroLimit(&'static str),
+    /// The soft response limit is below [`MIN_SOFT_RESPONSE_LIMIT`].
+    #[error(
+        "soft response limit of {0} bytes is below the minimum of {MIN_SOFT_RESPONSE_LIMIT} bytes"
+    )]
+    SoftResponseLimitTooLow(usize),
+}
+
+/// Default number of requests a peer can send per second before it's reported as a spammer.
+pub const DEFAULT_MAX_REQUESTS_PER_PEER_PER_SECOND: u32 = 250;
+
+/// Number of requests for data the node doesn't have a peer can send per window before it's
+/// reported as a spammer.
+const MAX_UNAVAILABLE_REQUESTS_PER_WINDOW: u32 = 16;
+
+/// The window over which requests of a peer are counted.
+const REQUEST_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Requests of a peer in the current window.
+#[derive(Debug)]
+struct PeerRequestWindow {
+    /// When the window started.
+    started_at: Instant,
+    /// Number of requests received in this window.
+    requests: u32,
+    /// Number of requests in this window that asked for data the node doesn't have.
+    unavailable: u32,
+    /// Number of response bytes served in this window.
+    bytes: usize,
+    /// Whether the peer has been reported in this window.
+    reported: bool,
+}
+
+impl PeerRequestWindow {
+    const fn new(started_at: Instant) -> Self {
+        Self { started_at, requests: 0, unavailable: 0, bytes: 0, reported: false }
+    }
+}
+
+/// Number of queued requests of a peer above which further requests of the peer are dropped.
+const MAX_QUEUED_REQUESTS_PER_PEER: usize = 64;
+
+/// Number of requests of a peer that are handled per poll, before only the requests of other peers
+/// are handled.
+///
+/// This is less than the default [`DEFAULT_BUDGET_TRY_DRAIN_ETH_REQUESTS`], so that a single peer
+/// can't take the whole budget of a poll.
+const MAX_REQUESTS_PER_PEER_PER_POLL: usize = 4;
+
+/// Number of incoming requests that are moved into the queues of their peers per poll.
+///
+/// This fills the queue of a flooding peer within a single poll, so that its further requests are
+/// dropped and the requests of other peers behind them are queued by the next poll.
+const MAX_INCOMING_REQUESTS_PER_POLL: usize = MAX_QUEUED_REQUESTS_PER_PEER;
+
+/// Incoming requests queued per peer.
+///
+/// Peers are served round-robin, so that a peer flooding requests can't delay the requests of
+/// other peers.
+#[derive(Debug)]
+struct PeerRequestQueues<R> {
+    /// Queued requests per peer.
+    queues: HashMap<PeerId, VecDeque<R>>,
+    /// Peers with queued requests, in the order they are served.
+    order: VecDeque<PeerId>,
+    /// Number of requests taken per peer in the current poll.
+    taken: HashMap<PeerId, usize>,
+    /// Total number of queued requests.
+    len: usize,
+}
+
+impl<R> PeerRequestQueues<R> {
+    /// Queues the request of the peer, or returns it if the queue of the peer is full.
+    fn push(&mut self, peer_id: PeerId, request: R) -> Result<(), R> {
+        let queue = self.queues.entry(peer_id).or_insert_with(|| {
+            self.order.push_back(peer_id);
+            VecDeque::new()
+        });
+        if queue.len() >= MAX_QUEUED_REQUESTS_PER_PEER {
+            return Err(request)
+        }
+        queue.push_back(request);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Takes the next request round-robin, skipping peers that already had
+    /// [`MAX_REQUESTS_PER_PEER_PER_POLL`] requests taken in the current poll.
+    fn pop(&mut self) -> Option<R> {
+        for _ in 0..self.order.len() {
+            let peer_id = self.order.pop_front()?;
+            let taken = self.taken.entry(peer_id).or_default();
+            if *taken >= MAX_REQUESTS_PER_PEER_PER_POLL {
+                self.order.push_back(peer_id);
+                continue
+            }
+            *taken += 1;
+
+            // peers in the order always have queued requests
+            let queue = self.queues.get_mut(&peer_id)?;
+            let request = queue.pop_front()?;
+            if queue.is_empty() {
+                self.queues.remove(&peer_id);
+            } else {
+                self.order.push_back(peer_id);
+            }
+            self.len -= 1;
+            return Some(request)
+        }
+        None
+    }
+
+    /// Starts a new poll, resetting the number of requests taken per peer.
+    fn start_poll(&mut self) {
+        self.taken.clear();
+    }
+
+    /// Returns the total number of queued requests.
+    const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no requests are queued.
+    const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<R> Default for PeerRequestQueues<R> {
+    fn default() -> Self {
+        Self {
+            queues: HashMap::default(),
+            order: VecDeque::new(),
+            taken: HashMap::default(),
+            len: 0,
+        }
+    }
+}
+
 /// Manages eth related requests on top of the p2p network.
 ///
-/// This can be spawned to another task and is supposed to be run as background service.
+/// Requests are served on blocking tasks, so that slow storage reads don't stall the handler.
+///
+/// This can be spawned to another task and is supposed to be run as background service. It shuts
+/// down once the channel of incoming requests is closed and the remaining requests are served.
 #[derive(Debug)]
 #[must_use = "Manager does nothing unless polled."]
 pub struct EthRequestHandler<C, N: NetworkPrimitives = EthNetworkPrimitives> {
-    /// The client type that can interact with the chain.
-    client: C,
-    /// Used for reporting peers.
-    // TODO use to report spammers
-    #[expect(dead_code)]
+    /// Serves the requests from storage.
+    server: EthRequestServer<C, N>,
+    /// Used for reporting peers that spam requests.
     peers: PeersHandle,
     /// Incoming request from the [`NetworkManager`](crate::NetworkManager).
     incoming_requests: ReceiverStream<IncomingEthRequest<N>>,
+    /// Incoming requests that wait to be served, per peer.
+    queued_requests: PeerRequestQueues<IncomingEthRequest<N>>,
+    /// Whether the channel of incoming requests is closed.
+    incoming_closed: bool,
+    /// Requests that are being served.
+    in_flight: FuturesUnordered<InFlightRequest>,
+    /// Number of requests being served per peer.
+    in_flight_per_peer: HashMap<PeerId, usize>,
+    /// Blocks that are no longer canonical, evicted from the caches.
+    reverted_blocks: Option<RevertedBlocks>,
+    /// Requests received per peer in the current window.
+    request_windows: HashMap<PeerId, PeerRequestWindow>,
+    /// Number of requests a peer can send per second before it's reported.
+    max_requests_per_second: u32,
+    /// Number of incoming requests that are handled per poll.
+    budget: u32,
     /// Metrics for the eth request handler.
     metrics: EthRequestHandlerMetrics,
 }
@@ -69,248 +371,558 @@ pub struct EthRequestHandler<C, N: NetworkPrimitives = EthNetworkPrimitives> {
 // === impl EthRequestHandler ===
 impl<C, N: NetworkPrimitives> EthRequestHandler<C, N> {
     /// Create a new instance
-    pub fn new(client: C, peers: PeersHandle, incoming: Receiver<IncomingEthRequest<N>>) -> Self {
+    ///
+    /// # Panics
+    ///
+    /// If the config is invalid, see [`EthRequestHandlerConfig::validate`].
+    pub fn new(
+        client: C,
+        peers: PeersHandle,
+        incoming: Receiver<IncomingEthRequest<N>>,
+        config: EthRequestHandlerConfig,
+    ) -> Self {
+        if let Err(err) = config.validate() {
+            panic!("invalid eth request handler config: {err}")
+        }
+        let metrics = EthRequestHandlerMetrics::default();
         Self {
-            client,
+            server: EthRequestServer {
+                client,
+                config,
+                header_cache: Arc::new(Mutex::new(LruMap::new_by_encoded_length(
+                    config.header_cache_bytes,
+                ))),
+                body_cache: Arc::new(Mutex::new(LruMap::new_by_encoded_length(
+                    config.body_cache_bytes,
+                ))),
+                receipts_cache: Arc::new(Mutex::new(LruMap::new_by_encoded_length(
+                    config.receipts_cache_bytes,
+                ))),
+                listener: Arc::new(NoopEthRequestListener),
+                metrics: metrics.clone(),
+            },
             peers,
             incoming_requests: ReceiverStream::new(incoming),
-            metrics: Default::default(),
+            queued_requests: PeerRequestQueues::default(),
+            incoming_closed: false,
+            in_flight: FuturesUnordered::new(),
+            in_flight_per_peer: HashMap::default(),
+            reverted_blocks: None,
+            request_windows: HashMap::default(),
+            max_requests_per_second: DEFAULT_MAX_REQUESTS_PER_PEER_PER_SECOND,
+            budget: DEFAULT_BUDGET_TRY_DRAIN_ETH_REQUESTS,
+            metrics,
         }
     }
-}
 
-impl<C, N> EthRequestHandler<C, N>
-where
-    N: NetworkPrimitives,
-    C: BlockReader,
-{
-    /// Returns the list of requested headers
-    fn get_headers_response(&self, request: GetBlockHeaders) -> Vec<C::Header> {
-        let GetBlockHeaders { start_block, limit, skip, direction } = request;
+    /// Sets the number of incoming requests that are handled per poll, before the handler yields
+    /// to other tasks. A larger budget serves requests faster at the cost of fairness towards other
+    /// tasks of the network.
+    ///
+    /// Defaults to [`DEFAULT_BUDGET_TRY_DRAIN_ETH_REQUESTS`]. At least one request is handled per
+    /// poll. A single peer can take at most 4 requests of the budget per poll.
+    pub fn with_budget(mut self, budget: u32) -> Self {
+        self.budget = budget.max(1);
+        self
+    }
 
-        let mut headers = Vec::new();
+    /// Sets the number of requests a peer can send per second before it's reported as a spammer.
+    ///
+    /// Defaults to [`DEFAULT_MAX_REQUESTS_PER_PEER_PER_SECOND`].
+    pub const fn with_max_requests_per_second(mut self, max_requests_per_second: u32) -> Self {
+        self.max_requests_per_second = max_requests_per_second;
+        self
+    }
 
-        let mut block: BlockHashOrNumber = match start_block {
-            BlockHashOrNumber::Hash(start) => start.into(),
-            BlockHashOrNumber::Number(num) => {
-                let Some(hash) = self.client.block_hash(num).unwrap_or_default() else {
-                    return headers
-                };
-                hash.into()
+    /// Sets the listener that is notified about every served request.
+    pub fn with_listener<L: EthRequestListener>(mut self, listener: L) -> Self {
+        self.server.listener = Arc::new(listener);
+        self
+    }
+
+    /// Evicts the blocks yielded by the given stream from the caches of served data.
+    ///
+    /// Each item should hold the hashes of blocks that are no longer canonical, e.g. blocks that
+    /// were reorged out.
+    pub fn with_reverted_blocks<S>(mut self, reverted_blocks: S) -> Self
+    where
+        S: Stream<Item = Vec<B256>> + Send + 'static,
+    {
+        self.reverted_blocks = Some(RevertedBlocks(Box::pin(reverted_blocks)));
+        self
+    }
+
+    /// Evicts reverted blocks from the caches.
+    fn poll_reverted_blocks(&mut self, cx: &mut Context<'_>) {
+        let Some(reverted_blocks) = &mut self.reverted_blocks else { return };
+        loop {
+            match reverted_blocks.0.poll_next_unpin(cx) {
+                Poll::Ready(Some(hashes)) => self.server.evict(&hashes),
+                Poll::Ready(None) => {
+                    self.reverted_blocks = None;
+                    return
+                }
+                Poll::Pending => return,
             }
-        };
+        }
+    }
 
-        let skip = skip as u64;
-        let mut total_bytes = 0;
+    /// Returns the requests of the peer in the current window, starting a new window if the
+    /// previous one has passed.
+    fn request_window(&mut self, peer_id: PeerId) -> &mut PeerRequestWindow {
+        let now = Instant::now();
+        let window =
+            self.request_windows.entry(peer_id).or_insert_with(|| PeerRequestWindow::new(now));
+        if now.duration_since(window.started_at) >= REQUEST_RATE_WINDOW {
+            *window = PeerRequestWindow::new(now);
+        }
+        window
+    }
 
-        for _ in 0..limit {
-            if let Some(header) = self.client.header_by_hash_or_number(block).unwrap_or_default() {
-                let number = header.number();
-                let parent_hash = header.parent_hash();
+    /// Counts the request of the peer and reports the peer if it exceeded the allowed number of
+    /// requests per second.
+    fn on_peer_request(&mut self, peer_id: PeerId) {
+        let max_requests = self.max_requests_per_second;
+        let window = self.request_window(peer_id);
+        window.requests += 1;
+        if window.requests > max_requests {
+            self.report_spammer(peer_id, "exceeded request rate limit");
+        }
+    }
 
-                total_bytes += header.length();
-                headers.push(header);
+    /// Counts a request of the peer that asked for data the node doesn't have and reports the peer
+    /// if it keeps doing so.
+    fn on_unavailable_request(&mut self, peer_id: PeerId) {
+        let window = self.request_window(peer_id);
+        window.unavailable += 1;
+        if window.unavailable > MAX_UNAVAILABLE_REQUESTS_PER_WINDOW {
+            self.report_spammer(peer_id, "repeatedly requested unavailable data");
+        }
+    }
 
-                if headers.len() >= MAX_HEADERS_SERVE || total_bytes > SOFT_RESPONSE_LIMIT {
-                    break
+    /// Reports the peer if it requested more than the protocol serve limit.
+    ///
+    /// The configured serve limits can be lower than the protocol limits, requests exceeding only
+    /// those are well-behaved and just truncated.
+    fn check_request_limit(
+        &mut self,
+        peer_id: PeerId,
+        requested: usize,
+        max: usize,
+        reason: &'static str,
+    ) {
+        if requested > max {
+            self.report_spammer(peer_id, reason);
+        }
+    }
+
+    /// Applies a reputation change to a peer that spams requests.
+    ///
+    /// A peer is reported at most once per window.
+    fn report_spammer(&mut self, peer_id: PeerId, reason: &'static str) {
+        let window = self.request_window(peer_id);
+        if std::mem::replace(&mut window.reported, true) {
+            return
+        }
+        trace!(target: "net::eth", %peer_id, reason, "reporting spamming peer");
+        self.metrics.eth_request_spammers_reported_total.increment(1);
+        self.peers.reputation_change(peer_id, ReputationChangeKind::BadMessage);
+    }
+
+    /// Returns `true` if the peer can't have more requests served concurrently.
+    fn is_peer_at_capacity(&self, peer_id: &PeerId) -> bool {
+        self.in_flight_per_peer.get(peer_id).copied().unwrap_or_default() >=
+            self.server.config.max_concurrent_requests_per_peer
+    }
+
+    /// Returns the number of requests waiting to be served.
+    fn queue_depth(&self) -> usize {
+        self.incoming_requests.as_ref().len() + self.queued_requests.len()
+    }
+
+    /// Moves up to [`MAX_INCOMING_REQUESTS_PER_POLL`] incoming requests into the queues of their
+    /// peers. Further requests of a peer with a full queue are dropped and the peer is reported.
+    ///
+    /// Returns `true` if there may be more incoming requests.
+    fn poll_incoming(&mut self, cx: &mut Context<'_>) -> bool {
+        let mut budget = MAX_INCOMING_REQUESTS_PER_POLL;
+        loop {
+            let incoming = match self.incoming_requests.poll_next_unpin(cx) {
+                Poll::Ready(Some(incoming)) => incoming,
+                Poll::Ready(None) => {
+                    self.incoming_closed = true;
+                    return false
                 }
+                Poll::Pending => return false,
+            };
 
-                match direction {
-                    HeadersDirection::Rising => {
-                        if let Some(next) = number.checked_add(1).and_then(|n| n.checked_add(skip))
-                        {
-                            block = next.into()
-                        } else {
-                            break
-                        }
-                    }
-                    HeadersDirection::Falling => {
-                        if skip > 0 {
-                            // prevent under flows for block.number == 0 and `block.number - skip <
-                            // 0`
-                            if let Some(next) =
-                                number.checked_sub(1).and_then(|num| num.checked_sub(skip))
-                            {
-                                block = next.into()
-                            } else {
-                                break
-                            }
-                        } else {
-                            block = parent_hash.into()
-                        }
-                    }
+            let peer_id = *incoming.peer_id();
+            if self.queued_requests.push(peer_id, incoming).is_err() {
+                trace!(target: "net::eth", %peer_id, "dropping request, too many requests queued");
+                self.metrics.eth_requests_rejected_queue_full_total.increment(1);
+                self.report_spammer(peer_id, "exceeded request queue");
+            }
+
+            budget -= 1;
+            if budget == 0 {
+                return true
+            }
+        }
+    }
+
+    /// Returns `true` if no more requests can be served concurrently.
+    fn is_at_capacity(&self) -> bool {
+        self.in_flight.len() >= self.server.config.max_concurrent_requests
+    }
+
+    /// Returns the number of response bytes the peer can still be served in the current window,
+    /// or `None` if bandwidth isn't limited.
+    fn remaining_bandwidth(&mut self, peer_id: PeerId) -> Option<usize> {
+        let max_bytes = self.server.config.max_bytes_per_peer_per_second?;
+        Some(max_bytes.saturating_sub(self.request_window(peer_id).bytes))
+    }
+
+    /// Polls the requests that are being served and accounts for the served bytes and requests
+    /// that asked for data the node doesn't have.
+    ///
+    /// Returns `true` if any request was served.
+    fn poll_in_flight(&mut self, cx: &mut Context<'_>) -> bool {
+        let mut served_any = false;
+        while let Poll::Ready(Some((peer_id, served))) = self.in_flight.poll_next_unpin(cx) {
+            served_any = true;
+            if let Entry::Occupied(mut entry) = self.in_flight_per_peer.entry(peer_id) {
+                *entry.get_mut() -= 1;
+                if *entry.get() == 0 {
+                    entry.remove();
                 }
-            } else {
-                break
+            }
+            self.request_window(peer_id).bytes += served.bytes;
+            if served.unavailable {
+                self.on_unavailable_request(peer_id);
             }
         }
+        self.metrics.eth_requests_in_flight.set(self.in_flight.len() as f64);
+        served_any
+    }
 
-        headers
+    /// Removes the request windows of peers that have passed.
+    fn prune_request_windows(&mut self) {
+        let now = Instant::now();
+        self.request_windows
+            .retain(|_, window| now.duration_since(window.started_at) < REQUEST_RATE_WINDOW);
+    }
+}
+
+impl<C, N> EthRequestHandler<C, N>
+where
+    N: NetworkPrimitives,
+    C: BlockReader<Block = N::Block, Header = N::BlockHeader, Receipt = N::Receipt>
+        + StateProviderFactory
+        + Clone
+        + 'static,
+{
+    /// Returns the `eth/69` response to the receipts request, served on the current thread.
+    ///
+    /// Bypasses the request queue and the per-peer limits, used for benchmarking.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn receipts69_response(&self, request: &GetReceipts) -> Receipts69<C::Receipt> {
+        let (receipts, _) =
+            self.server.get_receipts_response(&request.0, |hash| self.server.receipts(hash));
+        Receipts69(receipts)
+    }
+
+    /// Serves the request on a blocking task.
+    ///
+    /// The response is limited to the remaining bandwidth budget of the peer.
+    fn spawn_request<F>(&mut self, peer_id: PeerId, kind: EthRequestKind, serve: F)
+    where
+        F: FnOnce(&EthRequestServer<C, N>) -> ServedRequest + Send + 'static,
+    {
+        let mut server = self.server.clone();
+        if let Some(remaining) = self.remaining_bandwidth(peer_id) {
+            server.config.soft_response_limit = server.config.soft_response_limit.min(remaining);
+        }
+
+        let task = tokio::task::spawn_blocking(move || {
+            let started_at = std::time::Instant::now();
+            let served = serve(&server);
+            let elapsed = started_at.elapsed();
+            server.metrics.eth_request_service_duration_seconds.record(elapsed.as_secs_f64());
+            server.listener.on_request_served(&RequestObservation {
+                peer_id,
+                kind,
+                items: served.items,
+                bytes: served.bytes,
+                elapsed,
+            });
+            served
+        });
+
+        *self.in_flight_per_peer.entry(peer_id).or_default() += 1;
+        self.in_flight.push(InFlightRequest { peer_id, task });
+        self.metrics.eth_requests_in_flight.set(self.in_flight.len() as f64);
     }
 
     fn on_headers_request(
-        &self,
-        _peer_id: PeerId,
+        &mut self,
+        peer_id: PeerId,
         request: GetBlockHeaders,
         response: oneshot::Sender<RequestResult<BlockHeaders<C::Header>>>,
     ) {
         self.metrics.eth_headers_requests_received_total.increment(1);
-        let headers = self.get_headers_response(request);
-        let _ = response.send(Ok(BlockHeaders(headers)));
+        let requested = request.limit.try_into().unwrap_or(usize::MAX);
+        self.check_request_limit(
+            peer_id,
+            requested,
+            MAX_HEADERS_SERVE,
+            "requested too many headers",
+        );
+
+        self.spawn_request(peer_id, EthRequestKind::Headers, move |server| {
+            let headers = server.get_headers_response(request);
+            let served = ServedRequest {
+                unavailable: headers.is_empty(),
+                items: headers.len(),
+                bytes: headers.length(),
+            };
+            server.listener.on_headers_request(peer_id, &request, headers.len(), served.bytes);
+            let _ = response.send(Ok(BlockHeaders(headers)));
+            served
+        });
+    }
+
+    fn on_node_data_request(
+        &mut self,
+        peer_id: PeerId,
+        request: GetNodeData,
+        response: oneshot::Sender<RequestResult<NodeData>>,
+    ) {
+        self.metrics.eth_node_data_requests_received_total.increment(1);
+
+        self.spawn_request(peer_id, EthRequestKind::NodeData, move |server| {
+            let node_data = server.get_node_data_response(&request);
+            let served = ServedRequest {
+                unavailable: false,
+                items: node_data.len(),
+                bytes: node_data.length(),
+            };
+            server.listener.on_node_data_request(peer_id, &request, node_data.len(), served.bytes);
+            let _ = response.send(Ok(NodeData(node_data)));
+            served
+        });
     }
 
     fn on_bodies_request(
-        &self,
-        _peer_id: PeerId,
+        &mut self,
+        peer_id: PeerId,
         request: GetBlockBodies,
         response: oneshot::Sender<RequestResult<BlockBodies<<C::Block as Block>::Body>>>,
     ) {
         self.metrics.eth_bodies_requests_received_total.increment(1);
-        let mut bodies = Vec::new();
-
-        let mut total_bytes = 0;
-
-        for hash in request.0 {
-            if let Some(block) = self.client.block_by_hash(hash).unwrap_or_default() {
-                let body = block.into_body();
-                total_bytes += body.length();
-                bodies.push(body);
-
-                if bodies.len() >= MAX_BODIES_SERVE || total_bytes > SOFT_RESPONSE_LIMIT {
-                    break
-                }
-            } else {
-                break
-            }
-        }
+        let requested = request.0.len();
+        self.check_request_limit(peer_id, requested, MAX_BODIES_SERVE, "requested too many bodies");
 
-        let _ = response.send(Ok(BlockBodies(bodies)));
+        self.spawn_request(peer_id, EthRequestKind::Bodies, move |server| {
+            let (bodies, skipped) = server.get_bodies_response(&request.0);
+            let served = ServedRequest {
+                unavailable: server.on_unknown_hashes(bodies.len(), skipped),
+                items: bodies.len(),
+                bytes: bodies.length(),
+            };
+            server.listener.on_bodies_request(peer_id, &request, bodies.len(), served.bytes);
+            let _ = response.send(Ok(BlockBodies(bodies)));
+            served
+        });
     }
 
     fn on_receipts_request(
-        &self,
-        _peer_id: PeerId,
+        &mut self,
+        peer_id: PeerId,
         request: GetReceipts,
         response: oneshot::Sender<RequestResult<Receipts<C::Receipt>>>,
     ) {
         self.metrics.eth_receipts_requests_received_total.increment(1);
+        let requested = request.0.len();
+        self.check_request_limit(
+            peer_id,
+            requested,
+            MAX_RECEIPTS_SERVE,
+            "requested too many receipts",
+        );
 
-        let receipts = self.get_receipts_response(request, |receipts_by_block| {
-            receipts_by_block.into_iter().map(ReceiptWithBloom::from).collect::<Vec<_>>()
+        self.spawn_request(peer_id, EthRequestKind::Receipts, move |server| {
+            let (receipts, skipped) =
+                server.get_receipts_response(&request.0, |hash| server.receipts_with_bloom(hash));
+            let served = ServedRequest {
+                unavailable: server.on_unknown_hashes(receipts.len(), skipped),
+                items: receipts.len(),
+                bytes: receipts.length(),
+            };
+            server.listener.on_receipts_request(peer_id, &request, receipts.len(), served.bytes);
+            let _ = response.send(Ok(Receipts(receipts)));
+            served
         });
-
-        let _ = response.send(Ok(Receipts(receipts)));
     }
 
     fn on_receipts69_request(
-        &self,
-        _peer_id: PeerId,
+        &mut self,
+        peer_id: PeerId,
         request: GetReceipts,
         response: oneshot::Sender<RequestResult<Receipts69<C::Receipt>>>,
     ) {
         self.metrics.eth_receipts_requests_received_total.increment(1);
+        let requested = request.0.len();
+        self.check_request_limit(
+            peer_id,
+            requested,
+            MAX_RECEIPTS_SERVE,
+            "requested too many receipts",
+        );
 
-        let receipts = self.get_receipts_response(request, |receipts_by_block| {
+        self.spawn_request(peer_id, EthRequestKind::Receipts, move |server| {
             // skip bloom filter for eth69
-            receipts_by_block
+            let (receipts, skipped) =
+                server.get_receipts_response(&request.0, |hash| server.receipts(hash));
+            let served = ServedRequest {
+                unavailable: server.on_unknown_hashes(receipts.len(), skipped),
+                items: receipts.len(),
+                bytes: receipts.length(),
+            };
+            server.listener.on_receipts_request(peer_id, &request, receipts.len(), served.bytes);
+            let _ = response.send(Ok(Receipts69(receipts)));
+            served
         });
-
-        let _ = response.send(Ok(Receipts69(receipts)));
     }
 
-    /// Handles partial responses for [`GetReceipts70`] queries.
-    ///
-    /// This will adhere to the soft limit but allow filling the last vec partially.
     fn on_receipts70_request(
-        &self,
-        _peer_id: PeerId,
+        &mut self,
+        peer_id: PeerId,
         request: GetReceipts70,
         response: oneshot::Sender<RequestResult<Receipts70<C::Receipt>>>,
     ) {
         self.metrics.eth_receipts_requests_received_total.increment(1);
+        let requested = request.block_hashes.len();
+        self.check_request_limit(
+            peer_id,
+            requested,
+            MAX_RECEIPTS_SERVE,
+            "requested too many receipts",
+        );
 
-        let GetReceipts70 { first_block_receipt_index, block_hashes } = request;
-
-        let mut receipts = Vec::new();
-        let mut total_bytes = 0usize;
-        let mut last_block_incomplete = false;
+        self.spawn_request(peer_id, EthRequestKind::Receipts, move |server| {
+            let receipts = server.get_receipts70_response(&request);
+            let served = ServedRequest {
+                unavailable: false,
+                items: receipts.receipts.len(),
+                bytes: receipts.receipts.length(),
+            };
+            server.listener.on_receipts70_request(peer_id, &request, served.items, served.bytes);
+            let _ = response.send(Ok(receipts));
+            served
+        });
+    }
 
-        for (idx, hash) in block_hashes.into_iter().enumerate() {
-            if idx >= MAX_RECEIPTS_SERVE {
-                break
-            }
+    fn on_receipts_by_range_request(
+        &mut self,
+        peer_id: PeerId,
+        start: u64,
+        count: u64,
+        response: oneshot::Sender<RequestResult<Receipts69<C::Receipt>>>,
+    ) {
+        self.metrics.eth_receipts_requests_received_total.increment(1);
+        if count > self.server.config.max_receipts as u64 {
+            self.report_spammer(peer_id, "requested too many receipts");
+        }
 
-            let Some(mut block_receipts) =
-                self.client.receipts_by_block(BlockHashOrNumber::Hash(hash)).unwrap_or_default()
-            else {
-                break
+        self.spawn_request(peer_id, EthRequestKind::ReceiptsByRange, move |server| {
+            let receipts = server.get_receipts_by_range_response(start, count);
+            let served = ServedRequest {
+                unavailable: receipts.is_empty() && count > 0,
+                items: receipts.len(),
+                bytes: receipts.length(),
             };
+            server.listener.on_receipts_by_range_request(
+                peer_id,
+                start,
+                count,
+                receipts.len(),
+                served.bytes,
+            );
+            let _ = response.send(Ok(Receipts69(receipts)));
+            served
+        });
+    }
+}
 
-            if idx == 0 && first_block_receipt_index > 0 {
-                let skip = first_block_receipt_index as usize;
-                if skip >= block_receipts.len() {
-                    block_receipts.clear();
-                } else {
-                    block_receipts.drain(0..skip);
-                }
-            }
-
-            let block_size = block_receipts.length();
-
-            if total_bytes + block_size <= SOFT_RESPONSE_LIMIT {
-                total_bytes += block_size;
-                receipts.push(block_receipts);
-                continue;
-            }
-
-            let mut partial_block = Vec::new();
-            for receipt in block_receipts {
-                let receipt_size = receipt.length();
-                if total_bytes + receipt_size > SOFT_RESPONSE_LIMIT {
-                    break;
-                }
-                total_bytes += receipt_size;
-                partial_block.push(receipt);
-            }
+impl<C, N> EthRequestHandler<C, N>
+where
+    N: NetworkPrimitives,
+    C: BlockReader<Block = N::Block, Receipt = N::Receipt>
+        + HeaderProvider<Header = N::BlockHeader>
+        + StateProviderFactory
+        + Clone
+        + 'static,
+{
+    /// Serves the incoming request.
+    ///
+    /// Requests of a peer that has too many requests in flight or used up its bandwidth budget, and
+    /// expensive requests while the queue is backed up are answered with an empty response.
+    fn on_incoming_request(&mut self, incoming: IncomingEthRequest<N>) {
+        let peer_id = *incoming.peer_id();
+        self.on_peer_request(peer_id);
 
-            receipts.push(partial_block);
-            last_block_incomplete = true;
-            break;
+        if self.is_peer_at_capacity(&peer_id) {
+            trace!(target: "net::eth", %peer_id, "too many requests in flight, sending empty response");
+            self.metrics.eth_requests_rejected_peer_limit_total.increment(1);
+            incoming.respond_empty();
+            return
         }
 
-        let _ = response.send(Ok(Receipts70 { last_block_incomplete, receipts }));
-    }
-
-    #[inline]
-    fn get_receipts_response<T, F>(&self, request: GetReceipts, transform_fn: F) -> Vec<Vec<T>>
-    where
-        F: Fn(Vec<C::Receipt>) -> Vec<T>,
-        T: Encodable,
-    {
-        let mut receipts = Vec::new();
-        let mut total_bytes = 0;
+        if self.remaining_bandwidth(peer_id) == Some(0) {
+            trace!(target: "net::eth", %peer_id, "bandwidth budget exceeded, sending empty response");
+            self.metrics.eth_requests_rejected_bandwidth_total.increment(1);
+            incoming.respond_empty();
+            return
+        }
 
-        for hash in request.0 {
-            if let Some(receipts_by_block) =
-                self.client.receipts_by_block(BlockHashOrNumber::Hash(hash)).unwrap_or_default()
-            {
-                let transformed_receipts = transform_fn(receipts_by_block);
-                total_bytes += transformed_receipts.length();
-                receipts.push(transformed_receipts);
+        if incoming.is_expensive() &&
+            self.queue_depth() > self.server.config.shed_requests_queue_depth
+        {
+            trace!(target: "net::eth", %peer_id, "queue backed up, sending empty response");
+            self.metrics.eth_requests_shed_total.increment(1);
+            incoming.respond_empty();
+            return
+        }
 
-                if receipts.len() >= MAX_RECEIPTS_SERVE || total_bytes > SOFT_RESPONSE_LIMIT {
-                    break
-                }
-            } else {
-                break
+        match incoming {
+            IncomingEthRequest::GetBlockHeaders { peer_id, request, response } => {
+                self.on_headers_request(peer_id, request, response)
+            }
+            IncomingEthRequest::GetBlockBodies { peer_id, request, response } => {
+                self.on_bodies_request(peer_id, request, response)
+            }
+            IncomingEthRequest::GetNodeData { peer_id, request, response } => {
+                self.on_node_data_request(peer_id, request, response)
+            }
+            IncomingEthRequest::GetReceipts { peer_id, request, response } => {
+                self.on_receipts_request(peer_id, request, response)
+            }
+            IncomingEthRequest::GetReceipts69 { peer_id, request, response } => {
+                self.on_receipts69_request(peer_id, request, response)
+            }
+            IncomingEthRequest::GetReceipts70 { peer_id, request, response } => {
+                self.on_receipts70_request(peer_id, request, response)
+            }
+            IncomingEthRequest::GetReceiptsByRange { peer_id, start, count, response } => {
+                self.on_receipts_by_range_request(peer_id, start, count, response)
             }
         }
-
-        receipts
     }
 }
 
-/// An endless future.
+/// A future that serves requests until the channel of incoming requests is closed.
+///
+/// Once closed, the remaining queued and in flight requests are served before it resolves.
 ///
 /// This should be spawned or used as part of `tokio::select!`.
 impl<C, N> Future for EthRequestHandler<C, N>
@@ -318,48 +930,57 @@ where
     N: NetworkPrimitives,
     C: BlockReader<Block = N::Block, Receipt = N::Receipt>
         + HeaderProvider<Header = N::BlockHeader>
-        + Unpin,
+        + StateProviderFactory
+        + Clone
+        + Unpin
+        + 'static,
 {
     type Output = ();
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
 
+        // resumed once a request in flight is served
+        let maybe_more_incoming_requests = !this.is_at_capacity() && this.poll_incoming(cx);
+
+        this.metrics.eth_request_queue_depth.set(this.queue_depth() as f64);
+
+        this.queued_requests.start_poll();
+        let budget = this.budget;
         let mut acc = Duration::ZERO;
-        let maybe_more_incoming_requests = metered_poll_nested_stream_with_budget!(
+        let maybe_more_queued_requests = metered_poll_nested_stream_with_budget!(
             acc,
             "net::eth",
-            "Incoming eth requests stream",
-            DEFAULT_BUDGET_TRY_DRAIN_DOWNLOADERS,
-            this.incoming_requests.poll_next_unpin(cx),
-            |incoming| {
-                match incoming {
-                    IncomingEthRequest::GetBlockHeaders { peer_id, request, response } => {
-                        this.on_headers_request(peer_id, request, response)
-                    }
-                    IncomingEthRequest::GetBlockBodies { peer_id, request, response } => {
-                        this.on_bodies_request(peer_id, request, response)
-                    }
-                    IncomingEthRequest::GetNodeData { .. } => {
-                        this.metrics.eth_node_data_requests_received_total.increment(1);
-                    }
-                    IncomingEthRequest::GetReceipts { peer_id, request, response } => {
-                        this.on_receipts_request(peer_id, request, response)
-                    }
-                    IncomingEthRequest::GetReceipts69 { peer_id, request, response } => {
-                        this.on_receipts69_request(peer_id, request, response)
-                    }
-                    IncomingEthRequest::GetReceipts70 { peer_id, request, response } => {
-                        this.on_receipts70_request(peer_id, request, response)
-                    }
-                }
+            "Queued eth requests",
+            budget,
+            if this.is_at_capacity() {
+                Poll::Pending
+            } else {
+                this.queued_requests
+                    .pop()
+                    .map_or(Poll::Pending, |incoming| Poll::Ready(Some(incoming)))
             },
+            |incoming| this.on_incoming_request(incoming),
         );
 
         this.metrics.acc_duration_poll_eth_req_handler.set(acc.as_secs_f64());
 
+        let served_any = this.poll_in_flight(cx);
+
+        this.poll_reverted_blocks(cx);
+
+        this.prune_request_windows();
+
+        if this.incoming_closed && this.queued_requests.is_empty() && this.in_flight.is_empty() {
+            trace!(target: "net::eth", "incoming requests closed, shutting down");
+            return Poll::Ready(())
+        }
+
         // stream is fully drained and import futures pending
-        if maybe_more_incoming_requests {
+        if maybe_more_incoming_requests ||
+            maybe_more_queued_requests ||
+            (!this.is_at_capacity() && (served_any || !this.queued_requests.is_empty()))
+        {
             // make sure we're woken up again
             cx.waker().wake_by_ref();
         }
@@ -368,73 +989,2653 @@ where
     }
 }
 
-/// All `eth` request related to blocks delegated by the network.
+/// Serves eth requests from storage.
+///
+/// This is cheap to clone, so that requests can be served on blocking tasks.
 #[derive(Debug)]
-pub enum IncomingEthRequest<N: NetworkPrimitives = EthNetworkPrimitives> {
-    /// Request Block headers from the peer.
+struct EthRequestServer<C, N: NetworkPrimitives> {
+    /// The client type that can interact with the chain.
+    client: C,
+    /// Limits for serving requests.
+    config: EthRequestHandlerConfig,
+    /// Recently served headers by hash.
     ///
-    /// The response should be sent through the channel.
-    GetBlockHeaders {
-        /// The ID of the peer to request block headers from.
-        peer_id: PeerId,
-        /// The specific block headers requested.
-        request: GetBlockHeaders,
-        /// The channel sender for the response containing block headers.
-        response: oneshot::Sender<RequestResult<BlockHeaders<N::BlockHeader>>>,
-    },
-    /// Request Block bodies from the peer.
+    /// Headers looked up by number aren't cached, since the canonical header of a number can
+    /// change on reorgs.
+    header_cache: Arc<Mutex<LruMap<B256, N::BlockHeader, ByEncodedLength>>>,
+    /// Recently served block bodies by block hash.
+    body_cache: Arc<Mutex<LruMap<B256, N::BlockBody, ByEncodedLength>>>,
+    /// Recently served receipts with their blooms by block hash.
+    receipts_cache: Arc<Mutex<LruMap<B256, Vec<ReceiptWithBloom<N::Receipt>>, ByEncodedLength>>>,
+    /// Notified about every served request.
+    listener: Arc<dyn EthRequestListener>,
+    /// Metrics for the eth request handler.
+    metrics: EthRequestHandlerMetrics,
+}
+
+impl<C: Clone, N: NetworkPrimitives> Clone for EthRequestServer<C, N> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            config: self.config,
+            header_cache: self.header_cache.clone(),
+            body_cache: self.body_cache.clone(),
+            receipts_cache: self.receipts_cache.clone(),
+            listener: self.listener.clone(),
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+// === impl EthRequestServer ===
+impl<C, N: NetworkPrimitives> EthRequestServer<C, N> {
+    /// Returns the result of a storage lookup, treating a failed read like missing data so that the
+    /// response is terminated gracefully.
     ///
-    /// The response should be sent through the channel.
-    GetBlockBodies {
-        /// The ID of the peer to request block bodies from.
-        peer_id: PeerId,
-        /// The specific block bodies requested.
-        request: GetBlockBodies,
-        /// The channel sender for the response containing block bodies.
-        response: oneshot::Sender<RequestResult<BlockBodies<N::BlockBody>>>,
-    },
-    /// Request Node Data from the peer.
+    /// Read errors are logged and recorded, so that storage failures aren't mistaken for data the
+    /// node doesn't have.
+    fn lookup<T>(&self, res: ProviderResult<Option<T>>, id: impl fmt::Debug) -> Option<T> {
+        res.unwrap_or_else(|err| {
+            warn!(target: "net::eth", ?id, %err, "failed to read requested data from storage");
+            self.metrics.eth_request_storage_errors_total.increment(1);
+            None
+        })
+    }
+
+    /// Records the requested hashes the node didn't have.
     ///
-    /// The response should be sent through the channel.
-    GetNodeData {
-        /// The ID of the peer to request node data from.
-        peer_id: PeerId,
-        /// The specific node data requested.
-        request: GetNodeData,
-        /// The channel sender for the response containing node data.
-        response: oneshot::Sender<RequestResult<NodeData>>,
-    },
-    /// Request Receipts from the peer.
+    /// Returns `true` if most of the requested data was unknown.
+    fn on_unknown_hashes(&self, served: usize, skipped: usize) -> bool {
+        if skipped == 0 {
+            return false
+        }
+        self.metrics.eth_request_unknown_hashes_total.increment(skipped as u64);
+        skipped > served
+    }
+
+    /// Returns the cached value of the block or fetches and caches it.
+    fn cached<V: Clone + Encodable>(
+        &self,
+        cache: &Mutex<LruMap<B256, V, ByEncodedLength>>,
+        hash: B256,
+        fetch: impl FnOnce() -> Option<V>,
+    ) -> Option<V> {
+        if let Some(value) = cache.lock().get(&hash) {
+            self.metrics.eth_request_cache_hits_total.increment(1);
+            return Some(value.clone())
+        }
+        self.metrics.eth_request_cache_misses_total.increment(1);
+        let value = fetch()?;
+        cache.lock().insert(hash, value.clone());
+        Some(value)
+    }
+
+    /// Removes the given blocks from the caches.
+    fn evict(&self, hashes: &[B256]) {
+        let mut header_cache = self.header_cache.lock();
+        let mut body_cache = self.body_cache.lock();
+        let mut receipts_cache = self.receipts_cache.lock();
+        for hash in hashes {
+            header_cache.remove(hash);
+            body_cache.remove(hash);
+            receipts_cache.remove(hash);
+        }
+    }
+}
+
+impl<C, N> EthRequestServer<C, N>
+where
+    N: NetworkPrimitives,
+    C: BlockReader<Block = N::Block, Header = N::BlockHeader, Receipt = N::Receipt>
+        + StateProviderFactory,
+{
+    /// Returns the header of the given block, consulting the header cache for lookups by hash.
+    fn header(&self, block: BlockHashOrNumber) -> Option<C::Header> {
+        let BlockHashOrNumber::Hash(hash) = block else {
+            return self.lookup(self.client.header_by_hash_or_number(block), block)
+        };
+        self.cached(&self.header_cache, hash, || self.lookup(self.client.header(hash), hash))
+    }
+
+    /// Returns the body of the given block, consulting the body cache.
+    fn body(&self, hash: B256) -> Option<N::BlockBody> {
+        self.cached(&self.body_cache, hash, || {
+            self.lookup(self.client.block_by_hash(hash), hash).map(|block| block.into_body())
+        })
+    }
+
+    /// Returns the receipts of the given block.
+    fn receipts(&self, hash: B256) -> Option<Vec<C::Receipt>> {
+        self.lookup(self.client.receipts_by_block(hash.into()), hash)
+    }
+
+    /// Returns `true` if the node has the header of the given block and the block has no receipts.
     ///
-    /// The response should be sent through the channel.
-    GetReceipts {
-        /// The ID of the peer to request receipts from.
-        peer_id: PeerId,
-        /// The specific receipts requested.
-        request: GetReceipts,
-        /// The channel sender for the response containing receipts.
-        response: oneshot::Sender<RequestResult<Receipts<N::Receipt>>>,
-    },
-    /// Request Receipts from the peer without bloom filter.
+    /// Used to answer blocks without receipts even if receipts have been pruned. The receipts of
+    /// other pruned blocks can't be served, since an empty entry would contradict their header.
+    fn has_empty_receipts(&self, block: BlockHashOrNumber) -> bool {
+        self.header(block).is_some_and(|header| header.receipts_root() == EMPTY_ROOT_HASH)
+    }
+
+    /// Returns the receipts of the given block with their blooms, consulting the receipts cache.
     ///
-    /// The response should be sent through the channel.
-    GetReceipts69 {
-        /// The ID of the peer to request receipts from.
-        peer_id: PeerId,
-        /// The specific receipts requested.
-        request: GetReceipts,
-        /// The channel sender for the response containing Receipts69.
-        response: oneshot::Sender<RequestResult<Receipts69<N::Receipt>>>,
-    },
-    /// Request Receipts from the peer using eth/70.
+    /// Blocks are looked up by hash, so cached receipts never change.
+    fn receipts_with_bloom(&self, hash: B256) -> Option<Vec<ReceiptWithBloom<C::Receipt>>> {
+        self.cached(&self.receipts_cache, hash, || {
+            let receipts = self.receipts(hash)?;
+            Some(receipts.into_iter().map(ReceiptWithBloom::from).collect())
+        })
+    }
+
+    /// Returns up to `count` consecutive headers starting at block `start`, until their total
+    /// encoded size exceeds `max_bytes`.
+    fn headers_range(&self, start: u64, count: u64, max_bytes: usize) -> Vec<C::Header> {
+        if count == 0 {
+            return Vec::new()
+        }
+        let range = start..start.saturating_add(count);
+        let headers = self
+            .lookup(self.client.headers_range_with_limit(range.clone(), max_bytes).map(Some), range)
+            .unwrap_or_default();
+
+        // stop at the first missing header, like a walk by number would
+        headers
+            .into_iter()
+            .zip(start..)
+            .take_while(|(header, number)| header.number() == *number)
+            .map(|(header, _)| header)
+            .collect()
+    }
+
+    /// Returns up to `count` consecutive headers ending at block `end`, in descending order.
+    fn headers_range_rev(&self, end: u64, count: u64) -> Vec<C::Header> {
+        if count == 0 {
+            return Vec::new()
+        }
+        let range = end.saturating_sub(count - 1)..=end;
+        let headers = self
+            .lookup(self.client.headers_range(range.clone()).map(Some), range)
+            .unwrap_or_default();
+
+        // stop at the first missing header, like a walk by number would
+        headers
+            .into_iter()
+            .rev()
+            .zip((0..=end).rev())
+            .take_while(|(header, number)| header.number() == *number)
+            .map(|(header, _)| header)
+            .collect()
+    }
+
+    /// Returns up to `count` headers, starting at block `end` and walking down `step` blocks at a
+    /// time, in descending order.
     ///
-    /// The response should be sent through the channel.
-    GetReceipts70 {
-        /// The ID of the peer to request receipts from.
-        peer_id: PeerId,
-        /// The specific receipts requested including the `firstBlockReceiptIndex`.
-        request: GetReceipts70,
-        /// The channel sender for the response containing Receipts70.
-        response: oneshot::Sender<RequestResult<Receipts70<N::Receipt>>>,
-    },
+    /// The skipped headers are read too, so this should only be used if the walk spans few blocks.
+    fn headers_range_rev_step(&self, end: u64, count: u64, step: u64) -> Vec<C::Header> {
+        let count = count.min(end / step + 1);
+        if count == 0 {
+            return Vec::new()
+        }
+        let range = end - (count - 1) * step..=end;
+        let headers = self
+            .lookup(self.client.headers_range(range.clone()).map(Some), range)
+            .unwrap_or_default();
+
+        // stop at the first missing header, like a walk by number would
+        let mut numbers = (0..count).map(|i| end - i * step).peekable();
+        let mut stepped = Vec::new();
+        for header in headers.into_iter().rev() {
+            let Some(&number) = numbers.peek() else { break };
+            if header.number() > number {
+                // skipped by the walk
+                continue
+            }
+            if header.number() < number {
+                break
+            }
+            stepped.push(header);
+            numbers.next();
+        }
+        stepped
+    }
+
+    /// Returns the list of requested headers.
+    ///
+    /// Walks without skips are read in a single range call. Rising walks from a block requested by
+    /// number don't need to resolve the start block first. Falling walks are only read by number if
+    /// the start block was requested by number, walks from a block requested by hash follow the
+    /// parent hashes, since the block might not be canonical. Walks with skips look up every header
+    /// on its own, except for falling walks that span at most
+    /// [`EthRequestHandlerConfig::max_headers`] blocks, those are read in a single range call
+    /// once the walk continues by number, skipped headers included.
+    fn get_headers_response(&self, request: GetBlockHeaders) -> Vec<C::Header> {
+        let GetBlockHeaders { start_block, limit, skip, direction } = request;
+
+        let mut headers = Vec::new();
+        let by_number = matches!(start_block, BlockHashOrNumber::Number(_));
+
+        let mut block: BlockHashOrNumber = match start_block {
+            BlockHashOrNumber::Hash(start) => start.into(),
+            // contiguous rising walks are read by number without resolving the hash first
+            BlockHashOrNumber::Number(num) if direction.is_rising() && skip == 0 => num.into(),
+            BlockHashOrNumber::Number(num) => {
+                let Some(hash) = self.lookup(self.client.block_hash(num), num) else {
+                    self.metrics.eth_headers_start_not_found_total.increment(1);
+                    self.metrics.record_headers_response(0, 0);
+                    return headers
+                };
+                hash.into()
+            }
+        };
+
+        let skip = skip as u64;
+        let requested = limit;
+        let limit = limit.min(self.config.max_headers as u64);
+        let mut total_bytes = 0;
+        let mut truncated = false;
+
+        for _ in 0..limit {
+            if let BlockHashOrNumber::Number(next) = block &&
+                direction.is_rising() &&
+                skip == 0
+            {
+                // the rest of the walk is contiguous, so it's read in one call
+                let remaining = limit - headers.len() as u64;
+                let max_bytes = self.config.soft_response_limit - total_bytes;
+                for header in self.headers_range(next, remaining, max_bytes) {
+                    total_bytes += header.length();
+                    headers.push(header);
+                }
+                truncated = total_bytes > self.config.soft_response_limit &&
+                    (headers.len() as u64) < requested;
+                break
+            }
+
+            if let Some(header) = self.header(block) {
+                let number = header.number();
+                let parent_hash = header.parent_hash();
+
+                total_bytes += header.length();
+                headers.push(header);
+
+                if total_bytes > self.config.soft_response_limit {
+                    truncated = (headers.len() as u64) < requested;
+                    break
+                }
+
+                match direction {
+                    HeadersDirection::Rising => {
+                        if let Some(next) = number.checked_add(1).and_then(|n| n.checked_add(skip))
+                        {
+                            block = next.into()
+                        } else {
+                            break
+                        }
+                    }
+                    HeadersDirection::Falling => {
+                        if skip > 0 {
+                            // prevent under flows for block.number == 0 and `block.number - skip <
+                            // 0`
+                            let Some(next) =
+                                number.checked_sub(1).and_then(|num| num.checked_sub(skip))
+                            else {
+                                break
+                            };
+
+                            // the walk continues by number, so if the rest of it spans few
+                            // blocks, it's read in one call
+                            let remaining = limit - headers.len() as u64;
+                            let step = skip + 1;
+                            if remaining.saturating_sub(1).saturating_mul(step) <
+                                self.config.max_headers as u64
+                            {
+                                for header in self.headers_range_rev_step(next, remaining, step) {
+                                    total_bytes += header.length();
+                                    headers.push(header);
+
+                                    if total_bytes > self.config.soft_response_limit {
+                                        truncated = (headers.len() as u64) < requested;
+                                        break
+                                    }
+                                }
+                                break
+                            }
+                            block = next.into()
+                        } else if by_number {
+                            // the start block is canonical, so are its ancestors by number
+                            let Some(next) = number.checked_sub(1) else { break };
+                            let remaining = limit - headers.len() as u64;
+                            for header in self.headers_range_rev(next, remaining) {
+                                total_bytes += header.length();
+                                headers.push(header);
+
+                                if total_bytes > self.config.soft_response_limit {
+                                    truncated = (headers.len() as u64) < requested;
+                                    break
+                                }
+                            }
+                            break
+                        } else {
+                            block = parent_hash.into()
+                        }
+                    }
+                }
+            } else {
+                break
+            }
+        }
+
+        if truncated || (headers.len() as u64 == limit && limit < requested) {
+            self.metrics.eth_headers_response_truncated_total.increment(1);
+        }
+        if headers.is_empty() && limit > 0 {
+            // nothing was served, so the very first header is missing
+            self.metrics.eth_headers_start_not_found_total.increment(1);
+        }
+        self.metrics.record_headers_response(headers.len(), total_bytes);
+        headers
+    }
+
+    /// Returns the requested node data.
+    ///
+    /// Only contract bytecode can be looked up by its hash, trie nodes aren't stored by hash, so
+    /// requested hashes that can't be served are skipped.
+    fn get_node_data_response(&self, request: &GetNodeData) -> Vec<Bytes> {
+        let mut node_data = Vec::new();
+
+        let Some(state) = self.lookup(self.client.latest().map(Some), "latest state") else {
+            return node_data
+        };
+
+        let mut total_bytes = 0;
+
+        for hash in &request.0 {
+            if let Some(bytecode) = self.lookup(state.bytecode_by_hash(hash), hash) {
+                let bytes = bytecode.original_bytes();
+                total_bytes += bytes.len();
+                node_data.push(bytes);
+
+                if node_data.len() >= MAX_NODE_DATA_SERVE ||
+                    total_bytes > self.config.soft_response_limit
+                {
+                    break
+                }
+            }
+        }
+
+        node_data
+    }
+
+    /// Returns the bodies of the requested blocks and the number of skipped unknown hashes.
+    fn get_bodies_response(&self, hashes: &[B256]) -> (Vec<N::BlockBody>, usize) {
+        let mut bodies = Vec::new();
+        let mut skipped = 0;
+
+        // repeated hashes are answered again, but each distinct block is only looked up once
+        let mut fetched = HashMap::<B256, N::BlockBody>::default();
+
+        let mut total_bytes = 0;
+
+        let mut hashes = hashes.iter().copied();
+        for hash in hashes.by_ref().take(self.config.max_bodies) {
+            let body = match fetched.get(&hash) {
+                Some(body) => Some(body.clone()),
+                None => self.body(hash).inspect(|body| {
+                    fetched.insert(hash, body.clone());
+                }),
+            };
+
+            if let Some(body) = body {
+                total_bytes += body.length();
+                bodies.push(body);
+
+                if total_bytes > self.config.soft_response_limit {
+                    break
+                }
+            } else {
+                skipped += 1;
+            }
+        }
+
+        if hashes.next().is_some() {
+            self.metrics.eth_bodies_response_truncated_total.increment(1);
+        }
+        self.metrics.record_bodies_response(bodies.len(), total_bytes);
+        (bodies, skipped)
+    }
+
+    /// Returns the receipts of the requested blocks and the number of skipped unknown hashes.
+    ///
+    /// Blocks without receipts are answered with an empty entry even if their receipts have been
+    /// pruned. Other blocks whose receipts have been pruned are skipped like unknown blocks, so
+    /// that the receipts of the following blocks are still served.
+    #[inline]
+    fn get_receipts_response<T, F>(&self, hashes: &[B256], fetch: F) -> (Vec<Vec<T>>, usize)
+    where
+        F: Fn(B256) -> Option<Vec<T>>,
+        T: Encodable,
+    {
+        let mut receipts = Vec::with_capacity(hashes.len().min(self.config.max_receipts));
+        let mut skipped = 0;
+        let mut total_bytes = 0;
+        let mut total_receipts = 0;
+
+        let mut hashes = hashes.iter().copied();
+        for hash in hashes.by_ref().take(self.config.max_receipts) {
+            let receipts_by_block = match fetch(hash) {
+                Some(receipts_by_block) => Some(receipts_by_block),
+                None if self.has_empty_receipts(hash.into()) => Some(Vec::new()),
+                None => None,
+            };
+            if let Some(receipts_by_block) = receipts_by_block {
+                total_bytes += receipts_by_block.length();
+                total_receipts += receipts_by_block.len();
+                receipts.push(receipts_by_block);
+
+                if total_bytes > self.config.soft_response_limit ||
+                    total_receipts >= self.config.max_receipts_per_response
+                {
+                    break
+                }
+            } else {
+                skipped += 1;
+            }
+        }
+
+        if hashes.next().is_some() {
+            self.metrics.eth_receipts_response_truncated_total.increment(1);
+        }
+        self.metrics.record_receipts_response(receipts.len(), total_bytes);
+        (receipts, skipped)
+    }
+
+    /// Returns the receipts of `count` consecutive blocks starting at block `start`.
+    ///
+    /// Stops at the first block the node doesn't have receipts for. Blocks without receipts are
+    /// answered with an empty entry even if their receipts have been pruned.
+    fn get_receipts_by_range_response(&self, start: u64, count: u64) -> Vec<Vec<C::Receipt>> {
+        let mut receipts = Vec::with_capacity(count.min(self.config.max_receipts as u64) as usize);
+        let mut total_bytes = 0;
+        let mut total_receipts = 0;
+
+        let end = start.saturating_add(count);
+        let mut numbers = start..end;
+        for number in numbers.by_ref().take(self.config.max_receipts) {
+            let block_receipts = self
+                .lookup(self.client.receipts_by_block(number.into()), number)
+                .or_else(|| self.has_empty_receipts(number.into()).then(Vec::new));
+            let Some(block_receipts) = block_receipts else {
+                numbers = end..end;
+                break
+            };
+            total_bytes += block_receipts.length();
+            total_receipts += block_receipts.len();
+            receipts.push(block_receipts);
+
+            if total_bytes > self.config.soft_response_limit ||
+                total_receipts >= self.config.max_receipts_per_response
+            {
+                break
+            }
+        }
+
+        if !numbers.is_empty() {
+            self.metrics.eth_receipts_response_truncated_total.increment(1);
+        }
+        self.metrics.record_receipts_response(receipts.len(), total_bytes);
+        receipts
+    }
+
+    /// Returns the receipts for a [`GetReceipts70`] query.
+    ///
+    /// This will adhere to the soft limit and the maximum number of receipts per response, but
+    /// allow filling the last vec partially. Stops at the first block the node doesn't have
+    /// receipts for, blocks without receipts are answered with an empty entry even if their
+    /// receipts have been pruned.
+    fn get_receipts70_response(&self, request: &GetReceipts70) -> Receipts70<C::Receipt> {
+        let GetReceipts70 { first_block_receipt_index, block_hashes } = request;
+        let first_block_receipt_index = *first_block_receipt_index;
+
+        let mut receipts = Vec::new();
+        let mut total_bytes = 0usize;
+        let mut total_receipts = 0usize;
+        let mut last_block_incomplete = false;
+        let exceeds_limit = block_hashes.len() > self.config.max_receipts;
+
+        for (idx, hash) in block_hashes.iter().copied().take(self.config.max_receipts).enumerate() {
+            let Some(mut block_receipts) =
+                self.receipts(hash).or_else(|| self.has_empty_receipts(hash.into()).then(Vec::new))
+            else {
+                break
+            };
+
+            if idx == 0 && first_block_receipt_index > 0 {
+                let skip = first_block_receipt_index as usize;
+                if skip >= block_receipts.len() {
+                    block_receipts.clear();
+                } else {
+                    block_receipts.drain(0..skip);
+                }
+            }
+
+            let block_size = block_receipts.length();
+
+            if total_bytes + block_size <= self.config.soft_response_limit &&
+                total_receipts + block_receipts.len() <= self.config.max_receipts_per_response
+            {
+                total_bytes += block_size;
+                total_receipts += block_receipts.len();
+                receipts.push(block_receipts);
+                continue;
+            }
+
+            let mut partial_block = Vec::new();
+            for receipt in block_receipts {
+                let receipt_size = receipt.length();
+                if total_bytes + receipt_size > self.config.soft_response_limit ||
+                    total_receipts >= self.config.max_receipts_per_response
+                {
+                    break;
+                }
+                total_bytes += receipt_size;
+                total_receipts += 1;
+                partial_block.push(receipt);
+            }
+
+            receipts.push(partial_block);
+            last_block_incomplete = true;
+            break;
+        }
+
+        if last_block_incomplete || (exceeds_limit && receipts.len() == self.config.max_receipts) {
+            self.metrics.eth_receipts_response_truncated_total.increment(1);
+        }
+        self.metrics.record_receipts_response(receipts.len(), total_bytes);
+        Receipts70 { last_block_incomplete, receipts }
+    }
+}
+
+/// Stream of hashes of blocks that are no longer canonical.
+struct RevertedBlocks(Pin<Box<dyn Stream<Item = Vec<B256>> + Send>>);
+
+impl fmt::Debug for RevertedBlocks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RevertedBlocks").finish_non_exhaustive()
+    }
+}
+
+/// Outcome of a served request.
+#[derive(Debug, Default, Clone, Copy)]
+struct ServedRequest {
+    /// Whether the request asked for data the node doesn't have.
+    unavailable: bool,
+    /// Number of served items, e.g. headers or blocks with receipts.
+    items: usize,
+    /// Encoded size of the response.
+    bytes: usize,
+}
+
+/// A request that is being served on a blocking task.
+#[derive(Debug)]
+struct InFlightRequest {
+    /// The peer that sent the request.
+    peer_id: PeerId,
+    /// Resolves to the outcome of the request.
+    task: JoinHandle<ServedRequest>,
+}
+
+impl Future for InFlightRequest {
+    type Output = (PeerId, ServedRequest);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.task.poll_unpin(cx).map(|served| (this.peer_id, served.unwrap_or_default()))
+    }
+}
+
+/// Observes the requests served by the [`EthRequestHandler`], e.g. to debug peer behavior.
+///
+/// Each method is invoked on the blocking task that served the request, after the response was
+/// constructed, with the number of served items and the encoded size of the response. This
+/// includes requests that exceeded the serve limits and requests that were answered with an empty
+/// response. Requests that are dropped without a response aren't observed.
+///
+/// All methods do nothing by default.
+pub trait EthRequestListener: fmt::Debug + Send + Sync + 'static {
+    /// Invoked after a [`GetBlockHeaders`] request was served.
+    fn on_headers_request(
+        &self,
+        _peer_id: PeerId,
+        _request: &GetBlockHeaders,
+        _served: usize,
+        _bytes: usize,
+    ) {
+    }
+
+    /// Invoked after a [`GetBlockBodies`] request was served.
+    fn on_bodies_request(
+        &self,
+        _peer_id: PeerId,
+        _request: &GetBlockBodies,
+        _served: usize,
+        _bytes: usize,
+    ) {
+    }
+
+    /// Invoked after an `eth/68` or `eth/69` [`GetReceipts`] request was served.
+    fn on_receipts_request(
+        &self,
+        _peer_id: PeerId,
+        _request: &GetReceipts,
+        _served: usize,
+        _bytes: usize,
+    ) {
+    }
+
+    /// Invoked after a [`GetReceipts70`] request was served.
+    fn on_receipts70_request(
+        &self,
+        _peer_id: PeerId,
+        _request: &GetReceipts70,
+        _served: usize,
+        _bytes: usize,
+    ) {
+    }
+
+    /// Invoked after a [`IncomingEthRequest::GetReceiptsByRange`] request was served.
+    fn on_receipts_by_range_request(
+        &self,
+        _peer_id: PeerId,
+        _start: u64,
+        _count: u64,
+        _served: usize,
+        _bytes: usize,
+    ) {
+    }
+
+    /// Invoked after a [`GetNodeData`] request was served.
+    fn on_node_data_request(
+        &self,
+        _peer_id: PeerId,
+        _request: &GetNodeData,
+        _served: usize,
+        _bytes: usize,
+    ) {
+    }
+
+    /// Invoked after any request was served, following the method of the specific request.
+    ///
+    /// The observation also includes the time it took to serve the request, e.g. to meter
+    /// requests of all kinds in one place.
+    fn on_request_served(&self, _observation: &RequestObservation) {}
+}
+
+/// An [`EthRequestListener`] that does nothing.
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct NoopEthRequestListener;
+
+impl EthRequestListener for NoopEthRequestListener {}
+
+/// An [`EthRequestListener`] that logs every served request.
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct TracingEthRequestListener;
+
+impl EthRequestListener for TracingEthRequestListener {
+    fn on_headers_request(
+        &self,
+        peer_id: PeerId,
+        request: &GetBlockHeaders,
+        served: usize,
+        bytes: usize,
+    ) {
+        debug!(target: "net::eth", %peer_id, ?request, served, bytes, "served headers request");
+    }
+
+    fn on_bodies_request(
+        &self,
+        peer_id: PeerId,
+        request: &GetBlockBodies,
+        served: usize,
+        bytes: usize,
+    ) {
+        let requested = request.0.len();
+        debug!(target: "net::eth", %peer_id, requested, served, bytes, "served bodies request");
+    }
+
+    fn on_receipts_request(
+        &self,
+        peer_id: PeerId,
+        request: &GetReceipts,
+        served: usize,
+        bytes: usize,
+    ) {
+        let requested = request.0.len();
+        debug!(target: "net::eth", %peer_id, requested, served, bytes, "served receipts request");
+    }
+
+    fn on_receipts70_request(
+        &self,
+        peer_id: PeerId,
+        request: &GetReceipts70,
+        served: usize,
+        bytes: usize,
+    ) {
+        let requested = request.block_hashes.len();
+        debug!(target: "net::eth", %peer_id, requested, served, bytes, "served receipts request");
+    }
+
+    fn on_receipts_by_range_request(
+        &self,
+        peer_id: PeerId,
+        start: u64,
+        count: u64,
+        served: usize,
+        bytes: usize,
+    ) {
+        debug!(
+            target: "net::eth",
+            %peer_id,
+            start,
+            count,
+            served,
+            bytes,
+            "served receipts by range request"
+        );
+    }
+
+    fn on_node_data_request(
+        &self,
+        peer_id: PeerId,
+        request: &GetNodeData,
+        served: usize,
+        bytes: usize,
+    ) {
+        let requested = request.0.len();
+        debug!(target: "net::eth", %peer_id, requested, served, bytes, "served node data request");
+    }
+}
+
+/// Kind of an `eth` request served by the [`EthRequestHandler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EthRequestKind {
+    /// A `GetBlockHeaders` request.
+    Headers,
+    /// A `GetBlockBodies` request.
+    Bodies,
+    /// A `GetReceipts` request of any `eth` version.
+    Receipts,
+    /// A request for the receipts of a range of blocks.
+    ReceiptsByRange,
+    /// A `GetNodeData` request.
+    NodeData,
+}
+
+/// Summary of a request served by the [`EthRequestHandler`], see
+/// [`EthRequestListener::on_request_served`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestObservation {
+    /// The peer that sent the request.
+    pub peer_id: PeerId,
+    /// The kind of the request.
+    pub kind: EthRequestKind,
+    /// Number of served items, e.g. headers or blocks with receipts.
+    pub items: usize,
+    /// Encoded size of the response in bytes.
+    pub bytes: usize,
+    /// Time it took to serve the request.
+    pub elapsed: Duration,
+}
+
+/// All `eth` request related to blocks delegated by the network.
+#[derive(Debug)]
+pub enum IncomingEthRequest<N: NetworkPrimitives = EthNetworkPrimitives> {
+    /// Request Block headers from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetBlockHeaders {
+        /// The ID of the peer to request block headers from.
+        peer_id: PeerId,
+        /// The specific block headers requested.
+        request: GetBlockHeaders,
+        /// The channel sender for the response containing block headers.
+        response: oneshot::Sender<RequestResult<BlockHeaders<N::BlockHeader>>>,
+    },
+    /// Request Block bodies from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetBlockBodies {
+        /// The ID of the peer to request block bodies from.
+        peer_id: PeerId,
+        /// The specific block bodies requested.
+        request: GetBlockBodies,
+        /// The channel sender for the response containing block bodies.
+        response: oneshot::Sender<RequestResult<BlockBodies<N::BlockBody>>>,
+    },
+    /// Request Node Data from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetNodeData {
+        /// The ID of the peer to request node data from.
+        peer_id: PeerId,
+        /// The specific node data requested.
+        request: GetNodeData,
+        /// The channel sender for the response containing node data.
+        response: oneshot::Sender<RequestResult<NodeData>>,
+    },
+    /// Request Receipts from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetReceipts {
+        /// The ID of the peer to request receipts from.
+        peer_id: PeerId,
+        /// The specific receipts requested.
+        request: GetReceipts,
+        /// The channel sender for the response containing receipts.
+        response: oneshot::Sender<RequestResult<Receipts<N::Receipt>>>,
+    },
+    /// Request Receipts from the peer without bloom filter.
+    ///
+    /// The response should be sent through the channel.
+    GetReceipts69 {
+        /// The ID of the peer to request receipts from.
+        peer_id: PeerId,
+        /// The specific receipts requested.
+        request: GetReceipts,
+        /// The channel sender for the response containing Receipts69.
+        response: oneshot::Sender<RequestResult<Receipts69<N::Receipt>>>,
+    },
+    /// Request Receipts from the peer using eth/70.
+    ///
+    /// The response should be sent through the channel.
+    GetReceipts70 {
+        /// The ID of the peer to request receipts from.
+        peer_id: PeerId,
+        /// The specific receipts requested including the `firstBlockReceiptIndex`.
+        request: GetReceipts70,
+        /// The channel sender for the response containing Receipts70.
+        response: oneshot::Sender<RequestResult<Receipts70<N::Receipt>>>,
+    },
+    /// Request the receipts of a range of consecutive blocks, without bloom filter.
+    ///
+    /// This isn't part of the `eth` wire protocol, it's received from reth peers that negotiated
+    /// the [`receipts_range_capability`](crate::receipts_range_capability). The response should
+    /// be sent through the channel.
+    GetReceiptsByRange {
+        /// The ID of the peer to request receipts from.
+        peer_id: PeerId,
+        /// The number of the first block.
+        start: u64,
+        /// The number of consecutive blocks.
+        count: u64,
+        /// The channel sender for the response containing the receipts per block.
+        response: oneshot::Sender<RequestResult<Receipts69<N::Receipt>>>,
+    },
+}
+
+impl<N: NetworkPrimitives> IncomingEthRequest<N> {
+    /// Returns the id of the peer that sent the request.
+    pub const fn peer_id(&self) -> &PeerId {
+        match self {
+            Self::GetBlockHeaders { peer_id, .. } |
+            Self::GetBlockBodies { peer_id, .. } |
+            Self::GetNodeData { peer_id, .. } |
+            Self::GetReceipts { peer_id, .. } |
+            Self::GetReceipts69 { peer_id, .. } |
+            Self::GetReceipts70 { peer_id, .. } |
+            Self::GetReceiptsByRange { peer_id, .. } => peer_id,
+        }
+    }
+
+    /// Answers the request with an empty response, without serving it.
+    fn respond_empty(self) {
+        match self {
+            Self::GetBlockHeaders { response, .. } => {
+                let _ = response.send(Ok(BlockHeaders(Vec::new())));
+            }
+            Self::GetBlockBodies { response, .. } => {
+                let _ = response.send(Ok(BlockBodies(Vec::new())));
+            }
+            Self::GetNodeData { response, .. } => {
+                let _ = response.send(Ok(NodeData(Vec::new())));
+            }
+            Self::GetReceipts { response, .. } => {
+                let _ = response.send(Ok(Receipts(Vec::new())));
+            }
+            Self::GetReceipts69 { response, .. } | Self::GetReceiptsByRange { response, .. } => {
+                let _ = response.send(Ok(Receipts69(Vec::new())));
+            }
+            Self::GetReceipts70 { response, .. } => {
+                let _ = response
+                    .send(Ok(Receipts70 { last_block_incomplete: false, receipts: Vec::new() }));
+            }
+        }
+    }
+
+    /// Returns `true` if the request is for node data or receipts of many blocks, which aren't
+    /// served when the queue backs up.
+    fn is_expensive(&self) -> bool {
+        match self {
+            Self::GetNodeData { .. } => true,
+            Self::GetReceipts { request, .. } | Self::GetReceipts69 { request, .. } => {
+                request.0.len() > LARGE_RECEIPTS_REQUEST_BLOCKS
+            }
+            Self::GetReceipts70 { request, .. } => {
+                request.block_hashes.len() > LARGE_RECEIPTS_REQUEST_BLOCKS
+            }
+            Self::GetReceiptsByRange { count, .. } => *count > LARGE_RECEIPTS_REQUEST_BLOCKS as u64,
+            Self::GetBlockHeaders { .. } | Self::GetBlockBodies { .. } => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{keccak256, Address, U256};
+    use futures::future::poll_fn;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use rand::Rng;
+    use reth_network_api::test_utils::PeerCommand;
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_storage_api::errors::provider::ProviderError;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    /// Waits until all requests in flight are served.
+    async fn serve_in_flight<C, N: NetworkPrimitives>(handler: &mut EthRequestHandler<C, N>) {
+        poll_fn(|cx| {
+            handler.poll_in_flight(cx);
+            if handler.in_flight.is_empty() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn report_spamming_peer() {
+        let (peers_tx, mut peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<(), EthNetworkPrimitives>::new(
+            (),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        )
+        .with_max_requests_per_second(10);
+
+        let spammer = PeerId::random();
+        let peer = PeerId::random();
+        for _ in 0..10 {
+            handler.on_peer_request(spammer);
+            handler.on_peer_request(peer);
+        }
+        assert!(peers_rx.try_recv().is_err());
+
+        // exceeding the limit reports the peer once per window
+        for _ in 0..5 {
+            handler.on_peer_request(spammer);
+        }
+        let Ok(PeerCommand::ReputationChange(reported, kind)) = peers_rx.try_recv() else {
+            panic!("expected reputation change")
+        };
+        assert_eq!(reported, spammer);
+        assert_eq!(kind, ReputationChangeKind::BadMessage);
+        assert!(peers_rx.try_recv().is_err());
+
+        // the count is reset in the next window
+        tokio::time::advance(REQUEST_RATE_WINDOW).await;
+        handler.prune_request_windows();
+        assert!(handler.request_windows.is_empty());
+        for _ in 0..10 {
+            handler.on_peer_request(spammer);
+        }
+        assert!(peers_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn serve_node_data() {
+        let client = MockEthProvider::default();
+        let code = Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]);
+        client.add_account(
+            Address::with_last_byte(1),
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(code.clone()),
+        );
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        // unknown hashes are skipped
+        let request = GetNodeData(vec![B256::with_last_byte(1), keccak256(&code)]);
+        let (tx, rx) = oneshot::channel();
+        handler.on_node_data_request(PeerId::random(), request, tx);
+        assert_eq!(rx.await.unwrap().unwrap(), NodeData(vec![code]));
+    }
+
+    #[tokio::test]
+    async fn respond_to_unservable_node_data() {
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            reth_storage_api::noop::NoopProvider::default(),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        // the request is answered with an empty list instead of being dropped
+        let request = GetNodeData(vec![B256::with_last_byte(1)]);
+        let (tx, rx) = oneshot::channel();
+        handler.on_node_data_request(PeerId::random(), request, tx);
+        assert_eq!(rx.await.unwrap().unwrap(), NodeData(Vec::new()));
+    }
+
+    #[test]
+    fn record_storage_errors() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+            let (_requests_tx, requests_rx) = mpsc::channel(1);
+            let handler = EthRequestHandler::<(), EthNetworkPrimitives>::new(
+                (),
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig::default(),
+            );
+
+            let hash = B256::with_last_byte(1);
+            assert_eq!(handler.server.lookup(Ok(Some(1)), hash), Some(1));
+            assert_eq!(handler.server.lookup::<u64>(Ok(None), hash), None);
+            assert_eq!(
+                handler.server.lookup::<u64>(Err(ProviderError::BlockHashNotFound(hash)), hash),
+                None
+            );
+        });
+
+        let errors =
+            snapshotter.snapshot().into_vec().into_iter().find_map(|(key, _, _, value)| {
+                (key.key().name() == "network.eth_request_storage_errors_total").then_some(value)
+            });
+        assert_eq!(errors, Some(DebugValue::Counter(1)));
+    }
+
+    #[test]
+    fn truncate_headers_response() {
+        let client = MockEthProvider::default();
+        let headers = (0..5u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_headers: 2, ..Default::default() },
+        );
+
+        let request = GetBlockHeaders {
+            start_block: headers[0].hash_slow().into(),
+            limit: 5,
+            skip: 0,
+            direction: HeadersDirection::Rising,
+        };
+        assert_eq!(handler.server.get_headers_response(request), headers[..2]);
+    }
+
+    #[test]
+    fn serve_repeated_block_bodies() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let client = MockEthProvider::default();
+        let blocks = (1..=2u64)
+            .map(|number| reth_ethereum_primitives::Block {
+                header: alloy_consensus::Header { number, ..Default::default() },
+                body: Default::default(),
+            })
+            .collect::<Vec<_>>();
+        client.extend_blocks(blocks.iter().map(|block| (block.header.hash_slow(), block.clone())));
+
+        let (first, second) = (blocks[0].header.hash_slow(), blocks[1].header.hash_slow());
+        let (bodies, skipped) = metrics::with_local_recorder(&recorder, || {
+            let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+            let (_requests_tx, requests_rx) = mpsc::channel(1);
+            let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                client,
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                // every lookup goes to storage
+                EthRequestHandlerConfig { body_cache_bytes: 0, ..Default::default() },
+            );
+            handler.server.get_bodies_response(&[first, first, second, first])
+        });
+
+        // repeated hashes keep the response aligned with the request
+        let expected = [0, 0, 1, 0].map(|idx| blocks[idx].body.clone());
+        assert_eq!(bodies, expected);
+        assert_eq!(skipped, 0);
+
+        // but each distinct block is only looked up once
+        let misses =
+            snapshotter.snapshot().into_vec().into_iter().find_map(|(key, _, _, value)| {
+                (key.key().name() == "network.eth_request_cache_misses_total").then_some(value)
+            });
+        assert_eq!(misses, Some(DebugValue::Counter(2)));
+    }
+
+    #[tokio::test]
+    async fn report_oversized_headers_request() {
+        let (peers_tx, mut peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            MockEthProvider::default(),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_headers: 2, ..Default::default() },
+        );
+
+        let peer = PeerId::random();
+        let request = |limit| GetBlockHeaders {
+            start_block: 0u64.into(),
+            limit,
+            skip: 0,
+            direction: HeadersDirection::Rising,
+        };
+
+        // exceeding only the configured serve limit is not penalized
+        let (tx, _rx) = oneshot::channel();
+        handler.on_headers_request(peer, request(3), tx);
+        assert!(peers_rx.try_recv().is_err());
+
+        let (tx, _rx) = oneshot::channel();
+        handler.on_headers_request(peer, request(MAX_HEADERS_SERVE as u64 + 1), tx);
+
+        let Ok(PeerCommand::ReputationChange(reported, kind)) = peers_rx.try_recv() else {
+            panic!("expected reputation change")
+        };
+        assert_eq!(reported, peer);
+        assert_eq!(kind, ReputationChangeKind::BadMessage);
+        assert!(peers_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn report_peer_requesting_unavailable_bodies() {
+        let (peers_tx, mut peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            MockEthProvider::default(),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        let peer = PeerId::random();
+        for _ in 0..MAX_UNAVAILABLE_REQUESTS_PER_WINDOW {
+            let (tx, _rx) = oneshot::channel();
+            handler.on_bodies_request(peer, GetBlockBodies(vec![B256::random()]), tx);
+            serve_in_flight(&mut handler).await;
+        }
+        assert!(peers_rx.try_recv().is_err());
+
+        for _ in 0..2 {
+            let (tx, _rx) = oneshot::channel();
+            handler.on_bodies_request(peer, GetBlockBodies(vec![B256::random()]), tx);
+            serve_in_flight(&mut handler).await;
+        }
+        let Ok(PeerCommand::ReputationChange(reported, _)) = peers_rx.try_recv() else {
+            panic!("expected reputation change")
+        };
+        assert_eq!(reported, peer);
+        assert!(peers_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn skip_unknown_block_bodies() {
+        let client = MockEthProvider::default();
+        let blocks = (1..=2u64)
+            .map(|number| reth_ethereum_primitives::Block {
+                header: alloy_consensus::Header { number, ..Default::default() },
+                body: Default::default(),
+            })
+            .collect::<Vec<_>>();
+        client.extend_blocks(blocks.iter().map(|block| (block.header.hash_slow(), block.clone())));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        let request = GetBlockBodies(vec![
+            blocks[0].header.hash_slow(),
+            B256::random(),
+            blocks[1].header.hash_slow(),
+        ]);
+        let (tx, rx) = oneshot::channel();
+        handler.on_bodies_request(PeerId::random(), request, tx);
+
+        let bodies = rx.await.unwrap().unwrap().0;
+        assert_eq!(bodies, [blocks[0].body.clone(), blocks[1].body.clone()]);
+    }
+
+    #[tokio::test]
+    async fn skip_unknown_block_receipts() {
+        let client = MockEthProvider::default();
+        let headers = (1..=2u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+        client.extend_receipts(headers.iter().map(|header| {
+            let receipt = reth_ethereum_primitives::Receipt {
+                cumulative_gas_used: header.number,
+                ..Default::default()
+            };
+            (header.number, vec![receipt])
+        }));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_receipts: 2, ..Default::default() },
+        );
+
+        // hashes beyond the serve limit are dropped before any lookups
+        let request =
+            GetReceipts(vec![headers[0].hash_slow(), B256::random(), headers[1].hash_slow()]);
+        let (tx, rx) = oneshot::channel();
+        handler.on_receipts69_request(PeerId::random(), request, tx);
+        let receipts = rx.await.unwrap().unwrap().0;
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0][0].cumulative_gas_used, 1);
+
+        let request = GetReceipts(vec![B256::random(), headers[1].hash_slow()]);
+        let (tx, rx) = oneshot::channel();
+        handler.on_receipts69_request(PeerId::random(), request, tx);
+        let receipts = rx.await.unwrap().unwrap().0;
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0][0].cumulative_gas_used, 2);
+    }
+
+    #[tokio::test]
+    async fn serve_multi_block_receipts69() {
+        let client = MockEthProvider::default();
+        let headers = (1..=4u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+        // block `n` has `n` receipts
+        let block_receipts = |number: u64| {
+            (0..number)
+                .map(|i| reth_ethereum_primitives::Receipt {
+                    cumulative_gas_used: number * 100 + i,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>()
+        };
+        client.extend_receipts(
+            headers.iter().map(|header| (header.number, block_receipts(header.number))),
+        );
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        // receipts are served in request order
+        let order = [3, 0, 2, 1];
+        let request = GetReceipts(order.iter().map(|&i| headers[i].hash_slow()).collect());
+        let (tx, rx) = oneshot::channel();
+        handler.on_receipts69_request(PeerId::random(), request, tx);
+        let receipts = rx.await.unwrap().unwrap().0;
+
+        let expected = order.iter().map(|&i| block_receipts(headers[i].number)).collect::<Vec<_>>();
+        assert_eq!(receipts, expected);
+    }
+
+    #[test]
+    fn clamp_headers_request_limit() {
+        let client = MockEthProvider::default();
+        let headers = (0..5u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_headers: 3, ..Default::default() },
+        );
+
+        let request = GetBlockHeaders {
+            start_block: headers[0].hash_slow().into(),
+            limit: u64::MAX,
+            skip: 0,
+            direction: HeadersDirection::Rising,
+        };
+        assert_eq!(handler.server.get_headers_response(request), headers[..3]);
+    }
+
+    #[test]
+    fn serve_headers_from_cache() {
+        let client = MockEthProvider::default();
+        let mut headers = Vec::<alloy_consensus::Header>::new();
+        for number in 0..5u64 {
+            let parent_hash = headers.last().map(|parent| parent.hash_slow()).unwrap_or_default();
+            headers.push(alloy_consensus::Header { number, parent_hash, ..Default::default() });
+        }
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client.clone(),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        let request = GetBlockHeaders {
+            start_block: headers[4].hash_slow().into(),
+            limit: 5,
+            skip: 0,
+            direction: HeadersDirection::Falling,
+        };
+        let expected = headers.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(handler.server.get_headers_response(request), expected);
+
+        // the second pass is served without hitting storage
+        client.headers.lock().clear();
+        assert_eq!(handler.server.get_headers_response(request), expected);
+    }
+
+    #[tokio::test]
+    async fn limit_requests_in_flight() {
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (requests_tx, requests_rx) = mpsc::channel(2);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            MockEthProvider::default(),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_concurrent_requests: 1, ..Default::default() },
+        );
+
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            let (tx, rx) = oneshot::channel();
+            requests_tx
+                .send(IncomingEthRequest::GetBlockBodies {
+                    peer_id: PeerId::random(),
+                    request: GetBlockBodies(vec![B256::random()]),
+                    response: tx,
+                })
+                .await
+                .unwrap();
+            responses.push(rx);
+        }
+
+        // the second request stays queued while the first one is served
+        let _ = poll_fn(|cx| Poll::Ready(handler.poll_unpin(cx))).await;
+        assert_eq!(handler.queue_depth(), 1);
+
+        tokio::spawn(handler);
+        for rx in responses {
+            assert!(rx.await.unwrap().unwrap().0.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn serve_receipts_from_cache() {
+        let client = MockEthProvider::default();
+        let header = alloy_consensus::Header { number: 1, ..Default::default() };
+        let hash = header.hash_slow();
+        client.add_header(hash, header);
+        let receipt = reth_ethereum_primitives::Receipt {
+            cumulative_gas_used: 21_000,
+            logs: vec![alloy_primitives::Log::empty()],
+            ..Default::default()
+        };
+        client.add_receipts(1, vec![receipt.clone()]);
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client.clone(),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+        let expected = Receipts(vec![vec![ReceiptWithBloom::from(receipt)]]);
+
+        let (tx, rx) = oneshot::channel();
+        handler.on_receipts_request(PeerId::random(), GetReceipts(vec![hash]), tx);
+        assert_eq!(rx.await.unwrap().unwrap(), expected);
+
+        // the second request is served without looking up the receipts and computing the blooms
+        client.receipts.lock().clear();
+        let (tx, rx) = oneshot::channel();
+        handler.on_receipts_request(PeerId::random(), GetReceipts(vec![hash]), tx);
+        assert_eq!(rx.await.unwrap().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn serve_bodies_from_cache() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let client = MockEthProvider::default();
+        let block = reth_ethereum_primitives::Block {
+            header: alloy_consensus::Header { number: 1, ..Default::default() },
+            body: Default::default(),
+        };
+        let hash = block.header.hash_slow();
+        client.add_block(hash, block.clone());
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let (reverted_tx, reverted_rx) = mpsc::unbounded_channel();
+        let mut handler = metrics::with_local_recorder(&recorder, || {
+            EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                client.clone(),
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig::default(),
+            )
+            .with_reverted_blocks(UnboundedReceiverStream::new(reverted_rx))
+        });
+
+        let (tx, rx) = oneshot::channel();
+        handler.on_bodies_request(PeerId::random(), GetBlockBodies(vec![hash]), tx);
+        assert_eq!(rx.await.unwrap().unwrap().0, vec![block.body.clone()]);
+
+        // the second request is served without hitting storage
+        client.blocks.lock().clear();
+        let (tx, rx) = oneshot::channel();
+        handler.on_bodies_request(PeerId::random(), GetBlockBodies(vec![hash]), tx);
+        assert_eq!(rx.await.unwrap().unwrap().0, vec![block.body.clone()]);
+
+        let counter = |name: &str| {
+            snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .find_map(|(key, _, _, value)| (key.key().name() == name).then_some(value))
+        };
+        assert_eq!(counter("network.eth_request_cache_hits_total"), Some(DebugValue::Counter(1)));
+        assert_eq!(counter("network.eth_request_cache_misses_total"), Some(DebugValue::Counter(1)));
+
+        // reverted blocks are evicted from the cache
+        reverted_tx.send(vec![hash]).unwrap();
+        poll_fn(|cx| {
+            handler.poll_reverted_blocks(cx);
+            Poll::Ready(())
+        })
+        .await;
+        let (tx, rx) = oneshot::channel();
+        handler.on_bodies_request(PeerId::random(), GetBlockBodies(vec![hash]), tx);
+        assert!(rx.await.unwrap().unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn record_response_sizes() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let client = MockEthProvider::default();
+        let header = alloy_consensus::Header { number: 1, ..Default::default() };
+        client.add_header(header.hash_slow(), header.clone());
+
+        metrics::with_local_recorder(&recorder, || {
+            let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+            let (_requests_tx, requests_rx) = mpsc::channel(1);
+            let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                client,
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig::default(),
+            );
+
+            let request = GetBlockHeaders {
+                start_block: header.hash_slow().into(),
+                limit: 1,
+                skip: 0,
+                direction: HeadersDirection::Rising,
+            };
+            assert_eq!(handler.server.get_headers_response(request), vec![header.clone()]);
+        });
+
+        let sizes = snapshotter.snapshot().into_vec().into_iter().find_map(|(key, _, _, value)| {
+            (key.key().name() == "network.eth_headers_response_bytes").then_some(value)
+        });
+        let Some(DebugValue::Histogram(sizes)) = sizes else { panic!("expected histogram") };
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(sizes[0].into_inner(), header.length() as f64);
+    }
+
+    #[tokio::test]
+    async fn limit_bandwidth_per_peer() {
+        let client = MockEthProvider::default();
+        let headers = (0..5u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        let (peers_tx, mut peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig {
+                max_bytes_per_peer_per_second: Some(headers[0].length() * 2),
+                ..Default::default()
+            },
+        );
+
+        let request = |start: &alloy_consensus::Header, limit| GetBlockHeaders {
+            start_block: start.hash_slow().into(),
+            limit,
+            skip: 0,
+            direction: HeadersDirection::Rising,
+        };
+
+        // the response of the abusive peer is truncated to its budget
+        let abusive = PeerId::random();
+        let (tx, rx) = oneshot::channel();
+        handler.on_incoming_request(IncomingEthRequest::GetBlockHeaders {
+            peer_id: abusive,
+            request: request(&headers[0], 5),
+            response: tx,
+        });
+        assert_eq!(rx.await.unwrap().unwrap().0, headers[..3]);
+        serve_in_flight(&mut handler).await;
+
+        // further requests of the abusive peer are answered with empty responses
+        let (tx, rx) = oneshot::channel();
+        handler.on_incoming_request(IncomingEthRequest::GetBlockHeaders {
+            peer_id: abusive,
+            request: request(&headers[0], 5),
+            response: tx,
+        });
+        assert!(rx.await.unwrap().unwrap().0.is_empty());
+        assert!(handler.in_flight.is_empty());
+
+        // other peers are still served
+        let (tx, rx) = oneshot::channel();
+        handler.on_incoming_request(IncomingEthRequest::GetBlockHeaders {
+            peer_id: PeerId::random(),
+            request: request(&headers[4], 1),
+            response: tx,
+        });
+        assert_eq!(rx.await.unwrap().unwrap().0, headers[4..]);
+        assert!(peers_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn count_truncated_headers_responses() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let client = MockEthProvider::default();
+        let headers = (0..=MAX_HEADERS_SERVE as u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        metrics::with_local_recorder(&recorder, || {
+            let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+            let (_requests_tx, requests_rx) = mpsc::channel(1);
+            let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                client,
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig::default(),
+            );
+
+            let request = |limit| GetBlockHeaders {
+                start_block: headers[0].hash_slow().into(),
+                limit,
+                skip: 0,
+                direction: HeadersDirection::Rising,
+            };
+            let served = handler.server.get_headers_response(request(MAX_HEADERS_SERVE as u64));
+            assert_eq!(served.len(), MAX_HEADERS_SERVE);
+            let served = handler.server.get_headers_response(request(MAX_HEADERS_SERVE as u64 + 1));
+            assert_eq!(served.len(), MAX_HEADERS_SERVE);
+        });
+
+        let truncated =
+            snapshotter.snapshot().into_vec().into_iter().find_map(|(key, _, _, value)| {
+                (key.key().name() == "network.eth_headers_response_truncated_total")
+                    .then_some(value)
+            });
+        assert_eq!(truncated, Some(DebugValue::Counter(1)));
+    }
+
+    #[test]
+    fn serve_randomized_headers_requests() {
+        let mut rng = rand::rng();
+        let client = MockEthProvider::default();
+        let mut headers = Vec::<alloy_consensus::Header>::new();
+        for number in 0..32u64 {
+            let parent_hash = headers.last().map(|parent| parent.hash_slow()).unwrap_or_default();
+            let extra_data = Bytes::from(vec![0; rng.random_range(0..64)]);
+            headers.push(alloy_consensus::Header {
+                number,
+                parent_hash,
+                extra_data,
+                ..Default::default()
+            });
+        }
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_headers: 16, ..Default::default() },
+        );
+
+        for _ in 0..1000 {
+            let start = rng.random_range(0..headers.len() as u64 + 4);
+            let start_block = match headers.get(start as usize) {
+                Some(header) if rng.random_bool(0.5) => header.hash_slow().into(),
+                _ => start.into(),
+            };
+            let request = GetBlockHeaders {
+                start_block,
+                limit: rng.random_range(0..24),
+                skip: rng.random_range(0..4),
+                direction: if rng.random_bool(0.5) {
+                    HeadersDirection::Rising
+                } else {
+                    HeadersDirection::Falling
+                },
+            };
+            let soft_response_limit = rng.random_range(0..2048);
+            handler.server.config.soft_response_limit = soft_response_limit;
+
+            // walk the chain header by header
+            let mut expected = Vec::new();
+            let mut total_bytes = 0;
+            let mut number = Some(start);
+            while let Some(header) = number.and_then(|number| headers.get(number as usize)) {
+                if expected.len() as u64 >= request.limit.min(16) {
+                    break
+                }
+                total_bytes += header.length();
+                expected.push(header.clone());
+                if total_bytes > soft_response_limit {
+                    break
+                }
+                let step = request.skip as u64 + 1;
+                number = match request.direction {
+                    HeadersDirection::Rising => header.number.checked_add(step),
+                    HeadersDirection::Falling => header.number.checked_sub(step),
+                };
+            }
+
+            assert_eq!(handler.server.get_headers_response(request), expected, "{request:?}");
+        }
+    }
+
+    #[derive(Debug, Default, Clone)]
+    struct RecordingListener(Arc<Mutex<Vec<(&'static str, PeerId, usize, usize)>>>);
+
+    impl EthRequestListener for RecordingListener {
+        fn on_headers_request(
+            &self,
+            peer_id: PeerId,
+            _request: &GetBlockHeaders,
+            served: usize,
+            bytes: usize,
+        ) {
+            self.0.lock().push(("headers", peer_id, served, bytes));
+        }
+
+        fn on_bodies_request(
+            &self,
+            peer_id: PeerId,
+            _request: &GetBlockBodies,
+            served: usize,
+            bytes: usize,
+        ) {
+            self.0.lock().push(("bodies", peer_id, served, bytes));
+        }
+    }
+
+    #[derive(Debug, Default, Clone)]
+    struct ObservingListener(Arc<Mutex<Vec<RequestObservation>>>);
+
+    impl EthRequestListener for ObservingListener {
+        fn on_request_served(&self, observation: &RequestObservation) {
+            self.0.lock().push(*observation);
+        }
+    }
+
+    #[tokio::test]
+    async fn notify_listener() {
+        let client = MockEthProvider::default();
+        let headers = (0..2u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        let listener = RecordingListener::default();
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        )
+        .with_listener(listener.clone());
+
+        let peer = PeerId::random();
+        let request = GetBlockHeaders {
+            start_block: headers[0].hash_slow().into(),
+            limit: 2,
+            skip: 0,
+            direction: HeadersDirection::Rising,
+        };
+        let (tx, rx) = oneshot::channel();
+        handler.on_headers_request(peer, request, tx);
+        assert_eq!(rx.await.unwrap().unwrap().0, headers);
+
+        // empty responses are observed as well
+        let (tx, rx) = oneshot::channel();
+        handler.on_bodies_request(peer, GetBlockBodies(vec![B256::random()]), tx);
+        assert!(rx.await.unwrap().unwrap().0.is_empty());
+
+        serve_in_flight(&mut handler).await;
+        assert_eq!(
+            *listener.0.lock(),
+            vec![
+                ("headers", peer, 2, headers.length()),
+                ("bodies", peer, 0, Vec::<reth_ethereum_primitives::BlockBody>::new().length()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_lookup_does_not_block_other_requests() {
+        let client = MockEthProvider::default();
+        let header = alloy_consensus::Header::default();
+        client.add_header(header.hash_slow(), header.clone());
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client.clone(),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        // stall block lookups until the lock is released
+        let (locked_tx, locked_rx) = oneshot::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let blocks = client.blocks.clone();
+        let stall = std::thread::spawn(move || {
+            let _blocks = blocks.lock();
+            let _ = locked_tx.send(());
+            let _ = release_rx.recv();
+        });
+        locked_rx.await.unwrap();
+
+        let (bodies_tx, mut bodies_rx) = oneshot::channel();
+        handler.on_bodies_request(
+            PeerId::random(),
+            GetBlockBodies(vec![B256::random()]),
+            bodies_tx,
+        );
+
+        let (tx, rx) = oneshot::channel();
+        let request = GetBlockHeaders {
+            start_block: header.hash_slow().into(),
+            limit: 1,
+            skip: 0,
+            direction: HeadersDirection::Rising,
+        };
+        handler.on_headers_request(PeerId::random(), request, tx);
+        assert_eq!(rx.await.unwrap().unwrap().0, vec![header]);
+        assert!(bodies_rx.try_recv().is_err());
+
+        drop(release_tx);
+        stall.join().unwrap();
+        assert!(bodies_rx.await.unwrap().unwrap().0.is_empty());
+        serve_in_flight(&mut handler).await;
+    }
+
+    #[tokio::test]
+    async fn reject_requests_over_peer_limit() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let client = MockEthProvider::default();
+        let header = alloy_consensus::Header { number: 1, ..Default::default() };
+        let hash = header.hash_slow();
+        client.add_header(hash, header);
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (requests_tx, requests_rx) = mpsc::channel(16);
+        let mut handler = metrics::with_local_recorder(&recorder, || {
+            EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                client,
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig {
+                    max_concurrent_requests_per_peer: 2,
+                    ..Default::default()
+                },
+            )
+        });
+
+        let flooding = PeerId::random();
+        let peer = PeerId::random();
+        let mut responses = Vec::new();
+        for peer_id in [flooding, flooding, flooding, flooding, peer, peer] {
+            let (tx, rx) = oneshot::channel();
+            let request = GetBlockHeaders {
+                start_block: hash.into(),
+                limit: 1,
+                skip: 0,
+                direction: HeadersDirection::Rising,
+            };
+            requests_tx
+                .send(IncomingEthRequest::GetBlockHeaders { peer_id, request, response: tx })
+                .await
+                .unwrap();
+            responses.push(rx);
+        }
+
+        // all requests are taken in a single poll, before any request in flight is served
+        let _ = poll_fn(|cx| Poll::Ready(handler.poll_unpin(cx))).await;
+        tokio::spawn(handler);
+
+        let mut served = Vec::new();
+        for rx in responses {
+            served.push(rx.await.unwrap().unwrap().0.len());
+        }
+        // the requests of the flooding peer over its limit are answered with empty responses, the
+        // other peer is unaffected
+        assert_eq!(served, vec![1, 1, 0, 0, 1, 1]);
+
+        let rejected =
+            snapshotter.snapshot().into_vec().into_iter().find_map(|(key, _, _, value)| {
+                (key.key().name() == "network.eth_requests_rejected_peer_limit_total")
+                    .then_some(value)
+            });
+        assert_eq!(rejected, Some(DebugValue::Counter(2)));
+    }
+
+    #[tokio::test]
+    async fn serve_receipts_by_range() {
+        let client = MockEthProvider::default();
+        let receipts = (0..3u64)
+            .map(|number| {
+                vec![reth_ethereum_primitives::Receipt {
+                    cumulative_gas_used: number,
+                    ..Default::default()
+                }]
+            })
+            .collect::<Vec<_>>();
+        client.extend_receipts((0..3u64).zip(receipts.clone()));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        // the range stops at the first block without receipts
+        let (tx, rx) = oneshot::channel();
+        handler.on_incoming_request(IncomingEthRequest::GetReceiptsByRange {
+            peer_id: PeerId::random(),
+            start: 1,
+            count: 10,
+            response: tx,
+        });
+        assert_eq!(rx.await.unwrap().unwrap().0, receipts[1..]);
+        serve_in_flight(&mut handler).await;
+    }
+
+    #[test]
+    fn record_response_items() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let client = MockEthProvider::default();
+        let header = alloy_consensus::Header { number: 1, ..Default::default() };
+        client.add_header(header.hash_slow(), header.clone());
+
+        metrics::with_local_recorder(&recorder, || {
+            let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+            let (_requests_tx, requests_rx) = mpsc::channel(1);
+            let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                client,
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig::default(),
+            );
+
+            let request = GetBlockHeaders {
+                start_block: header.hash_slow().into(),
+                limit: 1,
+                skip: 0,
+                direction: HeadersDirection::Rising,
+            };
+            assert_eq!(handler.server.get_headers_response(request), vec![header.clone()]);
+            let (bodies, _) = handler.server.get_bodies_response(&[B256::random()]);
+            assert!(bodies.is_empty());
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let metric = |name: &str| {
+            snapshot
+                .iter()
+                .find_map(|(key, _, _, value)| (key.key().name() == name).then_some(value.clone()))
+        };
+        let Some(DebugValue::Histogram(items)) = metric("network.eth_headers_response_items")
+        else {
+            panic!("expected histogram")
+        };
+        assert_eq!(items.iter().map(|items| items.into_inner()).collect::<Vec<_>>(), vec![1.0]);
+        let Some(DebugValue::Histogram(items)) = metric("network.eth_bodies_response_items") else {
+            panic!("expected histogram")
+        };
+        assert_eq!(items.iter().map(|items| items.into_inner()).collect::<Vec<_>>(), vec![0.0]);
+        assert_eq!(
+            metric("network.eth_headers_empty_responses_total"),
+            Some(DebugValue::Counter(0))
+        );
+        assert_eq!(
+            metric("network.eth_bodies_empty_responses_total"),
+            Some(DebugValue::Counter(1))
+        );
+    }
+
+    #[test]
+    fn serve_falling_headers_by_number() {
+        let client = MockEthProvider::default();
+        let mut headers = Vec::<alloy_consensus::Header>::new();
+        for number in 0..10u64 {
+            let parent_hash = headers.last().map(|parent| parent.hash_slow()).unwrap_or_default();
+            headers.push(alloy_consensus::Header { number, parent_hash, ..Default::default() });
+        }
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        for start in [0, 1, 5, 9] {
+            let request = |start_block| GetBlockHeaders {
+                start_block,
+                limit: 20,
+                skip: 0,
+                direction: HeadersDirection::Falling,
+            };
+            let by_hash =
+                handler.server.get_headers_response(request(headers[start].hash_slow().into()));
+            let by_number = handler.server.get_headers_response(request((start as u64).into()));
+            let expected = headers[..=start].iter().rev().cloned().collect::<Vec<_>>();
+            assert_eq!(by_hash, expected);
+            assert_eq!(by_number, expected);
+        }
+    }
+
+    #[test]
+    fn serve_falling_headers_with_skip() {
+        let client = MockEthProvider::default();
+        // block 9 is missing
+        let headers = (0..20u64)
+            .filter(|number| *number != 9)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_headers: 16, ..Default::default() },
+        );
+
+        for (start, skip, limit) in [(19u64, 1, 8), (19, 2, 8), (4, 1, 8), (18, 5, 2), (19, 7, 8)] {
+            let request = GetBlockHeaders {
+                start_block: start.into(),
+                limit,
+                skip,
+                direction: HeadersDirection::Falling,
+            };
+            // look up the headers one by one, stopping at genesis or the missing block
+            let expected = (0..=start)
+                .rev()
+                .step_by(skip as usize + 1)
+                .take(limit as usize)
+                .map_while(|number| handler.server.header(number.into()))
+                .collect::<Vec<_>>();
+            assert_eq!(handler.server.get_headers_response(request), expected, "{request:?}");
+        }
+    }
+
+    #[test]
+    fn serve_rising_headers_by_number() {
+        let client = MockEthProvider::default();
+        let headers = (0..10u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_headers: 4, ..Default::default() },
+        );
+
+        for start in [0u64, 3, 8, 12] {
+            let request = GetBlockHeaders {
+                start_block: start.into(),
+                limit: 8,
+                skip: 0,
+                direction: HeadersDirection::Rising,
+            };
+            // look up the headers one by one
+            let expected = (start..start + 4)
+                .map_while(|number| handler.server.header(number.into()))
+                .collect::<Vec<_>>();
+            assert_eq!(handler.server.get_headers_response(request), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn shed_expensive_requests_when_queue_backs_up() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let client = MockEthProvider::default();
+        let code = Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]);
+        client.add_account(
+            Address::with_last_byte(1),
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(code.clone()),
+        );
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (requests_tx, requests_rx) = mpsc::channel(8);
+        let mut handler = metrics::with_local_recorder(&recorder, || {
+            EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                client,
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig { shed_requests_queue_depth: 2, ..Default::default() },
+            )
+        });
+
+        let mut node_data = Vec::new();
+        let mut headers = Vec::new();
+        for _ in 0..2 {
+            let (tx, rx) = oneshot::channel();
+            requests_tx
+                .send(IncomingEthRequest::GetNodeData {
+                    peer_id: PeerId::random(),
+                    request: GetNodeData(vec![keccak256(&code)]),
+                    response: tx,
+                })
+                .await
+                .unwrap();
+            node_data.push(rx);
+
+            let (tx, rx) = oneshot::channel();
+            requests_tx
+                .send(IncomingEthRequest::GetBlockHeaders {
+                    peer_id: PeerId::random(),
+                    request: GetBlockHeaders {
+                        start_block: B256::random().into(),
+                        limit: 1,
+                        skip: 0,
+                        direction: HeadersDirection::Rising,
+                    },
+                    response: tx,
+                })
+                .await
+                .unwrap();
+            headers.push(rx);
+        }
+
+        let _ = poll_fn(|cx| Poll::Ready(handler.poll_unpin(cx))).await;
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let metric = |name: &str| {
+            snapshot
+                .iter()
+                .find_map(|(key, _, _, value)| (key.key().name() == name).then_some(value.clone()))
+        };
+        assert_eq!(metric("network.eth_request_queue_depth"), Some(DebugValue::Gauge(4.0.into())));
+        assert_eq!(metric("network.eth_requests_shed_total"), Some(DebugValue::Counter(1)));
+
+        // the first node data request is answered with an empty response while 3 requests are
+        // queued behind it
+        tokio::spawn(handler);
+        let served = node_data.pop().unwrap();
+        let shed = node_data.pop().unwrap();
+        assert_eq!(shed.await.unwrap().unwrap(), NodeData(Vec::new()));
+        assert_eq!(served.await.unwrap().unwrap(), NodeData(vec![code]));
+        for rx in headers {
+            assert!(rx.await.is_ok());
+        }
+    }
+
+    #[test]
+    fn serve_empty_entries_for_pruned_receipts() {
+        let client = MockEthProvider::default();
+        let headers = (0..3u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        let hashes = headers.iter().map(|header| header.hash_slow()).collect::<Vec<_>>();
+        client.extend_headers(hashes.iter().copied().zip(headers));
+        let receipt = |number| {
+            vec![reth_ethereum_primitives::Receipt {
+                cumulative_gas_used: number,
+                ..Default::default()
+            }]
+        };
+        // the receipts of the middle block have been pruned
+        client.extend_receipts([(0, receipt(0)), (2, receipt(2))]);
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+        let server = &handler.server;
+        let expected = vec![receipt(0), Vec::new(), receipt(2)];
+
+        let (receipts, skipped) =
+            server.get_receipts_response(&hashes, |hash| server.receipts(hash));
+        assert_eq!(receipts, expected);
+        assert_eq!(skipped, 0);
+
+        // unknown blocks are still skipped
+        let (receipts, skipped) = server
+            .get_receipts_response(&[hashes[1], B256::random()], |hash| server.receipts(hash));
+        assert_eq!(receipts, vec![Vec::new()]);
+        assert_eq!(skipped, 1);
+
+        assert_eq!(server.get_receipts_by_range_response(0, 10), expected);
+
+        let request = GetReceipts70 { first_block_receipt_index: 0, block_hashes: hashes };
+        assert_eq!(server.get_receipts70_response(&request).receipts, expected);
+    }
+
+    #[test]
+    fn skip_pruned_receipts_of_non_empty_blocks() {
+        let client = MockEthProvider::default();
+        let headers = (0..3u64)
+            .map(|number| alloy_consensus::Header {
+                number,
+                // the middle block has receipts, so an empty entry would contradict its header
+                receipts_root: if number == 1 { B256::with_last_byte(1) } else { EMPTY_ROOT_HASH },
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let hashes = headers.iter().map(|header| header.hash_slow()).collect::<Vec<_>>();
+        client.extend_headers(hashes.iter().copied().zip(headers));
+        let receipt = |number| {
+            vec![reth_ethereum_primitives::Receipt {
+                cumulative_gas_used: number,
+                ..Default::default()
+            }]
+        };
+        // the receipts of the middle block have been pruned
+        client.extend_receipts([(0, receipt(0)), (2, receipt(2))]);
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+        let server = &handler.server;
+
+        // the pruned block is skipped like an unknown block
+        let (receipts, skipped) =
+            server.get_receipts_response(&hashes, |hash| server.receipts(hash));
+        assert_eq!(receipts, vec![receipt(0), receipt(2)]);
+        assert_eq!(skipped, 1);
+
+        // responses to consecutive blocks stop at the pruned block
+        assert_eq!(server.get_receipts_by_range_response(0, 10), vec![receipt(0)]);
+
+        let request = GetReceipts70 { first_block_receipt_index: 0, block_hashes: hashes };
+        assert_eq!(server.get_receipts70_response(&request).receipts, vec![receipt(0)]);
+    }
+
+    #[test]
+    fn clamp_receipts_by_receipt_count() {
+        let client = MockEthProvider::default();
+        let headers = (0..2u64)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        let hashes = headers.iter().map(|header| header.hash_slow()).collect::<Vec<_>>();
+        client.extend_headers(hashes.iter().copied().zip(headers));
+        let receipts = |number, count| {
+            (0..count)
+                .map(|_| reth_ethereum_primitives::Receipt {
+                    cumulative_gas_used: number,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>()
+        };
+        // a single fat block followed by a small one
+        client.extend_receipts([(0, receipts(0, 10)), (1, receipts(1, 1))]);
+
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_receipts_per_response: 4, ..Default::default() },
+        );
+        let server = &handler.server;
+
+        // the fat block is served whole, but no further blocks
+        let (served, skipped) = server.get_receipts_response(&hashes, |hash| server.receipts(hash));
+        assert_eq!(served, vec![receipts(0, 10)]);
+        assert_eq!(skipped, 0);
+        assert_eq!(server.get_receipts_by_range_response(0, 2), vec![receipts(0, 10)]);
+
+        // eth/70 responses are cut off within the fat block
+        let request = GetReceipts70 { first_block_receipt_index: 0, block_hashes: hashes };
+        let response = server.get_receipts70_response(&request);
+        assert!(response.last_block_incomplete);
+        assert_eq!(response.receipts, vec![receipts(0, 4)]);
+    }
+
+    #[tokio::test]
+    async fn drain_requests_up_to_budget() {
+        for (budget, queued) in [(1, 3), (10, 0)] {
+            let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+            let (requests_tx, requests_rx) = mpsc::channel(8);
+            let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                MockEthProvider::default(),
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig::default(),
+            )
+            .with_budget(budget);
+
+            let mut responses = Vec::new();
+            for _ in 0..4 {
+                let (tx, rx) = oneshot::channel();
+                requests_tx
+                    .send(IncomingEthRequest::GetBlockBodies {
+                        peer_id: PeerId::random(),
+                        request: GetBlockBodies(vec![B256::random()]),
+                        response: tx,
+                    })
+                    .await
+                    .unwrap();
+                responses.push(rx);
+            }
+
+            let _ = poll_fn(|cx| Poll::Ready(handler.poll_unpin(cx))).await;
+            assert_eq!(handler.queue_depth(), queued);
+
+            tokio::spawn(handler);
+            for rx in responses {
+                assert!(rx.await.unwrap().unwrap().0.is_empty());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn serve_peers_round_robin() {
+        let (peers_tx, mut peers_rx) = mpsc::unbounded_channel();
+        let (requests_tx, requests_rx) = mpsc::channel(128);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            MockEthProvider::default(),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        );
+
+        let request = |peer_id| {
+            let (tx, rx) = oneshot::channel();
+            let request = IncomingEthRequest::GetBlockBodies {
+                peer_id,
+                request: GetBlockBodies(vec![B256::random()]),
+                response: tx,
+            };
+            (request, rx)
+        };
+
+        let flooding = PeerId::random();
+        let mut flooding_responses = Vec::new();
+        for _ in 0..100 {
+            let (request, rx) = request(flooding);
+            requests_tx.send(request).await.unwrap();
+            flooding_responses.push(rx);
+        }
+        let (other_request, other_response) = request(PeerId::random());
+        requests_tx.send(other_request).await.unwrap();
+
+        // the first poll only takes requests of the flooding peer, the request of the other peer
+        // is taken and served by the second poll, right after the first request of the flooding
+        // peer
+        let _ = poll_fn(|cx| Poll::Ready(handler.poll_unpin(cx))).await;
+        assert_eq!(handler.queue_depth(), 100 - MAX_REQUESTS_PER_PEER_PER_POLL + 1);
+        let _ = poll_fn(|cx| Poll::Ready(handler.poll_unpin(cx))).await;
+        assert!(other_response.await.is_ok());
+        assert!(flooding_responses[2 * MAX_REQUESTS_PER_PEER_PER_POLL].try_recv().is_err());
+
+        // requests over the queue limit are dropped and the peer is reported
+        assert_eq!(
+            handler.queue_depth(),
+            MAX_QUEUED_REQUESTS_PER_PEER - MAX_REQUESTS_PER_PEER_PER_POLL
+        );
+        let queued = MAX_QUEUED_REQUESTS_PER_PEER + MAX_REQUESTS_PER_PEER_PER_POLL;
+        for rx in flooding_responses.drain(queued..) {
+            assert!(rx.await.is_err());
+        }
+        let Ok(PeerCommand::ReputationChange(reported, _)) = peers_rx.try_recv() else {
+            panic!("expected reputation change")
+        };
+        assert_eq!(reported, flooding);
+        serve_in_flight(&mut handler).await;
+    }
+
+    #[test]
+    fn cap_requests_taken_per_peer_per_poll() {
+        let mut queues = PeerRequestQueues::default();
+        let (a, b) = (PeerId::random(), PeerId::random());
+        for i in 0..6 {
+            queues.push(a, (a, i)).unwrap();
+        }
+        queues.push(b, (b, 0)).unwrap();
+
+        queues.start_poll();
+        let taken = std::iter::from_fn(|| queues.pop()).collect::<Vec<_>>();
+        assert_eq!(taken, vec![(a, 0), (b, 0), (a, 1), (a, 2), (a, 3)]);
+        assert_eq!(queues.len(), 2);
+
+        queues.start_poll();
+        assert_eq!(std::iter::from_fn(|| queues.pop()).collect::<Vec<_>>(), vec![(a, 4), (a, 5)]);
+        assert!(queues.is_empty());
+        assert!(queues.queues.is_empty() && queues.order.is_empty());
+
+        for i in 0..MAX_QUEUED_REQUESTS_PER_PEER {
+            queues.push(a, (a, i)).unwrap();
+        }
+        assert_eq!(queues.push(a, (a, 0)), Err((a, 0)));
+    }
+
+    #[test]
+    fn count_total_bytes_served() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let client = MockEthProvider::default();
+        let headers = (1..3u64)
+            .map(|number| alloy_consensus::Header {
+                number,
+                extra_data: vec![0; number as usize * 100].into(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        metrics::with_local_recorder(&recorder, || {
+            let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+            let (_requests_tx, requests_rx) = mpsc::channel(1);
+            let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                client,
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig::default(),
+            );
+
+            for header in &headers {
+                let request = GetBlockHeaders {
+                    start_block: header.hash_slow().into(),
+                    limit: 1,
+                    skip: 0,
+                    direction: HeadersDirection::Rising,
+                };
+                assert_eq!(handler.server.get_headers_response(request), vec![header.clone()]);
+            }
+        });
+
+        let total = headers.iter().map(|header| header.length() as u64).sum::<u64>();
+        let served =
+            snapshotter.snapshot().into_vec().into_iter().find_map(|(key, _, _, value)| {
+                (key.key().name() == "network.eth_headers_bytes_served_total").then_some(value)
+            });
+        assert_eq!(served, Some(DebugValue::Counter(total)));
+    }
+
+    #[tokio::test]
+    async fn serve_remaining_requests_on_shutdown() {
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (requests_tx, requests_rx) = mpsc::channel(8);
+        let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            MockEthProvider::default(),
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig { max_concurrent_requests: 1, ..Default::default() },
+        );
+
+        let mut responses = Vec::new();
+        for _ in 0..4 {
+            let (tx, rx) = oneshot::channel();
+            requests_tx
+                .send(IncomingEthRequest::GetBlockBodies {
+                    peer_id: PeerId::random(),
+                    request: GetBlockBodies(vec![B256::random()]),
+                    response: tx,
+                })
+                .await
+                .unwrap();
+            responses.push(rx);
+        }
+        drop(requests_tx);
+
+        // the handler resolves once all queued requests are served
+        tokio::time::timeout(Duration::from_secs(5), handler).await.unwrap();
+        for rx in responses {
+            assert!(rx.await.unwrap().unwrap().0.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn observe_served_requests() {
+        let client = MockEthProvider::default();
+        let header = alloy_consensus::Header { number: 1, ..Default::default() };
+        client.add_header(header.hash_slow(), header.clone());
+
+        let listener = ObservingListener::default();
+        let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+        let (_requests_tx, requests_rx) = mpsc::channel(1);
+        let mut handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+            client,
+            PeersHandle::new(peers_tx),
+            requests_rx,
+            EthRequestHandlerConfig::default(),
+        )
+        .with_listener(listener.clone());
+
+        let peer_id = PeerId::random();
+        let (tx, rx) = oneshot::channel();
+        handler.on_headers_request(
+            peer_id,
+            GetBlockHeaders {
+                start_block: header.hash_slow().into(),
+                limit: 1,
+                skip: 0,
+                direction: HeadersDirection::Rising,
+            },
+            tx,
+        );
+        assert_eq!(rx.await.unwrap().unwrap().0, vec![header.clone()]);
+        serve_in_flight(&mut handler).await;
+
+        let observations = listener.0.lock();
+        let [observation] = observations.as_slice() else { panic!("expected one observation") };
+        assert_eq!(observation.peer_id, peer_id);
+        assert_eq!(observation.kind, EthRequestKind::Headers);
+        assert_eq!(observation.items, 1);
+        assert_eq!(observation.bytes, vec![header].length());
+    }
+
+    #[test]
+    fn validate_config() {
+        assert_eq!(EthRequestHandlerConfig::default().validate(), Ok(()));
+        assert_eq!(
+            EthRequestHandlerConfig { max_headers: 0, ..Default::default() }.validate(),
+            Err(EthRequestHandlerConfigError::ZeroLimit("max_headers"))
+        );
+        assert_eq!(
+            EthRequestHandlerConfig { max_receipts: 0, ..Default::default() }.validate(),
+            Err(EthRequestHandlerConfigError::ZeroLimit("max_receipts"))
+        );
+        assert_eq!(
+            EthRequestHandlerConfig {
+                soft_response_limit: MIN_SOFT_RESPONSE_LIMIT - 1,
+                ..Default::default()
+            }
+            .validate(),
+            Err(EthRequestHandlerConfigError::SoftResponseLimitTooLow(MIN_SOFT_RESPONSE_LIMIT - 1))
+        );
+        assert_eq!(
+            EthRequestHandlerConfig {
+                soft_response_limit: MIN_SOFT_RESPONSE_LIMIT,
+                ..Default::default()
+            }
+            .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            EthRequestHandlerConfig {
+                max_bytes_per_peer_per_second: Some(0),
+                ..Default::default()
+            }
+            .validate(),
+            Err(EthRequestHandlerConfigError::ZeroLimit("max_bytes_per_peer_per_second"))
+        );
+    }
+
+    #[test]
+    fn count_empty_headers_responses() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let client = MockEthProvider::default();
+        let headers = (0..=2)
+            .map(|number| alloy_consensus::Header { number, ..Default::default() })
+            .collect::<Vec<_>>();
+        client.extend_headers(headers.iter().map(|header| (header.hash_slow(), header.clone())));
+
+        metrics::with_local_recorder(&recorder, || {
+            let (peers_tx, _peers_rx) = mpsc::unbounded_channel();
+            let (_requests_tx, requests_rx) = mpsc::channel(1);
+            let handler = EthRequestHandler::<_, EthNetworkPrimitives>::new(
+                client,
+                PeersHandle::new(peers_tx),
+                requests_rx,
+                EthRequestHandlerConfig::default(),
+            );
+
+            let request = |start_block: BlockHashOrNumber, limit, direction| GetBlockHeaders {
+                start_block,
+                limit,
+                skip: 0,
+                direction,
+            };
+
+            // the start block is not known
+            for request in [
+                request(B256::random().into(), 2, HeadersDirection::Rising),
+                request(10u64.into(), 2, HeadersDirection::Rising),
+                request(10u64.into(), 2, HeadersDirection::Falling),
+            ] {
+                assert!(handler.server.get_headers_response(request).is_empty());
+            }
+
+            // the start block is known, but the tail beyond our tip is empty
+            let served = handler.server.get_headers_response(request(
+                2u64.into(),
+                5,
+                HeadersDirection::Rising,
+            ));
+            assert_eq!(served, vec![headers[2].clone()]);
+
+            // nothing was requested
+            let request = request(headers[0].hash_slow().into(), 0, HeadersDirection::Rising);
+            assert!(handler.server.get_headers_response(request).is_empty());
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let metric = |name: &str| {
+            snapshot
+                .iter()
+                .find_map(|(key, _, _, value)| (key.key().name() == name).then_some(value.clone()))
+        };
+        assert_eq!(
+            metric("network.eth_headers_empty_responses_total"),
+            Some(DebugValue::Counter(4))
+        );
+        assert_eq!(
+            metric("network.eth_headers_start_not_found_total"),
+            Some(DebugValue::Counter(3))
+        );
+    }
 }
@@ -1,6 +1,9 @@
 //! A client implementation that can interact with the network and download data.
 
-use crate::{fetch::DownloadRequest, flattened_response::FlattenedResponse};
+use crate::{
+    fetch::{DownloadRequest, ReceiptsRangePeers},
+    flattened_response::FlattenedResponse,
+};
 use alloy_primitives::B256;
 use futures::{future, future::Either};
 use reth_eth_wire::{EthNetworkPrimitives, NetworkPrimitives};
@@ -39,6 +42,29 @@ pub struct FetchClient<N: NetworkPrimitives = EthNetworkPrimitives> {
     pub(crate) peers_handle: PeersHandle,
     /// Number of active peer sessions the node's currently handling.
     pub(crate) num_active_peers: Arc<AtomicUsize>,
+    /// The peers that support the receipts range protocol.
+    pub(crate) receipts_range_peers: ReceiptsRangePeers<N>,
+}
+
+impl<N: NetworkPrimitives> FetchClient<N> {
+    /// Requests the receipts of `count` consecutive blocks, starting at block `start`, from a
+    /// peer that supports the [`receipts_range_capability`](crate::receipts_range_capability).
+    ///
+    /// This is only negotiated between reth peers. If no connected peer supports it, this
+    /// returns [`RequestError::UnsupportedCapability`] without sending anything, and the receipts
+    /// have to be requested by block hash instead.
+    ///
+    /// The response may contain fewer blocks than requested.
+    pub fn get_receipts_by_range(
+        &self,
+        start: u64,
+        count: u64,
+    ) -> HeadersClientFuture<PeerRequestResult<Vec<Vec<N::Receipt>>>> {
+        match self.receipts_range_peers.request(start, count) {
+            Ok(rx) => Either::Left(FlattenedResponse::from(rx)),
+            Err(err) => Either::Right(future::err(err)),
+        }
+    }
 }
 
 impl<N: NetworkPrimitives> DownloadClient for FetchClient<N> {
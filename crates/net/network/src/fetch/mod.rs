@@ -1,8 +1,11 @@
 //! Fetch data from the network.
 
 mod client;
+mod receipts_range;
 
 pub use client::FetchClient;
+pub use receipts_range::receipts_range_capability;
+pub(crate) use receipts_range::{ReceiptsRangePeers, ReceiptsRangeProtocol};
 
 use crate::{message::BlockRequest, session::BlockRangeInfo};
 use alloy_primitives::B256;
@@ -26,12 +29,17 @@ use std::{
         Arc,
     },
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
 use tokio::sync::{mpsc, mpsc::UnboundedSender, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 type InflightHeadersRequest<H> = Request<HeadersRequest, PeerRequestResult<Vec<H>>>;
-type InflightBodiesRequest<B> = Request<(), PeerRequestResult<Vec<B>>>;
+type InflightBodiesRequest<B> = Request<Option<RangeInclusive<u64>>, PeerRequestResult<Vec<B>>>;
+
+/// How long a [`ServeLimit`] is kept before the peer is asked for the blocks below it again, in
+/// case it was only temporarily missing them.
+const SERVE_LIMIT_DECAY: Duration = Duration::from_secs(5 * 60);
 
 /// Manages data fetching operations.
 ///
@@ -57,6 +65,8 @@ pub struct StateFetcher<N: NetworkPrimitives = EthNetworkPrimitives> {
     download_requests_rx: UnboundedReceiverStream<DownloadRequest<N>>,
     /// Sender for download requests, used to detach a [`FetchClient`]
     download_requests_tx: UnboundedSender<DownloadRequest<N>>,
+    /// The peers that can be asked for receipts by block range, shared with every [`FetchClient`]
+    receipts_range_peers: ReceiptsRangePeers<N>,
 }
 
 // === impl StateSyncer ===
@@ -73,6 +83,7 @@ impl<N: NetworkPrimitives> StateFetcher<N> {
             queued_requests: Default::default(),
             download_requests_rx: UnboundedReceiverStream::new(download_requests_rx),
             download_requests_tx,
+            receipts_range_peers: Default::default(),
         }
     }
 
@@ -96,6 +107,8 @@ impl<N: NetworkPrimitives> StateFetcher<N> {
                 timeout,
                 last_response_likely_bad: false,
                 range_info,
+                headers_limit: Default::default(),
+                bodies_limit: Default::default(),
             },
         );
     }
@@ -140,13 +153,33 @@ impl<N: NetworkPrimitives> StateFetcher<N> {
     /// Returns the _next_ idle peer that's ready to accept a request,
     /// prioritizing those with the lowest timeout/latency and those that recently responded with
     /// adequate data. Additionally, if full blocks are required this prioritizes peers that have
-    /// full history available
+    /// full history available.
+    ///
+    /// Peers that announced that they don't have any of the required blocks, or recently responded
+    /// without them, are only chosen if no other peer is idle.
     fn next_best_peer(&self, requirement: BestPeerRequirements) -> Option<PeerId> {
-        let mut idle = self.peers.iter().filter(|(_, peer)| peer.state.is_idle());
-
-        let mut best_peer = idle.next()?;
+        let now = Instant::now();
+        let mut idle = self
+            .peers
+            .iter()
+            .filter(|(_, peer)| peer.state.is_idle())
+            .map(|(peer_id, peer)| (peer_id, peer, peer.may_serve(&requirement, now)));
+
+        let (best_id, best, mut best_may_serve) = idle.next()?;
+        let mut best_peer = (best_id, best);
+
+        for (peer_id, peer, may_serve) in idle {
+            let maybe_better = (peer_id, peer);
+
+            // peers that may serve the request are always preferred over limited ones
+            if may_serve != best_may_serve {
+                if may_serve {
+                    best_peer = maybe_better;
+                    best_may_serve = true;
+                }
+                continue
+            }
 
-        for maybe_better in idle {
             // replace best peer if our current best peer sent us a bad response last time
             if best_peer.1.last_response_likely_bad && !maybe_better.1.last_response_likely_bad {
                 best_peer = maybe_better;
@@ -251,8 +284,8 @@ impl<N: NetworkPrimitives> StateFetcher<N> {
                     direction,
                 })
             }
-            DownloadRequest::GetBlockBodies { request, response, .. } => {
-                let inflight = Request { request: (), response };
+            DownloadRequest::GetBlockBodies { request, response, range_hint, .. } => {
+                let inflight = Request { request: range_hint, response };
                 self.inflight_bodies_requests.insert(peer_id, inflight);
                 BlockRequest::GetBlockBodies(GetBlockBodies(request))
             }
@@ -286,6 +319,12 @@ impl<N: NetworkPrimitives> StateFetcher<N> {
         let is_likely_bad_response =
             resp.as_ref().is_some_and(|r| res.is_likely_bad_headers_response(&r.request));
 
+        // the block the peer doesn't have, if it responded without any of the requested headers
+        let missing = resp
+            .as_ref()
+            .filter(|_| res.as_ref().is_ok_and(|headers| headers.is_empty()))
+            .and_then(|r| r.request.start.as_number());
+
         if let Some(resp) = resp {
             // delegate the response
             let _ = resp.response.send(res.map(|h| (peer_id, h).into()));
@@ -295,6 +334,10 @@ impl<N: NetworkPrimitives> StateFetcher<N> {
             // update the peer's response state
             peer.last_response_likely_bad = is_likely_bad_response;
 
+            if let Some(block) = missing.filter(|block| *block < peer.latest()) {
+                peer.headers_limit.on_missing(block, Instant::now());
+            }
+
             // If the peer is still ready to accept new requests, we try to send a followup
             // request immediately.
             if peer.state.on_request_finished() && !is_error && !is_likely_bad_response {
@@ -316,13 +359,23 @@ impl<N: NetworkPrimitives> StateFetcher<N> {
     ) -> Option<BlockResponseOutcome> {
         let is_likely_bad_response = res.as_ref().map_or(true, |bodies| bodies.is_empty());
 
+        // the highest block the peer doesn't have, if it responded without any of the requested
+        // bodies
+        let mut missing = None;
         if let Some(resp) = self.inflight_bodies_requests.remove(&peer_id) {
+            if res.as_ref().is_ok_and(|bodies| bodies.is_empty()) {
+                missing = resp.request.map(|range| *range.end());
+            }
             let _ = resp.response.send(res.map(|b| (peer_id, b).into()));
         }
         if let Some(peer) = self.peers.get_mut(&peer_id) {
             // update the peer's response state
             peer.last_response_likely_bad = is_likely_bad_response;
 
+            if let Some(block) = missing.filter(|block| *block < peer.latest()) {
+                peer.bodies_limit.on_missing(block, Instant::now());
+            }
+
             if peer.state.on_request_finished() && !is_likely_bad_response {
                 return self.followup_request(peer_id)
             }
@@ -336,6 +389,7 @@ impl<N: NetworkPrimitives> StateFetcher<N> {
             request_tx: self.download_requests_tx.clone(),
             peers_handle: self.peers_handle.clone(),
             num_active_peers: Arc::clone(&self.num_active_peers),
+            receipts_range_peers: self.receipts_range_peers.clone(),
         }
     }
 }
@@ -370,6 +424,10 @@ struct Peer {
     last_response_likely_bad: bool,
     /// Tracks the range info for the peer.
     range_info: Option<BlockRangeInfo>,
+    /// The lowest header the peer is assumed to serve, derived from its empty responses.
+    headers_limit: ServeLimit,
+    /// The lowest block body the peer is assumed to serve, derived from its empty responses.
+    bodies_limit: ServeLimit,
 }
 
 impl Peer {
@@ -382,6 +440,13 @@ impl Peer {
         self.range_info.as_ref().map_or(0, |info| info.earliest())
     }
 
+    /// Returns the highest block number the peer is known to have.
+    fn latest(&self) -> u64 {
+        self.range_info
+            .as_ref()
+            .map_or(self.best_number, |info| info.latest().max(self.best_number))
+    }
+
     /// Returns true if the peer has the full history available.
     fn has_full_history(&self) -> bool {
         self.earliest() == 0
@@ -434,16 +499,57 @@ impl Peer {
         }
     }
 
+    /// Returns false if the peer announced that it doesn't have any of the blocks required, or
+    /// recently responded without the blocks.
+    ///
+    /// Only the earliest block of the announced range is considered, because the latest block is
+    /// only announced periodically and may lag behind.
+    fn may_serve(&self, requirement: &BestPeerRequirements, now: Instant) -> bool {
+        match requirement {
+            BestPeerRequirements::Header(number) => self.headers_limit.earliest(now) <= *number,
+            BestPeerRequirements::FullBlockRange(range) => {
+                self.earliest().max(self.bodies_limit.earliest(now)) <= *range.end()
+            }
+            BestPeerRequirements::None | BestPeerRequirements::FullBlock => true,
+        }
+    }
+
     /// Returns true if this peer is better than the other peer based on the given requirements.
     fn is_better(&self, other: &Self, requirement: &BestPeerRequirements) -> bool {
         match requirement {
-            BestPeerRequirements::None => false,
+            BestPeerRequirements::None | BestPeerRequirements::Header(_) => false,
             BestPeerRequirements::FullBlockRange(range) => self.has_better_range(other, range),
             BestPeerRequirements::FullBlock => self.has_full_history() && !other.has_full_history(),
         }
     }
 }
 
+/// The lowest block a peer is assumed to serve, derived from its empty responses.
+///
+/// Peers serve a contiguous range of blocks, so a peer that doesn't have a block below its latest
+/// block doesn't have any of the blocks before it either. The limit expires after
+/// [`SERVE_LIMIT_DECAY`].
+#[derive(Debug, Default)]
+struct ServeLimit {
+    /// The lowest block the peer is assumed to serve and when this expires.
+    limit: Option<(u64, Instant)>,
+}
+
+impl ServeLimit {
+    /// Returns the lowest block the peer is assumed to serve, `0` if unknown or expired.
+    fn earliest(&self, now: Instant) -> u64 {
+        self.limit.filter(|(_, expires_at)| now < *expires_at).map_or(0, |(earliest, _)| earliest)
+    }
+
+    /// Records that the peer responded without the given block.
+    fn on_missing(&mut self, block: u64, now: Instant) {
+        let earliest = block.saturating_add(1);
+        if earliest > self.earliest(now) {
+            self.limit = Some((earliest, now + SERVE_LIMIT_DECAY));
+        }
+    }
+}
+
 /// Tracks the state of an individual peer
 #[derive(Debug)]
 enum PeerState {
@@ -535,7 +641,10 @@ impl<N: NetworkPrimitives> DownloadRequest<N> {
     /// Returns the best peer requirements for this request.
     fn best_peer_requirements(&self) -> BestPeerRequirements {
         match self {
-            Self::GetBlockHeaders { .. } => BestPeerRequirements::None,
+            Self::GetBlockHeaders { request, .. } => request
+                .start
+                .as_number()
+                .map_or(BestPeerRequirements::None, BestPeerRequirements::Header),
             Self::GetBlockBodies { range_hint, .. } => {
                 if let Some(range) = range_hint {
                     BestPeerRequirements::FullBlockRange(range.clone())
@@ -573,6 +682,8 @@ pub(crate) enum BlockResponseOutcome {
 enum BestPeerRequirements {
     /// No additional requirements
     None,
+    /// Peer must have this header available.
+    Header(u64),
     /// Peer must have this block range available.
     FullBlockRange(RangeInclusive<u64>),
     /// Peer must have full range.
@@ -584,6 +695,7 @@ mod tests {
     use super::*;
     use crate::{peers::PeersManager, PeersConfig};
     use alloy_consensus::Header;
+    use alloy_eips::BlockHashOrNumber;
     use alloy_primitives::B512;
     use std::future::poll_fn;
 
@@ -696,6 +808,195 @@ mod tests {
         assert_eq!(fetcher.next_best_peer(BestPeerRequirements::None), Some(peer2));
     }
 
+    #[tokio::test]
+    async fn test_skip_peers_without_requested_range() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher =
+            StateFetcher::<EthNetworkPrimitives>::new(manager.handle(), Default::default());
+        let pruned = B512::random();
+        let full = B512::random();
+
+        let capabilities = Arc::new(Capabilities::from(vec![]));
+        let pruned_range = BlockRangeInfo::new(80, 100, B256::random());
+        fetcher.new_active_peer(
+            pruned,
+            B256::random(),
+            100,
+            Arc::clone(&capabilities),
+            Arc::new(AtomicU64::new(10)),
+            Some(pruned_range.clone()),
+        );
+
+        // the pruned peer doesn't have any of the requested blocks, but it's the only peer
+        let requirement = || BestPeerRequirements::FullBlockRange(10..=20);
+        assert_eq!(fetcher.next_best_peer(requirement()), Some(pruned));
+        assert_eq!(
+            fetcher.next_best_peer(BestPeerRequirements::FullBlockRange(70..=90)),
+            Some(pruned)
+        );
+
+        // requests are assigned to the peer that has the blocks, despite its higher timeout
+        fetcher.new_active_peer(
+            full,
+            B256::random(),
+            100,
+            Arc::clone(&capabilities),
+            Arc::new(AtomicU64::new(50)),
+            Some(BlockRangeInfo::new(0, 100, B256::random())),
+        );
+        assert_eq!(fetcher.next_best_peer(requirement()), Some(full));
+
+        // headers are still requested from the fastest peer
+        assert_eq!(fetcher.next_best_peer(BestPeerRequirements::None), Some(pruned));
+
+        // the pruned peer announced an updated range
+        pruned_range.update(0, 100, B256::random());
+        assert_eq!(fetcher.next_best_peer(requirement()), Some(pruned));
+    }
+
+    /// Dispatches the request and returns the peer it was assigned to.
+    fn dispatch(
+        fetcher: &mut StateFetcher<EthNetworkPrimitives>,
+        request: DownloadRequest<EthNetworkPrimitives>,
+    ) -> Option<PeerId> {
+        fetcher.queued_requests.push_back(request);
+        match fetcher.poll_action() {
+            PollAction::Ready(FetchAction::BlockRequest { peer_id, .. }) => Some(peer_id),
+            PollAction::NoRequests | PollAction::NoPeersAvailable => {
+                fetcher.queued_requests.clear();
+                None
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skip_peers_without_bodies_after_empty_response() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher =
+            StateFetcher::<EthNetworkPrimitives>::new(manager.handle(), Default::default());
+        let pruned = B512::random();
+        let full = B512::random();
+
+        let capabilities = Arc::new(Capabilities::from(vec![]));
+        for (peer_id, timeout) in [(pruned, 10), (full, 50)] {
+            fetcher.new_active_peer(
+                peer_id,
+                B256::random(),
+                100,
+                Arc::clone(&capabilities),
+                Arc::new(AtomicU64::new(timeout)),
+                None,
+            );
+        }
+
+        let bodies = |range: RangeInclusive<u64>| {
+            let (tx, _rx) = oneshot::channel();
+            DownloadRequest::GetBlockBodies {
+                request: vec![B256::random()],
+                response: tx,
+                priority: Priority::Normal,
+                range_hint: Some(range),
+            }
+        };
+
+        // the fastest peer doesn't have the blocks
+        assert_eq!(dispatch(&mut fetcher, bodies(10..=20)), Some(pruned));
+        assert_eq!(fetcher.on_block_bodies_response(pruned, Ok(vec![])), None);
+        assert_eq!(fetcher.peers[&pruned].bodies_limit.earliest(Instant::now()), 21);
+
+        // the range is requested from the other peer, and only falls back to the limited peer
+        // while the other peer is busy
+        assert_eq!(dispatch(&mut fetcher, bodies(10..=20)), Some(full));
+        assert_eq!(dispatch(&mut fetcher, bodies(15..=20)), Some(pruned));
+        assert_eq!(fetcher.on_block_bodies_response(pruned, Ok(vec![])), None);
+        assert_eq!(fetcher.on_block_bodies_response(full, Ok(vec![Default::default()])), None);
+        assert_eq!(dispatch(&mut fetcher, bodies(15..=20)), Some(full));
+        assert_eq!(dispatch(&mut fetcher, bodies(30..=40)), Some(pruned));
+        assert_eq!(fetcher.on_block_bodies_response(pruned, Ok(vec![Default::default()])), None);
+
+        // the peer is asked again once the limit expired
+        fetcher.peers.get_mut(&pruned).unwrap().bodies_limit.limit = Some((21, Instant::now()));
+        assert_eq!(dispatch(&mut fetcher, bodies(10..=20)), Some(pruned));
+
+        // blocks above the peer's latest block may just not have reached it yet
+        assert_eq!(fetcher.on_block_bodies_response(pruned, Ok(vec![])), None);
+        assert_eq!(fetcher.peers[&pruned].bodies_limit.earliest(Instant::now()), 21);
+        assert_eq!(dispatch(&mut fetcher, bodies(100..=110)), Some(pruned));
+        assert_eq!(fetcher.on_block_bodies_response(pruned, Ok(vec![])), None);
+        assert_eq!(fetcher.peers[&pruned].bodies_limit.earliest(Instant::now()), 21);
+    }
+
+    #[tokio::test]
+    async fn test_fall_back_to_limited_peer() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher =
+            StateFetcher::<EthNetworkPrimitives>::new(manager.handle(), Default::default());
+        let peer_id = B512::random();
+
+        fetcher.new_active_peer(
+            peer_id,
+            B256::random(),
+            100,
+            Arc::new(Capabilities::from(vec![])),
+            Arc::new(AtomicU64::new(10)),
+            None,
+        );
+        let peer = fetcher.peers.get_mut(&peer_id).unwrap();
+        peer.headers_limit.on_missing(50, Instant::now());
+        peer.bodies_limit.on_missing(50, Instant::now());
+
+        // the only peer is still asked, instead of stalling until the limits expired
+        assert_eq!(
+            fetcher.next_best_peer(BestPeerRequirements::FullBlockRange(10..=20)),
+            Some(peer_id)
+        );
+        assert_eq!(fetcher.next_best_peer(BestPeerRequirements::Header(10)), Some(peer_id));
+    }
+
+    #[tokio::test]
+    async fn test_skip_peers_without_headers_after_empty_response() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher =
+            StateFetcher::<EthNetworkPrimitives>::new(manager.handle(), Default::default());
+        let pruned = B512::random();
+        let full = B512::random();
+
+        let capabilities = Arc::new(Capabilities::from(vec![]));
+        for (peer_id, timeout) in [(pruned, 10), (full, 50)] {
+            fetcher.new_active_peer(
+                peer_id,
+                B256::random(),
+                100,
+                Arc::clone(&capabilities),
+                Arc::new(AtomicU64::new(timeout)),
+                None,
+            );
+        }
+
+        let headers = |start: BlockHashOrNumber| {
+            let (tx, _rx) = oneshot::channel();
+            DownloadRequest::GetBlockHeaders {
+                request: HeadersRequest { start, limit: 5, direction: Default::default() },
+                response: tx,
+                priority: Priority::Normal,
+            }
+        };
+
+        assert_eq!(dispatch(&mut fetcher, headers(10u64.into())), Some(pruned));
+        assert_eq!(fetcher.on_block_headers_response(pruned, Ok(vec![])), None);
+
+        assert_eq!(dispatch(&mut fetcher, headers(10u64.into())), Some(full));
+        assert_eq!(dispatch(&mut fetcher, headers(8u64.into())), Some(pruned));
+        assert_eq!(fetcher.on_block_headers_response(pruned, Ok(vec![])), None);
+        assert_eq!(fetcher.on_block_headers_response(full, Ok(vec![Header::default()])), None);
+        assert_eq!(dispatch(&mut fetcher, headers(8u64.into())), Some(full));
+        assert_eq!(dispatch(&mut fetcher, headers(11u64.into())), Some(pruned));
+        assert_eq!(fetcher.on_block_headers_response(pruned, Ok(vec![Header::default()])), None);
+
+        // requests by hash can't be checked against the limit
+        assert_eq!(dispatch(&mut fetcher, headers(B256::random().into())), Some(pruned));
+    }
+
     #[tokio::test]
     async fn test_on_block_headers_response() {
         let manager = PeersManager::new(PeersConfig::default());
@@ -794,6 +1095,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(0, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         let peer2 = Peer {
@@ -804,6 +1107,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(20)),
             last_response_likely_bad: false,
             range_info: None,
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // With None requirement, is_better should always return false
@@ -822,6 +1127,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(0, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer without full history (earliest = 50)
@@ -833,6 +1140,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(50, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer without range info (treated as full history)
@@ -844,6 +1153,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: None,
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer with full history is better than peer without
@@ -872,6 +1183,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(0, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer that doesn't cover the range (earliest too high)
@@ -883,6 +1196,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(70, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer that covers the requested range is better than one that doesn't
@@ -906,6 +1221,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(0, 50, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer without full history that also covers the range
@@ -917,6 +1234,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(30, 50, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // When both cover the range, prefer none
@@ -938,6 +1257,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(0, 50, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer without full history that also covers the range
@@ -949,6 +1270,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(30, 50, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // When both cover the range, prefer lower start value
@@ -970,6 +1293,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(0, 30, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer without full history that also doesn't cover the range
@@ -981,6 +1306,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(10, 30, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // When neither covers the range, prefer full history
@@ -1002,6 +1329,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(30, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer without range info
@@ -1013,6 +1342,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: None,
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer without range info is not better (we prefer peers with known ranges)
@@ -1038,6 +1369,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(30, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer without range info (treated as full history with unknown latest)
@@ -1049,6 +1382,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: None,
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer with range that covers is better than peer without range info
@@ -1073,6 +1408,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(70, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer without range info (treated as full history)
@@ -1084,6 +1421,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: None,
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer with range that doesn't cover is not better
@@ -1109,6 +1448,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(50, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer that's one block short at the start
@@ -1120,6 +1461,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(51, 100, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Peer that's one block short at the end
@@ -1131,6 +1474,8 @@ mod tests {
             timeout: Arc::new(AtomicU64::new(10)),
             last_response_likely_bad: false,
             range_info: Some(BlockRangeInfo::new(50, 99, B256::random())),
+            headers_limit: Default::default(),
+            bodies_limit: Default::default(),
         };
 
         // Exact coverage is better than short coverage
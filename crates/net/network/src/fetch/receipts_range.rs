@@ -0,0 +1,391 @@
+//! An `RLPx` sub-protocol to request the receipts of consecutive blocks by number.
+//!
+//! This isn't part of the `eth` protocol. It's only negotiated between reth peers that both
+//! announce the [`receipts_range_capability`], so a range can be fetched in a single round trip
+//! instead of resolving every block hash first. Peers without the capability are never asked.
+
+use crate::{
+    eth_requests::IncomingEthRequest,
+    protocol::{ConnectionHandler, OnNotSupported, ProtocolHandler},
+};
+use alloy_primitives::bytes::{BufMut, BytesMut};
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use futures::{stream::FuturesUnordered, Stream, StreamExt};
+use parking_lot::Mutex;
+use reth_eth_wire::{
+    capability::SharedCapabilities, message::RequestPair, multiplex::ProtocolConnection,
+    protocol::Protocol, Capability, NetworkPrimitives, Receipts69,
+};
+use reth_network_api::Direction;
+use reth_network_p2p::error::{PeerRequestResult, RequestError};
+use reth_network_peers::{PeerId, WithPeerId};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::{debug, trace};
+
+/// Message id of [`GetReceiptsByRange`].
+const GET_RECEIPTS_BY_RANGE_ID: u8 = 0x00;
+
+/// Message id of the response to [`GetReceiptsByRange`].
+const RECEIPTS_BY_RANGE_ID: u8 = 0x01;
+
+/// Returns the capability of the receipts range protocol.
+pub fn receipts_range_capability() -> Capability {
+    Capability::new_static("rethrcpt", 1)
+}
+
+/// Request for the receipts of `count` consecutive blocks, starting at block `start`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub(crate) struct GetReceiptsByRange {
+    /// The number of the first block.
+    pub(crate) start: u64,
+    /// The number of consecutive blocks.
+    pub(crate) count: u64,
+}
+
+/// A message of the receipts range protocol.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ReceiptsRangeMessage<R> {
+    /// Requests the receipts of a range of blocks.
+    GetReceiptsByRange(RequestPair<GetReceiptsByRange>),
+    /// The receipts per block, without bloom filter.
+    ReceiptsByRange(RequestPair<Receipts69<R>>),
+}
+
+impl<R: Encodable + Decodable> ReceiptsRangeMessage<R> {
+    /// Encodes the message, prefixed with its message id.
+    pub(crate) fn encoded(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        match self {
+            Self::GetReceiptsByRange(request) => {
+                buf.put_u8(GET_RECEIPTS_BY_RANGE_ID);
+                request.encode(&mut buf);
+            }
+            Self::ReceiptsByRange(response) => {
+                buf.put_u8(RECEIPTS_BY_RANGE_ID);
+                response.encode(&mut buf);
+            }
+        }
+        buf
+    }
+
+    /// Decodes a message that is prefixed with its message id.
+    pub(crate) fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let (&id, rest) = buf.split_first().ok_or(alloy_rlp::Error::InputTooShort)?;
+        *buf = rest;
+        match id {
+            GET_RECEIPTS_BY_RANGE_ID => Ok(Self::GetReceiptsByRange(RequestPair::decode(buf)?)),
+            RECEIPTS_BY_RANGE_ID => Ok(Self::ReceiptsByRange(RequestPair::decode(buf)?)),
+            _ => Err(alloy_rlp::Error::Custom("unknown receipts range message id")),
+        }
+    }
+}
+
+/// A request for a range of receipts sent to the connection of a peer.
+#[derive(Debug)]
+pub(crate) struct ReceiptsRangeCommand<R> {
+    request: GetReceiptsByRange,
+    response: oneshot::Sender<PeerRequestResult<Vec<Vec<R>>>>,
+}
+
+/// The connected peers that negotiated the receipts range protocol.
+///
+/// This is shared by the [`FetchClient`](crate::FetchClient) and the connections of the protocol.
+pub(crate) struct ReceiptsRangePeers<N: NetworkPrimitives> {
+    peers: Arc<Mutex<HashMap<PeerId, mpsc::UnboundedSender<ReceiptsRangeCommand<N::Receipt>>>>>,
+}
+
+impl<N: NetworkPrimitives> ReceiptsRangePeers<N> {
+    /// Sends the request to one of the peers that support the protocol.
+    ///
+    /// Returns [`RequestError::UnsupportedCapability`] if there's no such peer.
+    pub(crate) fn request(
+        &self,
+        start: u64,
+        count: u64,
+    ) -> Result<oneshot::Receiver<PeerRequestResult<Vec<Vec<N::Receipt>>>>, RequestError> {
+        let mut peers = self.peers.lock();
+        peers.retain(|_, to_connection| !to_connection.is_closed());
+        let to_connection = peers.values().next().ok_or(RequestError::UnsupportedCapability)?;
+
+        let (response, rx) = oneshot::channel();
+        let request = GetReceiptsByRange { start, count };
+        to_connection
+            .send(ReceiptsRangeCommand { request, response })
+            .map_err(|_| RequestError::ConnectionDropped)?;
+        Ok(rx)
+    }
+
+    /// Returns the number of connected peers that support the protocol.
+    pub(crate) fn len(&self) -> usize {
+        self.peers.lock().len()
+    }
+}
+
+impl<N: NetworkPrimitives> Clone for ReceiptsRangePeers<N> {
+    fn clone(&self) -> Self {
+        Self { peers: Arc::clone(&self.peers) }
+    }
+}
+
+impl<N: NetworkPrimitives> Default for ReceiptsRangePeers<N> {
+    fn default() -> Self {
+        Self { peers: Default::default() }
+    }
+}
+
+impl<N: NetworkPrimitives> fmt::Debug for ReceiptsRangePeers<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReceiptsRangePeers").field("peers", &self.len()).finish()
+    }
+}
+
+/// Announces the receipts range protocol to all peers.
+///
+/// Requests of remote peers are served by the
+/// [`EthRequestHandler`](crate::eth_requests::EthRequestHandler) as
+/// [`IncomingEthRequest::GetReceiptsByRange`].
+#[derive(Debug)]
+pub(crate) struct ReceiptsRangeProtocol<N: NetworkPrimitives> {
+    to_eth_request_handler: mpsc::Sender<IncomingEthRequest<N>>,
+    peers: ReceiptsRangePeers<N>,
+}
+
+impl<N: NetworkPrimitives> ReceiptsRangeProtocol<N> {
+    /// Creates the protocol that forwards requests to the given eth request handler and tracks
+    /// the peers that support it in `peers`.
+    pub(crate) const fn new(
+        to_eth_request_handler: mpsc::Sender<IncomingEthRequest<N>>,
+        peers: ReceiptsRangePeers<N>,
+    ) -> Self {
+        Self { to_eth_request_handler, peers }
+    }
+
+    fn connection_handler(&self) -> ReceiptsRangeConnectionHandler<N> {
+        ReceiptsRangeConnectionHandler {
+            to_eth_request_handler: self.to_eth_request_handler.clone(),
+            peers: self.peers.clone(),
+        }
+    }
+}
+
+impl<N: NetworkPrimitives> ProtocolHandler for ReceiptsRangeProtocol<N> {
+    type ConnectionHandler = ReceiptsRangeConnectionHandler<N>;
+
+    fn on_incoming(&self, _socket_addr: SocketAddr) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+
+    fn on_outgoing(
+        &self,
+        _socket_addr: SocketAddr,
+        _peer_id: PeerId,
+    ) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+}
+
+/// Sets up the receipts range protocol for a single connection.
+#[derive(Debug)]
+pub(crate) struct ReceiptsRangeConnectionHandler<N: NetworkPrimitives> {
+    to_eth_request_handler: mpsc::Sender<IncomingEthRequest<N>>,
+    peers: ReceiptsRangePeers<N>,
+}
+
+impl<N: NetworkPrimitives> ConnectionHandler for ReceiptsRangeConnectionHandler<N> {
+    type Connection = ReceiptsRangeConnection<N>;
+
+    fn protocol(&self) -> Protocol {
+        Protocol::new(receipts_range_capability(), 2)
+    }
+
+    fn on_unsupported_by_peer(
+        self,
+        _supported: &SharedCapabilities,
+        _direction: Direction,
+        _peer_id: PeerId,
+    ) -> OnNotSupported {
+        OnNotSupported::KeepAlive
+    }
+
+    fn into_connection(
+        self,
+        _direction: Direction,
+        peer_id: PeerId,
+        conn: ProtocolConnection,
+    ) -> Self::Connection {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.peers.peers.lock().insert(peer_id, tx);
+        trace!(target: "net::receipts_range", %peer_id, "established receipts range connection");
+        ReceiptsRangeConnection {
+            peer_id,
+            conn,
+            commands: UnboundedReceiverStream::new(rx),
+            next_request_id: 0,
+            inflight_requests: HashMap::new(),
+            serving: FuturesUnordered::new(),
+            to_eth_request_handler: self.to_eth_request_handler,
+        }
+    }
+}
+
+/// A response to a remote request that is being served.
+type ServingResponse = Pin<Box<dyn Future<Output = BytesMut> + Send>>;
+
+/// The receipts range protocol connection to a single peer.
+///
+/// Sends the requests of the [`FetchClient`](crate::FetchClient) to the peer and serves the
+/// requests of the peer. The connection is closed if the peer sends an invalid message.
+pub(crate) struct ReceiptsRangeConnection<N: NetworkPrimitives> {
+    peer_id: PeerId,
+    conn: ProtocolConnection,
+    commands: UnboundedReceiverStream<ReceiptsRangeCommand<N::Receipt>>,
+    next_request_id: u64,
+    inflight_requests: HashMap<u64, Inflight<N::Receipt>>,
+    serving: FuturesUnordered<ServingResponse>,
+    to_eth_request_handler: mpsc::Sender<IncomingEthRequest<N>>,
+}
+
+/// A request that was sent to the peer and waits for the response.
+struct Inflight<R> {
+    count: u64,
+    response: oneshot::Sender<PeerRequestResult<Vec<Vec<R>>>>,
+}
+
+impl<N: NetworkPrimitives> ReceiptsRangeConnection<N> {
+    /// Hands a request of the peer to the eth request handler and returns the encoded response.
+    ///
+    /// The request is answered with an empty response if the handler is busy or gone.
+    fn serve(&self, request: RequestPair<GetReceiptsByRange>) -> ServingResponse {
+        let RequestPair { request_id, message: GetReceiptsByRange { start, count } } = request;
+        let (response, rx) = oneshot::channel();
+        let _ = self.to_eth_request_handler.try_send(IncomingEthRequest::GetReceiptsByRange {
+            peer_id: self.peer_id,
+            start,
+            count,
+            response,
+        });
+        Box::pin(async move {
+            let receipts = rx.await.ok().and_then(Result::ok).unwrap_or(Receipts69(Vec::new()));
+            ReceiptsRangeMessage::ReceiptsByRange(RequestPair { request_id, message: receipts })
+                .encoded()
+        })
+    }
+
+    /// Resolves the request the response belongs to.
+    fn on_response(&mut self, response: RequestPair<Receipts69<N::Receipt>>) {
+        let RequestPair { request_id, message: Receipts69(receipts) } = response;
+        let Some(Inflight { count, response }) = self.inflight_requests.remove(&request_id) else {
+            debug!(target: "net::receipts_range", peer_id=%self.peer_id, request_id, "unsolicited receipts range response");
+            return
+        };
+        let res = if receipts.len() as u64 > count {
+            Err(RequestError::BadResponse)
+        } else {
+            Ok(WithPeerId::new(self.peer_id, receipts))
+        };
+        let _ = response.send(res);
+    }
+}
+
+impl<N: NetworkPrimitives> Stream for ReceiptsRangeConnection<N> {
+    type Item = BytesMut;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            // send new requests to the peer
+            if let Poll::Ready(Some(ReceiptsRangeCommand { request, response })) =
+                this.commands.poll_next_unpin(cx)
+            {
+                let request_id = this.next_request_id;
+                this.next_request_id += 1;
+                this.inflight_requests
+                    .insert(request_id, Inflight { count: request.count, response });
+                let request = RequestPair { request_id, message: request };
+                return Poll::Ready(Some(
+                    ReceiptsRangeMessage::<N::Receipt>::GetReceiptsByRange(request).encoded(),
+                ))
+            }
+
+            // send served responses to the peer
+            if let Poll::Ready(Some(response)) = this.serving.poll_next_unpin(cx) {
+                return Poll::Ready(Some(response))
+            }
+
+            let Some(msg) = ready!(this.conn.poll_next_unpin(cx)) else { return Poll::Ready(None) };
+            match ReceiptsRangeMessage::decode(&mut &msg[..]) {
+                Ok(ReceiptsRangeMessage::GetReceiptsByRange(request)) => {
+                    let response = this.serve(request);
+                    this.serving.push(response);
+                }
+                Ok(ReceiptsRangeMessage::ReceiptsByRange(response)) => this.on_response(response),
+                Err(err) => {
+                    debug!(target: "net::receipts_range", peer_id=%this.peer_id, %err, "invalid receipts range message, closing connection");
+                    return Poll::Ready(None)
+                }
+            }
+        }
+    }
+}
+
+impl<N: NetworkPrimitives> Drop for ReceiptsRangeConnection<N> {
+    fn drop(&mut self) {
+        for (_, Inflight { response, .. }) in self.inflight_requests.drain() {
+            let _ = response.send(Err(RequestError::ConnectionDropped));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_ethereum_primitives::Receipt;
+
+    #[test]
+    fn encode_decode_messages() {
+        let request = ReceiptsRangeMessage::<Receipt>::GetReceiptsByRange(RequestPair {
+            request_id: 1,
+            message: GetReceiptsByRange { start: 10, count: 5 },
+        });
+        let encoded = request.encoded();
+        assert_eq!(encoded[0], GET_RECEIPTS_BY_RANGE_ID);
+        assert_eq!(ReceiptsRangeMessage::decode(&mut &encoded[..]).unwrap(), request);
+
+        let receipts = vec![vec![Receipt { cumulative_gas_used: 21000, ..Default::default() }]];
+        let response = ReceiptsRangeMessage::ReceiptsByRange(RequestPair {
+            request_id: 1,
+            message: Receipts69(receipts),
+        });
+        let encoded = response.encoded();
+        assert_eq!(encoded[0], RECEIPTS_BY_RANGE_ID);
+        assert_eq!(ReceiptsRangeMessage::decode(&mut &encoded[..]).unwrap(), response);
+
+        let mut unknown = encoded.to_vec();
+        unknown[0] = 0x02;
+        assert!(ReceiptsRangeMessage::<Receipt>::decode(&mut &unknown[..]).is_err());
+    }
+
+    #[test]
+    fn request_without_capable_peer() {
+        let peers = ReceiptsRangePeers::<reth_eth_wire::EthNetworkPrimitives>::default();
+        assert!(matches!(peers.request(0, 10), Err(RequestError::UnsupportedCapability)));
+
+        // peers whose connection was closed are skipped
+        let (tx, rx) = mpsc::unbounded_channel();
+        peers.peers.lock().insert(PeerId::random(), tx);
+        drop(rx);
+        assert!(matches!(peers.request(0, 10), Err(RequestError::UnsupportedCapability)));
+        assert_eq!(peers.len(), 0);
+    }
+}
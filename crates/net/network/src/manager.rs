@@ -21,6 +21,7 @@ use crate::{
     discovery::Discovery,
     error::{NetworkError, ServiceKind},
     eth_requests::IncomingEthRequest,
+    fetch::ReceiptsRangeProtocol,
     import::{BlockImport, BlockImportEvent, BlockImportOutcome, BlockValidation, NewBlockEvent},
     listener::ConnectionListener,
     message::{NewBlockMessage, PeerMessage},
@@ -42,6 +43,7 @@ use reth_chainspec::EnrForkIdEntry;
 use reth_eth_wire::{DisconnectReason, EthNetworkPrimitives, NetworkPrimitives};
 use reth_fs_util::{self as fs, FsPathError};
 use reth_metrics::common::mpsc::UnboundedMeteredSender;
+use reth_net_nat::{NatPmpPortMapper, NatResolver, UdpNatPmpClient};
 use reth_network_api::{
     events::{PeerEvent, SessionInfo},
     test_utils::PeersHandle,
@@ -64,7 +66,10 @@ use std::{
     task::{Context, Poll},
     time::{Duration, Instant},
 };
-use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::{
+    mpsc::{self, error::TrySendError},
+    oneshot,
+};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, error, trace, warn};
 
@@ -139,6 +144,11 @@ pub struct NetworkManager<N: NetworkPrimitives = EthNetworkPrimitives> {
     metrics: NetworkMetrics,
     /// Disconnect metrics for the Network
     disconnect_metrics: DisconnectMetrics,
+    /// Keeps the NAT-PMP port mapper running, if spawned.
+    ///
+    /// Once this is dropped together with the network, the mapper releases its port mappings and
+    /// exits.
+    _natpmp_port_mapper: Option<oneshot::Sender<()>>,
 }
 
 impl NetworkManager {
@@ -187,7 +197,14 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
 
     /// Sets the dedicated channel for events intended for the
     /// [`EthRequestHandler`](crate::eth_requests::EthRequestHandler).
+    ///
+    /// The first time this is set, the receipts range protocol is announced to all peers, see
+    /// [`FetchClient::get_receipts_by_range`].
     pub fn set_eth_request_handler(&mut self, tx: mpsc::Sender<IncomingEthRequest<N>>) {
+        if self.to_eth_request_handler.is_none() {
+            let peers = self.fetch_client().receipts_range_peers;
+            self.add_rlpx_sub_protocol(ReceiptsRangeProtocol::new(tx.clone(), peers));
+        }
         self.to_eth_request_handler = Some(tx);
     }
 
@@ -295,6 +312,15 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
         let discv4 = discovery.discv4();
         let discv5 = discovery.discv5();
 
+        let natpmp_port_mapper = if nat == Some(NatResolver::NatPmp) {
+            let udp_port = discv4
+                .as_ref()
+                .map_or(discovery_v4_addr.port(), |discv4| discv4.local_addr().port());
+            spawn_natpmp_port_mapper(listener_addr.port(), udp_port)
+        } else {
+            None
+        };
+
         let num_active_peers = Arc::new(AtomicUsize::new(0));
 
         let sessions = SessionManager::new(
@@ -354,6 +380,7 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
             num_active_peers,
             metrics: Default::default(),
             disconnect_metrics: Default::default(),
+            _natpmp_port_mapper: natpmp_port_mapper,
         })
     }
 
@@ -434,7 +461,13 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
 
     /// Collect the peers from the [`NetworkManager`] and write them to the given
     /// `persistent_peers_file`.
+    ///
+    /// With the `serde` feature this also persists the peers' reputation, ban and fork id, see
+    /// [`PersistedPeer`](reth_network_types::PersistedPeer).
     pub fn write_peers_to_file(&self, persistent_peers_file: &Path) -> Result<(), FsPathError> {
+        #[cfg(feature = "serde")]
+        let known_peers = self.swarm.state().peers().iter_persisted_peers().collect::<Vec<_>>();
+        #[cfg(not(feature = "serde"))]
         let known_peers = self.all_peers().collect::<Vec<_>>();
         persistent_peers_file.parent().map(fs::create_dir_all).transpose()?;
         reth_fs_util::write_json_file(persistent_peers_file, &known_peers)?;
@@ -455,22 +488,21 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
         let hello_message = sessions.hello_message();
 
         #[expect(deprecated)]
-        NetworkStatus {
-            client_version: hello_message.client_version,
-            protocol_version: hello_message.protocol_version as u64,
-            eth_protocol_info: EthProtocolInfo {
-                difficulty: None,
-                head: status.blockhash,
-                network: status.chain.id(),
-                genesis: status.genesis,
-                config: Default::default(),
-            },
-            capabilities: hello_message
-                .protocols
-                .into_iter()
-                .map(|protocol| protocol.cap)
-                .collect(),
-        }
+        let eth_protocol_info = EthProtocolInfo {
+            difficulty: None,
+            head: status.blockhash,
+            network: status.chain.id(),
+            genesis: status.genesis,
+            config: Default::default(),
+        };
+
+        NetworkStatus::new(
+            hello_message.client_version,
+            hello_message.protocol_version as u64,
+            eth_protocol_info,
+            hello_message.protocols.into_iter().map(|protocol| protocol.cap).collect(),
+        )
+        .with_trusted_nodes_only(self.swarm.state().peers().trusted_nodes_only())
     }
 
     /// Sends an event to the [`TransactionsManager`](crate::transactions::TransactionsManager) if
@@ -690,6 +722,18 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
             NetworkHandleMessage::DisconnectPeer(peer_id, reason) => {
                 self.swarm.sessions_mut().disconnect(peer_id, reason);
             }
+            NetworkHandleMessage::SetTrustedNodesOnly(trusted_only) => {
+                self.swarm.state_mut().peers_mut().set_trusted_nodes_only(trusted_only);
+            }
+            NetworkHandleMessage::BanIp(ip, duration) => {
+                self.swarm.state_mut().peers_mut().ban_ip_for(ip, duration);
+                self.swarm
+                    .sessions_mut()
+                    .disconnect_ip(ip, Some(DisconnectReason::DisconnectRequested));
+            }
+            NetworkHandleMessage::UnbanIp(ip) => {
+                self.swarm.state_mut().peers_mut().unban_ip(ip);
+            }
             NetworkHandleMessage::ConnectPeer(peer_id, kind, addr) => {
                 self.swarm.state_mut().add_and_connect(peer_id, kind, addr);
             }
@@ -731,6 +775,14 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
             NetworkHandleMessage::GetPeerInfosByIds(peer_ids, tx) => {
                 let _ = tx.send(self.get_peer_infos_by_ids(peer_ids));
             }
+            NetworkHandleMessage::GetDiscoveryNodes(mut nodes, tx) => {
+                nodes.extend(self.swarm.state().discovery().dns_nodes().cloned());
+                for node in &mut nodes {
+                    node.fork_id_valid =
+                        node.fork_id.map(|fork_id| self.swarm.sessions().is_valid_fork_id(fork_id));
+                }
+                let _ = tx.send(nodes);
+            }
             NetworkHandleMessage::GetPeerInfosByPeerKind(kind, tx) => {
                 let peer_ids = self.swarm.state().peers().peers_by_kind(kind);
                 let _ = tx.send(self.get_peer_infos_by_ids(peer_ids));
@@ -1171,3 +1223,31 @@ struct NetworkManagerPollDurations {
     acc_network_handle: Duration,
     acc_swarm: Duration,
 }
+
+/// Spawns a task that maps the given ports on the default gateway via NAT-PMP and keeps renewing
+/// the mappings.
+///
+/// The task runs until the returned sender is dropped, and then releases the mappings. Returns
+/// `None` if no default gateway was found, which is always the case on other platforms than Linux.
+///
+/// The external IP is still resolved by the configured [`NatResolver`], which falls back to a
+/// public IP lookup if the gateway doesn't support NAT-PMP.
+fn spawn_natpmp_port_mapper(tcp_port: u16, udp_port: u16) -> Option<oneshot::Sender<()>> {
+    match UdpNatPmpClient::from_default_gateway() {
+        Ok(client) => {
+            debug!(target: "net",
+                gateway=%client.gateway(),
+                tcp_port,
+                udp_port,
+                "Spawning NAT-PMP port mapper"
+            );
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(NatPmpPortMapper::new(client, tcp_port, udp_port).run_until(rx));
+            Some(tx)
+        }
+        Err(err) => {
+            warn!(target: "net", %err, "Failed to spawn NAT-PMP port mapper, ports must be forwarded manually");
+            None
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use metrics::Histogram;
-use reth_eth_wire::DisconnectReason;
+use reth_eth_wire::{DisconnectReason, EthMessageID};
 use reth_ethereum_primitives::TxType;
 use reth_metrics::{
     metrics::{Counter, Gauge},
@@ -87,6 +87,8 @@ pub struct SessionManagerMetrics {
     pub(crate) total_outgoing_peer_messages_dropped: Counter,
     /// Number of queued outgoing messages
     pub(crate) queued_outgoing_messages: Gauge,
+    /// Latency of responses to requests sent to peers, in seconds.
+    pub(crate) response_latency: Histogram,
 }
 
 /// Metrics for the [`TransactionsManager`](crate::transactions::TransactionsManager).
@@ -98,6 +100,10 @@ pub struct TransactionsManagerMetrics {
     pub(crate) propagated_transactions: Counter,
     /// Total number of reported bad transactions
     pub(crate) reported_bad_transactions: Counter,
+    /// Total number of transactions requested by a peer, that weren't announced to that peer.
+    ///
+    /// Only counted if announced transactions are tracked, see `UnannouncedTransactionsPolicy`.
+    pub(crate) unannounced_pooled_transactions_requested: Counter,
 
     /* -- Freq txns already marked as seen by peer -- */
     /// Total number of messages from a peer, announcing transactions that have already been
@@ -312,8 +318,74 @@ impl DisconnectMetrics {
     }
 }
 
+/// Metrics for messages that were dropped because the peer exceeded the session's message rate
+/// limit, by message type.
+#[derive(Clone, Metrics)]
+#[metrics(scope = "network.throttled_messages")]
+pub struct ThrottledMessagesMetrics {
+    /// Number of throttled `NewBlockHashes` messages
+    pub(crate) new_block_hashes: Counter,
+
+    /// Number of throttled `NewBlock` messages
+    pub(crate) new_block: Counter,
+
+    /// Number of throttled `Transactions` messages
+    pub(crate) transactions: Counter,
+
+    /// Number of throttled `NewPooledTransactionHashes` messages
+    pub(crate) new_pooled_transaction_hashes: Counter,
+
+    /// Number of throttled `GetBlockHeaders` requests
+    pub(crate) get_block_headers: Counter,
+
+    /// Number of throttled `GetBlockBodies` requests
+    pub(crate) get_block_bodies: Counter,
+
+    /// Number of throttled `GetPooledTransactions` requests
+    pub(crate) get_pooled_transactions: Counter,
+
+    /// Number of throttled `GetNodeData` requests
+    pub(crate) get_node_data: Counter,
+
+    /// Number of throttled `GetReceipts` requests
+    pub(crate) get_receipts: Counter,
+
+    /// Number of throttled `BlockRangeUpdate` messages
+    pub(crate) block_range_update: Counter,
+
+    /// Number of throttled messages of any other type
+    pub(crate) other: Counter,
+}
+
+impl ThrottledMessagesMetrics {
+    /// Increments the proper counter for the given message type
+    pub(crate) fn increment(&self, id: EthMessageID) {
+        match id {
+            EthMessageID::NewBlockHashes => self.new_block_hashes.increment(1),
+            EthMessageID::NewBlock => self.new_block.increment(1),
+            EthMessageID::Transactions => self.transactions.increment(1),
+            EthMessageID::NewPooledTransactionHashes => {
+                self.new_pooled_transaction_hashes.increment(1)
+            }
+            EthMessageID::GetBlockHeaders => self.get_block_headers.increment(1),
+            EthMessageID::GetBlockBodies => self.get_block_bodies.increment(1),
+            EthMessageID::GetPooledTransactions => self.get_pooled_transactions.increment(1),
+            EthMessageID::GetNodeData => self.get_node_data.increment(1),
+            EthMessageID::GetReceipts => self.get_receipts.increment(1),
+            EthMessageID::BlockRangeUpdate => self.block_range_update.increment(1),
+            EthMessageID::Status |
+            EthMessageID::BlockHeaders |
+            EthMessageID::BlockBodies |
+            EthMessageID::PooledTransactions |
+            EthMessageID::NodeData |
+            EthMessageID::Receipts |
+            EthMessageID::Other(_) => self.other.increment(1),
+        }
+    }
+}
+
 /// Metrics for the `EthRequestHandler`
-#[derive(Metrics)]
+#[derive(Clone, Metrics)]
 #[metrics(scope = "network")]
 pub struct EthRequestHandlerMetrics {
     /// Number of `GetBlockHeaders` requests received
@@ -328,11 +400,130 @@ pub struct EthRequestHandlerMetrics {
     /// Number of `GetNodeData` requests received
     pub(crate) eth_node_data_requests_received_total: Counter,
 
+    /// Number of storage read errors while serving eth requests
+    pub(crate) eth_request_storage_errors_total: Counter,
+
+    /// Number of requested block hashes that were skipped because they are unknown
+    pub(crate) eth_request_unknown_hashes_total: Counter,
+
+    /// Number of times a peer was reported for spamming requests
+    pub(crate) eth_request_spammers_reported_total: Counter,
+
+    /// Encoded size in bytes of served `BlockHeaders` responses
+    pub(crate) eth_headers_response_bytes: Histogram,
+
+    /// Encoded size in bytes of served `BlockBodies` responses
+    pub(crate) eth_bodies_response_bytes: Histogram,
+
+    /// Encoded size in bytes of served `Receipts` responses
+    pub(crate) eth_receipts_response_bytes: Histogram,
+
+    /// Total encoded size in bytes of all served `BlockHeaders` responses
+    pub(crate) eth_headers_bytes_served_total: Counter,
+
+    /// Total encoded size in bytes of all served `BlockBodies` responses
+    pub(crate) eth_bodies_bytes_served_total: Counter,
+
+    /// Total encoded size in bytes of all served `Receipts` responses
+    pub(crate) eth_receipts_bytes_served_total: Counter,
+
+    /// Number of headers in served `BlockHeaders` responses
+    pub(crate) eth_headers_response_items: Histogram,
+
+    /// Number of block bodies in served `BlockBodies` responses
+    pub(crate) eth_bodies_response_items: Histogram,
+
+    /// Number of blocks with receipts in served `Receipts` responses
+    pub(crate) eth_receipts_response_items: Histogram,
+
+    /// Number of served `BlockHeaders` responses without any headers
+    pub(crate) eth_headers_empty_responses_total: Counter,
+
+    /// Number of `GetBlockHeaders` requests whose start block is not known
+    pub(crate) eth_headers_start_not_found_total: Counter,
+
+    /// Number of served `BlockBodies` responses without any block bodies
+    pub(crate) eth_bodies_empty_responses_total: Counter,
+
+    /// Number of served `Receipts` responses without any receipts
+    pub(crate) eth_receipts_empty_responses_total: Counter,
+
+    /// Number of `BlockHeaders` responses that were cut short by a serve limit
+    pub(crate) eth_headers_response_truncated_total: Counter,
+
+    /// Number of `BlockBodies` responses that were cut short by a serve limit
+    pub(crate) eth_bodies_response_truncated_total: Counter,
+
+    /// Number of `Receipts` responses that were cut short by a serve limit
+    pub(crate) eth_receipts_response_truncated_total: Counter,
+
+    /// Number of lookups of served data that were answered from the cache
+    pub(crate) eth_request_cache_hits_total: Counter,
+
+    /// Number of lookups of served data that missed the cache
+    pub(crate) eth_request_cache_misses_total: Counter,
+
+    /// Number of requests answered with an empty response because the peer had too many requests
+    /// in flight
+    pub(crate) eth_requests_rejected_peer_limit_total: Counter,
+
+    /// Number of requests answered with an empty response because the peer exceeded its
+    /// bandwidth budget
+    pub(crate) eth_requests_rejected_bandwidth_total: Counter,
+
+    /// Number of requests that are currently being served
+    pub(crate) eth_requests_in_flight: Gauge,
+
+    /// Number of requests waiting in the incoming queue
+    pub(crate) eth_request_queue_depth: Gauge,
+
+    /// Number of node data and large receipts requests answered with an empty response because the
+    /// queue backed up
+    pub(crate) eth_requests_shed_total: Counter,
+
+    /// Number of requests dropped because too many requests of the peer were queued
+    pub(crate) eth_requests_rejected_queue_full_total: Counter,
+
+    /// Time in seconds it took to serve a request from storage
+    pub(crate) eth_request_service_duration_seconds: Histogram,
+
     /// Duration in seconds of call to poll
     /// [`EthRequestHandler`](crate::eth_requests::EthRequestHandler).
     pub(crate) acc_duration_poll_eth_req_handler: Gauge,
 }
 
+impl EthRequestHandlerMetrics {
+    /// Records the number of items and the encoded size of a served `BlockHeaders` response
+    pub(crate) fn record_headers_response(&self, items: usize, bytes: usize) {
+        self.eth_headers_response_items.record(items as f64);
+        self.eth_headers_response_bytes.record(bytes as f64);
+        self.eth_headers_bytes_served_total.increment(bytes as u64);
+        if items == 0 {
+            self.eth_headers_empty_responses_total.increment(1);
+        }
+    }
+
+    /// Records the number of items and the encoded size of a served `BlockBodies` response
+    pub(crate) fn record_bodies_response(&self, items: usize, bytes: usize) {
+        self.eth_bodies_response_items.record(items as f64);
+        self.eth_bodies_response_bytes.record(bytes as f64);
+        self.eth_bodies_bytes_served_total.increment(bytes as u64);
+        if items == 0 {
+            self.eth_bodies_empty_responses_total.increment(1);
+        }
+    }
+
+    /// Records the number of items and the encoded size of a served `Receipts` response
+    pub(crate) fn record_receipts_response(&self, items: usize, bytes: usize) {
+        self.eth_receipts_response_items.record(items as f64);
+        self.eth_receipts_response_bytes.record(bytes as f64);
+        self.eth_receipts_bytes_served_total.increment(bytes as u64);
+        if items == 0 {
+            self.eth_receipts_empty_responses_total.increment(1);
+        }
+    }
+}
+
 /// Eth67 announcement metrics, track entries by `TxType`
 #[derive(Metrics)]
 #[metrics(scope = "network.transaction_fetcher")]
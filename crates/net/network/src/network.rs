@@ -9,16 +9,16 @@ use parking_lot::Mutex;
 use reth_discv4::{Discv4, NatResolver};
 use reth_discv5::Discv5;
 use reth_eth_wire::{
-    BlockRangeUpdate, DisconnectReason, EthNetworkPrimitives, NetworkPrimitives,
-    NewPooledTransactionHashes, SharedTransactions,
+    capability::Capabilities, BlockRangeUpdate, DisconnectReason, EthNetworkPrimitives,
+    NetworkPrimitives, NewPooledTransactionHashes, SharedTransactions,
 };
 use reth_ethereum_forks::Head;
 use reth_network_api::{
     events::{NetworkPeersEvents, PeerEvent, PeerEventStream},
     test_utils::{PeersHandle, PeersHandleProvider},
-    BlockDownloaderProvider, DiscoveryEvent, NetworkError, NetworkEvent,
-    NetworkEventListenerProvider, NetworkInfo, NetworkStatus, PeerInfo, PeerRequest, Peers,
-    PeersInfo,
+    BlockDownloaderProvider, DiscoveryEvent, DiscoverySource, DiscoveryTableEntry, NetworkError,
+    NetworkEvent, NetworkEventListenerProvider, NetworkInfo, NetworkStatus, PeerInfo, PeerRequest,
+    Peers, PeersInfo,
 };
 use reth_network_p2p::sync::{NetworkSyncUpdater, SyncState, SyncStateProvider};
 use reth_network_peers::{NodeRecord, PeerId};
@@ -26,11 +26,12 @@ use reth_network_types::{PeerAddr, PeerKind, Reputation, ReputationChangeKind};
 use reth_tokio_util::{EventSender, EventStream};
 use secp256k1::SecretKey;
 use std::{
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     sync::{
         atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tokio::sync::{
     mpsc::{self, UnboundedSender},
@@ -152,6 +153,14 @@ impl<N: NetworkPrimitives> NetworkHandle<N> {
         rx.await.unwrap()
     }
 
+    /// Returns the capabilities the peer announced in the `Hello` message of its session.
+    ///
+    /// Returns `None` if the peer isn't connected.
+    pub async fn peer_capabilities(&self, peer_id: PeerId) -> Option<Arc<Capabilities>> {
+        let peer = self.get_peer_by_id(peer_id).await.ok()??;
+        Some(peer.capabilities)
+    }
+
     /// Send message to gracefully shutdown node.
     ///
     /// This will disconnect all active and pending sessions and prevent
@@ -332,6 +341,49 @@ impl<N: NetworkPrimitives> Peers for NetworkHandle<N> {
         Ok(rx.await?)
     }
 
+    /// Collects the routing tables of the discv4 and discv5 services and sends them to the
+    /// [`NetworkManager`](crate::NetworkManager), which adds the nodes resolved via DNS and checks
+    /// the advertised fork ids.
+    async fn discovery_nodes(&self) -> Result<Vec<DiscoveryTableEntry>, NetworkError> {
+        let mut nodes = Vec::new();
+
+        if let Some(discv4) = &self.inner.discv4 {
+            let now = Instant::now();
+            let unix_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+            // the discv4 service only fails to answer if it terminated
+            let entries = discv4.table_entries().await.map_err(|_| NetworkError::ChannelClosed)?;
+            nodes.extend(entries.into_iter().map(|entry| {
+                let last_seen =
+                    unix_now.saturating_sub(now.saturating_duration_since(entry.last_seen));
+                DiscoveryTableEntry {
+                    record: entry.record,
+                    enr_seq: entry.enr_seq,
+                    fork_id: entry.fork_id,
+                    fork_id_valid: None,
+                    last_seen: Some(last_seen.as_secs()),
+                    source: DiscoverySource::Discv4,
+                }
+            }));
+        }
+
+        if let Some(discv5) = &self.inner.discv5 {
+            nodes.extend(discv5.table_entries().into_iter().map(|(record, enr_seq, fork_id)| {
+                DiscoveryTableEntry {
+                    record,
+                    enr_seq: Some(enr_seq),
+                    fork_id,
+                    fork_id_valid: None,
+                    last_seen: None,
+                    source: DiscoverySource::Discv5,
+                }
+            }));
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::GetDiscoveryNodes(nodes, tx));
+        Ok(rx.await?)
+    }
+
     /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to remove a peer from the
     /// set corresponding to given kind.
     fn remove_peer(&self, peer: PeerId, kind: PeerKind) {
@@ -350,6 +402,24 @@ impl<N: NetworkPrimitives> Peers for NetworkHandle<N> {
         self.send_message(NetworkHandleMessage::DisconnectPeer(peer, Some(reason)))
     }
 
+    /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to toggle whether only
+    /// trusted peers are connected to or accepted.
+    fn set_trusted_only(&self, trusted_only: bool) {
+        self.send_message(NetworkHandleMessage::SetTrustedNodesOnly(trusted_only))
+    }
+
+    /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to ban the given IP and
+    /// disconnect all sessions from it.
+    fn ban_ip(&self, ip: IpAddr, duration: Option<Duration>) {
+        self.send_message(NetworkHandleMessage::BanIp(ip, duration))
+    }
+
+    /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to lift the ban of the
+    /// given IP.
+    fn unban_ip(&self, ip: IpAddr) {
+        self.send_message(NetworkHandleMessage::UnbanIp(ip))
+    }
+
     /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to connect to the given
     /// peer.
     ///
@@ -506,6 +576,12 @@ pub(crate) enum NetworkHandleMessage<N: NetworkPrimitives = EthNetworkPrimitives
     RemovePeer(PeerId, PeerKind),
     /// Disconnects a connection to a peer if it exists, optionally providing a disconnect reason.
     DisconnectPeer(PeerId, Option<DisconnectReason>),
+    /// Toggles whether only trusted peers are connected to or accepted.
+    SetTrustedNodesOnly(bool),
+    /// Bans an IP indefinitely or for the given duration and disconnects all sessions from it.
+    BanIp(IpAddr, Option<Duration>),
+    /// Lifts the ban of an IP.
+    UnbanIp(IpAddr),
     /// Broadcasts an event to announce a new block to all nodes.
     AnnounceBlock(N::NewBlockPayload, B256),
     /// Sends a list of transactions to the given peer.
@@ -555,6 +631,9 @@ pub(crate) enum NetworkHandleMessage<N: NetworkPrimitives = EthNetworkPrimitives
     GetPeerInfoById(PeerId, oneshot::Sender<Option<PeerInfo>>),
     /// Gets `PeerInfo` for a specific peer kind via a oneshot sender.
     GetPeerInfosByPeerKind(PeerKind, oneshot::Sender<Vec<PeerInfo>>),
+    /// Completes the given snapshot of the discovery tables with the nodes resolved via DNS and
+    /// the fork id validation results, and returns it via a oneshot sender.
+    GetDiscoveryNodes(Vec<DiscoveryTableEntry>, oneshot::Sender<Vec<DiscoveryTableEntry>>),
     /// Gets the reputation for a specific peer via a oneshot sender.
     GetReputationById(PeerId, oneshot::Sender<Option<Reputation>>),
     /// Retrieves the `TransactionsHandle` via a oneshot sender.
@@ -17,18 +17,18 @@ use reth_network_types::{
     is_connection_failed_reputation,
     peers::{
         config::PeerBackoffDurations,
-        reputation::{DEFAULT_REPUTATION, MAX_TRUSTED_PEER_REPUTATION_CHANGE},
+        reputation::{BANNED_REPUTATION, DEFAULT_REPUTATION, MAX_TRUSTED_PEER_REPUTATION_CHANGE},
     },
-    ConnectionsConfig, Peer, PeerAddr, PeerConnectionState, PeerKind, PeersConfig,
+    ConnectionsConfig, Peer, PeerAddr, PeerConnectionState, PeerKind, PeersConfig, PersistedPeer,
     ReputationChangeKind, ReputationChangeOutcome, ReputationChangeWeights,
 };
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     fmt::Display,
     io::{self},
-    net::{IpAddr, SocketAddr},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use thiserror::Error;
 use tokio::{
@@ -92,6 +92,9 @@ pub struct PeersManager {
     incoming_ip_throttle_duration: Duration,
     /// IP address filter for restricting network connections to specific IP ranges.
     ip_filter: reth_net_banlist::IpFilter,
+    /// Max number of connected peers per IPv4 subnet, see
+    /// [`PeersConfig::max_peers_per_ip_subnet`].
+    max_peers_per_ip_subnet: Option<(u8, usize)>,
 }
 
 impl PeersManager {
@@ -101,16 +104,18 @@ impl PeersManager {
             refill_slots_interval,
             connection_info,
             reputation_weights,
-            ban_list,
+            mut ban_list,
             ban_duration,
             backoff_durations,
             trusted_nodes,
             trusted_nodes_only,
             trusted_nodes_resolution_interval,
             basic_nodes,
+            persisted_peers,
             max_backoff_count,
             incoming_ip_throttle_duration,
             ip_filter,
+            max_peers_per_ip_subnet,
         } = config;
         let (manager_tx, handle_rx) = mpsc::unbounded_channel();
         let now = Instant::now();
@@ -141,6 +146,30 @@ impl PeersManager {
             });
         }
 
+        // restore the state of known peers from the previous run
+        let now = SystemTime::now();
+        for persisted in persisted_peers {
+            let peer_id = persisted.record.id;
+            let Some(peer) = peers.get_mut(&peer_id) else { continue };
+            if peer.is_trusted() {
+                continue
+            }
+
+            peer.reputation = persisted.reputation;
+            peer.fork_id = persisted.fork_id.map(Box::new);
+
+            // a persisted ban never outlasts the configured ban duration
+            if let Some(remaining) = persisted.remaining_ban(now, ban_duration) {
+                let until = std::time::Instant::now() + remaining;
+                ban_list.ban_peer_until(peer_id, until);
+                // ensure the peer is not dialed until the ban is lifted
+                peer.reputation = peer.reputation.min(BANNED_REPUTATION - 1);
+            } else if peer.is_banned() {
+                // the ban expired in the meantime
+                peer.unban();
+            }
+        }
+
         trace!(target: "net::peers", trusted_peers=?trusted_peer_ids, "Initialized peers manager");
 
         Self {
@@ -167,6 +196,7 @@ impl PeersManager {
             net_connection_state: NetworkConnectionState::default(),
             incoming_ip_throttle_duration,
             ip_filter,
+            max_peers_per_ip_subnet,
         }
     }
 
@@ -183,13 +213,28 @@ impl PeersManager {
 
     /// Returns an iterator over all peers
     pub(crate) fn iter_peers(&self) -> impl Iterator<Item = NodeRecord> + '_ {
-        self.peers.iter().map(|(peer_id, v)| {
-            NodeRecord::new_with_ports(
-                v.addr.tcp().ip(),
-                v.addr.tcp().port(),
-                v.addr.udp().map(|addr| addr.port()),
-                *peer_id,
-            )
+        self.peers.iter().map(|(peer_id, v)| node_record(*peer_id, v))
+    }
+
+    /// Returns an iterator over the state of all peers that is persisted across restarts.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn iter_persisted_peers(&self) -> impl Iterator<Item = PersistedPeer> + '_ {
+        let now = std::time::Instant::now();
+        let unix_now = SystemTime::now();
+        self.peers.iter().map(move |(peer_id, peer)| {
+            let banned_until = self
+                .ban_list
+                .banned_peer_until(peer_id)
+                .and_then(|until| until.checked_duration_since(now))
+                .and_then(|remaining| (unix_now + remaining).duration_since(UNIX_EPOCH).ok())
+                // round up so that short bans are not lost
+                .map(|timestamp| timestamp.as_secs() + u64::from(timestamp.subsec_nanos() > 0));
+            PersistedPeer {
+                record: node_record(*peer_id, peer),
+                reputation: peer.reputation,
+                banned_until,
+                fork_id: peer.fork_id.as_deref().copied(),
+            }
         })
     }
 
@@ -231,6 +276,48 @@ impl PeersManager {
         self.connection_info.num_pending_out
     }
 
+    /// Returns `true` if only trusted peers are connected to or accepted.
+    #[inline]
+    pub(crate) const fn trusted_nodes_only(&self) -> bool {
+        self.trusted_nodes_only
+    }
+
+    /// Sets whether only trusted peers should be connected to or accepted.
+    ///
+    /// Enabling this disconnects all sessions with non-trusted peers and aborts pending dials to
+    /// them, disabling it refills the outbound slots from the known peers.
+    pub(crate) fn set_trusted_nodes_only(&mut self, trusted_nodes_only: bool) {
+        if self.trusted_nodes_only == trusted_nodes_only {
+            return
+        }
+        trace!(target: "net::peers", trusted_nodes_only, "changing trusted nodes only mode");
+        self.trusted_nodes_only = trusted_nodes_only;
+
+        if !trusted_nodes_only {
+            self.fill_outbound_slots();
+            return
+        }
+
+        for (peer_id, peer) in &self.peers {
+            if peer.is_trusted() || self.trusted_peer_ids.contains(peer_id) {
+                continue
+            }
+            match peer.state {
+                PeerConnectionState::In | PeerConnectionState::Out => {
+                    self.queued_actions.push_back(PeerAction::Disconnect {
+                        peer_id: *peer_id,
+                        reason: Some(DisconnectReason::UselessPeer),
+                    });
+                }
+                PeerConnectionState::PendingOut => {
+                    self.queued_actions
+                        .push_back(PeerAction::AbortPendingOutgoing { peer_id: *peer_id });
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Returns the number of currently backed off peers.
     #[inline]
     pub(crate) fn num_backed_off_peers(&self) -> usize {
@@ -242,6 +329,37 @@ impl PeersManager {
         self.peers.iter().filter(|(_, peer)| peer.kind.is_trusted() && peer.state.is_idle()).count()
     }
 
+    /// Returns the subnets that reached the [`PeersConfig::max_peers_per_ip_subnet`] limit.
+    ///
+    /// Connected peers and peers we're currently dialing count towards the limit of their subnet,
+    /// except for trusted and static peers and the given `skip` peer.
+    fn full_ip_subnets(&self, skip: Option<PeerId>) -> FullIpSubnets {
+        let Some((prefix_len, max_peers)) = self.max_peers_per_ip_subnet else {
+            return FullIpSubnets::default()
+        };
+
+        let mut peers_per_subnet = HashMap::<Ipv4Addr, usize>::new();
+        for (peer_id, peer) in &self.peers {
+            if peer.state.is_unconnected() ||
+                peer.is_trusted() ||
+                peer.is_static() ||
+                skip == Some(*peer_id)
+            {
+                continue
+            }
+            if let Some(subnet) = ip_subnet(peer.addr.tcp().ip(), prefix_len) {
+                *peers_per_subnet.entry(subnet).or_default() += 1;
+            }
+        }
+
+        let subnets = peers_per_subnet
+            .into_iter()
+            .filter(|(_, num_peers)| *num_peers >= max_peers)
+            .map(|(subnet, _)| subnet)
+            .collect();
+        FullIpSubnets { prefix_len, subnets }
+    }
+
     /// Invoked when a new _incoming_ tcp connection is accepted.
     ///
     /// returns an error if the inbound ip address is on the ban list
@@ -349,6 +467,10 @@ impl PeersManager {
             return
         }
 
+        // this needs to be checked before the peer itself is marked as connected
+        let exceeds_ip_subnet_limit = self.full_ip_subnets(Some(peer_id)).contains(addr.ip());
+        let mut is_static = false;
+
         // start a new tick, so the peer is not immediately rewarded for the time since last tick
         self.tick();
 
@@ -368,6 +490,7 @@ impl PeersManager {
                 peer.state = PeerConnectionState::In;
 
                 is_trusted = is_trusted || peer.is_trusted();
+                is_static = peer.is_static();
             }
             Entry::Vacant(entry) => {
                 // peer is missing in the table, we add it but mark it as to be removed after
@@ -389,6 +512,14 @@ impl PeersManager {
                 peer_id,
                 reason: Some(DisconnectReason::TooManyPeers),
             });
+        } else if !is_trusted && !is_static && exceeds_ip_subnet_limit {
+            // the subnet is already saturated, this is not a misbehavior of the peer so we only
+            // disconnect without any reputation change
+            trace!(target: "net::peers", ?peer_id, ?addr, "disconnecting peer exceeding ip subnet limit");
+            self.queued_actions.push_back(PeerAction::Disconnect {
+                peer_id,
+                reason: Some(DisconnectReason::TooManyPeers),
+            });
         }
     }
 
@@ -413,6 +544,22 @@ impl PeersManager {
         self.ban_list.ban_ip_until(ip, std::time::Instant::now() + self.ban_duration);
     }
 
+    /// Bans the IP indefinitely or for the given duration, even if it's not a global IP.
+    ///
+    /// Incoming connections from the IP are rejected and peers with that IP are no longer dialed.
+    /// Durations that exceed the representable time are treated as an indefinite ban.
+    pub(crate) fn ban_ip_for(&mut self, ip: IpAddr, duration: Option<Duration>) {
+        trace!(target: "net::peers", ?ip, ?duration, "banning ip");
+        let until = duration.and_then(|duration| std::time::Instant::now().checked_add(duration));
+        self.ban_list.force_ban_ip_with(ip, until);
+    }
+
+    /// Lifts the ban of the IP.
+    pub(crate) fn unban_ip(&mut self, ip: IpAddr) {
+        trace!(target: "net::peers", ?ip, "unbanning ip");
+        self.ban_list.unban_ip(&ip);
+    }
+
     /// Bans the IP temporarily to rate limit inbound connection attempts per IP.
     fn throttle_incoming_ip(&mut self, ip: IpAddr) {
         self.ban_list
@@ -943,11 +1090,16 @@ impl PeersManager {
     ///
     /// Returns `None` if no peer is available.
     fn best_unconnected(&mut self) -> Option<(PeerId, &mut Peer)> {
+        let full_ip_subnets = self.full_ip_subnets(None);
         let mut unconnected = self.peers.iter_mut().filter(|(_, peer)| {
             !peer.is_backed_off() &&
                 !peer.is_banned() &&
+                !self.ban_list.is_banned_ip(&peer.addr.tcp().ip()) &&
                 peer.state.is_unconnected() &&
-                (!self.trusted_nodes_only || peer.is_trusted())
+                (!self.trusted_nodes_only || peer.is_trusted()) &&
+                (peer.is_trusted() ||
+                    peer.is_static() ||
+                    !full_ip_subnets.contains(peer.addr.tcp().ip()))
         });
 
         // keep track of the best peer, if there's one
@@ -1217,6 +1369,11 @@ pub enum PeerAction {
         /// The peer ID.
         peer_id: PeerId,
     },
+    /// Abort a pending outgoing connection to an untrusted peer when trust-node-only is enabled.
+    AbortPendingOutgoing {
+        /// The peer ID.
+        peer_id: PeerId,
+    },
     /// Ban the peer in discovery.
     DiscoveryBanPeerId {
         /// The peer ID.
@@ -1245,6 +1402,41 @@ pub enum PeerAction {
     PeerRemoved(PeerId),
 }
 
+/// Returns the [`NodeRecord`] of the given peer.
+fn node_record(peer_id: PeerId, peer: &Peer) -> NodeRecord {
+    NodeRecord::new_with_ports(
+        peer.addr.tcp().ip(),
+        peer.addr.tcp().port(),
+        peer.addr.udp().map(|addr| addr.port()),
+        peer_id,
+    )
+}
+
+/// IPv4 subnets that reached the [`PeersConfig::max_peers_per_ip_subnet`] limit.
+#[derive(Debug, Default)]
+struct FullIpSubnets {
+    /// Prefix length of the subnets.
+    prefix_len: u8,
+    /// The saturated subnets.
+    subnets: HashSet<Ipv4Addr>,
+}
+
+impl FullIpSubnets {
+    /// Returns `true` if the ip belongs to one of the saturated subnets.
+    fn contains(&self, ip: IpAddr) -> bool {
+        ip_subnet(ip, self.prefix_len).is_some_and(|subnet| self.subnets.contains(&subnet))
+    }
+}
+
+/// Returns the IPv4 subnet with the given prefix length the ip belongs to.
+///
+/// Returns `None` for IPv6 addresses, IPv4-mapped addresses are treated as IPv4.
+fn ip_subnet(ip: IpAddr, prefix_len: u8) -> Option<Ipv4Addr> {
+    let IpAddr::V4(ip) = ip.to_canonical() else { return None };
+    let mask = u32::MAX.checked_shl(32 - u32::from(prefix_len.min(32))).unwrap_or(0);
+    Some(Ipv4Addr::from_bits(ip.to_bits() & mask))
+}
+
 /// Error thrown when a incoming connection is rejected right away
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum InboundConnectionError {
@@ -1269,9 +1461,10 @@ mod tests {
     };
     use reth_net_banlist::BanList;
     use reth_network_api::Direction;
-    use reth_network_peers::{PeerId, TrustedPeer};
+    use reth_network_peers::{NodeRecord, PeerId, TrustedPeer};
     use reth_network_types::{
-        peers::reputation::DEFAULT_REPUTATION, BackoffKind, Peer, ReputationChangeKind,
+        peers::reputation::{BANNED_REPUTATION, DEFAULT_REPUTATION},
+        BackoffKind, Peer, PersistedPeer, ReputationChangeKind,
     };
     use std::{
         future::{poll_fn, Future},
@@ -1279,7 +1472,7 @@ mod tests {
         net::{IpAddr, Ipv4Addr, SocketAddr},
         pin::Pin,
         task::{Context, Poll},
-        time::Duration,
+        time::{Duration, SystemTime, UNIX_EPOCH},
     };
     use url::Host;
 
@@ -1387,6 +1580,18 @@ mod tests {
         .await;
     }
 
+    #[test]
+    fn test_ban_ip_for_overflowing_duration() {
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2));
+        let mut peers = PeersManager::default();
+
+        peers.ban_ip_for(ip, Some(Duration::MAX));
+        assert!(peers.ban_list.is_banned_ip(&ip));
+
+        peers.unban_ip(ip);
+        assert!(!peers.ban_list.is_banned_ip(&ip));
+    }
+
     #[tokio::test]
     async fn test_unban() {
         let peer = PeerId::random();
@@ -2486,6 +2691,27 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_abort_untrusted_dials_with_trusted_nodes_only() {
+        let mut peers = PeersManager::new(PeersConfig::test());
+
+        let basic_peer = PeerId::random();
+        let basic_sock = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8009);
+        peers.add_peer(basic_peer, PeerAddr::from_tcp(basic_sock), None);
+        assert!(matches!(event!(peers), PeerAction::PeerAdded(_)));
+        match event!(peers) {
+            PeerAction::Connect { peer_id, .. } => assert_eq!(peer_id, basic_peer),
+            _ => unreachable!(),
+        }
+        assert_eq!(peers.peers[&basic_peer].state, PeerConnectionState::PendingOut);
+
+        peers.set_trusted_nodes_only(true);
+        match event!(peers) {
+            PeerAction::AbortPendingOutgoing { peer_id } => assert_eq!(peer_id, basic_peer),
+            _ => unreachable!(),
+        }
+    }
+
     #[tokio::test]
     async fn test_incoming_with_trusted_nodes_only() {
         let trusted_peer = PeerId::random();
@@ -3211,4 +3437,160 @@ mod tests {
         assert!(peers.on_incoming_pending_session(ip2).is_ok());
         assert!(peers.on_incoming_pending_session(ip3).is_ok());
     }
+
+    #[tokio::test]
+    async fn test_max_peers_per_ip_subnet_outbound() {
+        let config = PeersConfig::test().with_max_peers_per_ip_subnet(24, 2);
+        let mut peers = PeersManager::new(config);
+
+        for i in 1..=4 {
+            let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 1, i)), 30303);
+            peers.add_peer(PeerId::random(), PeerAddr::from_tcp(addr), None);
+        }
+        for i in 1..=2 {
+            let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 2, i)), 30303);
+            peers.add_peer(PeerId::random(), PeerAddr::from_tcp(addr), None);
+        }
+
+        peers.fill_outbound_slots();
+        let dialed = peers
+            .queued_actions
+            .iter()
+            .filter_map(|action| match action {
+                PeerAction::Connect { remote_addr, .. } => Some(remote_addr.ip()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let in_subnet = |octet| {
+            dialed
+                .iter()
+                .filter(|ip| matches!(ip, IpAddr::V4(ip) if ip.octets()[2] == octet))
+                .count()
+        };
+        // the first subnet is filled up, the remaining candidates are skipped
+        assert_eq!(in_subnet(1), 2);
+        // while the other subnet is still dialed
+        assert_eq!(in_subnet(2), 2);
+        assert_eq!(dialed.len(), 4);
+
+        // skipped candidates are not penalized
+        for peer in peers.peers.values().filter(|peer| peer.state.is_unconnected()) {
+            assert_eq!(peer.reputation, DEFAULT_REPUTATION);
+            assert!(!peer.is_backed_off());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_peers_per_ip_subnet_incoming() {
+        let config = PeersConfig::test().with_max_peers_per_ip_subnet(24, 1);
+        let mut peers = PeersManager::new(config);
+
+        let first = PeerId::random();
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 1, 1)), 30303);
+        assert!(peers.on_incoming_pending_session(addr.ip()).is_ok());
+        peers.on_incoming_session_established(first, addr);
+
+        let second = PeerId::random();
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 1, 2)), 30303);
+        assert!(peers.on_incoming_pending_session(addr.ip()).is_ok());
+        peers.on_incoming_session_established(second, addr);
+
+        let other_subnet = PeerId::random();
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 2, 1)), 30303);
+        assert!(peers.on_incoming_pending_session(addr.ip()).is_ok());
+        peers.on_incoming_session_established(other_subnet, addr);
+
+        let disconnected = peers
+            .queued_actions
+            .iter()
+            .filter_map(|action| match action {
+                PeerAction::Disconnect { peer_id, reason } => Some((*peer_id, *reason)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(disconnected, vec![(second, Some(DisconnectReason::TooManyPeers))]);
+        assert_eq!(peers.peers.get(&second).unwrap().reputation, DEFAULT_REPUTATION);
+    }
+
+    #[tokio::test]
+    async fn test_persisted_peers_roundtrip() {
+        let banned = PeerId::random();
+        let other = PeerId::random();
+        let mut peers = PeersManager::new(PeersConfig::test());
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        peers.add_peer(banned, PeerAddr::from_tcp(addr), None);
+        peers.add_peer(other, PeerAddr::from_tcp(addr), None);
+        peers.peers.get_mut(&other).unwrap().reputation = -1024;
+        peers.apply_reputation_change(&banned, ReputationChangeKind::BadProtocol);
+
+        let persisted = peers.iter_persisted_peers().collect::<Vec<_>>();
+        let restored = PeersManager::new(PeersConfig::test().with_persisted_peers(persisted));
+        assert_eq!(restored.peers.get(&other).unwrap().reputation, -1024);
+        assert!(restored.peers.get(&banned).unwrap().is_banned());
+        assert!(restored.ban_list.is_banned_peer(&banned));
+        assert!(!restored.ban_list.is_banned_peer(&other));
+    }
+
+    #[tokio::test]
+    async fn test_persisted_banned_peer_not_dialed_until_ban_expires() {
+        let banned = PeerId::random();
+        let other = PeerId::random();
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let unix_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let persisted = vec![
+            // a ban far in the future is clamped to the configured ban duration
+            PersistedPeer {
+                banned_until: Some(unix_now + 60 * 60 * 24 * 365),
+                ..NodeRecord::new(addr, banned).into()
+            },
+            NodeRecord::new(addr, other).into(),
+        ];
+        let mut peers = PeersManager::new(PeersConfig::test().with_persisted_peers(persisted));
+
+        peers.fill_outbound_slots();
+        match event!(peers) {
+            PeerAction::Connect { peer_id, .. } => assert_eq!(peer_id, other),
+            _ => unreachable!(),
+        }
+        poll_fn(|cx| {
+            assert!(peers.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+
+        tokio::time::sleep(peers.ban_duration).await;
+
+        match event!(peers) {
+            PeerAction::UnBanPeer { peer_id } => assert_eq!(peer_id, banned),
+            _ => unreachable!(),
+        }
+        assert!(!peers.peers.get(&banned).unwrap().is_banned());
+
+        peers.fill_outbound_slots();
+        match event!(peers) {
+            PeerAction::Connect { peer_id, .. } => assert_eq!(peer_id, banned),
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_persisted_expired_ban_is_lifted() {
+        let peer = PeerId::random();
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let persisted = vec![PersistedPeer {
+            reputation: BANNED_REPUTATION - 1,
+            banned_until: Some(1),
+            ..NodeRecord::new(addr, peer).into()
+        }];
+        let mut peers = PeersManager::new(PeersConfig::test().with_persisted_peers(persisted));
+        assert!(!peers.ban_list.is_banned_peer(&peer));
+        assert!(!peers.peers.get(&peer).unwrap().is_banned());
+
+        peers.fill_outbound_slots();
+        match event!(peers) {
+            PeerAction::Connect { peer_id, .. } => assert_eq!(peer_id, peer),
+            _ => unreachable!(),
+        }
+    }
 }
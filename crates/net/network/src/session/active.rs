@@ -13,27 +13,32 @@ use std::{
 
 use crate::{
     message::{NewBlockMessage, PeerMessage, PeerResponse, PeerResponseResult},
+    metrics::ThrottledMessagesMetrics,
     session::{
         conn::EthRlpxConnection,
         handle::{ActiveSessionMessage, SessionCommand},
+        rate_limit::{MessageRateLimiter, RateLimitOutcome},
+        timeout::RequestTimeoutEstimator,
         BlockRangeInfo, EthVersion, SessionId,
     },
 };
 use alloy_eips::merge::EPOCH_SLOTS;
 use alloy_primitives::Sealable;
 use futures::{stream::Fuse, SinkExt, StreamExt};
-use metrics::Gauge;
+use metrics::{Gauge, Histogram};
 use reth_eth_wire::{
     errors::{EthHandshakeError, EthStreamError},
     message::{EthBroadcastMessage, MessageError},
     Capabilities, DisconnectP2P, DisconnectReason, EthMessage, NetworkPrimitives, NewBlockPayload,
 };
-use reth_eth_wire_types::{message::RequestPair, RawCapabilityMessage};
+use reth_eth_wire_types::{
+    message::RequestPair, BlockBodies, BlockHeaders, NodeData, PooledTransactions,
+    RawCapabilityMessage, Receipts, Receipts69, Receipts70,
+};
 use reth_metrics::common::mpsc::MeteredPollSender;
 use reth_network_api::PeerRequest;
 use reth_network_p2p::error::RequestError;
 use reth_network_peers::PeerId;
-use reth_network_types::session::config::INITIAL_REQUEST_TIMEOUT;
 use reth_primitives_traits::Block;
 use rustc_hash::FxHashMap;
 use tokio::{
@@ -51,18 +56,6 @@ use tracing::{debug, trace};
 /// since the last update. The interval is set to one epoch duration in seconds.
 pub(super) const RANGE_UPDATE_INTERVAL: Duration = Duration::from_secs(EPOCH_SLOTS * 12);
 
-// Constants for timeout updating.
-
-/// Minimum timeout value
-const MINIMUM_TIMEOUT: Duration = Duration::from_secs(2);
-
-/// Maximum timeout value
-const MAXIMUM_TIMEOUT: Duration = INITIAL_REQUEST_TIMEOUT;
-/// How much the new measurements affect the current timeout (X percent)
-const SAMPLE_IMPACT: f64 = 0.1;
-/// Amount of RTTs before timeout
-const TIMEOUT_SCALING: u32 = 3;
-
 /// Restricts the number of queued outgoing messages for larger responses:
 ///  - Block Bodies
 ///  - Receipts
@@ -115,6 +108,10 @@ pub(crate) struct ActiveSession<N: NetworkPrimitives> {
     pub(crate) queued_outgoing: QueuedOutgoingMessages<N>,
     /// The maximum time we wait for a response from a peer.
     pub(crate) internal_request_timeout: Arc<AtomicU64>,
+    /// Adapts the `internal_request_timeout` to the response latency of the peer.
+    pub(crate) request_timeout_estimator: RequestTimeoutEstimator,
+    /// Records the response latency of the peer.
+    pub(crate) response_latency: Histogram,
     /// Interval when to check for timed out requests.
     pub(crate) internal_request_timeout_interval: Interval,
     /// If an [`ActiveSession`] does not receive a response at all within this duration then it is
@@ -135,6 +132,12 @@ pub(crate) struct ActiveSession<N: NetworkPrimitives> {
     /// The last latest block number we sent in a range update
     /// Used to avoid sending unnecessary updates when block height hasn't changed significantly
     pub(crate) last_sent_latest_block: Option<u64>,
+    /// Limits the rate of messages received from the remote peer, if configured.
+    ///
+    /// Responses to our own requests are not rate limited.
+    pub(crate) rate_limiter: Option<MessageRateLimiter>,
+    /// Counts the messages dropped by the rate limiter.
+    pub(crate) throttled_messages: ThrottledMessagesMetrics,
 }
 
 impl<N: NetworkPrimitives> ActiveSession<N> {
@@ -161,6 +164,69 @@ impl<N: NetworkPrimitives> ActiveSession<N> {
         self.queued_outgoing.messages.iter().filter(|m| m.is_response()).count()
     }
 
+    /// Checks the message read from the connection against the rate limit, if configured.
+    ///
+    /// Responses to our own requests are never rate limited.
+    fn check_rate_limit(&mut self, msg: &EthMessage<N>) -> RateLimitOutcome {
+        let Some(rate_limiter) = self.rate_limiter.as_mut() else {
+            return RateLimitOutcome::Allowed
+        };
+        if msg.is_response() {
+            return RateLimitOutcome::Allowed
+        }
+
+        let outcome = rate_limiter.try_acquire(Instant::now());
+        if outcome == RateLimitOutcome::Throttled {
+            self.throttled_messages.increment(msg.message_id());
+        }
+        outcome
+    }
+
+    /// Handles a message that exceeds the rate limit.
+    ///
+    /// Requests are answered with an empty response, so the peer doesn't wait for them to time
+    /// out. All other messages are dropped.
+    fn on_throttled_message(&mut self, msg: EthMessage<N>) {
+        /// Queues an empty response to the request
+        macro_rules! respond_empty {
+            ($req:ident, $resp_item:ident, $empty:expr) => {{
+                let (tx, response) = oneshot::channel();
+                let _ = tx.send(Ok($empty));
+                self.received_requests_from_remote.push(ReceivedRequest {
+                    request_id: $req.request_id,
+                    rx: PeerResponse::$resp_item { response },
+                    received: Instant::now(),
+                });
+            }};
+        }
+
+        match msg {
+            EthMessage::GetBlockHeaders(req) => {
+                respond_empty!(req, BlockHeaders, BlockHeaders(Vec::new()))
+            }
+            EthMessage::GetBlockBodies(req) => {
+                respond_empty!(req, BlockBodies, BlockBodies(Vec::new()))
+            }
+            EthMessage::GetPooledTransactions(req) => {
+                respond_empty!(req, PooledTransactions, PooledTransactions(Vec::new()))
+            }
+            EthMessage::GetNodeData(req) => respond_empty!(req, NodeData, NodeData(Vec::new())),
+            EthMessage::GetReceipts(req) => {
+                if self.conn.version() >= EthVersion::Eth69 {
+                    respond_empty!(req, Receipts69, Receipts69(Vec::new()))
+                } else {
+                    respond_empty!(req, Receipts, Receipts(Vec::new()))
+                }
+            }
+            EthMessage::GetReceipts70(req) => respond_empty!(
+                req,
+                Receipts70,
+                Receipts70 { last_block_incomplete: false, receipts: Vec::new() }
+            ),
+            _ => {}
+        }
+    }
+
     /// Handle a message read from the connection.
     ///
     /// Returns an error if the message is considered to be in violation of the protocol.
@@ -352,7 +418,12 @@ impl<N: NetworkPrimitives> ActiveSession<N> {
             PeerMessage::SendTransactions(msg) => {
                 self.queued_outgoing.push_back(EthBroadcastMessage::Transactions(msg).into());
             }
-            PeerMessage::BlockRangeUpdated(_) => {}
+            PeerMessage::BlockRangeUpdated(msg) => {
+                if self.conn.version() >= EthVersion::Eth69 {
+                    self.last_sent_latest_block = Some(msg.latest);
+                    self.queued_outgoing.push_back(EthMessage::BlockRangeUpdate(msg).into());
+                }
+            }
             PeerMessage::ReceivedTransaction(_) => {
                 unreachable!("Not emitted by network")
             }
@@ -521,9 +592,9 @@ impl<N: NetworkPrimitives> ActiveSession<N> {
     /// Updates the request timeout with a request's timestamps
     fn update_request_timeout(&mut self, sent: Instant, received: Instant) {
         let elapsed = received.saturating_duration_since(sent);
+        self.response_latency.record(elapsed.as_secs_f64());
 
-        let current = Duration::from_millis(self.internal_request_timeout.load(Ordering::Relaxed));
-        let request_timeout = calculate_new_timeout(current, elapsed);
+        let request_timeout = self.request_timeout_estimator.on_response(elapsed);
         self.internal_request_timeout.store(request_timeout.as_millis() as u64, Ordering::Relaxed);
         self.internal_request_timeout_interval = tokio::time::interval(request_timeout);
     }
@@ -710,6 +781,22 @@ impl<N: NetworkPrimitives> Future for ActiveSession<N> {
                         match res {
                             Ok(msg) => {
                                 trace!(target: "net::session", msg_id=?msg.message_id(), remote_peer_id=?this.remote_peer_id, "received eth message");
+                                match this.check_rate_limit(&msg) {
+                                    RateLimitOutcome::Allowed => {}
+                                    RateLimitOutcome::Throttled => {
+                                        trace!(target: "net::session", msg_id=?msg.message_id(), remote_peer_id=?this.remote_peer_id, "throttled message exceeding the rate limit");
+                                        this.on_throttled_message(msg);
+                                        progress = true;
+                                        continue
+                                    }
+                                    RateLimitOutcome::Exceeded => {
+                                        debug!(target: "net::session", remote_peer_id=?this.remote_peer_id, "peer exceeded the message rate limit, disconnecting");
+                                        // penalize the peer before the session is terminated
+                                        this.on_bad_message();
+                                        return this
+                                            .try_disconnect(DisconnectReason::UselessPeer, cx)
+                                    }
+                                }
                                 // decode and handle message
                                 match this.on_incoming_message(msg) {
                                     OnIncomingMessageOutcome::Ok => {
@@ -884,17 +971,6 @@ impl<N: NetworkPrimitives> From<EthBroadcastMessage<N>> for OutgoingMessage<N> {
     }
 }
 
-/// Calculates a new timeout using an updated estimation of the RTT
-#[inline]
-fn calculate_new_timeout(current_timeout: Duration, estimated_rtt: Duration) -> Duration {
-    let new_timeout = estimated_rtt.mul_f64(SAMPLE_IMPACT) * TIMEOUT_SCALING;
-
-    // this dampens sudden changes by taking a weighted mean of the old and new values
-    let smoothened_timeout = current_timeout.mul_f64(1.0 - SAMPLE_IMPACT) + new_timeout;
-
-    smoothened_timeout.clamp(MINIMUM_TIMEOUT, MAXIMUM_TIMEOUT)
-}
-
 /// A helper struct that wraps the queue of outgoing messages and a metric to track their count
 pub(crate) struct QueuedOutgoingMessages<N: NetworkPrimitives> {
     messages: VecDeque<OutgoingMessage<N>>,
@@ -938,14 +1014,18 @@ mod tests {
     use reth_chainspec::MAINNET;
     use reth_ecies::stream::ECIESStream;
     use reth_eth_wire::{
-        handshake::EthHandshake, EthNetworkPrimitives, EthStream, GetBlockBodies,
-        HelloMessageWithProtocols, P2PStream, StatusBuilder, UnauthedEthStream, UnauthedP2PStream,
-        UnifiedStatus,
+        handshake::EthHandshake, EthNetworkPrimitives, EthStream, GetBlockBodies, GetBlockHeaders,
+        HeadersDirection, HelloMessageWithProtocols, P2PStream, StatusBuilder, UnauthedEthStream,
+        UnauthedP2PStream, UnifiedStatus,
     };
     use reth_ethereum_forks::EthereumHardfork;
     use reth_network_peers::pk2id;
-    use reth_network_types::session::config::PROTOCOL_BREACH_REQUEST_TIMEOUT;
+    use reth_network_types::{
+        session::config::{INITIAL_REQUEST_TIMEOUT, PROTOCOL_BREACH_REQUEST_TIMEOUT},
+        SessionRateLimit,
+    };
     use secp256k1::{SecretKey, SECP256K1};
+    use std::num::NonZeroU32;
     use tokio::{
         net::{TcpListener, TcpStream},
         sync::mpsc,
@@ -967,6 +1047,7 @@ mod tests {
         status: UnifiedStatus,
         fork_filter: ForkFilter,
         next_id: usize,
+        rate_limit: Option<SessionRateLimit>,
     }
 
     impl<N: NetworkPrimitives> SessionBuilder<N> {
@@ -1069,6 +1150,11 @@ mod tests {
                         internal_request_timeout: Arc::new(AtomicU64::new(
                             INITIAL_REQUEST_TIMEOUT.as_millis() as u64,
                         )),
+                        request_timeout_estimator: RequestTimeoutEstimator::new(
+                            Default::default(),
+                            INITIAL_REQUEST_TIMEOUT,
+                        ),
+                        response_latency: Histogram::noop(),
                         protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
                         terminate_message: None,
                         range_info: None,
@@ -1079,6 +1165,10 @@ mod tests {
                         ),
                         range_update_interval: None,
                         last_sent_latest_block: None,
+                        rate_limiter: self
+                            .rate_limit
+                            .map(|rate_limit| MessageRateLimiter::new(rate_limit, Instant::now())),
+                        throttled_messages: Default::default(),
                     }
                 }
                 ev => {
@@ -1108,6 +1198,7 @@ mod tests {
                 fork_filter: MAINNET
                     .hardfork_fork_filter(EthereumHardfork::Frontier)
                     .expect("The Frontier fork filter should exist on mainnet"),
+                rate_limit: None,
             }
         }
     }
@@ -1198,6 +1289,112 @@ mod tests {
         rx.await.unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_disconnect_on_sustained_rate_limit() {
+        reth_tracing::init_test_tracing();
+        let mut builder = SessionBuilder {
+            rate_limit: Some(
+                SessionRateLimit::default()
+                    .with_messages_per_second(NonZeroU32::MIN)
+                    .with_burst(NonZeroU32::new(5).unwrap())
+                    .with_max_throttled_duration(Duration::ZERO),
+            ),
+            ..Default::default()
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
+            for _ in 0..100 {
+                if client_stream
+                    .send(EthMessage::NewPooledTransactionHashes66(Vec::new().into()))
+                    .await
+                    .is_err()
+                {
+                    break
+                }
+            }
+            loop {
+                match client_stream.next().await.unwrap() {
+                    Ok(_) => continue,
+                    Err(err) => {
+                        assert_eq!(err.as_disconnected().unwrap(), DisconnectReason::UselessPeer);
+                        break
+                    }
+                }
+            }
+        });
+
+        let (tx, rx) = oneshot::channel();
+
+        tokio::task::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let session = builder.connect_incoming(incoming).await;
+            session.await;
+
+            // the peer is penalized before the session terminates
+            let mut penalized = false;
+            while let Ok(msg) = builder.active_session_rx.as_mut().try_recv() {
+                penalized |= matches!(msg, ActiveSessionMessage::BadMessage { .. });
+            }
+            tx.send(penalized).unwrap();
+        });
+
+        tokio::task::spawn(fut);
+
+        assert!(rx.await.unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_answer_throttled_requests() {
+        reth_tracing::init_test_tracing();
+        let mut builder = SessionBuilder {
+            rate_limit: Some(
+                SessionRateLimit::default()
+                    .with_messages_per_second(NonZeroU32::MIN)
+                    .with_burst(NonZeroU32::MIN),
+            ),
+            ..Default::default()
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let (tx, rx) = oneshot::channel();
+        let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
+            let request = GetBlockHeaders {
+                start_block: 0u64.into(),
+                limit: 1,
+                skip: 0,
+                direction: HeadersDirection::Rising,
+            };
+            for request_id in 0..2 {
+                client_stream
+                    .send(EthMessage::GetBlockHeaders(RequestPair { request_id, message: request }))
+                    .await
+                    .unwrap();
+            }
+
+            // the first request is forwarded, the throttled one is answered right away
+            let msg = client_stream.next().await.unwrap().unwrap();
+            tx.send(msg).unwrap();
+        });
+
+        let (incoming, _) = listener.accept().await.unwrap();
+        let session = builder.connect_incoming(incoming).await;
+        tokio::task::spawn(session);
+        tokio::task::spawn(fut);
+
+        match rx.await.unwrap() {
+            EthMessage::BlockHeaders(RequestPair { request_id, message }) => {
+                assert_eq!(request_id, 1);
+                assert!(message.0.is_empty());
+            }
+            msg => panic!("unexpected message {msg:?}"),
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_request_timeout() {
         reth_tracing::init_test_tracing();
@@ -1323,20 +1520,4 @@ mod tests {
             _ => unreachable!(),
         }
     }
-
-    #[test]
-    fn timeout_calculation_sanity_tests() {
-        let rtt = Duration::from_secs(5);
-        // timeout for an RTT of `rtt`
-        let timeout = rtt * TIMEOUT_SCALING;
-
-        // if rtt hasn't changed, timeout shouldn't change
-        assert_eq!(calculate_new_timeout(timeout, rtt), timeout);
-
-        // if rtt changed, the new timeout should change less than it
-        assert!(calculate_new_timeout(timeout, rtt / 2) < timeout);
-        assert!(calculate_new_timeout(timeout, rtt / 2) > timeout / 2);
-        assert!(calculate_new_timeout(timeout, rtt * 2) > timeout);
-        assert!(calculate_new_timeout(timeout, rtt * 2) < timeout * 2);
-    }
 }
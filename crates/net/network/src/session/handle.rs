@@ -2,7 +2,7 @@
 
 use crate::{
     message::PeerMessage,
-    session::{conn::EthRlpxConnection, Direction, SessionId},
+    session::{conn::EthRlpxConnection, BlockRangeInfo, Direction, SessionId},
     PendingSessionHandshakeError,
 };
 use reth_ecies::ECIESError;
@@ -13,7 +13,15 @@ use reth_eth_wire::{
 use reth_network_api::PeerInfo;
 use reth_network_peers::{NodeRecord, PeerId};
 use reth_network_types::PeerKind;
-use std::{io, net::SocketAddr, sync::Arc, time::Instant};
+use std::{
+    io,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use tokio::sync::{
     mpsc::{self, error::SendError},
     oneshot,
@@ -29,6 +37,8 @@ pub struct PendingSessionHandle {
     pub(crate) disconnect_tx: Option<oneshot::Sender<()>>,
     /// The direction of the session
     pub(crate) direction: Direction,
+    /// The address of the remote peer
+    pub(crate) remote_addr: SocketAddr,
 }
 
 // === impl PendingSessionHandle ===
@@ -45,6 +55,11 @@ impl PendingSessionHandle {
     pub const fn direction(&self) -> Direction {
         self.direction
     }
+
+    /// Returns the address of the remote peer.
+    pub const fn remote_addr(&self) -> SocketAddr {
+        self.remote_addr
+    }
 }
 
 /// An established session with a remote peer.
@@ -75,6 +90,10 @@ pub struct ActiveSessionHandle<N: NetworkPrimitives> {
     pub(crate) local_addr: Option<SocketAddr>,
     /// The Status message the peer sent for the `eth` handshake
     pub(crate) status: Arc<UnifiedStatus>,
+    /// The range of blocks the peer advertised, starting with ETH69.
+    pub(crate) range_info: Option<BlockRangeInfo>,
+    /// The current timeout of requests sent to the peer in milliseconds, adapted by the session.
+    pub(crate) request_timeout: Arc<AtomicU64>,
 }
 
 // === impl ActiveSessionHandle ===
@@ -135,6 +154,11 @@ impl<N: NetworkPrimitives> ActiveSessionHandle<N> {
         self.remote_addr
     }
 
+    /// Returns the range of blocks the peer last advertised, starting with ETH69.
+    pub const fn range_info(&self) -> Option<&BlockRangeInfo> {
+        self.range_info.as_ref()
+    }
+
     /// Extracts the [`PeerInfo`] from the session handle.
     pub(crate) fn peer_info(&self, record: &NodeRecord, kind: PeerKind) -> PeerInfo {
         PeerInfo {
@@ -148,7 +172,9 @@ impl<N: NetworkPrimitives> ActiveSessionHandle<N> {
             client_version: self.client_version.clone(),
             eth_version: self.version,
             status: self.status.clone(),
+            block_range: self.range_info.as_ref().map(BlockRangeInfo::to_message),
             session_established: self.established,
+            request_timeout: Duration::from_millis(self.request_timeout.load(Ordering::Relaxed)),
             kind,
         }
     }
@@ -4,18 +4,22 @@ mod active;
 mod conn;
 mod counter;
 mod handle;
+mod rate_limit;
+mod timeout;
 mod types;
 pub use types::BlockRangeInfo;
 
 use crate::{
     message::PeerMessage,
-    metrics::SessionManagerMetrics,
+    metrics::{SessionManagerMetrics, ThrottledMessagesMetrics},
     protocol::{IntoRlpxSubProtocol, OnNotSupported, RlpxSubProtocolHandlers, RlpxSubProtocols},
     session::active::ActiveSession,
 };
 use active::QueuedOutgoingMessages;
+use alloy_eips::merge::EPOCH_SLOTS;
 use counter::SessionCounter;
 use futures::{future::Either, io, FutureExt, StreamExt};
+use rate_limit::MessageRateLimiter;
 use reth_ecies::{stream::ECIESStream, ECIESError};
 use reth_eth_wire::{
     errors::EthStreamError, handshake::EthRlpxHandshake, multiplex::RlpxProtocolMultiplexer,
@@ -27,18 +31,19 @@ use reth_ethereum_forks::{ForkFilter, ForkId, ForkTransition, Head};
 use reth_metrics::common::mpsc::MeteredPollSender;
 use reth_network_api::{PeerRequest, PeerRequestSender};
 use reth_network_peers::PeerId;
-use reth_network_types::SessionsConfig;
+use reth_network_types::{RequestTimeoutConfig, SessionRateLimit, SessionsConfig};
 use reth_tasks::TaskSpawner;
 use rustc_hash::FxHashMap;
 use secp256k1::SecretKey;
 use std::{
     collections::HashMap,
     future::Future,
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     sync::{atomic::AtomicU64, Arc},
     task::{Context, Poll},
     time::{Duration, Instant},
 };
+use timeout::RequestTimeoutEstimator;
 use tokio::{
     io::{AsyncRead, AsyncWrite},
     net::TcpStream,
@@ -71,11 +76,15 @@ pub struct SessionManager<N: NetworkPrimitives> {
     ///  The maximum initial time an [`ActiveSession`] waits for a response from the peer before it
     /// responds to an _internal_ request with a `TimeoutError`
     initial_internal_request_timeout: Duration,
+    /// Bounds of the request timeout that is adapted to each peer's response latency.
+    request_timeout: RequestTimeoutConfig,
     /// If an [`ActiveSession`] does not receive a response at all within this duration then it is
     /// considered a protocol violation and the session will initiate a drop.
     protocol_breach_request_timeout: Duration,
     /// The timeout after which a pending session attempt is considered failed.
     pending_session_timeout: Duration,
+    /// Rate limit for messages received from a peer, if any.
+    message_rate_limit: Option<SessionRateLimit>,
     /// The secret key used for authenticating sessions.
     secret_key: SecretKey,
     /// The `Status` message to send to peers.
@@ -115,11 +124,16 @@ pub struct SessionManager<N: NetworkPrimitives> {
     disconnections_counter: DisconnectionsCounter,
     /// Metrics for the session manager.
     metrics: SessionManagerMetrics,
+    /// Metrics for messages throttled by active sessions.
+    throttled_messages: ThrottledMessagesMetrics,
     /// The [`EthRlpxHandshake`] is used to perform the initial handshake with the peer.
     handshake: Arc<dyn EthRlpxHandshake>,
     /// Shared local range information that gets propagated to active sessions.
     /// This represents the range of blocks that this node can serve to other peers.
     local_range_info: BlockRangeInfo,
+    /// The local range that was last broadcast to all active ETH69 sessions, or advertised in the
+    /// handshake if it didn't change significantly since.
+    last_broadcast_block_range: BlockRangeUpdate,
 }
 
 // === impl SessionManager ===
@@ -152,8 +166,10 @@ impl<N: NetworkPrimitives> SessionManager<N> {
             next_id: 0,
             counter: SessionCounter::new(config.limits),
             initial_internal_request_timeout: config.initial_internal_request_timeout,
+            request_timeout: config.request_timeout,
             protocol_breach_request_timeout: config.protocol_breach_request_timeout,
             pending_session_timeout: config.pending_session_timeout,
+            message_rate_limit: config.message_rate_limit,
             secret_key,
             status,
             hello_message,
@@ -169,7 +185,9 @@ impl<N: NetworkPrimitives> SessionManager<N> {
             extra_protocols,
             disconnections_counter: Default::default(),
             metrics: Default::default(),
+            throttled_messages: Default::default(),
             handshake,
+            last_broadcast_block_range: local_range_info.to_message(),
             local_range_info,
         }
     }
@@ -297,6 +315,7 @@ impl<N: NetworkPrimitives> SessionManager<N> {
         let handle = PendingSessionHandle {
             disconnect_tx: Some(disconnect_tx),
             direction: Direction::Incoming,
+            remote_addr,
         };
         self.pending_sessions.insert(session_id, handle);
         self.counter.inc_pending_inbound();
@@ -339,6 +358,7 @@ impl<N: NetworkPrimitives> SessionManager<N> {
             let handle = PendingSessionHandle {
                 disconnect_tx: Some(disconnect_tx),
                 direction: Direction::Outgoing(remote_peer_id),
+                remote_addr,
             };
             self.pending_sessions.insert(session_id, handle);
             self.counter.inc_pending_outbound();
@@ -355,6 +375,20 @@ impl<N: NetworkPrimitives> SessionManager<N> {
         }
     }
 
+    /// Initiates a shutdown of all sessions with the given remote IP, including pending sessions.
+    pub fn disconnect_ip(&mut self, ip: IpAddr, reason: Option<DisconnectReason>) {
+        for session in self.active_sessions.values() {
+            if session.remote_addr().ip() == ip {
+                session.disconnect(reason);
+            }
+        }
+        for session in self.pending_sessions.values_mut() {
+            if session.remote_addr().ip() == ip {
+                session.disconnect();
+            }
+        }
+    }
+
     /// Initiates a shutdown of all sessions.
     ///
     /// It will trigger the disconnect on all the session tasks to gracefully terminate. The result
@@ -365,6 +399,15 @@ impl<N: NetworkPrimitives> SessionManager<N> {
         }
     }
 
+    /// Aborts the pending outgoing sessions with the given peer.
+    pub fn disconnect_pending_outgoing(&mut self, node: PeerId) {
+        for session in self.pending_sessions.values_mut() {
+            if session.direction() == Direction::Outgoing(node) {
+                session.disconnect();
+            }
+        }
+    }
+
     /// Disconnects all pending sessions.
     pub fn disconnect_all_pending(&mut self) {
         for session in self.pending_sessions.values_mut() {
@@ -546,6 +589,16 @@ impl<N: NetworkPrimitives> SessionManager<N> {
                     interval
                 });
 
+                // Track the range of blocks the peer advertised in its status, starting with
+                // ETH69. This is kept up to date by the session with the peer's `BlockRangeUpdate`s
+                let range_info = status
+                    .earliest_block
+                    .zip(status.latest_block)
+                    .filter(|_| version >= EthVersion::Eth69)
+                    .map(|(earliest, latest)| {
+                        BlockRangeInfo::new(earliest, latest, status.blockhash)
+                    });
+
                 let session = ActiveSession {
                     next_id: 0,
                     remote_peer_id: peer_id,
@@ -566,12 +619,21 @@ impl<N: NetworkPrimitives> SessionManager<N> {
                         self.initial_internal_request_timeout,
                     ),
                     internal_request_timeout: Arc::clone(&timeout),
+                    request_timeout_estimator: RequestTimeoutEstimator::new(
+                        self.request_timeout,
+                        self.initial_internal_request_timeout,
+                    ),
+                    response_latency: self.metrics.response_latency.clone(),
                     protocol_breach_request_timeout: self.protocol_breach_request_timeout,
                     terminate_message: None,
-                    range_info: None,
+                    range_info: range_info.clone(),
                     local_range_info: self.local_range_info.clone(),
                     range_update_interval,
                     last_sent_latest_block: None,
+                    rate_limiter: self
+                        .message_rate_limit
+                        .map(|rate_limit| MessageRateLimiter::new(rate_limit, Instant::now())),
+                    throttled_messages: self.throttled_messages.clone(),
                 };
 
                 self.spawn(session);
@@ -589,6 +651,8 @@ impl<N: NetworkPrimitives> SessionManager<N> {
                     client_version: Arc::clone(&client_version),
                     remote_addr,
                     local_addr,
+                    range_info: range_info.clone(),
+                    request_timeout: Arc::clone(&timeout),
                 };
 
                 self.active_sessions.insert(peer_id, handle);
@@ -608,7 +672,7 @@ impl<N: NetworkPrimitives> SessionManager<N> {
                     messages,
                     direction,
                     timeout,
-                    range_info: None,
+                    range_info,
                 })
             }
             PendingSessionEvent::Disconnected { remote_addr, session_id, direction, error } => {
@@ -699,9 +763,31 @@ impl<N: NetworkPrimitives> SessionManager<N> {
             block_range_update.latest,
             block_range_update.latest_hash,
         );
+
+        // Sessions only send periodic updates, so significant changes, e.g. because blocks were
+        // pruned or the node finished syncing, are broadcast right away
+        if is_significant_block_range_change(&self.last_broadcast_block_range, &block_range_update)
+        {
+            for (peer_id, session) in &self.active_sessions {
+                if session.version >= EthVersion::Eth69 {
+                    self.send_message(
+                        peer_id,
+                        PeerMessage::BlockRangeUpdated(block_range_update.clone()),
+                    );
+                }
+            }
+            self.last_broadcast_block_range = block_range_update;
+        }
     }
 }
 
+/// Returns `true` if the advertised block range moved by at least an epoch since it was last
+/// broadcast.
+fn is_significant_block_range_change(last: &BlockRangeUpdate, new: &BlockRangeUpdate) -> bool {
+    new.earliest.abs_diff(last.earliest) >= EPOCH_SLOTS ||
+        new.latest.abs_diff(last.latest) >= EPOCH_SLOTS
+}
+
 /// A counter for ongoing graceful disconnections attempts.
 #[derive(Default, Debug, Clone)]
 struct DisconnectionsCounter(Arc<()>);
@@ -0,0 +1,186 @@
+//! Rate limiting of messages received from a peer.
+
+use reth_network_types::SessionRateLimit;
+use std::time::{Duration, Instant};
+
+/// The outcome of [`MessageRateLimiter::try_acquire`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RateLimitOutcome {
+    /// The message is within the rate limit.
+    Allowed,
+    /// The message exceeds the rate limit and should be dropped.
+    Throttled,
+    /// The peer exceeded the rate limit for longer than allowed and should be disconnected.
+    Exceeded,
+}
+
+/// A token bucket that limits the rate of messages received from a peer.
+///
+/// The bucket holds up to `burst` tokens and is refilled with `messages_per_second` tokens per
+/// second. Every message consumes a token, messages that arrive while the bucket is empty are
+/// throttled.
+#[derive(Debug)]
+pub(crate) struct MessageRateLimiter {
+    /// Number of tokens added per second.
+    refill_rate: f64,
+    /// Maximum number of tokens.
+    burst: f64,
+    /// Currently available tokens.
+    tokens: f64,
+    /// The last time tokens were added.
+    last_refill: Instant,
+    /// How long the peer may exceed the rate limit before it should be disconnected.
+    max_throttled_duration: Duration,
+    /// The time the peer started exceeding the rate limit, if it currently does.
+    throttled_since: Option<Instant>,
+}
+
+impl MessageRateLimiter {
+    /// Creates a new rate limiter with a full bucket.
+    pub(crate) fn new(rate_limit: SessionRateLimit, now: Instant) -> Self {
+        let burst = rate_limit.burst.get() as f64;
+        Self {
+            refill_rate: rate_limit.messages_per_second.get() as f64,
+            burst,
+            tokens: burst,
+            last_refill: now,
+            max_throttled_duration: rate_limit.max_throttled_duration,
+            throttled_since: None,
+        }
+    }
+
+    /// Returns the number of currently available tokens.
+    #[cfg(test)]
+    const fn tokens(&self) -> f64 {
+        self.tokens
+    }
+
+    /// Adds the tokens accumulated since the last refill.
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.refill_rate).min(self.burst);
+        self.last_refill = now;
+    }
+
+    /// Attempts to consume a token for a message received at `now`.
+    ///
+    /// The peer is considered to exceed the rate limit from the first throttled message until the
+    /// bucket is full again.
+    pub(crate) fn try_acquire(&mut self, now: Instant) -> RateLimitOutcome {
+        self.refill(now);
+
+        if self.tokens >= 1.0 {
+            if self.tokens >= self.burst {
+                // the peer backed off long enough to recover its full burst
+                self.throttled_since = None;
+            }
+            self.tokens -= 1.0;
+            return RateLimitOutcome::Allowed
+        }
+
+        let throttled_since = *self.throttled_since.get_or_insert(now);
+        if now.saturating_duration_since(throttled_since) > self.max_throttled_duration {
+            RateLimitOutcome::Exceeded
+        } else {
+            RateLimitOutcome::Throttled
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+
+    fn rate_limit(messages_per_second: u32, burst: u32) -> SessionRateLimit {
+        SessionRateLimit::default()
+            .with_messages_per_second(NonZeroU32::new(messages_per_second).unwrap())
+            .with_burst(NonZeroU32::new(burst).unwrap())
+            .with_max_throttled_duration(Duration::from_secs(5))
+    }
+
+    #[test]
+    fn throttle_when_burst_is_exhausted() {
+        let now = Instant::now();
+        let mut limiter = MessageRateLimiter::new(rate_limit(10, 3), now);
+
+        for _ in 0..3 {
+            assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Allowed);
+        }
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Throttled);
+    }
+
+    #[test]
+    fn refill_over_time() {
+        let now = Instant::now();
+        let mut limiter = MessageRateLimiter::new(rate_limit(4, 5), now);
+        for _ in 0..5 {
+            assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Allowed);
+        }
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Throttled);
+
+        // 4 messages per second refill a token every 250ms
+        let now = now + Duration::from_millis(125);
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Throttled);
+        let now = now + Duration::from_millis(125);
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Allowed);
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Throttled);
+
+        let now = now + Duration::from_millis(750);
+        for _ in 0..3 {
+            assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Allowed);
+        }
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Throttled);
+    }
+
+    #[test]
+    fn refill_is_capped_at_burst() {
+        let now = Instant::now();
+        let mut limiter = MessageRateLimiter::new(rate_limit(4, 5), now);
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Allowed);
+
+        let now = now + Duration::from_secs(60);
+        limiter.refill(now);
+        assert_eq!(limiter.tokens(), 5.0);
+    }
+
+    #[test]
+    fn exceed_on_sustained_throttling() {
+        let mut now = Instant::now();
+        let mut limiter = MessageRateLimiter::new(rate_limit(4, 5), now);
+        for _ in 0..5 {
+            assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Allowed);
+        }
+
+        // the peer keeps sending twice the allowed rate
+        let deadline = now + Duration::from_secs(5);
+        while now <= deadline {
+            limiter.try_acquire(now);
+            assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Throttled);
+            now += Duration::from_millis(250);
+        }
+        limiter.try_acquire(now);
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Exceeded);
+    }
+
+    #[test]
+    fn recover_after_backing_off() {
+        let now = Instant::now();
+        let mut limiter = MessageRateLimiter::new(rate_limit(4, 5), now);
+        for _ in 0..5 {
+            assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Allowed);
+        }
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Throttled);
+
+        // the bucket is full again after 1.25s, which resets the throttling
+        let now = now + Duration::from_secs(2);
+        for _ in 0..5 {
+            assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Allowed);
+        }
+        let now = now + Duration::from_secs(10);
+        for _ in 0..5 {
+            assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Allowed);
+        }
+        assert_eq!(limiter.try_acquire(now), RateLimitOutcome::Throttled);
+    }
+}
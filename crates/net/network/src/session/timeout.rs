@@ -0,0 +1,165 @@
+//! Request timeout that adapts to the response latency of a peer.
+
+use reth_network_types::RequestTimeoutConfig;
+use std::time::Duration;
+
+/// How much a new latency sample affects the moving average and variance.
+const SAMPLE_IMPACT: f64 = 0.125;
+
+/// The lower bound of the standard deviation, relative to the average latency.
+///
+/// The variance of a peer with stable latency decays towards zero, which would leave no headroom
+/// for the slightest delay. Similar to the clock granularity term of TCP's retransmission timeout,
+/// this keeps the timeout at least `1 + deviation_factor / 8` times the average latency.
+const MIN_RELATIVE_DEVIATION: f64 = 0.125;
+
+/// Estimates the timeout of requests sent to a peer from the latency of its responses.
+///
+/// Tracks an exponential moving average of the response latency and of its variance. The timeout
+/// is the average plus [`RequestTimeoutConfig::deviation_factor`] standard deviations, clamped to
+/// the configured bounds. The standard deviation is at least [`MIN_RELATIVE_DEVIATION`] of the
+/// average.
+#[derive(Debug)]
+pub(crate) struct RequestTimeoutEstimator {
+    /// The configured bounds.
+    config: RequestTimeoutConfig,
+    /// The upper bound, the initial request timeout unless configured otherwise.
+    max_timeout: Duration,
+    /// Moving average of the response latency in seconds, `None` until the first response.
+    mean: Option<f64>,
+    /// Moving variance of the response latency in seconds squared.
+    variance: f64,
+}
+
+impl RequestTimeoutEstimator {
+    /// Creates a new estimator without any latency samples.
+    ///
+    /// The timeout is bounded by `initial_timeout` if no upper bound is configured.
+    pub(crate) fn new(config: RequestTimeoutConfig, initial_timeout: Duration) -> Self {
+        let max_timeout = config.max_timeout.unwrap_or(initial_timeout);
+        Self { config, max_timeout, mean: None, variance: 0.0 }
+    }
+
+    /// Records the latency of a response and returns the updated request timeout.
+    pub(crate) fn on_response(&mut self, latency: Duration) -> Duration {
+        let sample = latency.as_secs_f64();
+        match self.mean {
+            Some(mean) => {
+                let diff = sample - mean;
+                self.mean = Some(mean + SAMPLE_IMPACT * diff);
+                self.variance =
+                    (1.0 - SAMPLE_IMPACT) * (self.variance + SAMPLE_IMPACT * diff * diff);
+            }
+            None => {
+                // there's no deviation to observe yet, so assume half the latency
+                self.mean = Some(sample);
+                self.variance = (sample / 2.0).powi(2);
+            }
+        }
+        self.timeout()
+    }
+
+    /// Returns the request timeout derived from the observed latencies.
+    ///
+    /// Without any samples this is the upper bound.
+    pub(crate) fn timeout(&self) -> Duration {
+        let Some(mean) = self.mean else { return self.max_timeout };
+        let deviation = self.variance.sqrt().max(mean * MIN_RELATIVE_DEVIATION);
+        let timeout = mean + f64::from(self.config.deviation_factor) * deviation;
+        let timeout =
+            timeout.min(self.max_timeout.as_secs_f64()).max(self.config.min_timeout.as_secs_f64());
+        Duration::from_secs_f64(timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RequestTimeoutConfig {
+        RequestTimeoutConfig::default()
+            .with_min_timeout(Duration::from_millis(100))
+            .with_max_timeout(Duration::from_secs(20))
+            .with_deviation_factor(4)
+    }
+
+    fn new_estimator() -> RequestTimeoutEstimator {
+        RequestTimeoutEstimator::new(config(), Duration::from_secs(10))
+    }
+
+    fn assert_close(actual: Duration, expected: Duration) {
+        let diff = actual.as_secs_f64() - expected.as_secs_f64();
+        assert!(diff.abs() < 1e-6, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn first_sample_initializes_estimate() {
+        let mut estimator = new_estimator();
+        assert_eq!(estimator.timeout(), Duration::from_secs(20));
+
+        // mean 1s, stddev 0.5s
+        assert_close(estimator.on_response(Duration::from_secs(1)), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn moving_average_and_variance() {
+        let mut estimator = new_estimator();
+        estimator.on_response(Duration::from_secs(1));
+
+        // diff 1s: mean 1.125s, variance 0.875 * (0.25 + 0.125) = 0.328125
+        let timeout = estimator.on_response(Duration::from_secs(2));
+        let expected = 1.125 + 4.0 * 0.328125f64.sqrt();
+        assert_close(timeout, Duration::from_secs_f64(expected));
+    }
+
+    #[test]
+    fn converges_on_stable_latency() {
+        let mut estimator = new_estimator();
+        for _ in 0..200 {
+            estimator.on_response(Duration::from_millis(500));
+        }
+        // the deviation decays towards its lower bound, 1/8 of the average latency
+        assert_close(estimator.timeout(), Duration::from_millis(750));
+    }
+
+    #[test]
+    fn stable_latency_keeps_headroom() {
+        let mut estimator = new_estimator();
+        for _ in 0..1000 {
+            estimator.on_response(Duration::from_secs(2));
+        }
+        // a slightly delayed response doesn't time out
+        assert!(estimator.timeout() > Duration::from_millis(2500), "{:?}", estimator.timeout());
+    }
+
+    #[test]
+    fn jitter_increases_timeout() {
+        let mut stable = new_estimator();
+        let mut jittery = new_estimator();
+        for i in 0..100 {
+            stable.on_response(Duration::from_millis(500));
+            jittery.on_response(Duration::from_millis(if i % 2 == 0 { 200 } else { 800 }));
+        }
+        assert!(jittery.timeout() > stable.timeout());
+    }
+
+    #[test]
+    fn timeout_is_clamped() {
+        let mut estimator = new_estimator();
+        for _ in 0..200 {
+            estimator.on_response(Duration::from_millis(1));
+        }
+        assert_eq!(estimator.timeout(), Duration::from_millis(100));
+
+        let mut estimator = new_estimator();
+        assert_eq!(estimator.on_response(Duration::from_secs(60)), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn initial_timeout_is_default_upper_bound() {
+        let config = RequestTimeoutConfig::default().with_min_timeout(Duration::from_millis(100));
+        let mut estimator = RequestTimeoutEstimator::new(config, Duration::from_secs(30));
+        assert_eq!(estimator.timeout(), Duration::from_secs(30));
+        assert_eq!(estimator.on_response(Duration::from_secs(60)), Duration::from_secs(30));
+    }
+}
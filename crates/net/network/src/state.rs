@@ -119,6 +119,11 @@ impl<N: NetworkPrimitives> NetworkState<N> {
         &mut self.peers_manager
     }
 
+    /// Returns access to the [`Discovery`]
+    pub(crate) const fn discovery(&self) -> &Discovery {
+        &self.discovery
+    }
+
     /// Returns mutable access to the [`Discovery`]
     pub(crate) const fn discovery_mut(&mut self) -> &mut Discovery {
         &mut self.discovery
@@ -354,6 +359,9 @@ impl<N: NetworkPrimitives> NetworkState<N> {
                 self.state_fetcher.on_pending_disconnect(&peer_id);
                 self.queued_messages.push_back(StateAction::Disconnect { peer_id, reason: None });
             }
+            PeerAction::AbortPendingOutgoing { peer_id } => {
+                self.queued_messages.push_back(StateAction::AbortPendingOutgoing { peer_id });
+            }
             PeerAction::DiscoveryBanPeerId { peer_id, ip_addr } => {
                 self.ban_discovery(peer_id, ip_addr)
             }
@@ -549,6 +557,8 @@ pub(crate) enum StateAction<N: NetworkPrimitives> {
         /// Why the disconnect was initiated
         reason: Option<DisconnectReason>,
     },
+    /// Abort a pending outgoing connection to the given node.
+    AbortPendingOutgoing { peer_id: PeerId },
     /// Retrieved a [`ForkId`] from the peer via ENR request, See <https://eips.ethereum.org/EIPS/eip-868>
     DiscoveredEnrForkId {
         peer_id: PeerId,
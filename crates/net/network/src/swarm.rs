@@ -235,6 +235,9 @@ impl<N: NetworkPrimitives> Swarm<N> {
             StateAction::Disconnect { peer_id, reason } => {
                 self.sessions.disconnect(peer_id, reason);
             }
+            StateAction::AbortPendingOutgoing { peer_id } => {
+                self.sessions.disconnect_pending_outgoing(peer_id);
+            }
             StateAction::NewBlock { peer_id, block: msg } => {
                 let msg = PeerMessage::NewBlock(msg);
                 self.sessions.send_message(&peer_id, msg);
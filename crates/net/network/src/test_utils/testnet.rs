@@ -3,7 +3,7 @@
 use crate::{
     builder::ETH_REQUEST_CHANNEL_CAPACITY,
     error::NetworkError,
-    eth_requests::EthRequestHandler,
+    eth_requests::{EthRequestHandler, EthRequestHandlerConfig},
     protocol::IntoRlpxSubProtocol,
     transactions::{
         config::{StrictEthAnnouncementFilter, TransactionPropagationKind},
@@ -242,6 +242,7 @@ where
             Receipt = reth_ethereum_primitives::Receipt,
             Header = alloy_consensus::Header,
         > + HeaderProvider
+        + StateProviderFactory
         + Clone
         + Unpin
         + 'static,
@@ -314,6 +315,8 @@ where
             Receipt = reth_ethereum_primitives::Receipt,
             Header = alloy_consensus::Header,
         > + HeaderProvider
+        + StateProviderFactory
+        + Clone
         + Unpin
         + 'static,
     Pool: TransactionPool<
@@ -461,7 +464,12 @@ where
         let (tx, rx) = channel(ETH_REQUEST_CHANNEL_CAPACITY);
         self.network.set_eth_request_handler(tx);
         let peers = self.network.peers_handle();
-        let request_handler = EthRequestHandler::new(self.client.clone(), peers, rx);
+        let request_handler = EthRequestHandler::new(
+            self.client.clone(),
+            peers,
+            rx,
+            EthRequestHandlerConfig::default(),
+        );
         self.request_handler = Some(request_handler);
     }
 
@@ -568,6 +576,8 @@ where
             Receipt = reth_ethereum_primitives::Receipt,
             Header = alloy_consensus::Header,
         > + HeaderProvider
+        + StateProviderFactory
+        + Clone
         + Unpin
         + 'static,
     Pool: TransactionPool<
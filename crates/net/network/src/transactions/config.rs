@@ -2,7 +2,8 @@ use core::fmt;
 use std::{fmt::Debug, str::FromStr};
 
 use super::{
-    PeerMetadata, DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
+    constants::DEFAULT_SOFT_LIMIT_BYTE_SIZE_TRANSACTIONS_BROADCAST_MESSAGE, PeerMetadata,
+    DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
     DEFAULT_SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESP_ON_PACK_GET_POOLED_TRANSACTIONS_REQ,
     SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESPONSE,
 };
@@ -24,12 +25,22 @@ pub struct TransactionsManagerConfig {
     pub transaction_fetcher_config: TransactionFetcherConfig,
     /// Max number of seen transactions to store for each peer.
     pub max_transactions_seen_by_peer_history: u32,
-    /// How new pending transactions are propagated.
-    #[cfg_attr(feature = "serde", serde(default))]
-    pub propagation_mode: TransactionPropagationMode,
+    /// Which transactions are propagated in full and to how many peers.
+    ///
+    /// This can be changed at runtime via
+    /// [`TransactionsHandle::set_propagation_policy`](super::TransactionsHandle::set_propagation_policy).
+    ///
+    /// The fields are flattened, so `propagation_mode` is still (de)serialized as a field of this
+    /// config.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub broadcast_policy: TransactionBroadcastPolicy,
     /// Which peers we accept incoming transactions or announcements from.
     #[cfg_attr(feature = "serde", serde(default))]
     pub ingress_policy: TransactionIngressPolicy,
+    /// How requests for pooled transactions that weren't announced to the requesting peer are
+    /// answered.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub unannounced_policy: UnannouncedTransactionsPolicy,
 }
 
 impl Default for TransactionsManagerConfig {
@@ -37,8 +48,9 @@ impl Default for TransactionsManagerConfig {
         Self {
             transaction_fetcher_config: TransactionFetcherConfig::default(),
             max_transactions_seen_by_peer_history: DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
-            propagation_mode: TransactionPropagationMode::default(),
+            broadcast_policy: TransactionBroadcastPolicy::default(),
             ingress_policy: TransactionIngressPolicy::default(),
+            unannounced_policy: UnannouncedTransactionsPolicy::default(),
         }
     }
 }
@@ -87,6 +99,64 @@ impl FromStr for TransactionPropagationMode {
     }
 }
 
+/// Determines which new pending transactions are propagated in full and to how many peers.
+///
+/// Peers that don't receive a transaction in full receive its hash instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TransactionBroadcastPolicy {
+    /// How many peers receive transactions in full.
+    pub propagation_mode: TransactionPropagationMode,
+    /// Soft limit for the byte size of the full transactions sent to a peer at once.
+    ///
+    /// Transactions that don't fit are announced by hash. A single transaction that exceeds the
+    /// limit on its own is still sent in full.
+    pub max_full_transactions_size: usize,
+    /// Whether blob transactions are only ever announced by hash.
+    ///
+    /// This is enabled by default as required by
+    /// [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844#networking). If disabled, blob
+    /// transactions are sent in full like any other transaction, which is only useful for
+    /// networks that don't follow this rule.
+    pub blob_transactions_as_hashes: bool,
+}
+
+impl TransactionBroadcastPolicy {
+    /// Creates a policy with the given propagation mode and default limits.
+    pub const fn new(propagation_mode: TransactionPropagationMode) -> Self {
+        Self {
+            propagation_mode,
+            max_full_transactions_size: DEFAULT_SOFT_LIMIT_BYTE_SIZE_TRANSACTIONS_BROADCAST_MESSAGE,
+            blob_transactions_as_hashes: true,
+        }
+    }
+
+    /// Sets how many peers receive transactions in full.
+    pub const fn with_propagation_mode(mut self, mode: TransactionPropagationMode) -> Self {
+        self.propagation_mode = mode;
+        self
+    }
+
+    /// Sets the soft limit for the byte size of the full transactions sent to a peer at once.
+    pub const fn with_max_full_transactions_size(mut self, size: usize) -> Self {
+        self.max_full_transactions_size = size;
+        self
+    }
+
+    /// Sets whether blob transactions are only ever announced by hash.
+    pub const fn with_blob_transactions_as_hashes(mut self, as_hashes: bool) -> Self {
+        self.blob_transactions_as_hashes = as_hashes;
+        self
+    }
+}
+
+impl Default for TransactionBroadcastPolicy {
+    fn default() -> Self {
+        Self::new(TransactionPropagationMode::default())
+    }
+}
+
 /// Configuration for fetching transactions.
 #[derive(Debug, Constructor, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -226,6 +296,47 @@ impl FromStr for TransactionIngressPolicy {
     }
 }
 
+/// Determines how requests for pooled transactions that weren't announced to the requesting peer
+/// are answered.
+///
+/// Serving any pooled transaction on request lets peers query the local mempool without ever
+/// having received an announcement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnannouncedTransactionsPolicy {
+    /// Serve all requested transactions.
+    ///
+    /// The transactions announced to a peer aren't tracked with this policy, so requests for
+    /// unannounced transactions aren't counted either.
+    #[default]
+    Serve,
+    /// Serve all requested transactions, but penalize peers that request transactions that
+    /// weren't announced to them.
+    Penalize,
+    /// Omit transactions that weren't announced to the requesting peer from the response.
+    Omit,
+}
+
+impl UnannouncedTransactionsPolicy {
+    /// Returns `true` if the transactions announced to each peer need to be tracked.
+    pub const fn tracks_announced(&self) -> bool {
+        !matches!(self, Self::Serve)
+    }
+}
+
+impl FromStr for UnannouncedTransactionsPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Serve" | "serve" => Ok(Self::Serve),
+            "Penalize" | "penalize" => Ok(Self::Penalize),
+            "Omit" | "omit" => Ok(Self::Omit),
+            _ => Err(format!("Invalid unannounced transactions policy: {s}")),
+        }
+    }
+}
+
 /// Defines the outcome of evaluating a transaction against an `AnnouncementFilteringPolicy`.
 ///
 /// Dictates how the `TransactionManager` should proceed on an announced transaction.
@@ -308,6 +419,25 @@ pub type RelaxedEthAnnouncementFilter = TypedRelaxedFilter;
 mod tests {
     use super::*;
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_propagation_mode_serde_compat() {
+        let config = TransactionsManagerConfig::default();
+        let mut value = serde_json::to_value(&config).unwrap();
+        assert_eq!(value["propagation_mode"], serde_json::json!("Sqrt"));
+
+        // configs that only set the propagation mode keep the default limits
+        value.as_object_mut().unwrap().retain(|key, _| {
+            !matches!(key.as_str(), "max_full_transactions_size" | "blob_transactions_as_hashes")
+        });
+        value["propagation_mode"] = serde_json::json!("All");
+        let config: TransactionsManagerConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            config.broadcast_policy,
+            TransactionBroadcastPolicy::new(TransactionPropagationMode::All)
+        );
+    }
+
     #[test]
     fn test_transaction_propagation_mode_from_str() {
         // Test "sqrt" variant
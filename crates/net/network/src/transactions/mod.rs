@@ -18,14 +18,15 @@ pub use self::constants::{
 };
 use config::AnnouncementAcceptance;
 pub use config::{
-    AnnouncementFilteringPolicy, TransactionFetcherConfig, TransactionIngressPolicy,
-    TransactionPropagationMode, TransactionPropagationPolicy, TransactionsManagerConfig,
+    AnnouncementFilteringPolicy, TransactionBroadcastPolicy, TransactionFetcherConfig,
+    TransactionIngressPolicy, TransactionPropagationMode, TransactionPropagationPolicy,
+    TransactionsManagerConfig, UnannouncedTransactionsPolicy,
 };
 use policy::NetworkPolicies;
 
 pub(crate) use fetcher::{FetchEvent, TransactionFetcher};
 
-use self::constants::{tx_manager::*, DEFAULT_SOFT_LIMIT_BYTE_SIZE_TRANSACTIONS_BROADCAST_MESSAGE};
+use self::constants::tx_manager::*;
 use crate::{
     budget::{
         DEFAULT_BUDGET_TRY_DRAIN_NETWORK_TRANSACTION_EVENTS,
@@ -182,6 +183,14 @@ impl<N: NetworkPrimitives> TransactionsHandle<N> {
         self.send(TransactionsCommand::BroadcastTransactions(transactions))
     }
 
+    /// Replaces the policy that determines which transactions are propagated in full and to how
+    /// many peers.
+    ///
+    /// The new policy applies from the next propagation round on.
+    pub fn set_propagation_policy(&self, policy: TransactionBroadcastPolicy) {
+        self.send(TransactionsCommand::SetPropagationPolicy(policy))
+    }
+
     /// Request the transaction hashes known by specific peers.
     pub async fn get_transaction_hashes(
         &self,
@@ -865,7 +874,8 @@ where
         let mut propagated = PropagatedTransactions::default();
 
         // filter all transactions unknown to the peer
-        let mut full_transactions = FullTransactionsBuilder::new(peer.version);
+        let mut full_transactions =
+            FullTransactionsBuilder::new(peer.version, &self.config.broadcast_policy);
 
         let to_propagate = self.pool.get_all(txs).into_iter().map(PropagateTransaction::pool_tx);
 
@@ -896,6 +906,7 @@ where
                 propagated.0.entry(hash).or_default().push(PropagateKind::Hash(peer_id));
                 // mark transaction as seen by peer
                 peer.seen_transactions.insert(hash);
+                peer.on_announced(hash);
             }
 
             // send hashes of transactions
@@ -971,6 +982,7 @@ where
 
             for hash in new_pooled_hashes.iter_hashes().copied() {
                 propagated.0.entry(hash).or_default().push(PropagateKind::Hash(peer_id));
+                peer.on_announced(hash);
             }
 
             trace!(target: "net::tx::propagation", ?peer_id, ?new_pooled_hashes, "Propagating transactions to peer");
@@ -1005,7 +1017,8 @@ where
         }
 
         // send full transactions to a set of the connected peers based on the configured mode
-        let max_num_full = self.config.propagation_mode.full_peer_count(self.peers.len());
+        let policy = self.config.broadcast_policy;
+        let max_num_full = policy.propagation_mode.full_peer_count(self.peers.len());
 
         // Note: Assuming ~random~ order due to random state of the peers map hasher
         for (peer_idx, (peer_id, peer)) in self.peers.iter_mut().enumerate() {
@@ -1014,10 +1027,10 @@ where
                 continue
             }
             // determine whether to send full tx objects or hashes.
-            let mut builder = if peer_idx > max_num_full {
+            let mut builder = if peer_idx >= max_num_full {
                 PropagateTransactionsBuilder::pooled(peer.version)
             } else {
-                PropagateTransactionsBuilder::full(peer.version)
+                PropagateTransactionsBuilder::full(peer.version, &policy)
             };
 
             if propagation_mode.is_forced() {
@@ -1053,6 +1066,7 @@ where
                     propagated.0.entry(hash).or_default().push(PropagateKind::Hash(*peer_id));
                     // mark transaction as seen by peer
                     peer.seen_transactions.insert(hash);
+                    peer.on_announced(hash);
                 }
 
                 trace!(target: "net::tx", ?peer_id, num_txs=?new_pooled_hashes.len(), "Propagating tx hashes to peer");
@@ -1116,8 +1130,32 @@ where
                 let _ = response.send(Ok(PooledTransactions::default()));
                 return
             }
+
+            let mut hashes = request.0;
+            // announced transactions are only tracked if the policy requires them
+            if let Some(announced) = &peer.announced_transactions {
+                let unannounced = hashes.iter().filter(|hash| !announced.contains(hash)).count();
+                if unannounced > 0 {
+                    trace!(target: "net::tx", ?peer_id, unannounced, policy=?self.config.unannounced_policy, "Peer requested transactions that weren't announced to it");
+                    self.metrics
+                        .unannounced_pooled_transactions_requested
+                        .increment(unannounced as u64);
+                    match self.config.unannounced_policy {
+                        UnannouncedTransactionsPolicy::Serve => {}
+                        UnannouncedTransactionsPolicy::Penalize => {
+                            // reported before the response is sent
+                            self.network
+                                .reputation_change(peer_id, ReputationChangeKind::BadMessage)
+                        }
+                        UnannouncedTransactionsPolicy::Omit => {
+                            hashes.retain(|hash| announced.contains(hash))
+                        }
+                    }
+                }
+            }
+
             let transactions = self.pool.get_pooled_transaction_elements(
-                request.0,
+                hashes,
                 GetPooledTransactionLimit::ResponseSizeSoftLimit(
                     self.transaction_fetcher.info.soft_limit_byte_size_pooled_transactions_response,
                 ),
@@ -1158,6 +1196,10 @@ where
                 let propagated = self.propagate_transactions(txs, PropagationMode::Forced);
                 self.pool.on_propagated(propagated);
             }
+            TransactionsCommand::SetPropagationPolicy(policy) => {
+                trace!(target: "net::tx", ?policy, "Updating transaction propagation policy");
+                self.config.broadcast_policy = policy;
+            }
             TransactionsCommand::GetTransactionHashes { peers, tx } => {
                 let mut res = HashMap::with_capacity(peers.len());
                 for peer_id in peers {
@@ -1188,13 +1230,17 @@ where
         let SessionInfo { peer_id, client_version, version, .. } = info;
 
         // Insert a new peer into the peerset.
-        let peer = PeerMetadata::<N>::new(
+        let mut peer = PeerMetadata::<N>::new(
             messages,
             version,
             client_version,
             self.config.max_transactions_seen_by_peer_history,
             info.peer_kind,
         );
+        if self.config.unannounced_policy.tracks_announced() {
+            peer =
+                peer.with_announced_transactions(self.config.max_transactions_seen_by_peer_history);
+        }
         let peer = match self.peers.entry(peer_id) {
             Entry::Occupied(mut entry) => {
                 entry.insert(peer);
@@ -1226,6 +1272,7 @@ where
         let mut msg_builder = PooledTransactionsHashesBuilder::new(version);
         for pooled_tx in pooled_txs {
             peer.seen_transactions.insert(*pooled_tx.hash());
+            peer.on_announced(*pooled_tx.hash());
             msg_builder.push_pooled(pooled_tx);
         }
 
@@ -1737,8 +1784,8 @@ impl<T> PropagateTransactionsBuilder<T> {
     }
 
     /// Create a builder that sends transactions in full and records transactions that don't fit.
-    fn full(version: EthVersion) -> Self {
-        Self::Full(FullTransactionsBuilder::new(version))
+    fn full(version: EthVersion, policy: &TransactionBroadcastPolicy) -> Self {
+        Self::Full(FullTransactionsBuilder::new(version, policy))
     }
 
     /// Returns true if no transactions are recorded.
@@ -1786,13 +1833,17 @@ struct PropagateTransactions<T> {
 }
 
 /// Helper type for constructing the full transaction message that enforces the
-/// [`DEFAULT_SOFT_LIMIT_BYTE_SIZE_TRANSACTIONS_BROADCAST_MESSAGE`] for full transaction broadcast
+/// [`TransactionBroadcastPolicy::max_full_transactions_size`] for full transaction broadcast
 /// and enforces other propagation rules for EIP-4844 and tracks those transactions that can't be
 /// broadcasted in full.
 #[derive(Debug, Clone)]
 struct FullTransactionsBuilder<T> {
-    /// The soft limit to enforce for a single broadcast message of full transactions.
+    /// The total size of the transactions to be broadcasted.
     total_size: usize,
+    /// The soft limit to enforce for a single broadcast message of full transactions.
+    max_size: usize,
+    /// Whether blob transactions are always announced by hash.
+    blob_transactions_as_hashes: bool,
     /// All transactions to be broadcasted.
     transactions: Vec<Arc<T>>,
     /// Transactions that didn't fit into the broadcast message
@@ -1801,9 +1852,11 @@ struct FullTransactionsBuilder<T> {
 
 impl<T> FullTransactionsBuilder<T> {
     /// Create a builder for the negotiated version of the peer's session
-    fn new(version: EthVersion) -> Self {
+    fn new(version: EthVersion, policy: &TransactionBroadcastPolicy) -> Self {
         Self {
             total_size: 0,
+            max_size: policy.max_full_transactions_size,
+            blob_transactions_as_hashes: policy.blob_transactions_as_hashes,
             pooled: PooledTransactionsHashesBuilder::new(version),
             transactions: vec![],
         }
@@ -1848,15 +1901,18 @@ impl<T: SignedTransaction> FullTransactionsBuilder<T> {
         //  via `GetPooledTransactions`.
         //
         // From: <https://eips.ethereum.org/EIPS/eip-4844#networking>
-        if !transaction.transaction.is_broadcastable_in_full() {
+        let as_hash = if transaction.transaction.is_eip4844() {
+            self.blob_transactions_as_hashes
+        } else {
+            !transaction.transaction.is_broadcastable_in_full()
+        };
+        if as_hash {
             self.pooled.push(transaction);
             return
         }
 
         let new_size = self.total_size + transaction.size;
-        if new_size > DEFAULT_SOFT_LIMIT_BYTE_SIZE_TRANSACTIONS_BROADCAST_MESSAGE &&
-            self.total_size > 0
-        {
+        if new_size > self.max_size && self.total_size > 0 {
             // transaction does not fit into the message
             self.pooled.push(transaction);
             return
@@ -1967,6 +2023,11 @@ pub struct PeerMetadata<N: NetworkPrimitives = EthNetworkPrimitives> {
     /// the sense that transactions are preemptively marked as seen by peer when they are sent to
     /// the peer.
     seen_transactions: LruCache<TxHash>,
+    /// Keeps track of the transactions that were announced to the peer by hash, which the peer
+    /// is expected to request.
+    ///
+    /// Only tracked if required by the [`UnannouncedTransactionsPolicy`].
+    announced_transactions: Option<LruCache<TxHash>>,
     /// A communication channel directly to the peer's session task.
     request_tx: PeerRequestSender<PeerRequest<N>>,
     /// negotiated version of the session.
@@ -1988,6 +2049,7 @@ impl<N: NetworkPrimitives> PeerMetadata<N> {
     ) -> Self {
         Self {
             seen_transactions: LruCache::new(max_transactions_seen_by_peer),
+            announced_transactions: None,
             request_tx,
             version,
             client_version,
@@ -1995,6 +2057,19 @@ impl<N: NetworkPrimitives> PeerMetadata<N> {
         }
     }
 
+    /// Tracks up to `max_transactions_announced` transactions announced to the peer by hash.
+    pub fn with_announced_transactions(mut self, max_transactions_announced: u32) -> Self {
+        self.announced_transactions = Some(LruCache::new(max_transactions_announced));
+        self
+    }
+
+    /// Records that the transaction was announced to the peer by hash, if tracked.
+    fn on_announced(&mut self, hash: TxHash) {
+        if let Some(announced) = &mut self.announced_transactions {
+            announced.insert(hash);
+        }
+    }
+
     /// Returns a reference to the peer's request sender channel.
     pub const fn request_tx(&self) -> &PeerRequestSender<PeerRequest<N>> {
         &self.request_tx
@@ -2036,6 +2111,8 @@ enum TransactionsCommand<N: NetworkPrimitives = EthNetworkPrimitives> {
     PropagateTransactions(Vec<TxHash>),
     /// Propagate a collection of broadcastable transactions in full to all peers.
     BroadcastTransactions(Vec<PropagateTransaction<N::BroadcastedTransaction>>),
+    /// Replace the [`TransactionBroadcastPolicy`] of the [`TransactionsManager`].
+    SetPropagationPolicy(TransactionBroadcastPolicy),
     /// Request transaction hashes known by specific peers from the [`TransactionsManager`].
     GetTransactionHashes {
         peers: Vec<PeerId>,
@@ -2126,13 +2203,17 @@ mod tests {
             transactions::{buffer_hash_to_tx_fetcher, new_mock_session, new_tx_manager},
             Testnet,
         },
-        transactions::config::RelaxedEthAnnouncementFilter,
+        transactions::{
+            config::RelaxedEthAnnouncementFilter,
+            constants::DEFAULT_SOFT_LIMIT_BYTE_SIZE_TRANSACTIONS_BROADCAST_MESSAGE,
+        },
         NetworkConfigBuilder, NetworkManager,
     };
     use alloy_consensus::{TxEip1559, TxLegacy};
     use alloy_primitives::{hex, Signature, TxKind, U256};
     use alloy_rlp::Decodable;
     use futures::FutureExt;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
     use reth_chainspec::MIN_TRANSACTION_GAS;
     use reth_ethereum_primitives::{PooledTransactionVariant, Transaction, TransactionSigned};
     use reth_network_api::{NetworkInfo, PeerKind};
@@ -2140,6 +2221,7 @@ mod tests {
         error::{RequestError, RequestResult},
         sync::{NetworkSyncUpdater, SyncState},
     };
+    use reth_network_types::{PeersConfig, DEFAULT_REPUTATION};
     use reth_storage_api::noop::NoopProvider;
     use reth_transaction_pool::test_utils::{
         testing_pool, MockTransaction, MockTransactionFactory, TestPool,
@@ -2552,6 +2634,110 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_on_get_pooled_transactions_unannounced() {
+        reth_tracing::init_test_tracing();
+
+        for policy in [
+            UnannouncedTransactionsPolicy::Serve,
+            UnannouncedTransactionsPolicy::Omit,
+            UnannouncedTransactionsPolicy::Penalize,
+        ] {
+            let secret_key = SecretKey::new(&mut rand_08::thread_rng());
+            let config = NetworkConfigBuilder::eth(secret_key)
+                .disable_discovery()
+                .listener_port(0)
+                // never dial the peer, so the only reputation change is the one under test
+                .peer_config(PeersConfig::default().with_max_outbound(0))
+                .build(NoopProvider::default());
+            let transactions_manager_config =
+                TransactionsManagerConfig { unannounced_policy: policy, ..Default::default() };
+            let (network_handle, network, mut tx_manager, _) = NetworkManager::new(config)
+                .await
+                .unwrap()
+                .into_builder()
+                .transactions(testing_pool(), transactions_manager_config)
+                .split_with_handle();
+
+            let recorder = DebuggingRecorder::new();
+            let snapshotter = recorder.snapshotter();
+            tx_manager.metrics =
+                metrics::with_local_recorder(&recorder, TransactionsManagerMetrics::default);
+
+            // the peer must be known to the peers manager for reputation changes to apply
+            let peer_id = PeerId::random();
+            let remote_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+            network_handle.add_peer(peer_id, remote_addr);
+            tokio::spawn(network);
+
+            // announced transactions are tracked from the start of the session, as configured
+            let (tx, _rx) = mpsc::channel::<PeerRequest>(10);
+            let session_info = SessionInfo {
+                peer_id,
+                remote_addr,
+                client_version: Arc::from(""),
+                capabilities: Arc::new(vec![].into()),
+                status: Arc::new(Default::default()),
+                version: EthVersion::Eth68,
+                peer_kind: PeerKind::Basic,
+            };
+            let messages: PeerRequestSender<PeerRequest> = PeerRequestSender::new(peer_id, tx);
+            tx_manager
+                .on_network_event(NetworkEvent::ActivePeerSession { info: session_info, messages });
+            assert_eq!(
+                tx_manager.peers[&peer_id].announced_transactions.is_some(),
+                policy.tracks_announced()
+            );
+
+            let announced = MockTransaction::eip1559();
+            let unannounced = MockTransaction::eip1559();
+            for tx in [&announced, &unannounced] {
+                tx_manager
+                    .pool
+                    .add_transaction(reth_transaction_pool::TransactionOrigin::External, tx.clone())
+                    .await
+                    .unwrap();
+            }
+            tx_manager.propagate_hashes_to(
+                vec![*announced.get_hash()],
+                peer_id,
+                PropagationMode::Forced,
+            );
+
+            let (send, mut receive) = oneshot::channel();
+            tx_manager.on_get_pooled_transactions(
+                peer_id,
+                GetPooledTransactions(vec![*announced.get_hash(), *unannounced.get_hash()]),
+                send,
+            );
+            let Ok(Ok(PooledTransactions(transactions))) = receive.try_recv() else {
+                panic!("expected response")
+            };
+            let served = transactions.iter().map(|tx| *tx.tx_hash()).collect::<Vec<_>>();
+
+            if policy == UnannouncedTransactionsPolicy::Omit {
+                assert_eq!(served, vec![*announced.get_hash()]);
+            } else {
+                assert_eq!(served, vec![*announced.get_hash(), *unannounced.get_hash()]);
+            }
+
+            let unannounced_requested =
+                snapshotter.snapshot().into_vec().into_iter().find_map(|(key, _, _, value)| {
+                    (key.key().name() == "network.unannounced_pooled_transactions_requested")
+                        .then_some(value)
+                });
+            let expected_requested = if policy.tracks_announced() { 1 } else { 0 };
+            assert_eq!(unannounced_requested, Some(DebugValue::Counter(expected_requested)));
+
+            let reputation = network_handle.reputation_by_id(peer_id).await.unwrap().unwrap();
+            if policy == UnannouncedTransactionsPolicy::Penalize {
+                assert!(reputation < DEFAULT_REPUTATION);
+            } else {
+                assert_eq!(reputation, DEFAULT_REPUTATION);
+            }
+        }
+    }
+
     // Ensure that when the remote peer only returns part of the requested transactions, the
     // replied transactions are removed from the `tx_fetcher`, while the unresponsive ones are
     // re-buffered.
@@ -2789,8 +2975,10 @@ mod tests {
 
     #[test]
     fn test_transaction_builder_large() {
-        let mut builder =
-            PropagateTransactionsBuilder::<TransactionSigned>::full(EthVersion::Eth68);
+        let mut builder = PropagateTransactionsBuilder::<TransactionSigned>::full(
+            EthVersion::Eth68,
+            &Default::default(),
+        );
         assert!(builder.is_empty());
 
         let mut factory = MockTransactionFactory::default();
@@ -2818,8 +3006,10 @@ mod tests {
 
     #[test]
     fn test_transaction_builder_eip4844() {
-        let mut builder =
-            PropagateTransactionsBuilder::<TransactionSigned>::full(EthVersion::Eth68);
+        let mut builder = PropagateTransactionsBuilder::<TransactionSigned>::full(
+            EthVersion::Eth68,
+            &Default::default(),
+        );
         assert!(builder.is_empty());
 
         let mut factory = MockTransactionFactory::default();
@@ -2842,6 +3032,21 @@ mod tests {
         assert_eq!(txs.len(), 1);
     }
 
+    #[test]
+    fn test_transaction_builder_eip4844_in_full() {
+        let policy = TransactionBroadcastPolicy::default().with_blob_transactions_as_hashes(false);
+        let mut builder =
+            PropagateTransactionsBuilder::<TransactionSigned>::full(EthVersion::Eth68, &policy);
+
+        let mut factory = MockTransactionFactory::default();
+        let tx = PropagateTransaction::pool_tx(Arc::new(factory.create_eip4844()));
+        builder.push(&tx);
+
+        let txs = builder.build();
+        assert!(txs.pooled.is_none());
+        assert_eq!(txs.full.unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_propagate_full() {
         reth_tracing::init_test_tracing();
@@ -2895,6 +3100,71 @@ mod tests {
         assert!(propagated.0.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_set_propagation_policy() {
+        reth_tracing::init_test_tracing();
+
+        let (mut tx_manager, network) = new_tx_manager().await;
+        let peer_id = PeerId::random();
+
+        // ensure not syncing
+        network.handle().update_sync_state(SyncState::Idle);
+
+        // mock a peer
+        let (tx, _rx) = mpsc::channel::<PeerRequest>(1);
+        let session_info = SessionInfo {
+            peer_id,
+            remote_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
+            client_version: Arc::from(""),
+            capabilities: Arc::new(vec![].into()),
+            status: Arc::new(Default::default()),
+            version: EthVersion::Eth68,
+            peer_kind: PeerKind::Basic,
+        };
+        let messages: PeerRequestSender<PeerRequest> = PeerRequestSender::new(peer_id, tx);
+        tx_manager
+            .on_network_event(NetworkEvent::ActivePeerSession { info: session_info, messages });
+
+        let mut factory = MockTransactionFactory::default();
+        let mut new_txs = || {
+            (0..2)
+                .map(|_| PropagateTransaction::pool_tx(Arc::new(factory.create_eip1559())))
+                .collect::<Vec<_>>()
+        };
+        let num_full = |propagated: &PropagatedTransactions| {
+            propagated.0.values().flatten().filter(|kind| kind.is_full()).count()
+        };
+
+        // the only peer receives all transactions in full
+        let propagated = tx_manager.propagate_transactions(new_txs(), PropagationMode::Basic);
+        assert_eq!(propagated.0.len(), 2);
+        assert_eq!(num_full(&propagated), 2);
+
+        // only the first transaction fits into the limit of full transactions
+        let handle = tx_manager.handle();
+        let txs = new_txs();
+        handle.set_propagation_policy(
+            TransactionBroadcastPolicy::default().with_max_full_transactions_size(txs[0].size),
+        );
+        let cmd = tx_manager.command_rx.next().await.unwrap();
+        tx_manager.on_command(cmd);
+
+        let propagated = tx_manager.propagate_transactions(txs, PropagationMode::Basic);
+        assert_eq!(propagated.0.len(), 2);
+        assert_eq!(num_full(&propagated), 1);
+
+        // no peer receives transactions in full
+        handle.set_propagation_policy(TransactionBroadcastPolicy::new(
+            TransactionPropagationMode::Max(0),
+        ));
+        let cmd = tx_manager.command_rx.next().await.unwrap();
+        tx_manager.on_command(cmd);
+
+        let propagated = tx_manager.propagate_transactions(new_txs(), PropagationMode::Basic);
+        assert_eq!(propagated.0.len(), 2);
+        assert_eq!(num_full(&propagated), 0);
+    }
+
     #[tokio::test]
     async fn test_relaxed_filter_ignores_unknown_tx_types() {
         reth_tracing::init_test_tracing();
@@ -4,7 +4,9 @@ use alloy_primitives::map::HashSet;
 use futures::StreamExt;
 use reth_chainspec::{MAINNET, SEPOLIA};
 use reth_discv4::Discv4Config;
-use reth_eth_wire::{DisconnectReason, EthNetworkPrimitives, HeadersDirection};
+use reth_eth_wire::{
+    Capability, DisconnectReason, EthNetworkPrimitives, EthVersion, HeadersDirection,
+};
 use reth_network::{
     test_utils::{NetworkEventStream, PeerConfig, Testnet},
     BlockDownloaderProvider, NetworkConfigBuilder, NetworkEvent, NetworkEventListenerProvider,
@@ -199,6 +201,37 @@ async fn test_get_peer_by_id() {
     assert!(peer.is_none());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_peer_capabilities() {
+    reth_tracing::init_test_tracing();
+    let net = Testnet::create(3).await;
+
+    let mut handles = net.handles();
+    let handle0 = handles.next().unwrap();
+    let handle1 = handles.next().unwrap();
+    let handle2 = handles.next().unwrap();
+
+    drop(handles);
+    let _handle = net.spawn();
+
+    let mut listener0 = NetworkEventStream::new(handle0.event_listener());
+
+    handle0.add_peer(*handle1.peer_id(), handle1.local_addr());
+    let _ = listener0.next_session_established().await.unwrap();
+
+    // both sides report the negotiated eth version
+    let peer = handle0.get_peer_by_id(*handle1.peer_id()).await.unwrap().unwrap();
+    let remote = handle1.get_peer_by_id(*handle0.peer_id()).await.unwrap().unwrap();
+    assert_eq!(peer.eth_version, remote.eth_version);
+    assert_eq!(peer.eth_version, EthVersion::LATEST);
+    assert!(peer.capabilities.capabilities().contains(&Capability::eth(peer.eth_version)));
+
+    let capabilities = handle0.peer_capabilities(*handle1.peer_id()).await.unwrap();
+    assert_eq!(capabilities, peer.capabilities);
+
+    assert!(handle0.peer_capabilities(*handle2.peer_id()).await.is_none());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_connect_with_boot_nodes() {
@@ -699,6 +732,78 @@ async fn test_disconnect_then_connect() {
     assert_eq!(peer, *handle1.peer_id());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_set_trusted_only() {
+    reth_tracing::init_test_tracing();
+
+    let net = Testnet::create(3).await;
+
+    let mut handles = net.handles();
+    let handle0 = handles.next().unwrap();
+    let untrusted = handles.next().unwrap();
+    let trusted = handles.next().unwrap();
+
+    drop(handles);
+    let _handle = net.spawn();
+
+    let mut listener0 = NetworkEventStream::new(handle0.event_listener());
+    handle0.add_peer(*untrusted.peer_id(), untrusted.local_addr());
+    handle0.add_trusted_peer(*trusted.peer_id(), trusted.local_addr());
+    let established: HashSet<_> = listener0.take_session_established(2).await.into_iter().collect();
+    assert_eq!(established, HashSet::from_iter([*untrusted.peer_id(), *trusted.peer_id()]));
+    assert!(!handle0.network_status().await.unwrap().trusted_nodes_only);
+
+    handle0.set_trusted_only(true);
+
+    // only the session with the untrusted peer is terminated
+    let (peer, _) = listener0.next_session_closed().await.unwrap();
+    assert_eq!(peer, *untrusted.peer_id());
+    assert_eq!(handle0.num_connected_peers(), 1);
+    assert!(handle0.network_status().await.unwrap().trusted_nodes_only);
+
+    handle0.set_trusted_only(false);
+    assert!(!handle0.network_status().await.unwrap().trusted_nodes_only);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_ban_ip() {
+    reth_tracing::init_test_tracing();
+
+    let net = Testnet::create(2).await;
+
+    let mut handles = net.handles();
+    let handle0 = handles.next().unwrap();
+    let handle1 = handles.next().unwrap();
+
+    drop(handles);
+    let _handle = net.spawn();
+
+    let mut listener0 = NetworkEventStream::new(handle0.event_listener());
+    handle1.add_peer(*handle0.peer_id(), handle0.local_addr());
+    let peer = listener0.next_session_established().await.unwrap();
+    assert_eq!(peer, *handle1.peer_id());
+
+    let ip = handle0.get_peer_by_id(peer).await.unwrap().unwrap().remote_addr.ip();
+    handle0.ban_ip(ip, None);
+
+    // the active session from the banned ip is terminated
+    let (peer, _) = listener0.next_session_closed().await.unwrap();
+    assert_eq!(peer, *handle1.peer_id());
+
+    // neither incoming connections from nor dials to the banned ip succeed
+    handle1.connect_peer(*handle0.peer_id(), handle0.local_addr());
+    handle0.connect_peer(*handle1.peer_id(), handle1.local_addr());
+    let established =
+        tokio::time::timeout(Duration::from_secs(1), listener0.next_session_established()).await;
+    assert!(established.is_err());
+    assert_eq!(handle0.num_connected_peers(), 0);
+
+    handle0.unban_ip(ip);
+    handle0.connect_peer(*handle1.peer_id(), handle1.local_addr());
+    let peer = listener0.next_session_established().await.unwrap();
+    assert_eq!(peer, *handle1.peer_id());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_connect_peer_in_different_network_should_fail() {
     reth_tracing::init_test_tracing();
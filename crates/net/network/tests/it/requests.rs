@@ -12,6 +12,7 @@ use reth_network::{
 use reth_network_api::{NetworkInfo, Peers};
 use reth_network_p2p::{
     bodies::client::BodiesClient,
+    error::RequestError,
     headers::client::{HeadersClient, HeadersRequest},
 };
 use reth_provider::test_utils::MockEthProvider;
@@ -526,3 +527,75 @@ async fn test_eth69_get_receipts() {
         assert_eq!(receipts_response.0[0][1].cumulative_gas_used, 42000);
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_receipts_by_range() {
+    reth_tracing::init_test_tracing();
+    let mock_provider = Arc::new(MockEthProvider::default());
+
+    let mut net = Testnet::create_with(2, mock_provider.clone()).await;
+
+    // install request handlers, this announces the receipts range protocol
+    net.for_each_mut(|peer| peer.install_request_handler());
+
+    let handle0 = net.peers()[0].handle();
+    let mut events0 = NetworkEventStream::new(handle0.event_listener());
+
+    let handle1 = net.peers()[1].handle();
+
+    let _handle = net.spawn();
+
+    let fetch0 = handle0.fetch_client().await.unwrap();
+
+    handle0.add_peer(*handle1.peer_id(), handle1.local_addr());
+    let connected = events0.next_session_established().await.unwrap();
+    assert_eq!(connected, *handle1.peer_id());
+
+    for block_num in 1..=3 {
+        let receipts = (1..=block_num)
+            .map(|i| reth_ethereum_primitives::Receipt {
+                cumulative_gas_used: 21000 * i,
+                success: true,
+                ..Default::default()
+            })
+            .collect();
+        mock_provider.add_receipts(block_num, receipts);
+    }
+
+    // the response stops at the first block the peer doesn't have
+    let res = fetch0.get_receipts_by_range(1, 10).await.unwrap();
+    assert_eq!(res.peer_id(), *handle1.peer_id());
+    let receipts = res.into_data();
+    assert_eq!(receipts.iter().map(Vec::len).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(receipts[2][2].cumulative_gas_used, 63000);
+
+    let receipts = fetch0.get_receipts_by_range(2, 1).await.unwrap().into_data();
+    assert_eq!(receipts.iter().map(Vec::len).collect::<Vec<_>>(), vec![2]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_receipts_by_range_unsupported() {
+    reth_tracing::init_test_tracing();
+    let mock_provider = Arc::new(MockEthProvider::default());
+
+    let mut net = Testnet::create_with(2, mock_provider.clone()).await;
+
+    // only the first peer serves requests, so the protocol isn't shared
+    net.peers_mut()[0].install_request_handler();
+
+    let handle0 = net.peers()[0].handle();
+    let mut events0 = NetworkEventStream::new(handle0.event_listener());
+
+    let handle1 = net.peers()[1].handle();
+
+    let _handle = net.spawn();
+
+    let fetch0 = handle0.fetch_client().await.unwrap();
+
+    handle0.add_peer(*handle1.peer_id(), handle1.local_addr());
+    let connected = events0.next_session_established().await.unwrap();
+    assert_eq!(connected, *handle1.peer_id());
+
+    let res = fetch0.get_receipts_by_range(1, 10).await;
+    assert_eq!(res.unwrap_err(), RequestError::UnsupportedCapability);
+}
@@ -1,7 +1,8 @@
 //! Session tests
 
+use alloy_primitives::B256;
 use futures::StreamExt;
-use reth_eth_wire::EthVersion;
+use reth_eth_wire::{BlockRangeUpdate, EthVersion};
 use reth_network::{
     test_utils::{NetworkEventStream, PeerConfig, Testnet},
     NetworkEvent, NetworkEventListenerProvider,
@@ -10,7 +11,9 @@ use reth_network_api::{
     events::{PeerEvent, SessionInfo},
     NetworkInfo, Peers,
 };
+use reth_network_p2p::sync::NetworkSyncUpdater;
 use reth_storage_api::noop::NoopProvider;
+use std::time::Duration;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_session_established_with_highest_version() {
@@ -485,3 +488,52 @@ async fn test_overlapping_version_sets_negotiation() {
 
     handle.terminate().await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_eth69_broadcast_block_range_update() {
+    reth_tracing::init_test_tracing();
+
+    let mut net = Testnet::create(0).await;
+
+    let p0 = PeerConfig::with_protocols(NoopProvider::default(), Some(EthVersion::Eth69.into()));
+    net.add_peer_with_config(p0).await.unwrap();
+
+    let p1 = PeerConfig::with_protocols(NoopProvider::default(), Some(EthVersion::Eth69.into()));
+    net.add_peer_with_config(p1).await.unwrap();
+
+    let mut handles = net.handles();
+    let handle0 = handles.next().unwrap();
+    let handle1 = handles.next().unwrap();
+    drop(handles);
+
+    let handle = net.spawn();
+
+    let mut listener1 = NetworkEventStream::new(handle1.event_listener());
+    handle0.add_peer(*handle1.peer_id(), handle1.local_addr());
+    let peer = listener1.next_session_established().await.unwrap();
+    assert_eq!(peer, *handle0.peer_id());
+
+    // the range advertised in the status is tracked
+    let info = handle1.get_peer_by_id(peer).await.unwrap().unwrap();
+    let advertised = info.block_range.unwrap();
+    assert_eq!(advertised.earliest, info.status.earliest_block.unwrap());
+    assert_eq!(advertised.latest, info.status.latest_block.unwrap());
+
+    // pruning moves the earliest block, which is broadcast right away
+    let update =
+        BlockRangeUpdate { earliest: 100, latest: 1_000, latest_hash: B256::with_last_byte(1) };
+    handle0.update_block_range(update.clone());
+
+    let received = async {
+        loop {
+            let info = handle1.get_peer_by_id(peer).await.unwrap().unwrap();
+            if info.block_range.as_ref() == Some(&update) {
+                break
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), received).await.unwrap();
+
+    handle.terminate().await;
+}
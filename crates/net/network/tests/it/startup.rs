@@ -4,12 +4,15 @@ use std::{
 };
 
 use reth_chainspec::MAINNET;
-use reth_discv4::{Discv4Config, NatResolver, DEFAULT_DISCOVERY_ADDR, DEFAULT_DISCOVERY_PORT};
+use reth_discv4::{
+    test_utils::rng_record, Discv4Config, NatResolver, DEFAULT_DISCOVERY_ADDR,
+    DEFAULT_DISCOVERY_PORT,
+};
 use reth_network::{
     error::{NetworkError, ServiceKind},
     Discovery, NetworkConfigBuilder, NetworkManager,
 };
-use reth_network_api::{NetworkInfo, PeersInfo};
+use reth_network_api::{DiscoverySource, NetworkInfo, Peers, PeersInfo};
 use reth_storage_api::noop::NoopProvider;
 use secp256k1::SecretKey;
 use tokio::net::TcpListener;
@@ -191,3 +194,25 @@ async fn test_node_record_address_with_nat_disable_discovery() {
 
     assert_eq!(record.address, IpAddr::V4(std::net::Ipv4Addr::LOCALHOST));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_discovery_nodes_include_boot_node() {
+    let secret_key = SecretKey::new(&mut rand_08::thread_rng());
+    let boot_node = rng_record(&mut rand_08::thread_rng());
+    let config = NetworkConfigBuilder::eth(secret_key)
+        .listener_port(0)
+        .discovery_port(0)
+        .disable_dns_discovery()
+        .boot_nodes([boot_node])
+        .build_with_noop_provider(MAINNET.clone());
+
+    let network = NetworkManager::new(config).await.unwrap();
+    let handle = network.handle().clone();
+    tokio::task::spawn(network);
+
+    let nodes = handle.discovery_nodes().await.unwrap();
+    let node = nodes.iter().find(|node| node.record == boot_node).expect("boot node is in table");
+    assert_eq!(node.source, DiscoverySource::Discv4);
+    assert_eq!(node.fork_id_valid, None);
+    assert!(node.last_seen.is_some());
+}
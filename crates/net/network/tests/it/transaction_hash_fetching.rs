@@ -1,7 +1,9 @@
 use alloy_primitives::U256;
 use reth_network::{
     test_utils::Testnet,
-    transactions::{TransactionPropagationMode::Max, TransactionsManagerConfig},
+    transactions::{
+        TransactionBroadcastPolicy, TransactionPropagationMode::Max, TransactionsManagerConfig,
+    },
 };
 use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
 use reth_tracing::init_test_tracing;
@@ -13,7 +15,10 @@ use tokio::time::Duration;
 async fn transaction_hash_fetching() {
     init_test_tracing();
 
-    let mut config = TransactionsManagerConfig { propagation_mode: Max(0), ..Default::default() };
+    let mut config = TransactionsManagerConfig {
+        broadcast_policy: TransactionBroadcastPolicy::new(Max(0)),
+        ..Default::default()
+    };
     config.transaction_fetcher_config.max_inflight_requests = 1;
 
     let provider = MockEthProvider::default();
@@ -1,14 +1,16 @@
 //! Testing gossiping of transactions.
 use alloy_consensus::TxLegacy;
-use alloy_primitives::{Signature, U256};
+use alloy_primitives::{Signature, B256, U256};
 use futures::StreamExt;
+use reth_eth_wire::GetPooledTransactions;
 use reth_ethereum_primitives::TransactionSigned;
 use reth_network::{
     test_utils::{NetworkEventStream, Testnet},
     transactions::config::{
         TransactionIngressPolicy, TransactionPropagationKind, TransactionsManagerConfig,
+        UnannouncedTransactionsPolicy,
     },
-    NetworkEvent, NetworkEventListenerProvider, Peers,
+    NetworkEvent, NetworkEventListenerProvider, PeerRequest, Peers,
 };
 use reth_network_api::{events::PeerEvent, PeerKind, PeersInfo};
 use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
@@ -16,7 +18,7 @@ use reth_transaction_pool::{
     test_utils::TransactionGenerator, AddedTransactionOutcome, PoolTransaction, TransactionPool,
 };
 use std::sync::Arc;
-use tokio::join;
+use tokio::{join, sync::oneshot};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_tx_gossip() {
@@ -300,3 +302,44 @@ async fn test_sending_invalid_transactions() {
     // ensure txs never made it to the pool
     assert!(tx_listener.try_recv().is_err());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unannounced_tx_request_penalization() {
+    reth_tracing::init_test_tracing();
+
+    let provider = MockEthProvider::default();
+
+    let tx_manager_config = TransactionsManagerConfig {
+        unannounced_policy: UnannouncedTransactionsPolicy::Penalize,
+        ..Default::default()
+    };
+
+    let net = Testnet::create_with(2, provider.clone()).await;
+    let net = net.with_eth_pool_config(tx_manager_config);
+
+    let handle = net.spawn();
+
+    // connect all the peers
+    handle.connect_peers().await;
+
+    let peer0 = &handle.peers()[0];
+    let peer1 = &handle.peers()[1];
+
+    let peer0_reputation_before =
+        peer1.network().reputation_by_id(*peer0.peer_id()).await.unwrap().unwrap();
+
+    // request a transaction from peer1 that was never announced to peer0
+    let (send, receive) = oneshot::channel();
+    peer0.network().send_request(
+        *peer1.peer_id(),
+        PeerRequest::GetPooledTransactions {
+            request: GetPooledTransactions(vec![B256::random()]),
+            response: send,
+        },
+    );
+    receive.await.unwrap().unwrap();
+
+    let peer0_reputation_after =
+        peer1.network().reputation_by_id(*peer0.peer_id()).await.unwrap().unwrap();
+    assert!(peer0_reputation_after < peer0_reputation_before);
+}
@@ -856,7 +856,10 @@ impl<Node: FullNodeTypes> BuilderContext<Node> {
     {
         let (handle, network, txpool, eth) = builder
             .transactions_with_policy(pool, tx_config, propagation_policy)
-            .request_handler(self.provider().clone())
+            .request_handler_with_config(
+                self.provider().clone(),
+                self.config().network.eth_request_handler_config(self.reth_config()),
+            )
             .split_with_handle();
 
         self.executor.spawn_critical_blocking("p2p txpool", Box::pin(txpool));
@@ -931,6 +934,9 @@ impl<Node: FullNodeTypes<Types: NodeTypes<ChainSpec: Hardforks>>> BuilderContext
     where
         N: NetworkPrimitives,
     {
+        // reject invalid serve limits of the config file before the network is started
+        self.config().network.eth_request_handler_config(self.reth_config()).validate()?;
+
         let secret_key = self.network_secret(&self.config().datadir())?;
         let default_peers_path = self.config().datadir().known_peers();
         let builder = self
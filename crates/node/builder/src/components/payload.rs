@@ -107,7 +107,10 @@ where
         let (payload_service, payload_service_handle) =
             PayloadBuilderService::new(payload_generator, ctx.provider().canonical_state_stream());
 
-        ctx.task_executor().spawn_critical("payload builder service", Box::pin(payload_service));
+        ctx.task_executor()
+            .spawn_critical_with_graceful_shutdown_signal("payload builder service", |shutdown| {
+                payload_service.with_graceful_shutdown(shutdown)
+            });
 
         Ok(payload_service_handle)
     }
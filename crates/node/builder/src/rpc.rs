@@ -23,7 +23,7 @@ use reth_node_core::{
     node_config::NodeConfig,
     version::{version_metadata, CLIENT_CODE},
 };
-use reth_payload_builder::{PayloadBuilderHandle, PayloadStore};
+use reth_payload_builder::{PayloadBuilderHandle, PayloadKind, PayloadStore};
 use reth_rpc::{
     eth::{core::EthRpcConverterFor, DevSigner, EthApiTypes, FullEthApiServer},
     AdminApi,
@@ -1394,7 +1394,13 @@ where
             ctx.node.provider().clone(),
             ctx.config.chain.clone(),
             ctx.beacon_engine_handle.clone(),
-            PayloadStore::new(ctx.node.payload_builder_handle().clone()),
+            PayloadStore::new(ctx.node.payload_builder_handle().clone()).with_resolve_kind(
+                if ctx.config.engine.wait_for_payload_deadline {
+                    PayloadKind::WaitForDeadline
+                } else {
+                    PayloadKind::Earliest
+                },
+            ),
             ctx.node.pool().clone(),
             Box::new(ctx.node.task_executor().clone()),
             client,
@@ -34,6 +34,7 @@ pub struct DefaultEngineValues {
     state_root_fallback: bool,
     always_process_payload_attributes_on_canonical_head: bool,
     allow_unwind_canonical_header: bool,
+    wait_for_payload_deadline: bool,
     storage_worker_count: Option<usize>,
     account_worker_count: Option<usize>,
 }
@@ -154,6 +155,12 @@ impl DefaultEngineValues {
         self
     }
 
+    /// Set whether to wait for the payload job deadline when resolving payloads by default
+    pub const fn with_wait_for_payload_deadline(mut self, v: bool) -> Self {
+        self.wait_for_payload_deadline = v;
+        self
+    }
+
     /// Set the default storage worker count
     pub const fn with_storage_worker_count(mut self, v: Option<usize>) -> Self {
         self.storage_worker_count = v;
@@ -187,6 +194,7 @@ impl Default for DefaultEngineValues {
             state_root_fallback: false,
             always_process_payload_attributes_on_canonical_head: false,
             allow_unwind_canonical_header: false,
+            wait_for_payload_deadline: false,
             storage_worker_count: None,
             account_worker_count: None,
         }
@@ -299,6 +307,13 @@ pub struct EngineArgs {
     #[arg(long = "engine.allow-unwind-canonical-header", default_value_t = DefaultEngineValues::get_global().allow_unwind_canonical_header)]
     pub allow_unwind_canonical_header: bool,
 
+    /// Wait for the payload job deadline before responding to `engine_getPayload`, so the most
+    /// profitable payload is returned instead of the best one built so far.
+    ///
+    /// Note: this delays `engine_getPayload` responses until the payload job deadline.
+    #[arg(long = "engine.wait-for-payload-deadline", default_value_t = DefaultEngineValues::get_global().wait_for_payload_deadline)]
+    pub wait_for_payload_deadline: bool,
+
     /// Configure the number of storage proof workers in the Tokio blocking pool.
     /// If not specified, defaults to 2x available parallelism, clamped between 2 and 64.
     #[arg(long = "engine.storage-worker-count", default_value = Resettable::from(DefaultEngineValues::get_global().storage_worker_count.map(|v| v.to_string().into())))]
@@ -331,6 +346,7 @@ impl Default for EngineArgs {
             state_root_fallback,
             always_process_payload_attributes_on_canonical_head,
             allow_unwind_canonical_header,
+            wait_for_payload_deadline,
             storage_worker_count,
             account_worker_count,
         } = DefaultEngineValues::get_global().clone();
@@ -355,6 +371,7 @@ impl Default for EngineArgs {
             state_root_fallback,
             always_process_payload_attributes_on_canonical_head,
             allow_unwind_canonical_header,
+            wait_for_payload_deadline,
             storage_worker_count,
             account_worker_count,
         }
@@ -439,6 +456,7 @@ mod tests {
             state_root_fallback: true,
             always_process_payload_attributes_on_canonical_head: true,
             allow_unwind_canonical_header: true,
+            wait_for_payload_deadline: true,
             storage_worker_count: Some(16),
             account_worker_count: Some(8),
         };
@@ -467,6 +485,7 @@ mod tests {
             "--engine.state-root-fallback",
             "--engine.always-process-payload-attributes-on-canonical-head",
             "--engine.allow-unwind-canonical-header",
+            "--engine.wait-for-payload-deadline",
             "--engine.storage-worker-count",
             "16",
             "--engine.account-worker-count",
@@ -21,8 +21,11 @@ use reth_discv5::{
 use reth_net_banlist::IpFilter;
 use reth_net_nat::{NatResolver, DEFAULT_NET_IF_NAME};
 use reth_network::{
+    eth_requests::{EthRequestHandlerConfig, MIN_SOFT_RESPONSE_LIMIT},
     transactions::{
-        config::{TransactionIngressPolicy, TransactionPropagationKind},
+        config::{
+            TransactionIngressPolicy, TransactionPropagationKind, UnannouncedTransactionsPolicy,
+        },
         constants::{
             tx_fetcher::{
                 DEFAULT_MAX_CAPACITY_CACHE_PENDING_FETCH, DEFAULT_MAX_COUNT_CONCURRENT_REQUESTS,
@@ -32,7 +35,8 @@ use reth_network::{
                 DEFAULT_MAX_COUNT_PENDING_POOL_IMPORTS, DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
             },
         },
-        TransactionFetcherConfig, TransactionPropagationMode, TransactionsManagerConfig,
+        TransactionBroadcastPolicy, TransactionFetcherConfig, TransactionPropagationMode,
+        TransactionsManagerConfig,
         DEFAULT_SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESP_ON_PACK_GET_POOLED_TRANSACTIONS_REQ,
         SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESPONSE,
     },
@@ -99,7 +103,7 @@ pub struct NetworkArgs {
     #[arg(long, verbatim_doc_comment)]
     pub no_persist_peers: bool,
 
-    /// NAT resolution method (any|none|upnp|publicip|extip:\<IP\>)
+    /// NAT resolution method (any|none|upnp|natpmp|publicip|extip:\<IP\>)
     #[arg(long, default_value = "any")]
     pub nat: NatResolver,
 
@@ -173,6 +177,38 @@ pub struct NetworkArgs {
     #[arg(long = "max-tx-pending-fetch", value_name = "COUNT", default_value_t = DEFAULT_MAX_CAPACITY_CACHE_PENDING_FETCH, verbatim_doc_comment)]
     pub max_capacity_cache_txns_pending_fetch: u32,
 
+    /// Max number of headers to serve in one response to a peer.
+    ///
+    /// Default is 1024.
+    #[arg(long = "max-headers-serve", value_name = "COUNT", value_parser = parse_serve_limit, verbatim_doc_comment)]
+    pub max_headers_serve: Option<usize>,
+
+    /// Max number of block bodies to serve in one response to a peer.
+    ///
+    /// Default is 1024.
+    #[arg(long = "max-bodies-serve", value_name = "COUNT", value_parser = parse_serve_limit, verbatim_doc_comment)]
+    pub max_bodies_serve: Option<usize>,
+
+    /// Max number of blocks to serve receipts of in one response to a peer.
+    ///
+    /// Default is 1024.
+    #[arg(long = "max-receipts-serve", value_name = "COUNT", value_parser = parse_serve_limit, verbatim_doc_comment)]
+    pub max_receipts_serve: Option<usize>,
+
+    /// Sets the max accumulated byte size of headers, bodies, receipts or node data to serve in
+    /// one response to a peer. Must be at least 64 KiB.
+    ///
+    /// Default is 2 MiB.
+    #[arg(long = "eth-response-soft-limit", value_name = "BYTES", value_parser = parse_eth_response_soft_limit, verbatim_doc_comment)]
+    pub eth_response_soft_limit: Option<usize>,
+
+    /// Max number of response bytes to serve to a single peer per second. Once used up, further
+    /// requests of the peer are answered with empty responses.
+    ///
+    /// Unlimited by default.
+    #[arg(long = "max-serve-bytes-per-peer", value_name = "BYTES", value_parser = parse_serve_limit, verbatim_doc_comment)]
+    pub max_serve_bytes_per_peer: Option<usize>,
+
     /// Name of network interface used to communicate with peers.
     ///
     /// If flag is set, but no value is passed, the default interface for docker `eth0` is tried.
@@ -191,6 +227,13 @@ pub struct NetworkArgs {
     #[arg(long = "tx-ingress-policy", default_value_t = TransactionIngressPolicy::All)]
     pub tx_ingress_policy: TransactionIngressPolicy,
 
+    /// Unannounced transactions policy
+    ///
+    /// Determines how requests for pooled transactions that weren't announced to the requesting
+    /// peer are answered: served, served while penalizing the peer, or omitted.
+    #[arg(long = "tx-unannounced-policy", default_value_t = UnannouncedTransactionsPolicy::Serve)]
+    pub tx_unannounced_policy: UnannouncedTransactionsPolicy,
+
     /// Disable transaction pool gossip
     ///
     /// Disables gossiping of transactions in the mempool to peers. This can be omitted for
@@ -285,6 +328,36 @@ impl NetworkArgs {
         }
     }
 
+    /// Configures and returns an `EthRequestHandlerConfig` based on the current settings.
+    ///
+    /// Limits that aren't set on the command line are taken from the `[network]` section of the
+    /// given [`Config`], if set there.
+    pub fn eth_request_handler_config(&self, config: &Config) -> EthRequestHandlerConfig {
+        let defaults = EthRequestHandlerConfig::default();
+        EthRequestHandlerConfig {
+            max_receipts: self
+                .max_receipts_serve
+                .or(config.network.max_receipts_serve)
+                .unwrap_or(defaults.max_receipts),
+            max_headers: self
+                .max_headers_serve
+                .or(config.network.max_headers_serve)
+                .unwrap_or(defaults.max_headers),
+            max_bodies: self
+                .max_bodies_serve
+                .or(config.network.max_bodies_serve)
+                .unwrap_or(defaults.max_bodies),
+            soft_response_limit: self
+                .eth_response_soft_limit
+                .or(config.network.eth_response_soft_limit)
+                .unwrap_or(defaults.soft_response_limit),
+            max_bytes_per_peer_per_second: self
+                .max_serve_bytes_per_peer
+                .or(config.network.max_serve_bytes_per_peer),
+            ..defaults
+        }
+    }
+
     /// Configures and returns a `TransactionsManagerConfig` based on the current settings.
     pub const fn transactions_manager_config(&self) -> TransactionsManagerConfig {
         TransactionsManagerConfig {
@@ -296,8 +369,9 @@ impl NetworkArgs {
                 self.max_capacity_cache_txns_pending_fetch,
             ),
             max_transactions_seen_by_peer_history: self.max_seen_tx_history,
-            propagation_mode: self.propagation_mode,
+            broadcast_policy: TransactionBroadcastPolicy::new(self.propagation_mode),
             ingress_policy: self.tx_ingress_policy,
+            unannounced_policy: self.tx_unannounced_policy,
         }
     }
 
@@ -483,9 +557,15 @@ impl Default for NetworkArgs {
             max_pending_pool_imports: DEFAULT_MAX_COUNT_PENDING_POOL_IMPORTS,
             max_seen_tx_history: DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
             max_capacity_cache_txns_pending_fetch: DEFAULT_MAX_CAPACITY_CACHE_PENDING_FETCH,
+            max_headers_serve: None,
+            max_bodies_serve: None,
+            max_receipts_serve: None,
+            eth_response_soft_limit: None,
+            max_serve_bytes_per_peer: None,
             net_if: None,
             tx_propagation_policy: TransactionPropagationKind::default(),
             tx_ingress_policy: TransactionIngressPolicy::default(),
+            tx_unannounced_policy: UnannouncedTransactionsPolicy::default(),
             disable_tx_gossip: false,
             propagation_mode: TransactionPropagationMode::Sqrt,
             required_block_hashes: vec![],
@@ -709,6 +789,24 @@ fn parse_block_num_hash(s: &str) -> Result<BlockNumHash, String> {
     }
 }
 
+/// Parses a limit for serving eth requests, which must be non-zero.
+fn parse_serve_limit(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>().map_err(|err| err.to_string())? {
+        0 => Err("must be greater than zero".to_string()),
+        limit => Ok(limit),
+    }
+}
+
+/// Parses the soft response limit for serving eth requests, which must be at least
+/// [`MIN_SOFT_RESPONSE_LIMIT`].
+fn parse_eth_response_soft_limit(s: &str) -> Result<usize, String> {
+    let limit = s.parse::<usize>().map_err(|err| err.to_string())?;
+    if limit < MIN_SOFT_RESPONSE_LIMIT {
+        return Err(format!("must be at least {MIN_SOFT_RESPONSE_LIMIT} bytes"))
+    }
+    Ok(limit)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -734,11 +832,71 @@ mod tests {
         let args = CommandParser::<NetworkArgs>::parse_from(["reth", "--nat", "none"]).args;
         assert_eq!(args.nat, NatResolver::None);
 
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth", "--nat", "natpmp"]).args;
+        assert_eq!(args.nat, NatResolver::NatPmp);
+
         let args =
             CommandParser::<NetworkArgs>::parse_from(["reth", "--nat", "extip:0.0.0.0"]).args;
         assert_eq!(args.nat, NatResolver::ExternalIp("0.0.0.0".parse().unwrap()));
     }
 
+    #[test]
+    fn parse_eth_request_handler_args() {
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth"]).args;
+        assert_eq!(
+            args.eth_request_handler_config(&Config::default()),
+            EthRequestHandlerConfig::default()
+        );
+
+        let args = CommandParser::<NetworkArgs>::parse_from([
+            "reth",
+            "--max-headers-serve",
+            "128",
+            "--eth-response-soft-limit",
+            "65536",
+        ])
+        .args;
+        let config = args.eth_request_handler_config(&Config::default());
+        assert_eq!(config.max_headers, 128);
+        assert_eq!(config.soft_response_limit, 65536);
+        assert_eq!(config.max_bytes_per_peer_per_second, None);
+
+        // limits that aren't set on the command line are taken from the config file
+        let mut reth_config = Config::default();
+        reth_config.network.max_headers_serve = Some(256);
+        reth_config.network.max_bodies_serve = Some(256);
+        reth_config.network.max_serve_bytes_per_peer = Some(1 << 20);
+        let config = args.eth_request_handler_config(&reth_config);
+        assert_eq!(config.max_headers, 128);
+        assert_eq!(config.max_bodies, 256);
+        assert_eq!(config.max_receipts, EthRequestHandlerConfig::default().max_receipts);
+        assert_eq!(config.max_bytes_per_peer_per_second, Some(1 << 20));
+
+        let args = CommandParser::<NetworkArgs>::parse_from([
+            "reth",
+            "--max-serve-bytes-per-peer",
+            "4096",
+        ])
+        .args;
+        let config = args.eth_request_handler_config(&reth_config);
+        assert_eq!(config.max_bytes_per_peer_per_second, Some(4096));
+
+        assert!(CommandParser::<NetworkArgs>::try_parse_from(["reth", "--max-bodies-serve", "0"])
+            .is_err());
+        assert!(CommandParser::<NetworkArgs>::try_parse_from([
+            "reth",
+            "--max-serve-bytes-per-peer",
+            "0"
+        ])
+        .is_err());
+        assert!(CommandParser::<NetworkArgs>::try_parse_from([
+            "reth",
+            "--eth-response-soft-limit",
+            "65535"
+        ])
+        .is_err());
+    }
+
     #[test]
     fn parse_peer_args() {
         let args =
@@ -97,7 +97,7 @@ use reth_node_api::{
 use reth_node_builder::rpc::{EngineApiBuilder, PayloadValidatorBuilder};
 use reth_node_core::version::{version_metadata, CLIENT_CODE};
 use reth_optimism_rpc::engine::OP_ENGINE_CAPABILITIES;
-use reth_payload_builder::PayloadStore;
+use reth_payload_builder::{PayloadKind, PayloadStore};
 use reth_rpc_engine_api::{EngineApi, EngineCapabilities};
 
 /// Builder for basic [`OpEngineApi`] implementation.
@@ -139,7 +139,13 @@ where
             ctx.node.provider().clone(),
             ctx.config.chain.clone(),
             ctx.beacon_engine_handle.clone(),
-            PayloadStore::new(ctx.node.payload_builder_handle().clone()),
+            PayloadStore::new(ctx.node.payload_builder_handle().clone()).with_resolve_kind(
+                if ctx.config.engine.wait_for_payload_deadline {
+                    PayloadKind::WaitForDeadline
+                } else {
+                    PayloadKind::Earliest
+                },
+            ),
             ctx.node.pool().clone(),
             Box::new(ctx.node.task_executor().clone()),
             client,
@@ -219,6 +219,10 @@ impl<N: NodePrimitives> OpBuiltPayload<N> {
 impl<N: NodePrimitives> BuiltPayload for OpBuiltPayload<N> {
     type Primitives = N;
 
+    fn payload_id(&self) -> Option<PayloadId> {
+        Some(self.id)
+    }
+
     fn block(&self) -> &SealedBlock<N::Block> {
         self.block()
     }
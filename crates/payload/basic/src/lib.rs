@@ -9,12 +9,16 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 use crate::metrics::PayloadBuilderMetrics;
+use alloy_consensus::BlockHeader;
 use alloy_eips::merge::SLOT_DURATION;
 use alloy_primitives::{B256, U256};
 use futures_core::ready;
 use futures_util::FutureExt;
 use reth_chain_state::CanonStateNotification;
-use reth_payload_builder::{KeepPayloadJobAlive, PayloadId, PayloadJob, PayloadJobGenerator};
+use reth_payload_builder::{
+    KeepPayloadJobAlive, PayloadId, PayloadJob, PayloadJobGenerator, PayloadJobStats,
+    PayloadSummary,
+};
 use reth_payload_builder_primitives::PayloadBuilderError;
 use reth_payload_primitives::{BuiltPayload, PayloadBuilderAttributes, PayloadKind};
 use reth_primitives_traits::{HeaderTy, NodePrimitives, SealedHeader};
@@ -28,7 +32,7 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tokio::{
     sync::{oneshot, Semaphore},
@@ -165,6 +169,15 @@ where
 
         let cached_reads = self.maybe_pre_cached(parent_header.hash());
 
+        // build the empty payload right away, so there's a payload that can be delivered before
+        // the first build finished, but don't block the payload builder service with it
+        let (tx, pending_pre_built_payload) = oneshot::channel();
+        let empty_config = config.clone();
+        let builder = self.builder.clone();
+        self.executor.spawn_blocking(Box::pin(async move {
+            let _ = tx.send(builder.build_empty_payload(empty_config));
+        }));
+
         let mut job = BasicPayloadJob {
             config,
             executor: self.executor.clone(),
@@ -172,11 +185,17 @@ where
             // ticks immediately
             interval: tokio::time::interval(self.config.interval),
             best_payload: PayloadState::Missing,
+            pre_built_payload: None,
+            pending_pre_built_payload: Some(pending_pre_built_payload),
             pending_block: None,
             cached_reads,
             payload_task_guard: self.payload_task_guard.clone(),
             metrics: Default::default(),
             builder: self.builder.clone(),
+            iterations: 0,
+            started_at: Instant::now(),
+            time_to_first_build: None,
+            deadline_waiters: Vec::new(),
         };
 
         // start the first job right away
@@ -313,6 +332,13 @@ where
     interval: Interval,
     /// The best payload so far and its state.
     best_payload: PayloadState<Builder::BuiltPayload>,
+    /// The empty payload that was built when the job was created.
+    ///
+    /// This is delivered if no better payload has been built yet.
+    pre_built_payload: Option<Builder::BuiltPayload>,
+    /// Receiver for the empty payload until it has been built.
+    pending_pre_built_payload:
+        Option<oneshot::Receiver<Result<Builder::BuiltPayload, PayloadBuilderError>>>,
     /// Receiver for the block that is currently being built.
     pending_block: Option<PendingPayload<Builder::BuiltPayload>>,
     /// Restricts how many generator tasks can be executed at once.
@@ -328,6 +354,15 @@ where
     ///
     /// See [`PayloadBuilder`]
     builder: Builder,
+    /// Number of payload build attempts that have completed.
+    iterations: u64,
+    /// When this job was created.
+    started_at: Instant,
+    /// Time it took to build the first payload, `None` until a payload has been built.
+    time_to_first_build: Option<Duration>,
+    /// Pending [`PayloadKind::WaitForDeadline`] resolves that receive the best payload once the
+    /// deadline is reached.
+    deadline_waiters: Vec<oneshot::Sender<Result<Builder::BuiltPayload, PayloadBuilderError>>>,
 }
 
 impl<Tasks, Builder> BasicPayloadJob<Tasks, Builder>
@@ -360,6 +395,19 @@ where
 
         self.pending_block = Some(PendingPayload { _cancel, payload: rx });
     }
+
+    /// Sends the best payload to all resolves that are waiting for the deadline.
+    fn resolve_deadline_waiters(&mut self) {
+        let waiters = std::mem::take(&mut self.deadline_waiters);
+        if waiters.is_empty() {
+            return
+        }
+        let best_payload =
+            self.best_payload().or_else(|err| self.pre_built_payload.clone().ok_or(err));
+        for tx in waiters {
+            let _ = tx.send(best_payload.clone());
+        }
+    }
 }
 
 impl<Tasks, Builder> Future for BasicPayloadJob<Tasks, Builder>
@@ -374,9 +422,23 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
 
+        // check if the empty payload is ready
+        if let Some(rx) = &mut this.pending_pre_built_payload &&
+            let Poll::Ready(res) = rx.poll_unpin(cx)
+        {
+            this.pending_pre_built_payload = None;
+            match res.map_err(Into::into).and_then(|res| res) {
+                Ok(payload) => this.pre_built_payload = Some(payload),
+                Err(err) => {
+                    debug!(target: "payload_builder", id = %this.config.payload_id(), %err, "failed to pre-build empty payload");
+                }
+            }
+        }
+
         // check if the deadline is reached
         if this.deadline.as_mut().poll(cx).is_ready() {
             trace!(target: "payload_builder", "payload building deadline reached");
+            this.resolve_deadline_waiters();
             return Poll::Ready(Ok(()))
         }
 
@@ -391,7 +453,15 @@ where
 
         // poll the pending block
         if let Some(mut fut) = this.pending_block.take() {
-            match fut.poll_unpin(cx) {
+            let res = fut.poll_unpin(cx);
+            if res.is_ready() {
+                this.iterations += 1;
+            }
+            if matches!(res, Poll::Ready(Ok(BuildOutcome::Better { .. } | BuildOutcome::Freeze(_))))
+            {
+                this.time_to_first_build.get_or_insert_with(|| this.started_at.elapsed());
+            }
+            match res {
                 Poll::Ready(Ok(outcome)) => match outcome {
                     BuildOutcome::Better { payload, cached_reads } => {
                         this.cached_reads = Some(cached_reads);
@@ -437,20 +507,22 @@ where
     type BuiltPayload = Builder::BuiltPayload;
 
     fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError> {
-        if let Some(payload) = self.best_payload.payload() {
-            Ok(payload.clone())
-        } else {
-            // No payload has been built yet, but we need to return something that the CL then
-            // can deliver, so we need to return an empty payload.
-            //
-            // Note: it is assumed that this is unlikely to happen, as the payload job is
-            // started right away and the first full block should have been
-            // built by the time CL is requesting the payload.
-            self.metrics.inc_requested_empty_payload();
-            self.builder.build_empty_payload(self.config.clone())
+        // This is called on the payload builder service's task, so no payload is built here. Until
+        // the first build finished, the service falls back to the pre-built payload.
+        self.best_payload.payload().cloned().ok_or(PayloadBuilderError::MissingPayload)
+    }
+
+    fn best_payload_summary(&self) -> Result<PayloadSummary, PayloadBuilderError> {
+        match self.best_payload.payload().or(self.pre_built_payload.as_ref()) {
+            Some(payload) => Ok(PayloadSummary::new(payload)),
+            None => Err(PayloadBuilderError::MissingPayload),
         }
     }
 
+    fn pre_built_payload(&self) -> Option<Self::BuiltPayload> {
+        self.pre_built_payload.clone()
+    }
+
     fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError> {
         Ok(self.config.attributes.clone())
     }
@@ -463,6 +535,22 @@ where
         &mut self,
         kind: PayloadKind,
     ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+        if kind == PayloadKind::WaitForDeadline && !self.deadline.is_elapsed() {
+            debug!(target: "payload_builder", id=%self.config.payload_id(), "waiting for payload job deadline");
+            let (tx, rx) = oneshot::channel();
+            self.deadline_waiters.push(tx);
+            let fut = ResolveBestPayload {
+                best_payload: None,
+                maybe_better: None,
+                empty_payload: None,
+                deadline_payload: Some(rx),
+            };
+            return (fut, KeepPayloadJobAlive::Yes)
+        }
+
+        // this job is terminated, so anyone waiting for the deadline is served right away
+        self.resolve_deadline_waiters();
+
         let best_payload = self.best_payload.payload().cloned();
         if best_payload.is_none() && self.pending_block.is_none() {
             // ensure we have a job scheduled if we don't have a best payload yet and none is active
@@ -518,10 +606,35 @@ where
             best_payload,
             maybe_better,
             empty_payload: empty_payload.filter(|_| kind != PayloadKind::WaitForPending),
+            deadline_payload: None,
         };
 
         (fut, KeepPayloadJobAlive::No)
     }
+
+    fn stats(&self) -> Option<PayloadJobStats> {
+        let best_payload = self.best_payload.payload();
+        Some(PayloadJobStats {
+            iterations: self.iterations,
+            best_fees: best_payload.map(|payload| payload.fees()).unwrap_or_default(),
+            best_gas_used: best_payload
+                .map(|payload| payload.block().gas_used())
+                .unwrap_or_default(),
+            elapsed: self.started_at.elapsed(),
+            time_to_first_build: self.time_to_first_build,
+        })
+    }
+
+    fn extend_deadline(&mut self, extension: Duration) -> bool {
+        if self.deadline.is_elapsed() {
+            return false
+        }
+
+        let deadline = self.deadline.deadline() + extension;
+        self.deadline.as_mut().reset(deadline);
+        trace!(target: "payload_builder", id = %self.config.payload_id(), ?extension, "extended payload job deadline");
+        true
+    }
 }
 
 /// Represents the current state of a payload being built.
@@ -561,6 +674,9 @@ impl<P> PayloadState<P> {
 ///
 /// If no payload has been built so far, it will either return an empty payload or the result of the
 /// in progress build job, whatever finishes first.
+///
+/// If the payload was requested with [`PayloadKind::WaitForDeadline`], this only resolves once the
+/// job reached its deadline.
 #[derive(Debug)]
 pub struct ResolveBestPayload<Payload> {
     /// Best payload so far.
@@ -569,11 +685,16 @@ pub struct ResolveBestPayload<Payload> {
     pub maybe_better: Option<PendingPayload<Payload>>,
     /// The empty payload building job in progress, if any.
     pub empty_payload: Option<oneshot::Receiver<Result<Payload, PayloadBuilderError>>>,
+    /// The best payload of the job that is delivered once its deadline is reached, if any.
+    pub deadline_payload: Option<oneshot::Receiver<Result<Payload, PayloadBuilderError>>>,
 }
 
 impl<Payload> ResolveBestPayload<Payload> {
     const fn is_empty(&self) -> bool {
-        self.best_payload.is_none() && self.maybe_better.is_none() && self.empty_payload.is_none()
+        self.best_payload.is_none() &&
+            self.maybe_better.is_none() &&
+            self.empty_payload.is_none() &&
+            self.deadline_payload.is_none()
     }
 }
 
@@ -586,6 +707,14 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
 
+        // wait for the payload that is delivered once the job's deadline is reached
+        if let Some(fut) = Pin::new(&mut this.deadline_payload).as_pin_mut() {
+            let res = ready!(fut.poll(cx));
+            this.deadline_payload = None;
+            debug!(target: "payload_builder", "resolving payload at deadline");
+            return Poll::Ready(res.map_err(Into::into).and_then(|res| res))
+        }
+
         // check if there is a better payload before returning the best payload
         if let Some(fut) = Pin::new(&mut this.maybe_better).as_pin_mut() &&
             let Poll::Ready(res) = fut.poll(cx)
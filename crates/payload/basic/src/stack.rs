@@ -155,6 +155,13 @@ where
 {
     type Primitives = L::Primitives;
 
+    fn payload_id(&self) -> Option<PayloadId> {
+        match self {
+            Self::Left(l) => l.payload_id(),
+            Self::Right(r) => r.payload_id(),
+        }
+    }
+
     fn block(&self) -> &SealedBlock<<L::Primitives as NodePrimitives>::Block> {
         match self {
             Self::Left(l) => l.block(),
@@ -1,5 +1,9 @@
-use reth_payload_primitives::PayloadTypes;
+use crate::metrics::PayloadEventsMetrics;
+use alloy_primitives::B256;
+use alloy_rpc_types_engine::PayloadId;
+use reth_payload_primitives::{BuiltPayload, PayloadBuilderAttributes, PayloadTypes};
 use std::{
+    collections::VecDeque,
     pin::Pin,
     task::{ready, Context, Poll},
 };
@@ -16,10 +20,71 @@ pub enum Events<T: PayloadTypes> {
     /// The payload attributes as
     /// they are received from the CL through the engine api.
     Attributes(T::PayloadBuilderAttributes),
+    /// The payload attributes that replaced the attributes of an existing payload job with the
+    /// same payload id.
+    AttributesReplaced(T::PayloadBuilderAttributes),
     /// The built payload that has been just built.
     /// Triggered by the CL whenever it asks for an execution payload.
     /// This event is only thrown if the CL is a validator.
     BuiltPayload(T::BuiltPayload),
+    /// The payload job with the given id has been resolved and dropped.
+    ///
+    /// This follows the [`Events::BuiltPayload`] of the job, if it resolved successfully. Jobs
+    /// that are kept alive after being resolved don't emit this event.
+    Resolved(PayloadId),
+    /// A payload job has built a payload with higher fees than the previously broadcast one.
+    ///
+    /// Unlike [`Events::BuiltPayload`], this is emitted while the job is still running.
+    BetterPayload(T::BuiltPayload),
+    /// The payload job with the given id failed and has been dropped.
+    JobFailed {
+        /// The identifier of the failed payload job.
+        id: PayloadId,
+        /// The error the job failed with.
+        error: String,
+    },
+    /// The payload job with the given id has been cancelled and dropped before it was resolved.
+    Cancelled(PayloadId),
+    /// The payload job with the given id finished on its own and has been dropped before it was
+    /// resolved.
+    Finished(PayloadId),
+    /// The payload job with the given id has been dropped because its parent block is no longer
+    /// canonical.
+    Invalidated(PayloadId),
+}
+
+impl<T: PayloadTypes> Events<T> {
+    /// Returns the identifier of the payload this event belongs to.
+    ///
+    /// This is `None` for built payloads that don't report their payload id, see
+    /// [`BuiltPayload::payload_id`].
+    pub fn payload_id(&self) -> Option<PayloadId> {
+        match self {
+            Self::Attributes(attr) | Self::AttributesReplaced(attr) => Some(attr.payload_id()),
+            Self::BuiltPayload(payload) | Self::BetterPayload(payload) => payload.payload_id(),
+            Self::Resolved(id) |
+            Self::JobFailed { id, .. } |
+            Self::Cancelled(id) |
+            Self::Finished(id) |
+            Self::Invalidated(id) => Some(*id),
+        }
+    }
+
+    /// Returns `true` if no further events are emitted for the payload after this event, because
+    /// its job has been dropped.
+    ///
+    /// [`Events::BuiltPayload`] is not final, because the job may be kept alive after it has been
+    /// resolved.
+    pub const fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Self::Resolved(_) |
+                Self::JobFailed { .. } |
+                Self::Cancelled(_) |
+                Self::Finished(_) |
+                Self::Invalidated(_)
+        )
+    }
 }
 
 /// Represents a receiver for various payload events.
@@ -34,20 +99,217 @@ impl<T: PayloadTypes> PayloadEvents<T> {
     pub fn into_stream(self) -> BroadcastStream<Events<T>> {
         BroadcastStream::new(self.receiver)
     }
+
+    /// Convert this receiver into a stream of [`PayloadEvent`]s.
+    ///
+    /// Unlike [`Self::into_stream`], this surfaces events that were dropped because the subscriber
+    /// fell behind as [`PayloadEvent::Lagged`], so that consumers can resynchronize.
+    pub fn into_event_stream(self) -> PayloadEventStream<T> {
+        PayloadEventStream { st: self.into_stream(), lagged: 0, metrics: Default::default() }
+    }
+
     /// Asynchronously receives the next payload event.
     pub async fn recv(self) -> Option<Result<Events<T>, BroadcastStreamRecvError>> {
         let mut event_stream = self.into_stream();
         event_stream.next().await
     }
 
+    /// Returns a new stream that only yields the events of the payload with the given identifier.
+    ///
+    /// Like [`Self::into_event_stream`], events that were dropped because the subscriber fell
+    /// behind are surfaced as [`PayloadEvent::Lagged`].
+    pub fn into_payload_id_stream(self, id: PayloadId) -> PayloadIdEventStream<T> {
+        PayloadIdEventStream { st: self.into_stream(), id, lagged: 0, metrics: Default::default() }
+    }
+
     /// Returns a new stream that yields all built payloads.
     pub fn into_built_payload_stream(self) -> BuiltPayloadStream<T> {
-        BuiltPayloadStream { st: self.into_stream() }
+        self.into_built_payload_stream_with_replay(Vec::new())
+    }
+
+    /// Returns a new stream that first yields the given payloads, which have been built before
+    /// subscribing, and then all payloads built from now on.
+    ///
+    /// Replayed payloads that are still received afterwards are skipped, so every payload is
+    /// yielded once. Payloads are matched by their block hash, so later payloads of the same
+    /// payload id, e.g. of a job that is kept alive, are still yielded.
+    pub fn into_built_payload_stream_with_replay(
+        self,
+        replay: Vec<T::BuiltPayload>,
+    ) -> BuiltPayloadStream<T> {
+        BuiltPayloadStream {
+            st: self.into_stream(),
+            replayed: replay.iter().map(|payload| payload.block().hash()).collect(),
+            replay: replay.into(),
+            lagged: 0,
+            metrics: Default::default(),
+        }
     }
 
     /// Returns a new stream that yields received payload attributes
     pub fn into_attributes_stream(self) -> PayloadAttributeStream<T> {
-        PayloadAttributeStream { st: self.into_stream() }
+        PayloadAttributeStream { st: self.into_stream(), lagged: 0, metrics: Default::default() }
+    }
+}
+
+/// An item yielded by the [`PayloadEventStream`].
+#[derive(Clone, Debug)]
+pub enum PayloadEvent<T: PayloadTypes> {
+    /// A payload event.
+    Event(Events<T>),
+    /// The subscriber fell behind and the given number of events were dropped.
+    Lagged(u64),
+}
+
+/// A stream of payload events that reports dropped events as [`PayloadEvent::Lagged`].
+#[derive(Debug)]
+#[pin_project::pin_project]
+pub struct PayloadEventStream<T: PayloadTypes> {
+    /// The stream of events.
+    #[pin]
+    st: BroadcastStream<Events<T>>,
+    /// Total number of events this subscriber has missed.
+    lagged: u64,
+    /// Metrics for dropped events.
+    metrics: PayloadEventsMetrics,
+}
+
+impl<T: PayloadTypes> PayloadEventStream<T> {
+    /// Returns the total number of events this subscriber has missed because it fell behind.
+    pub const fn lagged(&self) -> u64 {
+        self.lagged
+    }
+}
+
+impl<T: PayloadTypes> Stream for PayloadEventStream<T> {
+    type Item = PayloadEvent<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let item = ready!(this.st.poll_next(cx)).map(|res| match res {
+            Ok(event) => PayloadEvent::Event(event),
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                record_lagged(this.lagged, this.metrics, skipped);
+                PayloadEvent::Lagged(skipped)
+            }
+        });
+        Poll::Ready(item)
+    }
+}
+
+/// Records that a subscriber fell behind and missed the given number of events.
+fn record_lagged(lagged: &mut u64, metrics: &PayloadEventsMetrics, skipped: u64) {
+    debug!(%skipped, "payload event stream lagging behind");
+    *lagged += skipped;
+    metrics.lagged_events.increment(skipped);
+}
+
+/// A stream that yields the events of a single payload.
+#[derive(Debug)]
+#[pin_project::pin_project]
+pub struct PayloadIdEventStream<T: PayloadTypes> {
+    /// The stream of events.
+    #[pin]
+    st: BroadcastStream<Events<T>>,
+    /// The identifier of the payload to yield events for.
+    id: PayloadId,
+    /// Total number of events this subscriber has missed.
+    lagged: u64,
+    /// Metrics for dropped events.
+    metrics: PayloadEventsMetrics,
+}
+
+impl<T: PayloadTypes> PayloadIdEventStream<T> {
+    /// Returns the total number of events this subscriber has missed because it fell behind.
+    ///
+    /// Dropped events of all payloads are counted, because it's unknown which payload they
+    /// belonged to.
+    pub const fn lagged(&self) -> u64 {
+        self.lagged
+    }
+}
+
+impl<T: PayloadTypes> Stream for PayloadIdEventStream<T> {
+    type Item = PayloadEvent<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let this = self.as_mut().project();
+            return match ready!(this.st.poll_next(cx)) {
+                Some(Ok(event)) if event.payload_id() == Some(*this.id) => {
+                    Poll::Ready(Some(PayloadEvent::Event(event)))
+                }
+                Some(Ok(_)) => {
+                    // ignoring events of other payloads
+                    continue
+                }
+                Some(Err(BroadcastStreamRecvError::Lagged(skipped))) => {
+                    record_lagged(this.lagged, this.metrics, skipped);
+                    Poll::Ready(Some(PayloadEvent::Lagged(skipped)))
+                }
+                None => Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// A subscription to the events of a single payload.
+///
+/// Unlike the [`PayloadIdEventStream`], this stream terminates after the final event of the
+/// payload, see [`Events::is_final`], or right away if the payload is unknown.
+///
+/// If the subscriber falls behind, the final event may have been dropped, so the subscription
+/// terminates as well and [`PayloadSubscription::lagged`] reports the number of missed events.
+#[derive(Debug)]
+pub struct PayloadSubscription<T: PayloadTypes> {
+    /// The stream of events, `None` once the subscription terminated.
+    st: Option<PayloadIdEventStream<T>>,
+    /// Number of events that were dropped because the subscriber fell behind.
+    lagged: u64,
+}
+
+impl<T: PayloadTypes> PayloadSubscription<T> {
+    /// Creates a new subscription to the events of the payload with the given identifier.
+    ///
+    /// If `events` is `None`, the subscription is terminated right away.
+    pub fn new(events: Option<PayloadEvents<T>>, id: PayloadId) -> Self {
+        Self { st: events.map(|events| events.into_payload_id_stream(id)), lagged: 0 }
+    }
+
+    /// Returns `true` if the subscription has terminated.
+    pub const fn is_terminated(&self) -> bool {
+        self.st.is_none()
+    }
+
+    /// Returns the number of events that were dropped because the subscriber fell behind.
+    ///
+    /// If this is non-zero, the subscription terminated without yielding all events of the
+    /// payload.
+    pub const fn lagged(&self) -> u64 {
+        self.lagged
+    }
+}
+
+impl<T: PayloadTypes> Stream for PayloadSubscription<T> {
+    type Item = Events<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let Some(st) = this.st.as_mut() else { return Poll::Ready(None) };
+        let event = match ready!(Pin::new(st).poll_next(cx)) {
+            Some(PayloadEvent::Event(event)) => Some(event),
+            Some(PayloadEvent::Lagged(skipped)) => {
+                // the final event may have been dropped, so there's no telling whether more
+                // events follow
+                this.lagged = skipped;
+                None
+            }
+            None => None,
+        };
+        if event.as_ref().is_none_or(|event| event.is_final()) {
+            this.st = None;
+        }
+        Poll::Ready(event)
     }
 }
 
@@ -58,6 +320,23 @@ pub struct BuiltPayloadStream<T: PayloadTypes> {
     /// The stream of events.
     #[pin]
     st: BroadcastStream<Events<T>>,
+    /// Payloads built before subscribing that are yielded first.
+    replay: VecDeque<T::BuiltPayload>,
+    /// Block hashes of the replayed payloads that haven't been received since.
+    replayed: Vec<B256>,
+    /// Total number of events this subscriber has missed.
+    lagged: u64,
+    /// Metrics for dropped events.
+    metrics: PayloadEventsMetrics,
+}
+
+impl<T: PayloadTypes> BuiltPayloadStream<T> {
+    /// Returns the total number of events this subscriber has missed because it fell behind.
+    ///
+    /// Dropped events are skipped by this stream, so this can be used to detect missed events.
+    pub const fn lagged(&self) -> u64 {
+        self.lagged
+    }
 }
 
 impl<T: PayloadTypes> Stream for BuiltPayloadStream<T> {
@@ -65,14 +344,26 @@ impl<T: PayloadTypes> Stream for BuiltPayloadStream<T> {
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         loop {
-            return match ready!(self.as_mut().project().st.poll_next(cx)) {
-                Some(Ok(Events::BuiltPayload(payload))) => Poll::Ready(Some(payload)),
-                Some(Ok(Events::Attributes(_))) => {
-                    // ignoring attributes
+            let this = self.as_mut().project();
+            if let Some(payload) = this.replay.pop_front() {
+                return Poll::Ready(Some(payload))
+            }
+            return match ready!(this.st.poll_next(cx)) {
+                Some(Ok(Events::BuiltPayload(payload))) => {
+                    let hash = payload.block().hash();
+                    if let Some(idx) = this.replayed.iter().position(|replayed| *replayed == hash) {
+                        // the payload has been replayed already
+                        this.replayed.swap_remove(idx);
+                        continue
+                    }
+                    Poll::Ready(Some(payload))
+                }
+                Some(Ok(_)) => {
+                    // ignoring all other events
                     continue
                 }
-                Some(Err(err)) => {
-                    debug!(%err, "payload event stream lagging behind");
+                Some(Err(BroadcastStreamRecvError::Lagged(skipped))) => {
+                    record_lagged(this.lagged, this.metrics, skipped);
                     continue
                 }
                 None => Poll::Ready(None),
@@ -88,6 +379,19 @@ pub struct PayloadAttributeStream<T: PayloadTypes> {
     /// The stream of events.
     #[pin]
     st: BroadcastStream<Events<T>>,
+    /// Total number of events this subscriber has missed.
+    lagged: u64,
+    /// Metrics for dropped events.
+    metrics: PayloadEventsMetrics,
+}
+
+impl<T: PayloadTypes> PayloadAttributeStream<T> {
+    /// Returns the total number of events this subscriber has missed because it fell behind.
+    ///
+    /// Dropped events are skipped by this stream, so this can be used to detect missed events.
+    pub const fn lagged(&self) -> u64 {
+        self.lagged
+    }
 }
 
 impl<T: PayloadTypes> Stream for PayloadAttributeStream<T> {
@@ -95,14 +399,15 @@ impl<T: PayloadTypes> Stream for PayloadAttributeStream<T> {
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         loop {
-            return match ready!(self.as_mut().project().st.poll_next(cx)) {
+            let this = self.as_mut().project();
+            return match ready!(this.st.poll_next(cx)) {
                 Some(Ok(Events::Attributes(attr))) => Poll::Ready(Some(attr)),
-                Some(Ok(Events::BuiltPayload(_))) => {
-                    // ignoring payloads
+                Some(Ok(_)) => {
+                    // ignoring all other events
                     continue
                 }
-                Some(Err(err)) => {
-                    debug!(%err, "payload event stream lagging behind");
+                Some(Err(BroadcastStreamRecvError::Lagged(skipped))) => {
+                    record_lagged(this.lagged, this.metrics, skipped);
                     continue
                 }
                 None => Poll::Ready(None),
@@ -110,3 +415,96 @@ impl<T: PayloadTypes> Stream for PayloadAttributeStream<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use reth_ethereum_engine_primitives::{EthBuiltPayload, EthPayloadTypes};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn surface_lagged_events() {
+        let (tx, receiver) = broadcast::channel::<Events<EthPayloadTypes>>(2);
+        for seed in 0..5 {
+            tx.send(Events::Cancelled(PayloadId::new([seed; 8]))).unwrap();
+        }
+
+        let mut events = PayloadEvents { receiver }.into_event_stream();
+        assert!(matches!(events.next().await.unwrap(), PayloadEvent::Lagged(3)));
+        assert_eq!(events.lagged(), 3);
+
+        for seed in 3..5 {
+            let event = events.next().await.unwrap();
+            assert!(
+                matches!(event, PayloadEvent::Event(Events::Cancelled(id)) if id == PayloadId::new([seed; 8]))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn count_lagged_events_of_filtered_streams() {
+        let id = PayloadId::new([1; 8]);
+        let (tx, receiver) = broadcast::channel::<Events<EthPayloadTypes>>(2);
+        let mut payload_events = PayloadEvents { receiver }.into_payload_id_stream(id);
+        let mut attributes = PayloadEvents { receiver: tx.subscribe() }.into_attributes_stream();
+        for _ in 0..5 {
+            tx.send(Events::Cancelled(id)).unwrap();
+        }
+        drop(tx);
+
+        assert!(matches!(payload_events.next().await, Some(PayloadEvent::Lagged(3))));
+        for _ in 0..2 {
+            assert!(matches!(
+                payload_events.next().await,
+                Some(PayloadEvent::Event(Events::Cancelled(_)))
+            ));
+        }
+        assert!(payload_events.next().await.is_none());
+        assert_eq!(payload_events.lagged(), 3);
+
+        assert!(attributes.next().await.is_none());
+        assert_eq!(attributes.lagged(), 3);
+    }
+
+    #[tokio::test]
+    async fn skip_replayed_payloads_once() {
+        let id = PayloadId::new([1; 8]);
+        let payload = |number| {
+            let block = alloy_consensus::Block {
+                header: alloy_consensus::Header { number, ..Default::default() },
+                body: Default::default(),
+            };
+            let block = Arc::new(reth_primitives_traits::SealedBlock::seal_slow(block));
+            EthBuiltPayload::new(id, block, U256::ZERO, None)
+        };
+
+        let (tx, receiver) = broadcast::channel::<Events<EthPayloadTypes>>(8);
+        let payloads =
+            PayloadEvents { receiver }.into_built_payload_stream_with_replay(vec![payload(1)]);
+        for number in [1, 2, 1] {
+            tx.send(Events::BuiltPayload(payload(number))).unwrap();
+        }
+        drop(tx);
+
+        // the replayed payload is only skipped once, and other payloads of the same job are kept
+        let numbers: Vec<_> =
+            payloads.map(|payload| payload.block().header().number).collect().await;
+        assert_eq!(numbers, vec![1, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn terminate_lagged_subscription() {
+        let id = PayloadId::new([1; 8]);
+        let (tx, receiver) = broadcast::channel::<Events<EthPayloadTypes>>(2);
+        let mut subscription = PayloadSubscription::new(Some(PayloadEvents { receiver }), id);
+        for _ in 0..3 {
+            tx.send(Events::Cancelled(PayloadId::new([2; 8]))).unwrap();
+        }
+
+        // the subscription ends instead of waiting for a final event that may have been dropped
+        assert!(subscription.next().await.is_none());
+        assert!(subscription.is_terminated());
+        assert_eq!(subscription.lagged(), 1);
+    }
+}
@@ -9,6 +9,11 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 mod events;
-pub use crate::events::{Events, PayloadEvents};
+pub use crate::events::{
+    BuiltPayloadStream, Events, PayloadEvent, PayloadEventStream, PayloadEvents,
+    PayloadIdEventStream, PayloadSubscription,
+};
+
+mod metrics;
 
 pub use reth_payload_primitives::PayloadBuilderError;
@@ -0,0 +1,11 @@
+//! Payload events metrics.
+
+use reth_metrics::{metrics::Counter, Metrics};
+
+/// Metrics for subscribers of the payload events.
+#[derive(Metrics, Clone)]
+#[metrics(scope = "payloads.events")]
+pub(crate) struct PayloadEventsMetrics {
+    /// Total number of events dropped because a subscriber fell behind
+    pub(crate) lagged_events: Counter,
+}
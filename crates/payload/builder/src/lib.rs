@@ -120,9 +120,14 @@ pub use alloy_rpc_types::engine::PayloadId;
 pub use reth_payload_builder_primitives::PayloadBuilderError;
 pub use reth_payload_primitives::PayloadKind;
 pub use service::{
-    PayloadBuilderHandle, PayloadBuilderService, PayloadServiceCommand, PayloadStore,
+    NoopChainEvents, PayloadBuilderHandle, PayloadBuilderService, PayloadJobLimitPolicy,
+    PayloadResolveError, PayloadServiceCommand, PayloadStore, WaitForPayloadError,
+    WeakPayloadBuilderHandle, DEFAULT_MAX_FEE_RECIPIENT_LABELS, DEFAULT_MAX_PAYLOAD_JOBS,
+};
+pub use traits::{
+    KeepPayloadJobAlive, PayloadAttributesModifier, PayloadJob, PayloadJobGenerator,
+    PayloadJobStats, PayloadSummary,
 };
-pub use traits::{KeepPayloadJobAlive, PayloadJob, PayloadJobGenerator};
 
 // re-export the Ethereum engine primitives for convenience
 #[doc(inline)]
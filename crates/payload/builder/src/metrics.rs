@@ -1,9 +1,15 @@
 //! Payload builder service metrics.
 
+use alloy_primitives::Address;
 use reth_metrics::{
-    metrics::{Counter, Gauge},
+    metrics::{Counter, Gauge, Histogram},
     Metrics,
 };
+use std::{collections::HashMap, time::Duration};
+
+/// Label value shared by all fee recipients once the maximum number of labeled fee recipients is
+/// reached.
+const OTHER_FEE_RECIPIENT_LABEL: &str = "other";
 
 /// Payload builder service metrics
 #[derive(Metrics, Clone)]
@@ -11,10 +17,26 @@ use reth_metrics::{
 pub(crate) struct PayloadBuilderServiceMetrics {
     /// Number of active jobs
     pub(crate) active_jobs: Gauge,
+    /// Number of active jobs right after a new job was enqueued
+    pub(crate) payload_jobs_enqueued: Histogram,
     /// Total number of initiated jobs
     pub(crate) initiated_jobs: Counter,
     /// Total number of failed jobs
     pub(crate) failed_jobs: Counter,
+    /// Total number of jobs that were replaced by a job with new attributes
+    pub(crate) replaced_jobs: Counter,
+    /// Total number of cancelled jobs
+    pub(crate) cancelled_jobs: Counter,
+    /// Total number of jobs terminated because their parent block was reorged out
+    pub(crate) invalidated_jobs: Counter,
+    /// Total number of jobs rejected because the maximum number of active jobs was reached
+    pub(crate) rejected_jobs: Counter,
+    /// Total number of jobs evicted because the maximum number of active jobs was reached
+    pub(crate) evicted_jobs: Counter,
+    /// Total number of payload build iterations completed by all jobs
+    pub(crate) build_iterations: Counter,
+    /// Number of build iterations the last resolved job completed
+    pub(crate) payload_build_iterations: Gauge,
     /// Coinbase revenue for best payloads
     pub(crate) best_revenue: Gauge,
     /// Current block returned as the best payload
@@ -23,6 +45,14 @@ pub(crate) struct PayloadBuilderServiceMetrics {
     pub(crate) resolved_revenue: Gauge,
     /// Current block returned as the resolved payload
     pub(crate) resolved_block: Gauge,
+    /// Time from the creation of a job until it built its first payload
+    pub(crate) payload_time_to_first_build: Histogram,
+    /// Time from a resolve request until the payload is resolved
+    pub(crate) resolve_duration: Histogram,
+    /// Gas used by resolved payloads
+    pub(crate) resolved_gas_used: Histogram,
+    /// Number of transactions in resolved payloads
+    pub(crate) resolved_tx_count: Histogram,
 }
 
 impl PayloadBuilderServiceMetrics {
@@ -34,10 +64,43 @@ impl PayloadBuilderServiceMetrics {
         self.failed_jobs.increment(1);
     }
 
+    pub(crate) fn inc_replaced_jobs(&self) {
+        self.replaced_jobs.increment(1);
+    }
+
+    pub(crate) fn inc_cancelled_jobs(&self) {
+        self.cancelled_jobs.increment(1);
+    }
+
+    pub(crate) fn inc_invalidated_jobs(&self) {
+        self.invalidated_jobs.increment(1);
+    }
+
+    pub(crate) fn inc_rejected_jobs(&self) {
+        self.rejected_jobs.increment(1);
+    }
+
+    pub(crate) fn inc_evicted_jobs(&self) {
+        self.evicted_jobs.increment(1);
+    }
+
+    pub(crate) fn inc_build_iterations(&self, value: u64) {
+        self.build_iterations.increment(value);
+    }
+
+    pub(crate) fn set_payload_build_iterations(&self, value: u64) {
+        self.payload_build_iterations.set(value as f64)
+    }
+
     pub(crate) fn set_active_jobs(&self, value: usize) {
         self.active_jobs.set(value as f64)
     }
 
+    pub(crate) fn record_enqueued_job(&self, active_jobs: usize) {
+        self.set_active_jobs(active_jobs);
+        self.payload_jobs_enqueued.record(active_jobs as f64)
+    }
+
     pub(crate) fn set_best_revenue(&self, block: u64, value: f64) {
         self.best_block.set(block as f64);
         self.best_revenue.set(value)
@@ -47,4 +110,87 @@ impl PayloadBuilderServiceMetrics {
         self.resolved_block.set(block as f64);
         self.resolved_revenue.set(value)
     }
+
+    pub(crate) fn record_time_to_first_build(&self, duration: Duration) {
+        self.payload_time_to_first_build.record(duration)
+    }
+
+    pub(crate) fn record_resolve_duration(&self, duration: Duration) {
+        self.resolve_duration.record(duration)
+    }
+
+    pub(crate) fn record_resolved_payload(&self, gas_used: u64, tx_count: usize) {
+        self.resolved_gas_used.record(gas_used as f64);
+        self.resolved_tx_count.record(tx_count as f64)
+    }
+}
+
+/// Payload revenue metrics of a single fee recipient.
+#[derive(Metrics, Clone)]
+#[metrics(scope = "payloads.fee_recipient")]
+pub(crate) struct FeeRecipientMetrics {
+    /// Coinbase revenue for best payloads
+    pub(crate) best_revenue: Gauge,
+    /// Coinbase revenue for resolved payloads
+    pub(crate) resolved_revenue: Gauge,
+    /// Total number of resolved payloads
+    pub(crate) resolved_payloads: Counter,
+}
+
+impl FeeRecipientMetrics {
+    pub(crate) fn set_best_revenue(&self, value: f64) {
+        self.best_revenue.set(value)
+    }
+
+    pub(crate) fn set_resolved_revenue(&self, value: f64) {
+        self.resolved_revenue.set(value);
+        self.resolved_payloads.increment(1);
+    }
+}
+
+/// Tracks the [`FeeRecipientMetrics`] of all fee recipients, labeled by fee recipient address.
+///
+/// The number of distinct labels is capped, once the cap is reached all additional fee recipients
+/// share the metrics labeled with [`OTHER_FEE_RECIPIENT_LABEL`].
+#[derive(Debug)]
+pub(crate) struct FeeRecipientMetricsRegistry {
+    /// Metrics of the fee recipients that have their own label.
+    pub(crate) recipients: HashMap<Address, FeeRecipientMetrics>,
+    /// Metrics shared by all fee recipients over the cap, created on first use.
+    pub(crate) other: Option<FeeRecipientMetrics>,
+    /// Maximum number of fee recipients that have their own label.
+    max_recipients: usize,
+}
+
+impl FeeRecipientMetricsRegistry {
+    pub(crate) fn new(max_recipients: usize) -> Self {
+        Self { recipients: HashMap::default(), other: None, max_recipients }
+    }
+
+    pub(crate) const fn set_max_recipients(&mut self, max_recipients: usize) {
+        self.max_recipients = max_recipients;
+    }
+
+    /// Returns the metrics of the given fee recipient.
+    pub(crate) fn get(&mut self, recipient: Address) -> FeeRecipientMetrics {
+        if let Some(metrics) = self.recipients.get(&recipient) {
+            return metrics.clone()
+        }
+
+        if self.recipients.len() < self.max_recipients {
+            let metrics =
+                FeeRecipientMetrics::new_with_labels(&[("fee_recipient", recipient.to_string())]);
+            self.recipients.insert(recipient, metrics.clone());
+            return metrics
+        }
+
+        self.other
+            .get_or_insert_with(|| {
+                FeeRecipientMetrics::new_with_labels(&[(
+                    "fee_recipient",
+                    OTHER_FEE_RECIPIENT_LABEL,
+                )])
+            })
+            .clone()
+    }
 }
@@ -1,6 +1,6 @@
 //! A payload builder service task that does nothing.
 
-use crate::{service::PayloadServiceCommand, PayloadBuilderHandle};
+use crate::{service::PayloadServiceCommand, PayloadBuilderHandle, PayloadResolveError};
 use futures_util::{ready, StreamExt};
 use reth_payload_primitives::{PayloadBuilderAttributes, PayloadTypes};
 use std::{
@@ -49,10 +49,36 @@ where
                     let id = attr.payload_id();
                     tx.send(Ok(id)).ok()
                 }
+                PayloadServiceCommand::BuildNewPayloadForce(attr, tx) => {
+                    let id = attr.payload_id();
+                    tx.send(Ok(id)).ok()
+                }
                 PayloadServiceCommand::BestPayload(_, tx) => tx.send(None).ok(),
+                PayloadServiceCommand::BestPayloadSummary(_, tx) => tx.send(None).ok(),
                 PayloadServiceCommand::PayloadTimestamp(_, tx) => tx.send(None).ok(),
-                PayloadServiceCommand::Resolve(_, _, tx) => tx.send(None).ok(),
+                PayloadServiceCommand::PayloadAttributes(_, tx) => tx.send(None).ok(),
+                PayloadServiceCommand::Resolve(_, _, tx) => {
+                    tx.send(Err(PayloadResolveError::UnknownPayloadId)).ok()
+                }
+                PayloadServiceCommand::ResolveBatch(payloads, tx) => {
+                    let resolved =
+                        payloads.iter().map(|_| Err(PayloadResolveError::UnknownPayloadId));
+                    tx.send(resolved.collect()).ok()
+                }
+                PayloadServiceCommand::JobStats(_, tx) => tx.send(None).ok(),
+                PayloadServiceCommand::JobAge(_, tx) => tx.send(None).ok(),
+                PayloadServiceCommand::PayloadIds(tx) => tx.send(Vec::new()).ok(),
+                PayloadServiceCommand::AllAttributes(tx) => tx.send(Vec::new()).ok(),
+                PayloadServiceCommand::JobsByParent(_, tx) => tx.send(Vec::new()).ok(),
+                PayloadServiceCommand::Contains(_, tx) => tx.send(false).ok(),
+                PayloadServiceCommand::Cancel(_, tx) => tx.send(false).ok(),
+                PayloadServiceCommand::ExtendDeadline(_, _, tx) => tx.send(false).ok(),
                 PayloadServiceCommand::Subscribe(_) => None,
+                PayloadServiceCommand::SubscribeTo(_, tx) => tx.send(None).ok(),
+                PayloadServiceCommand::Shutdown(tx) => {
+                    let _ = tx.send(());
+                    return Poll::Ready(())
+                }
             };
         }
     }
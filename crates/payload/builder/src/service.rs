@@ -4,34 +4,55 @@
 //! Once a new payload is created, it is continuously updated.
 
 use crate::{
-    metrics::PayloadBuilderServiceMetrics, traits::PayloadJobGenerator, KeepPayloadJobAlive,
-    PayloadJob,
+    metrics::{FeeRecipientMetrics, FeeRecipientMetricsRegistry, PayloadBuilderServiceMetrics},
+    traits::PayloadJobGenerator,
+    KeepPayloadJobAlive, PayloadAttributesModifier, PayloadJob, PayloadJobStats, PayloadSummary,
 };
 use alloy_consensus::BlockHeader;
-use alloy_primitives::BlockTimestamp;
+use alloy_primitives::{BlockTimestamp, B256, U256};
 use alloy_rpc_types::engine::PayloadId;
-use futures_util::{future::FutureExt, Stream, StreamExt};
+use futures_util::{
+    future::{FutureExt, Shared},
+    stream::FuturesUnordered,
+    task::AtomicWaker,
+    Stream, StreamExt,
+};
+use parking_lot::{Mutex, MutexGuard};
 use reth_chain_state::CanonStateNotification;
-use reth_payload_builder_primitives::{Events, PayloadBuilderError, PayloadEvents};
+use reth_payload_builder_primitives::{
+    BuiltPayloadStream, Events, PayloadBuilderError, PayloadEvents, PayloadIdEventStream,
+    PayloadSubscription,
+};
 use reth_payload_primitives::{BuiltPayload, PayloadBuilderAttributes, PayloadKind, PayloadTypes};
-use reth_primitives_traits::NodePrimitives;
+use reth_primitives_traits::{BlockBody, NodePrimitives};
+use reth_tasks::shutdown::{GracefulShutdown, GracefulShutdownGuard};
+use schnellru::{ByLength, LruMap};
 use std::{
+    collections::{HashMap, HashSet},
     fmt,
     future::Future,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
-use tokio::sync::{
-    broadcast, mpsc,
-    oneshot::{self, Receiver},
-    watch,
+use tokio::{
+    sync::{
+        broadcast, mpsc,
+        oneshot::{self, Receiver},
+    },
+    task::JoinHandle,
+    time::{Instant, Sleep},
 };
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, info, trace, warn};
 
 type PayloadFuture<P> = Pin<Box<dyn Future<Output = Result<P, PayloadBuilderError>> + Send>>;
 
+/// A resolve future that is shared by all concurrent requests to resolve the same payload.
+type SharedPayloadFuture<P> =
+    Shared<Pin<Box<dyn Future<Output = Result<P, Arc<PayloadBuilderError>>> + Send>>>;
+
 /// A communication channel to the [`PayloadBuilderService`] that can retrieve payloads.
 ///
 /// This type is intended to be used to retrieve payloads from the service (e.g. from the engine
@@ -39,6 +60,10 @@ type PayloadFuture<P> = Pin<Box<dyn Future<Output = Result<P, PayloadBuilderErro
 #[derive(Debug)]
 pub struct PayloadStore<T: PayloadTypes> {
     inner: Arc<PayloadBuilderHandle<T>>,
+    /// Recently requested payload attributes.
+    attributes: Mutex<PayloadAttributesCache<T>>,
+    /// How payloads are resolved by [`PayloadStore::try_resolve`].
+    resolve_kind: PayloadKind,
 }
 
 impl<T> PayloadStore<T>
@@ -49,20 +74,54 @@ where
     ///
     /// Note: depending on the installed [`PayloadJobGenerator`], this may or may not terminate the
     /// job, See [`PayloadJob::resolve`].
+    pub async fn try_resolve_kind(
+        &self,
+        id: PayloadId,
+        kind: PayloadKind,
+    ) -> Result<T::BuiltPayload, PayloadResolveError> {
+        self.inner.try_resolve_kind(id, kind).await
+    }
+
+    /// Resolves the payload job and returns the best payload that has been built so far.
+    ///
+    /// The payload is resolved with the configured [`PayloadKind`], which is
+    /// [`PayloadKind::Earliest`] by default, see [`PayloadStore::with_resolve_kind`].
+    pub async fn try_resolve(&self, id: PayloadId) -> Result<T::BuiltPayload, PayloadResolveError> {
+        self.try_resolve_kind(id, self.resolve_kind).await
+    }
+
+    /// Resolves the payload job once its deadline is reached and returns the final best payload.
+    ///
+    /// Unlike [`PayloadKind::Earliest`], which returns as soon as any payload is ready, this gives
+    /// the job as much time as possible to improve the payload, see
+    /// [`PayloadKind::WaitForDeadline`].
+    pub async fn try_resolve_latest(
+        &self,
+        id: PayloadId,
+    ) -> Result<T::BuiltPayload, PayloadResolveError> {
+        self.try_resolve_kind(id, PayloadKind::WaitForDeadline).await
+    }
+
+    /// Resolves the payload job and returns the best payload that has been built so far.
+    ///
+    /// Note: depending on the installed [`PayloadJobGenerator`], this may or may not terminate the
+    /// job, See [`PayloadJob::resolve`].
+    #[deprecated(note = "use `try_resolve_kind` instead, which reports why resolving failed")]
     pub async fn resolve_kind(
         &self,
         id: PayloadId,
         kind: PayloadKind,
     ) -> Option<Result<T::BuiltPayload, PayloadBuilderError>> {
-        self.inner.resolve_kind(id, kind).await
+        PayloadResolveError::into_option(self.try_resolve_kind(id, kind).await)
     }
 
     /// Resolves the payload job and returns the best payload that has been built so far.
+    #[deprecated(note = "use `try_resolve` instead, which reports why resolving failed")]
     pub async fn resolve(
         &self,
         id: PayloadId,
     ) -> Option<Result<T::BuiltPayload, PayloadBuilderError>> {
-        self.resolve_kind(id, PayloadKind::Earliest).await
+        PayloadResolveError::into_option(self.try_resolve(id).await)
     }
 
     /// Returns the best payload for the given identifier.
@@ -84,6 +143,137 @@ where
     ) -> Option<Result<u64, PayloadBuilderError>> {
         self.inner.payload_timestamp(id).await
     }
+
+    /// Returns the payload attributes associated with the given identifier.
+    ///
+    /// Successful lookups are cached, so repeated requests for the same payload are served without
+    /// querying the service. Cached attributes are evicted once they are replaced or their job is
+    /// dropped, as reported by the payload events of the service.
+    pub async fn payload_attributes(
+        &self,
+        id: PayloadId,
+    ) -> Option<Result<T::PayloadBuilderAttributes, PayloadBuilderError>> {
+        let subscribed = {
+            let mut cache = self.attributes.lock();
+            if let Some(attr) = cache.get(id) {
+                return Some(Ok(attr))
+            }
+            cache.events.is_some()
+        };
+
+        // subscribe before the lookup, so that no replacement of the attributes is missed
+        let events =
+            if subscribed { None } else { self.inner.subscribe().await.ok().map(|ev| ev.receiver) };
+
+        let res = self.inner.payload_attributes(id).await;
+        if let Some(Ok(attr)) = &res {
+            self.attributes.lock().insert(id, attr.clone(), events);
+        }
+        res
+    }
+
+    /// Returns the identifiers of all payload jobs that are currently being built.
+    pub async fn payload_ids(&self) -> Vec<PayloadId> {
+        self.inner.payload_ids().await
+    }
+
+    /// Returns the identifiers of all payload jobs that are building on top of the given parent.
+    pub async fn payload_ids_for_parent(&self, parent: B256) -> Vec<PayloadId> {
+        self.inner.payload_ids_for_parent(parent).await
+    }
+
+    /// Returns `true` if a payload job with the given identifier is currently being built.
+    ///
+    /// Use this to check whether a payload is still being built: the job is removed once it has
+    /// been resolved or has finished.
+    pub async fn contains(&self, id: PayloadId) -> bool {
+        self.inner.contains(id).await
+    }
+
+    /// Waits until a payload has been built for the given identifier.
+    ///
+    /// This returns the best payload right away if one is available, otherwise it waits until the
+    /// job has built a payload. If no payload has been built within the given `timeout`, the
+    /// job is resolved with [`PayloadKind::WaitForPending`].
+    pub async fn wait_for_payload(
+        &self,
+        id: PayloadId,
+        timeout: Duration,
+    ) -> Result<T::BuiltPayload, WaitForPayloadError> {
+        // subscribe first so we don't miss any payloads built in the meantime
+        let mut events = self.inner.subscribe_id(id).await?;
+
+        match self.best_payload(id).await {
+            Some(Ok(payload)) => return Ok(payload),
+            Some(Err(err)) => {
+                trace!(target: "payload_builder", %id, %err, "no payload built yet, waiting");
+            }
+            None => return Err(WaitForPayloadError::UnknownPayload(id)),
+        }
+
+        let wait = async {
+            while let Some(event) = events.next().await {
+                if let Events::BuiltPayload(payload) | Events::BetterPayload(payload) = event {
+                    return Some(payload)
+                }
+            }
+            None
+        };
+
+        if let Ok(Some(payload)) = tokio::time::timeout(timeout, wait).await {
+            return Ok(payload)
+        }
+
+        match self.try_resolve_kind(id, PayloadKind::WaitForPending).await {
+            Ok(payload) => Ok(payload),
+            Err(PayloadResolveError::Builder(err)) => Err(err.into()),
+            Err(_) => Err(WaitForPayloadError::Timeout(id)),
+        }
+    }
+}
+
+/// Errors returned when resolving a payload.
+#[derive(Debug, thiserror::Error)]
+pub enum PayloadResolveError {
+    /// There's no payload job for the given identifier.
+    #[error("unknown payload id")]
+    UnknownPayloadId,
+    /// The job for the given identifier has already been resolved and its payload is no longer
+    /// retained.
+    #[error("payload job already resolved")]
+    JobAlreadyResolved {
+        /// When the job was resolved.
+        resolved_at: Instant,
+    },
+    /// The payload builder failed.
+    #[error(transparent)]
+    Builder(#[from] PayloadBuilderError),
+}
+
+impl PayloadResolveError {
+    /// Converts the result of resolving a payload into the `Option` based representation of the
+    /// deprecated resolve APIs.
+    fn into_option<P>(res: Result<P, Self>) -> Option<Result<P, PayloadBuilderError>> {
+        match res {
+            Ok(payload) => Some(Ok(payload)),
+            Err(Self::Builder(err)) => Some(Err(err)),
+            Err(Self::UnknownPayloadId | Self::JobAlreadyResolved { .. }) => None,
+        }
+    }
+}
+
+/// Errors returned by [`PayloadStore::wait_for_payload`].
+#[derive(Debug, thiserror::Error)]
+pub enum WaitForPayloadError {
+    /// There's no payload job for the given identifier.
+    #[error("unknown payload id {0}")]
+    UnknownPayload(PayloadId),
+    /// No payload has been built for the given identifier in time.
+    #[error("timed out waiting for payload {0}")]
+    Timeout(PayloadId),
+    /// The payload builder failed.
+    #[error(transparent)]
+    Builder(#[from] PayloadBuilderError),
 }
 
 impl<T> PayloadStore<T>
@@ -92,7 +282,17 @@ where
 {
     /// Create a new instance
     pub fn new(inner: PayloadBuilderHandle<T>) -> Self {
-        Self { inner: Arc::new(inner) }
+        Self {
+            inner: Arc::new(inner),
+            attributes: Mutex::new(PayloadAttributesCache::new(PAYLOAD_ATTRIBUTES_CACHE_CAPACITY)),
+            resolve_kind: PayloadKind::Earliest,
+        }
+    }
+
+    /// Sets the [`PayloadKind`] used by [`PayloadStore::try_resolve`].
+    pub const fn with_resolve_kind(mut self, kind: PayloadKind) -> Self {
+        self.resolve_kind = kind;
+        self
     }
 }
 
@@ -105,6 +305,79 @@ where
     }
 }
 
+/// Payload attributes cached by the [`PayloadStore`].
+///
+/// The cache follows the payload events of the service: attributes are evicted on
+/// [`Events::AttributesReplaced`] and once the job is dropped. Attributes are only cached while
+/// subscribed to the payload events.
+#[derive(Debug)]
+struct PayloadAttributesCache<T: PayloadTypes> {
+    entries: LruMap<PayloadId, T::PayloadBuilderAttributes>,
+    /// Payload events used to evict stale entries.
+    events: Option<broadcast::Receiver<Events<T>>>,
+}
+
+impl<T: PayloadTypes> PayloadAttributesCache<T> {
+    fn new(capacity: u32) -> Self {
+        Self { entries: LruMap::new(ByLength::new(capacity)), events: None }
+    }
+
+    /// Returns the cached attributes of the given payload.
+    fn get(&mut self, id: PayloadId) -> Option<T::PayloadBuilderAttributes> {
+        self.evict_stale();
+        self.entries.get(&id).cloned()
+    }
+
+    /// Caches the attributes of the given payload, using the given events if not subscribed yet.
+    fn insert(
+        &mut self,
+        id: PayloadId,
+        attr: T::PayloadBuilderAttributes,
+        events: Option<broadcast::Receiver<Events<T>>>,
+    ) {
+        if self.events.is_none() {
+            self.events = events;
+        }
+        if self.events.is_some() {
+            self.entries.insert(id, attr);
+            // the attributes may have been replaced since subscribing
+            self.evict_stale();
+        }
+    }
+
+    /// Evicts the attributes of all payloads that were replaced or dropped since the last call.
+    fn evict_stale(&mut self) {
+        let Some(events) = &mut self.events else { return };
+        loop {
+            let id = match events.try_recv() {
+                Ok(Events::AttributesReplaced(attr) | Events::Attributes(attr)) => {
+                    attr.payload_id()
+                }
+                Ok(
+                    Events::Resolved(id) |
+                    Events::JobFailed { id, .. } |
+                    Events::Cancelled(id) |
+                    Events::Finished(id) |
+                    Events::Invalidated(id),
+                ) => id,
+                Ok(Events::BuiltPayload(_) | Events::BetterPayload(_)) => continue,
+                Err(broadcast::error::TryRecvError::Empty) => return,
+                Err(broadcast::error::TryRecvError::Lagged(_)) => {
+                    // events were missed, so any entry may be stale
+                    self.entries.clear();
+                    continue
+                }
+                Err(broadcast::error::TryRecvError::Closed) => {
+                    self.entries.clear();
+                    self.events = None;
+                    return
+                }
+            };
+            self.entries.remove(&id);
+        }
+    }
+}
+
 /// A communication channel to the [`PayloadBuilderService`].
 ///
 /// This is the API used to create new payloads and to get the current state of existing ones.
@@ -123,8 +396,17 @@ impl<T: PayloadTypes> PayloadBuilderHandle<T> {
         Self { to_service }
     }
 
+    /// Returns a [`WeakPayloadBuilderHandle`] that does not keep the command channel to the
+    /// service open.
+    pub fn downgrade(&self) -> WeakPayloadBuilderHandle<T> {
+        WeakPayloadBuilderHandle { to_service: self.to_service.downgrade() }
+    }
+
     /// Sends a message to the service to start building a new payload for the given payload.
     ///
+    /// If a job for the payload id is already in progress with the same attributes, this is a
+    /// no-op. If the attributes differ, [`PayloadBuilderError::DuplicatePayload`] is returned.
+    ///
     /// Returns a receiver that will receive the payload id.
     pub fn send_new_payload(
         &self,
@@ -135,6 +417,22 @@ impl<T: PayloadTypes> PayloadBuilderHandle<T> {
         rx
     }
 
+    /// Sends a message to the service to start building a new payload for the given payload,
+    /// terminating any existing job for the same payload id.
+    ///
+    /// Unlike [`Self::send_new_payload`], this restarts the job with the given attributes instead
+    /// of ignoring the request if a job for the payload id is already in progress.
+    ///
+    /// Returns a receiver that will receive the payload id.
+    pub fn send_and_replace_payload(
+        &self,
+        attr: T::PayloadBuilderAttributes,
+    ) -> Receiver<Result<PayloadId, PayloadBuilderError>> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.to_service.send(PayloadServiceCommand::BuildNewPayloadForce(attr, tx));
+        rx
+    }
+
     /// Returns the best payload for the given identifier.
     /// Note: this does not resolve the job if it's still in progress.
     pub async fn best_payload(
@@ -146,18 +444,106 @@ impl<T: PayloadTypes> PayloadBuilderHandle<T> {
         rx.await.ok()?
     }
 
+    /// Returns the best payload for the given identifier, blocking the current thread until the
+    /// service has responded.
+    ///
+    /// This is intended for synchronous callers, see also [`Self::best_payload`].
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called within an asynchronous execution context.
+    pub fn best_payload_blocking(
+        &self,
+        id: PayloadId,
+    ) -> Option<Result<T::BuiltPayload, PayloadBuilderError>> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(PayloadServiceCommand::BestPayload(id, tx)).ok()?;
+        rx.blocking_recv().ok()?
+    }
+
     /// Resolves the payload job and returns the best payload that has been built so far.
+    ///
+    /// Returns [`PayloadResolveError::UnknownPayloadId`] if there's no job for the given
+    /// identifier and [`PayloadResolveError::JobAlreadyResolved`] if the job has already been
+    /// resolved and its payload is no longer retained.
+    pub async fn try_resolve_kind(
+        &self,
+        id: PayloadId,
+        kind: PayloadKind,
+    ) -> Result<T::BuiltPayload, PayloadResolveError> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service
+            .send(PayloadServiceCommand::Resolve(id, kind, tx))
+            .map_err(|_| PayloadBuilderError::ChannelClosed)?;
+        let fut = rx.await.map_err(PayloadBuilderError::from)??;
+        Ok(fut.await?)
+    }
+
+    /// Resolves the payload job and returns the best payload that has been built so far.
+    #[deprecated(note = "use `try_resolve_kind` instead, which reports why resolving failed")]
     pub async fn resolve_kind(
         &self,
         id: PayloadId,
         kind: PayloadKind,
     ) -> Option<Result<T::BuiltPayload, PayloadBuilderError>> {
+        PayloadResolveError::into_option(self.try_resolve_kind(id, kind).await)
+    }
+
+    /// Resolves multiple payload jobs with a single request to the service.
+    ///
+    /// The returned payloads are in the same order as the given identifiers.
+    pub async fn resolve_batch(
+        &self,
+        payloads: Vec<(PayloadId, PayloadKind)>,
+    ) -> Result<Vec<Result<T::BuiltPayload, PayloadResolveError>>, PayloadBuilderError> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service
+            .send(PayloadServiceCommand::ResolveBatch(payloads, tx))
+            .map_err(|_| PayloadBuilderError::ChannelClosed)?;
+        let futs = rx
+            .await?
+            .into_iter()
+            .map(|res| async move { Ok::<_, PayloadResolveError>(res?.await?) });
+        Ok(futures_util::future::join_all(futs).await)
+    }
+
+    /// Cancels the payload job with the given identifier.
+    ///
+    /// The job is dropped without being resolved and a [`Events::Cancelled`] event is emitted.
+    ///
+    /// Returns `true` if a matching job was found and removed.
+    pub async fn cancel(&self, id: PayloadId) -> bool {
+        let (tx, rx) = oneshot::channel();
+        if self.to_service.send(PayloadServiceCommand::Cancel(id, tx)).is_err() {
+            return false
+        }
+        rx.await.unwrap_or_default()
+    }
+
+    /// Extends the deadline of the payload job with the given identifier, so it keeps building for
+    /// longer.
+    ///
+    /// Returns `false` if there is no active job for the given identifier or the job could not
+    /// extend its deadline, e.g. because it already completed.
+    pub async fn extend_deadline(&self, id: PayloadId, extension: Duration) -> bool {
+        let (tx, rx) = oneshot::channel();
+        if self.to_service.send(PayloadServiceCommand::ExtendDeadline(id, extension, tx)).is_err() {
+            return false
+        }
+        rx.await.unwrap_or_default()
+    }
+
+    /// Shuts down the payload builder service.
+    ///
+    /// All active jobs are resolved with [`PayloadKind::Earliest`] before the service terminates.
+    /// This returns once the service has terminated.
+    pub async fn shutdown(&self) {
         let (tx, rx) = oneshot::channel();
-        self.to_service.send(PayloadServiceCommand::Resolve(id, kind, tx)).ok()?;
-        match rx.await.transpose()? {
-            Ok(fut) => Some(fut.await),
-            Err(e) => Some(Err(e.into())),
+        if self.to_service.send(PayloadServiceCommand::Shutdown(tx)).is_err() {
+            // service already terminated
+            return
         }
+        let _ = rx.await;
     }
 
     /// Sends a message to the service to subscribe to payload events.
@@ -168,6 +554,46 @@ impl<T: PayloadTypes> PayloadBuilderHandle<T> {
         Ok(PayloadEvents { receiver: rx.await? })
     }
 
+    /// Returns a stream of all built payloads.
+    ///
+    /// Unlike subscribing via [`Self::subscribe`], the stream first replays the payloads that have
+    /// been built recently, before subscribing, and then continues with the newly built payloads.
+    pub async fn built_payloads_stream(
+        &self,
+    ) -> Result<BuiltPayloadStream<T>, PayloadBuilderError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.to_service.send(PayloadServiceCommand::SubscribeBuiltPayloads(tx));
+        let (replay, receiver) = rx.await?;
+        Ok(PayloadEvents { receiver }.into_built_payload_stream_with_replay(replay))
+    }
+
+    /// Subscribes to the events of the payload with the given identifier.
+    ///
+    /// Unlike [`Self::subscribe`], the returned stream ignores events of other payloads. Events
+    /// that were missed because the subscriber fell behind are reported as
+    /// [`PayloadEvent::Lagged`](reth_payload_builder_primitives::PayloadEvent::Lagged).
+    pub async fn subscribe_id(
+        &self,
+        id: PayloadId,
+    ) -> Result<PayloadIdEventStream<T>, PayloadBuilderError> {
+        Ok(self.subscribe().await?.into_payload_id_stream(id))
+    }
+
+    /// Subscribes to the events of the payload with the given identifier until the payload has
+    /// been resolved or its job has been dropped.
+    ///
+    /// The returned stream terminates right away if there's no active job for the given
+    /// identifier.
+    pub async fn subscribe_to(
+        &self,
+        id: PayloadId,
+    ) -> Result<PayloadSubscription<T>, PayloadBuilderError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.to_service.send(PayloadServiceCommand::SubscribeTo(id, tx));
+        let events = rx.await?.map(|receiver| PayloadEvents { receiver });
+        Ok(PayloadSubscription::new(events, id))
+    }
+
     /// Returns the payload timestamp associated with the given identifier.
     ///
     /// Note: this returns the timestamp of the payload and does not resolve the job.
@@ -179,6 +605,100 @@ impl<T: PayloadTypes> PayloadBuilderHandle<T> {
         self.to_service.send(PayloadServiceCommand::PayloadTimestamp(id, tx)).ok()?;
         rx.await.ok()?
     }
+
+    /// Returns the payload attributes associated with the given identifier.
+    ///
+    /// Note: this returns the attributes of the payload and does not resolve the job.
+    pub async fn payload_attributes(
+        &self,
+        id: PayloadId,
+    ) -> Option<Result<T::PayloadBuilderAttributes, PayloadBuilderError>> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(PayloadServiceCommand::PayloadAttributes(id, tx)).ok()?;
+        rx.await.ok()?
+    }
+
+    /// Returns the build statistics of the payload job with the given identifier.
+    ///
+    /// Returns `None` if there's no such job or the job does not track statistics, see
+    /// [`PayloadJob::stats`].
+    pub async fn job_stats(&self, id: PayloadId) -> Option<PayloadJobStats> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(PayloadServiceCommand::JobStats(id, tx)).ok()?;
+        rx.await.ok()?
+    }
+
+    /// Returns a summary of the best payload for the given identifier.
+    ///
+    /// Unlike [`Self::best_payload`], this does not need to transfer the whole payload.
+    /// Note: this does not resolve the job if it's still in progress.
+    pub async fn best_payload_summary(
+        &self,
+        id: PayloadId,
+    ) -> Option<Result<PayloadSummary, PayloadBuilderError>> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(PayloadServiceCommand::BestPayloadSummary(id, tx)).ok()?;
+        rx.await.ok()?
+    }
+
+    /// Returns how long the payload job with the given identifier has been alive.
+    ///
+    /// Returns `None` if there's no active job for the given identifier.
+    pub async fn job_age(&self, id: PayloadId) -> Option<Duration> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(PayloadServiceCommand::JobAge(id, tx)).ok()?;
+        rx.await.ok()?
+    }
+
+    /// Returns the identifiers of all payload jobs that are currently being built.
+    pub async fn payload_ids(&self) -> Vec<PayloadId> {
+        let (tx, rx) = oneshot::channel();
+        if self.to_service.send(PayloadServiceCommand::PayloadIds(tx)).is_err() {
+            return Vec::new()
+        }
+        rx.await.unwrap_or_default()
+    }
+
+    /// Returns the identifiers and payload attributes of all payload jobs that are currently being
+    /// built.
+    ///
+    /// This is equivalent to calling [`Self::payload_attributes`] for every job in
+    /// [`Self::payload_ids`], but only needs a single round trip to the service. Jobs that fail to
+    /// return their attributes are skipped.
+    pub async fn all_attributes(&self) -> Vec<(PayloadId, T::PayloadBuilderAttributes)> {
+        let (tx, rx) = oneshot::channel();
+        if self.to_service.send(PayloadServiceCommand::AllAttributes(tx)).is_err() {
+            return Vec::new()
+        }
+        rx.await.unwrap_or_default()
+    }
+
+    /// Returns the identifiers of all payload jobs that are building on top of the given parent.
+    ///
+    /// This is useful to clean up stale jobs when the parent block is reorged out.
+    pub async fn payload_ids_for_parent(&self, parent: B256) -> Vec<PayloadId> {
+        let (tx, rx) = oneshot::channel();
+        if self.to_service.send(PayloadServiceCommand::JobsByParent(parent, tx)).is_err() {
+            return Vec::new()
+        }
+        rx.await.unwrap_or_default()
+    }
+
+    /// Returns the number of payload jobs that are currently being built.
+    ///
+    /// See also [`Self::payload_ids`].
+    pub async fn active_job_count(&self) -> usize {
+        self.payload_ids().await.len()
+    }
+
+    /// Returns `true` if a payload job with the given identifier is currently being built.
+    pub async fn contains(&self, id: PayloadId) -> bool {
+        let (tx, rx) = oneshot::channel();
+        if self.to_service.send(PayloadServiceCommand::Contains(id, tx)).is_err() {
+            return false
+        }
+        rx.await.unwrap_or_default()
+    }
 }
 
 impl<T> Clone for PayloadBuilderHandle<T>
@@ -190,6 +710,33 @@ where
     }
 }
 
+/// A weak reference to the [`PayloadBuilderService`], see [`PayloadBuilderHandle::downgrade`].
+///
+/// Unlike [`PayloadBuilderHandle`], this does not keep the command channel to the service open.
+#[derive(Debug)]
+pub struct WeakPayloadBuilderHandle<T: PayloadTypes> {
+    /// Weak sender half of the message channel to the [`PayloadBuilderService`].
+    to_service: mpsc::WeakUnboundedSender<PayloadServiceCommand<T>>,
+}
+
+impl<T: PayloadTypes> WeakPayloadBuilderHandle<T> {
+    /// Tries to convert this into a [`PayloadBuilderHandle`].
+    ///
+    /// Returns `None` if the command channel to the service has been closed.
+    pub fn upgrade(&self) -> Option<PayloadBuilderHandle<T>> {
+        self.to_service.upgrade().map(PayloadBuilderHandle::new)
+    }
+}
+
+impl<T> Clone for WeakPayloadBuilderHandle<T>
+where
+    T: PayloadTypes,
+{
+    fn clone(&self) -> Self {
+        Self { to_service: self.to_service.clone() }
+    }
+}
+
 /// A service that manages payload building tasks.
 ///
 /// This type is an endless future that manages the building of payloads.
@@ -198,6 +745,11 @@ where
 ///
 /// By design, this type relies entirely on the [`PayloadJobGenerator`] to create new payloads and
 /// does know nothing about how to build them, it just drives their jobs to completion.
+///
+/// Every job is polled on its own task, so CPU-heavy build iterations of multiple jobs make
+/// progress concurrently. The service wakes up whenever a job made progress and checks the jobs in
+/// reverse creation order: the most recently created job, which is the one the CL is most likely
+/// waiting on, is always checked before older jobs.
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct PayloadBuilderService<Gen, St, T>
@@ -208,29 +760,99 @@ where
 {
     /// The type that knows how to create new payloads.
     generator: Gen,
-    /// All active payload jobs.
-    payload_jobs: Vec<(Gen::Job, PayloadId)>,
-    /// Copy of the sender half, so new [`PayloadBuilderHandle`] can be created on demand.
-    service_tx: mpsc::UnboundedSender<PayloadServiceCommand<T>>,
+    /// All active payload jobs, ordered by creation time (oldest first).
+    payload_jobs: Vec<ActivePayloadJob<Gen::Job>>,
+    /// Wakes the service whenever one of the spawned jobs has been polled.
+    job_progress: Arc<AtomicWaker>,
+    /// The maximum number of payload jobs that can be active at the same time.
+    max_jobs: usize,
+    /// What to do when a new job is requested while `max_jobs` jobs are active.
+    job_limit_policy: PayloadJobLimitPolicy,
+    /// Optional deadline after which a job is resolved and dropped automatically.
+    job_deadline: Option<Duration>,
+    /// Payloads of jobs that exceeded their deadline and are being resolved.
+    expired_jobs: FuturesUnordered<PayloadFuture<T::BuiltPayload>>,
+    /// Weak copy of the sender half, so new [`PayloadBuilderHandle`] can be created on demand
+    /// without keeping the command channel open.
+    service_tx: mpsc::WeakUnboundedSender<PayloadServiceCommand<T>>,
     /// Receiver half of the command channel.
     command_rx: UnboundedReceiverStream<PayloadServiceCommand<T>>,
     /// Metrics for the payload builder service
     metrics: PayloadBuilderServiceMetrics,
+    /// Revenue metrics labeled by the fee recipient of the payload jobs.
+    fee_recipient_metrics: FeeRecipientMetricsRegistry,
     /// Chain events notification stream
     chain_events: St,
     /// Payload events handler, used to broadcast and subscribe to payload events.
     payload_events: broadcast::Sender<Events<T>>,
-    /// We retain latest resolved payload just to make sure that we can handle repeating
-    /// requests for it gracefully.
-    cached_payload_rx: watch::Receiver<Option<(PayloadId, BlockTimestamp, T::BuiltPayload)>>,
-    /// Sender half of the cached payload channel.
-    cached_payload_tx: watch::Sender<Option<(PayloadId, BlockTimestamp, T::BuiltPayload)>>,
+    /// We retain recently resolved payloads just to make sure that we can handle repeating
+    /// requests for them gracefully, even if the job has already been terminated.
+    resolved_payloads: LruMap<PayloadId, ResolvedPayload<T::BuiltPayload>>,
+    /// How long a resolved payload is retained.
+    resolved_payloads_ttl: Duration,
+    /// Sender half of the resolved payloads channel, used by the resolve futures.
+    resolved_payloads_tx: mpsc::UnboundedSender<ResolvedPayload<T::BuiltPayload>>,
+    /// Receiver half of the resolved payloads channel.
+    resolved_payloads_rx: mpsc::UnboundedReceiver<ResolvedPayload<T::BuiltPayload>>,
+    /// Resolve futures of payloads that are currently being resolved, shared with repeated
+    /// requests for the same payload until the resolved payload is tracked.
+    resolving: HashMap<PayloadId, SharedPayloadFuture<T::BuiltPayload>>,
+    /// Optional signal that initiates the shutdown of the service once fired.
+    shutdown_signal: Option<GracefulShutdown>,
+    /// Guard of the fired shutdown signal, held until all remaining jobs have been resolved.
+    shutdown_guard: Option<GracefulShutdownGuard>,
+    /// Whether the service is shutting down and no longer accepts new payload jobs.
+    shutting_down: bool,
+    /// Senders that are notified once the service terminated.
+    shutdown_tx: Vec<oneshot::Sender<()>>,
+    /// Optional policy check that runs before a new payload job is created.
+    attr_validator: Option<AttributesValidator<T::PayloadBuilderAttributes>>,
+    /// Optional hook that modifies the attributes before a new payload job is created.
+    attr_modifier: Option<Box<dyn PayloadAttributesModifier<T::PayloadBuilderAttributes>>>,
 }
 
 const PAYLOAD_EVENTS_BUFFER_SIZE: usize = 20;
 
+/// The default number of recently resolved payloads that are retained.
+const RESOLVED_PAYLOADS_CAPACITY: u32 = 16;
+
+/// The default duration for which resolved payloads are retained.
+const RESOLVED_PAYLOADS_TTL: Duration = Duration::from_secs(60);
+
+/// The default maximum number of payload jobs that can be active at the same time.
+pub const DEFAULT_MAX_PAYLOAD_JOBS: usize = 64;
+
+/// The default maximum number of distinct fee recipients that are used as metric labels.
+pub const DEFAULT_MAX_FEE_RECIPIENT_LABELS: usize = 64;
+
+/// The number of payload attributes that are cached by the [`PayloadStore`].
+const PAYLOAD_ATTRIBUTES_CACHE_CAPACITY: u32 = 16;
+
+/// A chain events stream that never yields, for services that don't track the canonical chain.
+///
+/// See [`PayloadBuilderService::standalone`].
+pub type NoopChainEvents<N> = futures_util::stream::Pending<CanonStateNotification<N>>;
+
 // === impl PayloadBuilderService ===
 
+impl<Gen, T>
+    PayloadBuilderService<Gen, NoopChainEvents<<T::BuiltPayload as BuiltPayload>::Primitives>, T>
+where
+    T: PayloadTypes,
+    Gen: PayloadJobGenerator,
+    Gen::Job: PayloadJob<PayloadAttributes = T::PayloadBuilderAttributes>,
+    <Gen::Job as PayloadJob>::BuiltPayload: Into<T::BuiltPayload>,
+{
+    /// Creates a new payload builder service that does not receive any chain events and returns
+    /// the [`PayloadBuilderHandle`] to interact with it.
+    ///
+    /// This is useful for standalone builders that only receive payload attributes and never
+    /// track the canonical chain, so [`PayloadJobGenerator::on_new_state`] is never invoked.
+    pub fn standalone(generator: Gen) -> (Self, PayloadBuilderHandle<T>) {
+        Self::new(generator, futures_util::stream::pending())
+    }
+}
+
 impl<Gen, St, T> PayloadBuilderService<Gen, St, T>
 where
     T: PayloadTypes,
@@ -248,52 +870,265 @@ where
         let (service_tx, command_rx) = mpsc::unbounded_channel();
         let (payload_events, _) = broadcast::channel(PAYLOAD_EVENTS_BUFFER_SIZE);
 
-        let (cached_payload_tx, cached_payload_rx) = watch::channel(None);
+        let (resolved_payloads_tx, resolved_payloads_rx) = mpsc::unbounded_channel();
 
         let service = Self {
             generator,
             payload_jobs: Vec::new(),
-            service_tx,
+            job_progress: Arc::default(),
+            max_jobs: DEFAULT_MAX_PAYLOAD_JOBS,
+            job_limit_policy: PayloadJobLimitPolicy::default(),
+            job_deadline: None,
+            expired_jobs: FuturesUnordered::new(),
+            service_tx: service_tx.downgrade(),
             command_rx: UnboundedReceiverStream::new(command_rx),
             metrics: Default::default(),
+            fee_recipient_metrics: FeeRecipientMetricsRegistry::new(
+                DEFAULT_MAX_FEE_RECIPIENT_LABELS,
+            ),
             chain_events,
             payload_events,
-            cached_payload_rx,
-            cached_payload_tx,
+            resolved_payloads: LruMap::new(ByLength::new(RESOLVED_PAYLOADS_CAPACITY)),
+            resolved_payloads_ttl: RESOLVED_PAYLOADS_TTL,
+            resolved_payloads_tx,
+            resolved_payloads_rx,
+            resolving: HashMap::default(),
+            shutdown_signal: None,
+            shutdown_guard: None,
+            shutting_down: false,
+            shutdown_tx: Vec::new(),
+            attr_validator: None,
+            attr_modifier: None,
         };
 
-        let handle = service.handle();
-        (service, handle)
+        (service, PayloadBuilderHandle::new(service_tx))
     }
 
-    /// Returns a handle to the service.
-    pub fn handle(&self) -> PayloadBuilderHandle<T> {
-        PayloadBuilderHandle::new(self.service_tx.clone())
+    /// Sets the maximum number of payload jobs that can be active at the same time.
+    ///
+    /// Defaults to [`DEFAULT_MAX_PAYLOAD_JOBS`]. Once the limit is reached, new jobs are handled
+    /// according to the configured [`PayloadJobLimitPolicy`], see
+    /// [`Self::with_job_limit_policy`].
+    pub const fn with_max_jobs(mut self, max_jobs: usize) -> Self {
+        self.max_jobs = max_jobs;
+        self
     }
 
-    /// Create clone on `payload_events` sending handle that could be used by builder to produce
-    /// additional events during block building
-    pub fn payload_events_handle(&self) -> broadcast::Sender<Events<T>> {
-        self.payload_events.clone()
+    /// Sets the maximum number of distinct fee recipients that get their own label in the revenue
+    /// metrics.
+    ///
+    /// Defaults to [`DEFAULT_MAX_FEE_RECIPIENT_LABELS`]. Revenue of all additional fee recipients
+    /// is recorded under the `other` label, which bounds the cardinality of the metrics.
+    pub const fn with_max_fee_recipient_labels(mut self, max: usize) -> Self {
+        self.fee_recipient_metrics.set_max_recipients(max);
+        self
     }
 
-    /// Returns true if the given payload is currently being built.
-    fn contains_payload(&self, id: PayloadId) -> bool {
-        self.payload_jobs.iter().any(|(_, job_id)| *job_id == id)
+    /// Sets the [`PayloadJobLimitPolicy`] that is applied once the maximum number of active jobs
+    /// is reached.
+    pub const fn with_job_limit_policy(mut self, policy: PayloadJobLimitPolicy) -> Self {
+        self.job_limit_policy = policy;
+        self
     }
 
-    /// Returns the best payload for the given identifier that has been built so far.
-    fn best_payload(&self, id: PayloadId) -> Option<Result<T::BuiltPayload, PayloadBuilderError>> {
-        let res = self
-            .payload_jobs
-            .iter()
-            .find(|(_, job_id)| *job_id == id)
-            .map(|(j, _)| j.best_payload().map(|p| p.into()));
-        if let Some(Ok(ref best)) = res {
-            self.metrics.set_best_revenue(best.block().number(), f64::from(best.fees()));
-        }
+    /// Sets the capacity of the payload events channel.
+    ///
+    /// Defaults to 20. Subscribers that fall behind by more than this many events will miss the
+    /// oldest events, so a larger buffer helps slow subscribers and builders that emit many events
+    /// per slot, at the cost of retaining more (potentially large) payloads in memory.
+    ///
+    /// Note: this replaces the events channel, so it must be configured before any subscriptions
+    /// are made via [`Self::payload_events_handle`].
+    pub fn with_events_buffer(mut self, size: usize) -> Self {
+        self.payload_events = broadcast::channel(size).0;
+        self
+    }
 
-        res
+    /// Sets the number of recently resolved payloads that are retained, so that repeated requests
+    /// for them can be served after the job has been terminated.
+    ///
+    /// Defaults to 16.
+    pub fn with_resolved_payloads_capacity(mut self, capacity: u32) -> Self {
+        self.resolved_payloads = LruMap::new(ByLength::new(capacity));
+        self
+    }
+
+    /// Sets how long a resolved payload is retained.
+    ///
+    /// Defaults to 60 seconds.
+    pub const fn with_resolved_payloads_ttl(mut self, ttl: Duration) -> Self {
+        self.resolved_payloads_ttl = ttl;
+        self
+    }
+
+    /// Sets a deadline for all payload jobs.
+    ///
+    /// Jobs that are still active once the deadline has elapsed since their creation are resolved
+    /// with [`PayloadKind::Earliest`] and dropped, even if the payload was never requested.
+    pub const fn with_job_deadline(mut self, deadline: Duration) -> Self {
+        self.job_deadline = Some(deadline);
+        self
+    }
+
+    /// Sets a validator that is invoked with the attributes of every new payload job before the
+    /// job is created.
+    ///
+    /// If the validator returns an error, no job is created and the error is returned to the
+    /// caller. This allows enforcing policies, e.g. on the fee recipient, independently of the
+    /// [`PayloadJobGenerator`].
+    pub fn with_attributes_validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&T::PayloadBuilderAttributes) -> Result<(), PayloadBuilderError> + Send + 'static,
+    {
+        self.attr_validator = Some(AttributesValidator(Box::new(validator)));
+        self
+    }
+
+    /// Sets a modifier that is applied to the attributes of every new payload job before the
+    /// job is created.
+    ///
+    /// If the modifier returns an error, no job is created and the error is returned to the
+    /// caller.
+    pub fn with_attributes_modifier<M>(mut self, modifier: M) -> Self
+    where
+        M: PayloadAttributesModifier<T::PayloadBuilderAttributes> + 'static,
+    {
+        self.attr_modifier = Some(Box::new(modifier));
+        self
+    }
+
+    /// Sets a shutdown signal that initiates the shutdown of the service once fired.
+    ///
+    /// On shutdown, the service stops accepting new payload jobs and resolves all remaining jobs
+    /// with [`PayloadKind::Earliest`]. The service completes once all resolved payloads have been
+    /// broadcast, and only then releases the [`GracefulShutdownGuard`].
+    pub fn with_graceful_shutdown(mut self, signal: GracefulShutdown) -> Self {
+        self.shutdown_signal = Some(signal);
+        self
+    }
+
+    /// Returns a handle to the service.
+    ///
+    /// The service does not keep its command channel open, so this returns `None` once all
+    /// [`PayloadBuilderHandle`]s have been dropped.
+    pub fn handle(&self) -> Option<PayloadBuilderHandle<T>> {
+        self.service_tx.upgrade().map(PayloadBuilderHandle::new)
+    }
+
+    /// Create clone on `payload_events` sending handle that could be used by builder to produce
+    /// additional events during block building
+    pub fn payload_events_handle(&self) -> broadcast::Sender<Events<T>> {
+        self.payload_events.clone()
+    }
+
+    /// Returns true if the given payload is currently being built.
+    fn contains_payload(&self, id: PayloadId) -> bool {
+        self.payload_jobs.iter().any(|job| job.id == id)
+    }
+
+    /// Returns `true` if the active payload job for the payload id of the given attributes was
+    /// created with different attributes.
+    fn has_conflicting_attributes(&self, attr: &T::PayloadBuilderAttributes) -> bool {
+        let id = attr.payload_id();
+        self.payload_jobs
+            .iter()
+            .find(|job| job.id == id)
+            .and_then(|job| job.job.lock().payload_attributes().ok())
+            .is_some_and(|existing| !same_attributes(&existing, attr))
+    }
+
+    /// Returns the build statistics of the payload job with the given identifier.
+    fn job_stats(&self, id: PayloadId) -> Option<PayloadJobStats> {
+        self.payload_jobs.iter().find(|job| job.id == id).and_then(|job| job.job.lock().stats())
+    }
+
+    /// Returns how long the payload job with the given identifier has been alive.
+    fn job_age(&self, id: PayloadId) -> Option<Duration> {
+        self.payload_jobs.iter().find(|job| job.id == id).map(|job| job.created_at.elapsed())
+    }
+
+    /// Returns the identifiers of all active payload jobs.
+    fn payload_ids(&self) -> Vec<PayloadId> {
+        self.payload_jobs.iter().map(|job| job.id).collect()
+    }
+
+    /// Returns the identifiers of all active payload jobs that build on top of the given parent.
+    fn payload_ids_for_parent(&self, parent: B256) -> Vec<PayloadId> {
+        self.payload_jobs.iter().filter(|job| job.parent == parent).map(|job| job.id).collect()
+    }
+
+    /// Returns the recently resolved payload for the given identifier, if it has not expired yet.
+    fn resolved_payload(&self, id: PayloadId) -> Option<&ResolvedPayload<T::BuiltPayload>> {
+        self.resolved_payloads
+            .peek(&id)
+            .filter(|resolved| resolved.resolved_at.elapsed() < self.resolved_payloads_ttl)
+    }
+
+    /// Subscribes to the payload events and returns the payloads that have been resolved recently,
+    /// oldest first.
+    ///
+    /// Resolved payloads are tracked before they are broadcast, so every payload that is built
+    /// before the returned receiver was created is included.
+    fn subscribe_built_payloads(
+        &mut self,
+    ) -> (Vec<T::BuiltPayload>, broadcast::Receiver<Events<T>>) {
+        let receiver = self.payload_events.subscribe();
+        while let Ok(resolved) = self.resolved_payloads_rx.try_recv() {
+            self.resolving.remove(&resolved.id);
+            self.resolved_payloads.insert(resolved.id, resolved);
+        }
+
+        let mut resolved = self
+            .resolved_payloads
+            .iter()
+            .map(|(_, resolved)| resolved)
+            .filter(|resolved| resolved.resolved_at.elapsed() < self.resolved_payloads_ttl)
+            .collect::<Vec<_>>();
+        resolved.sort_by_key(|resolved| resolved.resolved_at);
+        (resolved.into_iter().map(|resolved| resolved.payload.clone()).collect(), receiver)
+    }
+
+    /// Returns the best payload for the given identifier that has been built so far.
+    ///
+    /// Falls back to the resolved payload if the job has already been terminated and its payload
+    /// has been resolved.
+    fn best_payload(&self, id: PayloadId) -> Option<Result<T::BuiltPayload, PayloadBuilderError>> {
+        let job = self.payload_jobs.iter().find(|job| job.id == id);
+        let res = job
+            .map(|job| {
+                // fall back to the pre-built payload if the job has not built a payload yet
+                let job = job.job.lock();
+                job.best_payload().or_else(|err| job.pre_built_payload().ok_or(err))
+            })
+            .map(|res| res.map(|p| p.into()))
+            .or_else(|| self.resolved_payload(id).map(|resolved| Ok(resolved.payload.clone())));
+        if let Some(Ok(ref best)) = res {
+            self.metrics.set_best_revenue(best.block().number(), f64::from(best.fees()));
+            if let Some(job) = job {
+                job.fee_recipient_metrics.set_best_revenue(f64::from(best.fees()));
+            }
+        }
+
+        res
+    }
+
+    /// Returns a summary of the best payload for the given identifier that has been built so far.
+    fn best_payload_summary(
+        &self,
+        id: PayloadId,
+    ) -> Option<Result<PayloadSummary, PayloadBuilderError>> {
+        let job = self.payload_jobs.iter().find(|job| job.id == id);
+        let res = job.map(|job| job.job.lock().best_payload_summary()).or_else(|| {
+            self.resolved_payload(id).map(|resolved| Ok(PayloadSummary::new(&resolved.payload)))
+        });
+        if let Some(Ok(ref summary)) = res {
+            self.metrics.set_best_revenue(summary.block_number, f64::from(summary.fees));
+            if let Some(job) = job {
+                job.fee_recipient_metrics.set_best_revenue(f64::from(summary.fees));
+            }
+        }
+
+        res
     }
 
     /// Returns the best payload for the given identifier that has been built so far and terminates
@@ -302,48 +1137,325 @@ where
         &mut self,
         id: PayloadId,
         kind: PayloadKind,
-    ) -> Option<PayloadFuture<T::BuiltPayload>> {
+    ) -> Result<PayloadFuture<T::BuiltPayload>, PayloadResolveError> {
         debug!(target: "payload_builder", %id, "resolving payload job");
 
-        if let Some((cached, _, payload)) = &*self.cached_payload_rx.borrow() &&
-            *cached == id
-        {
-            return Some(Box::pin(core::future::ready(Ok(payload.clone()))));
+        self.prune_resolving();
+
+        // hand out the in-flight resolve so that all callers receive the same payload
+        if let Some(fut) = self.resolving.get(&id) {
+            debug!(target: "payload_builder", %id, "payload is already being resolved");
+            return Ok(unshare_payload_future(fut.clone()))
+        }
+
+        if let Some(resolved) = self.resolved_payload(id) {
+            return Ok(Box::pin(core::future::ready(Ok(resolved.payload.clone()))));
         }
 
-        let job = self.payload_jobs.iter().position(|(_, job_id)| *job_id == id)?;
-        let (fut, keep_alive) = self.payload_jobs[job].0.resolve_kind(kind);
-        let payload_timestamp = self.payload_jobs[job].0.payload_timestamp();
+        let Some(job) = self.payload_jobs.iter().position(|job| job.id == id) else {
+            // the payload may have been resolved already but is no longer retained
+            if let Some(resolved) = self.resolved_payloads.peek(&id) {
+                return Err(PayloadResolveError::JobAlreadyResolved {
+                    resolved_at: resolved.resolved_at,
+                })
+            }
+            return Err(PayloadResolveError::UnknownPayloadId)
+        };
+        let iterations = self.payload_jobs[job].sync_iterations();
+        self.metrics.inc_build_iterations(iterations);
+        self.metrics.set_payload_build_iterations(self.payload_jobs[job].iterations);
+
+        let (fut, keep_alive) = self.payload_jobs[job].job.lock().resolve_kind(kind);
+        let payload_timestamp = self.payload_jobs[job].job.lock().payload_timestamp();
+        let fee_recipient_metrics = self.payload_jobs[job].fee_recipient_metrics.clone();
 
         if keep_alive == KeepPayloadJobAlive::No {
-            let (_, id) = self.payload_jobs.swap_remove(job);
+            // the resolved payload is retained once the resolve future completed, a payload built
+            // before may be outdated by then
+            let ActivePayloadJob { id, .. } = self.payload_jobs.remove(job);
+            self.generator.on_job_removed(id);
             debug!(target: "payload_builder", %id, "terminated resolved job");
         }
 
+        let fut = share_payload_future(self.track_resolved_payload(
+            id,
+            fut,
+            keep_alive,
+            payload_timestamp,
+            fee_recipient_metrics,
+        ));
+        self.resolving.insert(id, fut.clone());
+        let fut = unshare_payload_future(fut);
+        let resolve_metrics = self.metrics.clone();
+        let started_at = Instant::now();
+        Ok(Box::pin(async move {
+            let res = fut.await;
+            resolve_metrics.record_resolve_duration(started_at.elapsed());
+            res
+        }))
+    }
+
+    /// Removes the resolve futures that can no longer be shared, because resolving failed or all
+    /// callers dropped them before the payload was resolved.
+    fn prune_resolving(&mut self) {
+        self.resolving.retain(|_, fut| {
+            !matches!(fut.peek(), Some(Err(_))) && fut.strong_count().is_some_and(|count| count > 1)
+        });
+    }
+
+    /// Wraps the resolve future of a job so that metrics, payload events and the resolved
+    /// payloads are updated once the payload is resolved.
+    ///
+    /// If the job is not kept alive, [`Events::Resolved`] is emitted once the future completed.
+    fn track_resolved_payload(
+        &self,
+        id: PayloadId,
+        fut: <Gen::Job as PayloadJob>::ResolvePayloadFuture,
+        keep_alive: KeepPayloadJobAlive,
+        payload_timestamp: Result<u64, PayloadBuilderError>,
+        fee_recipient_metrics: FeeRecipientMetrics,
+    ) -> PayloadFuture<T::BuiltPayload> {
         // Since the fees will not be known until the payload future is resolved / awaited, we wrap
         // the future in a new future that will update the metrics.
         let resolved_metrics = self.metrics.clone();
         let payload_events = self.payload_events.clone();
-        let cached_payload_tx = self.cached_payload_tx.clone();
+        let resolved_payloads_tx = self.resolved_payloads_tx.clone();
 
         let fut = async move {
             let res = fut.await;
             if let Ok(payload) = &res {
-                if payload_events.receiver_count() > 0 {
-                    payload_events.send(Events::BuiltPayload(payload.clone().into())).ok();
+                // track the payload before broadcasting it, so that subscribers that missed the
+                // event can still receive it from the resolved payloads
+                if let Ok(timestamp) = payload_timestamp {
+                    let _ = resolved_payloads_tx.send(ResolvedPayload {
+                        id,
+                        timestamp,
+                        payload: payload.clone().into(),
+                        resolved_at: Instant::now(),
+                    });
                 }
 
-                if let Ok(timestamp) = payload_timestamp {
-                    let _ = cached_payload_tx.send(Some((id, timestamp, payload.clone().into())));
+                if payload_events.receiver_count() > 0 {
+                    payload_events.send(Events::BuiltPayload(payload.clone().into())).ok();
                 }
 
                 resolved_metrics
                     .set_resolved_revenue(payload.block().number(), f64::from(payload.fees()));
+                fee_recipient_metrics.set_resolved_revenue(f64::from(payload.fees()));
+                resolved_metrics.record_resolved_payload(
+                    payload.block().header().gas_used(),
+                    payload.block().body().transactions().len(),
+                );
+            }
+            if keep_alive == KeepPayloadJobAlive::No {
+                payload_events.send(Events::Resolved(id)).ok();
             }
             res.map(|p| p.into())
         };
 
-        Some(Box::pin(fut))
+        Box::pin(fut)
+    }
+
+    /// Removes the payload job with the given identifier without resolving it.
+    ///
+    /// Returns `true` if a matching job was found.
+    fn cancel(&mut self, id: PayloadId) -> bool {
+        let Some(job) = self.payload_jobs.iter().position(|job| job.id == id) else {
+            trace!(target: "payload_builder", %id, "no matching payload job found to cancel");
+            return false
+        };
+
+        // dropping the job terminates it
+        let _ = self.payload_jobs.remove(job);
+        self.generator.on_job_removed(id);
+        self.metrics.inc_cancelled_jobs();
+        self.metrics.set_active_jobs(self.payload_jobs.len());
+        self.payload_events.send(Events::Cancelled(id)).ok();
+        debug!(target: "payload_builder", %id, "cancelled payload job");
+
+        true
+    }
+
+    /// Terminates all payload jobs that build on top of one of the given blocks, because these
+    /// blocks are no longer canonical.
+    fn invalidate_jobs(&mut self, reorged: &HashSet<B256>) {
+        let len = self.payload_jobs.len();
+        for idx in (0..len).rev() {
+            if !reorged.contains(&self.payload_jobs[idx].parent) {
+                continue
+            }
+
+            // dropping the job terminates it
+            let ActivePayloadJob { id, parent, .. } = self.payload_jobs.remove(idx);
+            self.generator.on_job_removed(id);
+            self.metrics.inc_invalidated_jobs();
+            self.payload_events.send(Events::Invalidated(id)).ok();
+            debug!(target: "payload_builder", %id, %parent, "terminated payload job building on reorged block");
+        }
+
+        if self.payload_jobs.len() != len {
+            self.metrics.set_active_jobs(self.payload_jobs.len());
+        }
+    }
+
+    /// Extends the deadline of the payload job with the given identifier.
+    ///
+    /// This extends both the deadline of the job itself and the deadline enforced by the service,
+    /// if configured.
+    fn extend_deadline(&mut self, id: PayloadId, extension: Duration) -> bool {
+        let Some(job) = self.payload_jobs.iter_mut().find(|job| job.id == id) else {
+            trace!(target: "payload_builder", %id, "no matching payload job found to extend");
+            return false
+        };
+
+        let mut extended = job.job.lock().extend_deadline(extension);
+        if let Some(deadline) = job.deadline.as_mut() &&
+            !deadline.is_elapsed()
+        {
+            let until = deadline.deadline() + extension;
+            deadline.as_mut().reset(until);
+            extended = true;
+        }
+
+        if extended {
+            debug!(target: "payload_builder", %id, ?extension, "extended payload job deadline");
+        }
+        extended
+    }
+
+    /// Initiates the shutdown of the service by resolving all active jobs.
+    ///
+    /// The service terminates once all resolved payloads have been delivered.
+    fn shutdown(&mut self) {
+        if self.shutting_down {
+            return
+        }
+        self.shutting_down = true;
+        info!(target: "payload_builder", jobs = self.payload_jobs.len(), "shutting down payload builder service");
+        for job in std::mem::take(&mut self.payload_jobs) {
+            let (fut, _) = job.job.lock().resolve_kind(PayloadKind::Earliest);
+            self.generator.on_job_removed(job.id);
+            let payload_timestamp = job.job.lock().payload_timestamp();
+            let fut = self.track_resolved_payload(
+                job.id,
+                fut,
+                KeepPayloadJobAlive::No,
+                payload_timestamp,
+                job.fee_recipient_metrics,
+            );
+            self.expired_jobs.push(fut);
+        }
+        self.metrics.set_active_jobs(0);
+    }
+
+    /// Broadcasts an [`Events::BetterPayload`] event if the best payload of the job has higher fees
+    /// than the last one that was broadcast for it.
+    ///
+    /// The fees are compared using [`PayloadJob::best_payload_summary`], so the payload is only
+    /// cloned if it is broadcast.
+    fn notify_better_payload(&self, job: &mut ActivePayloadJob<Gen::Job>) {
+        if self.payload_events.receiver_count() == 0 {
+            return
+        }
+        let best = job.job.lock();
+        if let Ok(summary) = best.best_payload_summary() &&
+            job.best_fees.is_none_or(|fees| summary.fees > fees) &&
+            let Ok(payload) = best.best_payload()
+        {
+            drop(best);
+            job.best_fees = Some(payload.fees());
+            self.payload_events.send(Events::BetterPayload(payload.into())).ok();
+        }
+    }
+
+    /// Applies the configured attributes modifier, if any.
+    fn modify_attributes(
+        &self,
+        attr: T::PayloadBuilderAttributes,
+    ) -> Result<T::PayloadBuilderAttributes, PayloadBuilderError> {
+        let Some(modifier) = &self.attr_modifier else { return Ok(attr) };
+        let (id, parent) = (attr.payload_id(), attr.parent());
+        modifier.modify(attr).inspect_err(|err| {
+            warn!(target: "payload_builder", %err, %id, %parent, "Failed to modify payload attributes");
+        })
+    }
+
+    /// Checks the given attributes against the configured validator, if any.
+    fn validate_attributes(
+        &self,
+        attr: &T::PayloadBuilderAttributes,
+    ) -> Result<(), PayloadBuilderError> {
+        let Some(validator) = &self.attr_validator else { return Ok(()) };
+        (validator.0)(attr).inspect_err(|err| {
+            warn!(target: "payload_builder", %err, id = %attr.payload_id(), parent = %attr.parent(), "Payload attributes rejected by validator");
+        })
+    }
+
+    /// Creates a new payload job for the given attributes via the generator and starts tracking
+    /// it.
+    fn create_payload_job(
+        &mut self,
+        attr: &T::PayloadBuilderAttributes,
+    ) -> Result<(), PayloadBuilderError>
+    where
+        Gen::Job: Send + Unpin + 'static,
+    {
+        let id = attr.payload_id();
+
+        if let Err(err) = self.ensure_job_capacity() {
+            self.metrics.inc_rejected_jobs();
+            warn!(target: "payload_builder", %err, %id, max_jobs = self.max_jobs, "Rejected new payload job");
+            return Err(err)
+        }
+
+        let job = self.generator.new_payload_job(attr.clone()).inspect_err(|err| {
+            self.metrics.inc_failed_jobs();
+            warn!(target: "payload_builder", %err, %id, "Failed to create payload builder job");
+        })?;
+
+        self.payload_jobs.push(ActivePayloadJob {
+            job: SpawnedPayloadJob::spawn(job, self.job_progress.clone()),
+            id,
+            parent: attr.parent(),
+            fee_recipient_metrics: self.fee_recipient_metrics.get(attr.suggested_fee_recipient()),
+            created_at: Instant::now(),
+            time_to_first_build: None,
+            iterations: 0,
+            best_fees: None,
+            deadline: self.job_deadline.map(|deadline| Box::pin(tokio::time::sleep(deadline))),
+        });
+        self.metrics.record_enqueued_job(self.payload_jobs.len());
+
+        Ok(())
+    }
+
+    /// Makes sure there's room for a new payload job according to the configured
+    /// [`PayloadJobLimitPolicy`].
+    ///
+    /// Returns [`PayloadBuilderError::TooManyJobs`] if the new job must be rejected.
+    fn ensure_job_capacity(&mut self) -> Result<(), PayloadBuilderError> {
+        if self.payload_jobs.len() < self.max_jobs {
+            return Ok(())
+        }
+
+        if self.job_limit_policy == PayloadJobLimitPolicy::EvictOldest &&
+            let Some(oldest) = self
+                .payload_jobs
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, job)| job.created_at)
+                .map(|(idx, _)| idx)
+        {
+            let ActivePayloadJob { id, .. } = self.payload_jobs.remove(oldest);
+            self.generator.on_job_removed(id);
+            warn!(target: "payload_builder", %id, max_jobs = self.max_jobs, "Too many active payload jobs, evicted oldest job");
+            self.metrics.inc_evicted_jobs();
+            self.metrics.set_active_jobs(self.payload_jobs.len());
+            self.payload_events.send(Events::Cancelled(id)).ok();
+            return Ok(())
+        }
+
+        Err(PayloadBuilderError::TooManyJobs)
     }
 }
 
@@ -356,17 +1468,15 @@ where
 {
     /// Returns the payload timestamp for the given payload.
     fn payload_timestamp(&self, id: PayloadId) -> Option<Result<u64, PayloadBuilderError>> {
-        if let Some((cached_id, timestamp, _)) = *self.cached_payload_rx.borrow() &&
-            cached_id == id
-        {
-            return Some(Ok(timestamp));
+        if let Some(resolved) = self.resolved_payload(id) {
+            return Some(Ok(resolved.timestamp));
         }
 
         let timestamp = self
             .payload_jobs
             .iter()
-            .find(|(_, job_id)| *job_id == id)
-            .map(|(j, _)| j.payload_timestamp());
+            .find(|job| job.id == id)
+            .map(|job| job.job.lock().payload_timestamp());
 
         if timestamp.is_none() {
             trace!(target: "payload_builder", %id, "no matching payload job found to get timestamp for");
@@ -374,6 +1484,40 @@ where
 
         timestamp
     }
+
+    /// Returns the payload attributes for the given payload.
+    fn payload_attributes(
+        &self,
+        id: PayloadId,
+    ) -> Option<Result<T::PayloadBuilderAttributes, PayloadBuilderError>> {
+        let attributes = self
+            .payload_jobs
+            .iter()
+            .find(|job| job.id == id)
+            .map(|job| job.job.lock().payload_attributes());
+
+        if attributes.is_none() {
+            trace!(target: "payload_builder", %id, "no matching payload job found to get attributes for");
+        }
+
+        attributes
+    }
+
+    /// Returns the payload attributes of all active payload jobs.
+    ///
+    /// Jobs that fail to return their attributes are skipped.
+    fn all_attributes(&self) -> Vec<(PayloadId, T::PayloadBuilderAttributes)> {
+        self.payload_jobs
+            .iter()
+            .filter_map(|job| match job.job.lock().payload_attributes() {
+                Ok(attributes) => Some((job.id, attributes)),
+                Err(err) => {
+                    trace!(target: "payload_builder", id=%job.id, %err, "failed to get payload attributes");
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 impl<Gen, St, T, N> Future for PayloadBuilderService<Gen, St, T>
@@ -381,7 +1525,7 @@ where
     T: PayloadTypes,
     N: NodePrimitives,
     Gen: PayloadJobGenerator + Unpin + 'static,
-    <Gen as PayloadJobGenerator>::Job: Unpin + 'static,
+    <Gen as PayloadJobGenerator>::Job: Send + Unpin + 'static,
     St: Stream<Item = CanonStateNotification<N>> + Send + Unpin + 'static,
     Gen::Job: PayloadJob<PayloadAttributes = T::PayloadBuilderAttributes>,
     <Gen::Job as PayloadJob>::BuiltPayload: Into<T::BuiltPayload>,
@@ -390,36 +1534,128 @@ where
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
+        this.job_progress.register(cx.waker());
         loop {
+            // initiate the shutdown once the shutdown signal fired
+            if let Some(signal) = this.shutdown_signal.as_mut() &&
+                let Poll::Ready(guard) = signal.poll_unpin(cx)
+            {
+                this.shutdown_signal = None;
+                this.shutdown_guard = Some(guard);
+                this.shutdown();
+            }
+
+            // track recently resolved payloads, this must happen before handling new commands so
+            // that repeated requests for a payload that was just resolved can be served
+            while let Poll::Ready(Some(resolved)) = this.resolved_payloads_rx.poll_recv(cx) {
+                this.resolving.remove(&resolved.id);
+                this.resolved_payloads.insert(resolved.id, resolved);
+            }
+            this.prune_resolving();
+
             // notify the generator of new chain events
             while let Poll::Ready(Some(new_head)) = this.chain_events.poll_next_unpin(cx) {
+                // terminate jobs that build on blocks that were reorged out
+                if let Some(reverted) = new_head.reverted() {
+                    let mut reorged =
+                        reverted.blocks_iter().map(|block| block.hash()).collect::<HashSet<_>>();
+                    for block in new_head.committed().blocks_iter() {
+                        reorged.remove(&block.hash());
+                    }
+                    this.invalidate_jobs(&reorged);
+                }
                 this.generator.on_new_state(new_head);
             }
 
-            // we poll all jobs first, so we always have the latest payload that we can report if
+            // we check all jobs first, so we always have the latest payload that we can report if
             // requests
-            // we don't care about the order of the jobs, so we can just swap_remove them
+            // jobs are kept in creation order and checked newest first, because the most recent
+            // job is the one the CL is most likely waiting on. Removing and re-inserting at the
+            // same index only shifts jobs that were already checked in this pass.
+            // No round-robin cursor is needed to keep this fair: every job builds on its own task,
+            // so the tokio scheduler shares the CPU between them, and this loop only checks each
+            // job's task once per pass.
             for idx in (0..this.payload_jobs.len()).rev() {
-                let (mut job, id) = this.payload_jobs.swap_remove(idx);
+                let mut job = this.payload_jobs.remove(idx);
+                let id = job.id;
+
+                // resolve and drop jobs that exceeded their deadline
+                let deadline_elapsed = job
+                    .deadline
+                    .as_mut()
+                    .is_some_and(|deadline| deadline.poll_unpin(cx).is_ready());
+                if deadline_elapsed {
+                    debug!(target: "payload_builder", %id, "payload job deadline elapsed, resolving");
+                    let (fut, _) = job.job.lock().resolve_kind(PayloadKind::Earliest);
+                    let payload_timestamp = job.job.lock().payload_timestamp();
+                    let fut = this.track_resolved_payload(
+                        id,
+                        fut,
+                        KeepPayloadJobAlive::No,
+                        payload_timestamp,
+                        job.fee_recipient_metrics,
+                    );
+                    this.expired_jobs.push(fut);
+                    this.generator.on_job_removed(id);
+                    this.metrics.set_active_jobs(this.payload_jobs.len());
+                    continue
+                }
+
+                // drain better payloads from the job, which is polled on its own task
+                let poll = job.job.poll_task(cx);
+                let iterations = job.sync_iterations();
+                this.metrics.inc_build_iterations(iterations);
+                if let Some(elapsed) = job.record_first_build() {
+                    this.metrics.record_time_to_first_build(elapsed);
+                }
+
+                // notify subscribers if the job has built a better payload, including the final
+                // payload of a finished job
+                if matches!(poll, Poll::Ready(Ok(_)) | Poll::Pending) {
+                    this.notify_better_payload(&mut job);
+                }
 
-                // drain better payloads from the job
-                match job.poll_unpin(cx) {
+                match poll {
                     Poll::Ready(Ok(_)) => {
+                        this.generator.on_job_removed(id);
                         this.metrics.set_active_jobs(this.payload_jobs.len());
                         trace!(target: "payload_builder", %id, "payload job finished");
+                        this.payload_events.send(Events::Finished(id)).ok();
                     }
                     Poll::Ready(Err(err)) => {
                         warn!(target: "payload_builder",%err, ?id, "Payload builder job failed; resolving payload");
+                        this.generator.on_job_removed(id);
                         this.metrics.inc_failed_jobs();
                         this.metrics.set_active_jobs(this.payload_jobs.len());
+                        this.payload_events
+                            .send(Events::JobFailed { id, error: err.to_string() })
+                            .ok();
                     }
                     Poll::Pending => {
-                        // still pending, put it back
-                        this.payload_jobs.push((job, id));
+                        // still pending, put it back at its position
+                        this.payload_jobs.insert(idx, job);
                     }
                 }
             }
 
+            // drive the resolution of expired jobs
+            while let Poll::Ready(Some(res)) = this.expired_jobs.poll_next_unpin(cx) {
+                if let Err(err) = res {
+                    warn!(target: "payload_builder", %err, "Failed to resolve expired payload job");
+                }
+            }
+
+            // terminate once all jobs have been resolved after a shutdown was requested
+            if this.shutting_down && this.expired_jobs.is_empty() {
+                for tx in this.shutdown_tx.drain(..) {
+                    let _ = tx.send(());
+                }
+                // release the guard only after all payloads have been delivered
+                drop(this.shutdown_guard.take());
+                debug!(target: "payload_builder", "payload builder service terminated");
+                return Poll::Ready(())
+            }
+
             // marker for exit condition
             let mut new_job = false;
 
@@ -427,47 +1663,162 @@ where
             while let Poll::Ready(Some(cmd)) = this.command_rx.poll_next_unpin(cx) {
                 match cmd {
                     PayloadServiceCommand::BuildNewPayload(attr, tx) => {
+                        if this.shutting_down {
+                            let _ = tx.send(Err(PayloadBuilderError::ShuttingDown));
+                            continue
+                        }
+                        let attr = match this.modify_attributes(attr) {
+                            Ok(attr) => attr,
+                            Err(err) => {
+                                let _ = tx.send(Err(err));
+                                continue
+                            }
+                        };
                         let id = attr.payload_id();
                         let mut res = Ok(id);
 
-                        if this.contains_payload(id) {
+                        if this.has_conflicting_attributes(&attr) {
+                            warn!(target: "payload_builder", %id, parent = %attr.parent(), "Payload job already in progress with different attributes");
+                            res = Err(PayloadBuilderError::DuplicatePayload(id));
+                        } else if this.contains_payload(id) {
                             debug!(target: "payload_builder",%id, parent = %attr.parent(), "Payload job already in progress, ignoring.");
+                        } else if let Err(err) = this.validate_attributes(&attr) {
+                            res = Err(err);
                         } else {
                             // no job for this payload yet, create one
-                            let parent = attr.parent();
-                            match this.generator.new_payload_job(attr.clone()) {
-                                Ok(job) => {
-                                    info!(target: "payload_builder", %id, %parent, "New payload job created");
+                            match this.create_payload_job(&attr) {
+                                Ok(()) => {
+                                    info!(target: "payload_builder", %id, parent = %attr.parent(), "New payload job created");
                                     this.metrics.inc_initiated_jobs();
                                     new_job = true;
-                                    this.payload_jobs.push((job, id));
                                     this.payload_events.send(Events::Attributes(attr)).ok();
                                 }
-                                Err(err) => {
-                                    this.metrics.inc_failed_jobs();
-                                    warn!(target: "payload_builder", %err, %id, "Failed to create payload builder job");
-                                    res = Err(err);
-                                }
+                                Err(err) => res = Err(err),
                             }
                         }
 
                         // return the id of the payload
                         let _ = tx.send(res);
                     }
+                    PayloadServiceCommand::BuildNewPayloadForce(attr, tx) => {
+                        if this.shutting_down {
+                            let _ = tx.send(Err(PayloadBuilderError::ShuttingDown));
+                            continue
+                        }
+                        let attr = match this.modify_attributes(attr) {
+                            Ok(attr) => attr,
+                            Err(err) => {
+                                let _ = tx.send(Err(err));
+                                continue
+                            }
+                        };
+                        let id = attr.payload_id();
+
+                        // keep the existing job if the new attributes are rejected
+                        if let Err(err) = this.validate_attributes(&attr) {
+                            let _ = tx.send(Err(err));
+                            continue
+                        }
+
+                        // terminate the existing job, if any
+                        let existing = this.payload_jobs.iter().position(|job| job.id == id);
+                        let replaced = existing.map(|idx| this.payload_jobs.remove(idx));
+                        if replaced.is_some() {
+                            this.generator.on_job_removed(id);
+                        }
+
+                        let res = match this.create_payload_job(&attr) {
+                            Ok(()) => {
+                                new_job = true;
+                                if replaced.is_some() {
+                                    info!(target: "payload_builder", %id, parent = %attr.parent(), "Payload job replaced");
+                                    this.metrics.inc_replaced_jobs();
+                                    this.payload_events.send(Events::AttributesReplaced(attr)).ok();
+                                } else {
+                                    info!(target: "payload_builder", %id, parent = %attr.parent(), "New payload job created");
+                                    this.metrics.inc_initiated_jobs();
+                                    this.payload_events.send(Events::Attributes(attr)).ok();
+                                }
+                                Ok(id)
+                            }
+                            Err(err) => {
+                                if replaced.is_some() {
+                                    this.metrics.set_active_jobs(this.payload_jobs.len());
+                                    this.payload_events.send(Events::Cancelled(id)).ok();
+                                }
+                                Err(err)
+                            }
+                        };
+
+                        let _ = tx.send(res);
+                    }
                     PayloadServiceCommand::BestPayload(id, tx) => {
                         let _ = tx.send(this.best_payload(id));
                     }
+                    PayloadServiceCommand::BestPayloadSummary(id, tx) => {
+                        let _ = tx.send(this.best_payload_summary(id));
+                    }
                     PayloadServiceCommand::PayloadTimestamp(id, tx) => {
                         let timestamp = this.payload_timestamp(id);
                         let _ = tx.send(timestamp);
                     }
+                    PayloadServiceCommand::PayloadAttributes(id, tx) => {
+                        let attributes = this.payload_attributes(id);
+                        let _ = tx.send(attributes);
+                    }
                     PayloadServiceCommand::Resolve(id, strategy, tx) => {
                         let _ = tx.send(this.resolve(id, strategy));
                     }
+                    PayloadServiceCommand::ResolveBatch(payloads, tx) => {
+                        let resolved = payloads
+                            .into_iter()
+                            .map(|(id, strategy)| this.resolve(id, strategy))
+                            .collect();
+                        let _ = tx.send(resolved);
+                    }
+                    PayloadServiceCommand::JobStats(id, tx) => {
+                        let _ = tx.send(this.job_stats(id));
+                    }
+                    PayloadServiceCommand::JobAge(id, tx) => {
+                        let _ = tx.send(this.job_age(id));
+                    }
+                    PayloadServiceCommand::PayloadIds(tx) => {
+                        let _ = tx.send(this.payload_ids());
+                    }
+                    PayloadServiceCommand::AllAttributes(tx) => {
+                        let _ = tx.send(this.all_attributes());
+                    }
+                    PayloadServiceCommand::JobsByParent(parent, tx) => {
+                        let _ = tx.send(this.payload_ids_for_parent(parent));
+                    }
+                    PayloadServiceCommand::Contains(id, tx) => {
+                        let _ = tx.send(this.contains_payload(id));
+                    }
+                    PayloadServiceCommand::Cancel(id, tx) => {
+                        let _ = tx.send(this.cancel(id));
+                    }
+                    PayloadServiceCommand::ExtendDeadline(id, extension, tx) => {
+                        let _ = tx.send(this.extend_deadline(id, extension));
+                    }
                     PayloadServiceCommand::Subscribe(tx) => {
                         let new_rx = this.payload_events.subscribe();
                         let _ = tx.send(new_rx);
                     }
+                    PayloadServiceCommand::SubscribeBuiltPayloads(tx) => {
+                        let _ = tx.send(this.subscribe_built_payloads());
+                    }
+                    PayloadServiceCommand::SubscribeTo(id, tx) => {
+                        // only subscribe if there are events left to be emitted for the payload
+                        let new_rx =
+                            this.contains_payload(id).then(|| this.payload_events.subscribe());
+                        let _ = tx.send(new_rx);
+                    }
+                    PayloadServiceCommand::Shutdown(tx) => {
+                        this.shutdown_tx.push(tx);
+                        this.shutdown();
+                        // poll again to drive the resolution of the remaining jobs
+                        new_job = true;
+                    }
                 }
             }
 
@@ -478,44 +1829,2004 @@ where
     }
 }
 
-/// Message type for the [`PayloadBuilderService`].
-pub enum PayloadServiceCommand<T: PayloadTypes> {
-    /// Start building a new payload.
-    BuildNewPayload(
-        T::PayloadBuilderAttributes,
-        oneshot::Sender<Result<PayloadId, PayloadBuilderError>>,
-    ),
-    /// Get the best payload so far
-    BestPayload(PayloadId, oneshot::Sender<Option<Result<T::BuiltPayload, PayloadBuilderError>>>),
-    /// Get the payload timestamp for the given payload
-    PayloadTimestamp(PayloadId, oneshot::Sender<Option<Result<u64, PayloadBuilderError>>>),
-    /// Resolve the payload and return the payload
-    Resolve(
-        PayloadId,
-        /* kind: */ PayloadKind,
-        oneshot::Sender<Option<PayloadFuture<T::BuiltPayload>>>,
-    ),
-    /// Payload service events
-    Subscribe(oneshot::Sender<broadcast::Receiver<Events<T>>>),
+/// Converts the resolve future into a [`SharedPayloadFuture`] that can be handed out to multiple
+/// callers.
+fn share_payload_future<P>(fut: PayloadFuture<P>) -> SharedPayloadFuture<P>
+where
+    P: Clone + Send + Sync + 'static,
+{
+    let fut: Pin<Box<dyn Future<Output = _> + Send>> =
+        Box::pin(fut.map(|res| res.map_err(Arc::new)));
+    fut.shared()
 }
 
-impl<T> fmt::Debug for PayloadServiceCommand<T>
+/// Converts the [`SharedPayloadFuture`] back into a [`PayloadFuture`].
+fn unshare_payload_future<P>(fut: SharedPayloadFuture<P>) -> PayloadFuture<P>
 where
-    T: PayloadTypes,
+    P: Clone + Send + Sync + 'static,
 {
+    Box::pin(fut.map(|res| {
+        res.map_err(|err| Arc::try_unwrap(err).unwrap_or_else(PayloadBuilderError::other))
+    }))
+}
+
+/// Returns `true` if both payload attributes describe the same payload.
+fn same_attributes<A: PayloadBuilderAttributes>(a: &A, b: &A) -> bool {
+    a.payload_id() == b.payload_id() &&
+        a.parent() == b.parent() &&
+        a.timestamp() == b.timestamp() &&
+        a.parent_beacon_block_root() == b.parent_beacon_block_root() &&
+        a.suggested_fee_recipient() == b.suggested_fee_recipient() &&
+        a.prev_randao() == b.prev_randao() &&
+        a.withdrawals() == b.withdrawals()
+}
+
+/// Validates payload attributes before a payload job is created for them.
+struct AttributesValidator<A>(Box<dyn Fn(&A) -> Result<(), PayloadBuilderError> + Send>);
+
+impl<A> fmt::Debug for AttributesValidator<A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::BuildNewPayload(f0, f1) => {
-                f.debug_tuple("BuildNewPayload").field(&f0).field(&f1).finish()
-            }
-            Self::BestPayload(f0, f1) => {
-                f.debug_tuple("BestPayload").field(&f0).field(&f1).finish()
-            }
-            Self::PayloadTimestamp(f0, f1) => {
-                f.debug_tuple("PayloadTimestamp").field(&f0).field(&f1).finish()
-            }
-            Self::Resolve(f0, f1, _f2) => f.debug_tuple("Resolve").field(&f0).field(&f1).finish(),
-            Self::Subscribe(f0) => f.debug_tuple("Subscribe").field(&f0).finish(),
-        }
+        f.debug_struct("AttributesValidator").finish_non_exhaustive()
+    }
+}
+
+/// An active payload job tracked by the [`PayloadBuilderService`].
+#[derive(Debug)]
+struct ActivePayloadJob<Job> {
+    /// The job that builds the payload.
+    job: SpawnedPayloadJob<Job>,
+    /// The identifier of the payload that is being built.
+    id: PayloadId,
+    /// The hash of the parent block the payload is built on.
+    parent: B256,
+    /// Revenue metrics of the fee recipient of the payload.
+    fee_recipient_metrics: FeeRecipientMetrics,
+    /// When the job was created.
+    created_at: Instant,
+    /// Time it took the job to build its first payload, as reported by its stats, `None` until
+    /// a payload has been built.
+    time_to_first_build: Option<Duration>,
+    /// Number of build iterations the job has completed, as of the last poll.
+    iterations: u64,
+    /// Fees of the best payload that has been broadcast as [`Events::BetterPayload`].
+    best_fees: Option<U256>,
+    /// Deadline after which the job is resolved and dropped, if configured.
+    deadline: Option<Pin<Box<Sleep>>>,
+}
+
+impl<Job: PayloadJob> ActivePayloadJob<Job> {
+    /// Syncs the tracked build iterations with the stats reported by the job and returns the number
+    /// of iterations completed since the last sync.
+    fn sync_iterations(&mut self) -> u64 {
+        let Some(stats) = self.job.lock().stats() else { return 0 };
+        let completed = stats.iterations.saturating_sub(self.iterations);
+        self.iterations = stats.iterations;
+        completed
+    }
+
+    /// Records the time it took the job to build its first payload, once the job reports it in
+    /// its stats.
+    ///
+    /// Returns the recorded time only on the first call after the job built a payload.
+    fn record_first_build(&mut self) -> Option<Duration> {
+        if self.time_to_first_build.is_some() {
+            return None
+        }
+        let elapsed = self.job.lock().stats()?.time_to_first_build?;
+        self.time_to_first_build = Some(elapsed);
+        Some(elapsed)
+    }
+}
+
+/// A payload job that is polled on its own task.
+///
+/// The job is shared with the task, so the service can still query and resolve it. The task is
+/// aborted once this is dropped.
+#[derive(Debug)]
+struct SpawnedPayloadJob<Job> {
+    /// The job that is polled by the task.
+    job: Arc<Mutex<Job>>,
+    /// The task that polls the job to completion.
+    task: JoinHandle<Result<(), PayloadBuilderError>>,
+}
+
+impl<Job> SpawnedPayloadJob<Job>
+where
+    Job: PayloadJob + Send + Unpin + 'static,
+{
+    /// Spawns a task that polls the given job and wakes the service every time the job has been
+    /// polled, so it can pick up better payloads.
+    fn spawn(job: Job, progress: Arc<AtomicWaker>) -> Self {
+        let job = Arc::new(Mutex::new(job));
+        let task = tokio::spawn({
+            let job = job.clone();
+            futures_util::future::poll_fn(move |cx| {
+                let poll = job.lock().poll_unpin(cx);
+                progress.wake();
+                poll
+            })
+        });
+        Self { job, task }
+    }
+}
+
+impl<Job> SpawnedPayloadJob<Job> {
+    /// Locks the job.
+    ///
+    /// The lock is also held while the job is being polled, so this must not be held across an
+    /// await point.
+    fn lock(&self) -> MutexGuard<'_, Job> {
+        self.job.lock()
+    }
+
+    /// Polls the task for the outcome of the job.
+    ///
+    /// Panics of the job are propagated.
+    fn poll_task(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), PayloadBuilderError>> {
+        self.task.poll_unpin(cx).map(|res| match res {
+            Ok(res) => res,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(err) => Err(PayloadBuilderError::other(err)),
+        })
+    }
+}
+
+impl<Job> Drop for SpawnedPayloadJob<Job> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A payload that has been resolved recently.
+#[derive(Debug)]
+struct ResolvedPayload<P> {
+    /// The identifier of the payload.
+    id: PayloadId,
+    /// The timestamp of the payload.
+    timestamp: BlockTimestamp,
+    /// The resolved payload.
+    payload: P,
+    /// When the payload was resolved.
+    resolved_at: Instant,
+}
+
+/// Determines how the [`PayloadBuilderService`] handles new jobs once the maximum number of active
+/// jobs is reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PayloadJobLimitPolicy {
+    /// Reject the new job with [`PayloadBuilderError::TooManyJobs`].
+    #[default]
+    Reject,
+    /// Evict the oldest active job to make room for the new one.
+    EvictOldest,
+}
+
+/// Message type for the [`PayloadBuilderService`].
+pub enum PayloadServiceCommand<T: PayloadTypes> {
+    /// Start building a new payload.
+    BuildNewPayload(
+        T::PayloadBuilderAttributes,
+        oneshot::Sender<Result<PayloadId, PayloadBuilderError>>,
+    ),
+    /// Start building a new payload, replacing any existing job for the same payload id.
+    BuildNewPayloadForce(
+        T::PayloadBuilderAttributes,
+        oneshot::Sender<Result<PayloadId, PayloadBuilderError>>,
+    ),
+    /// Get the best payload so far
+    BestPayload(PayloadId, oneshot::Sender<Option<Result<T::BuiltPayload, PayloadBuilderError>>>),
+    /// Get a summary of the best payload so far
+    BestPayloadSummary(
+        PayloadId,
+        oneshot::Sender<Option<Result<PayloadSummary, PayloadBuilderError>>>,
+    ),
+    /// Get the payload timestamp for the given payload
+    PayloadTimestamp(PayloadId, oneshot::Sender<Option<Result<u64, PayloadBuilderError>>>),
+    /// Get the payload attributes for the given payload
+    PayloadAttributes(
+        PayloadId,
+        oneshot::Sender<Option<Result<T::PayloadBuilderAttributes, PayloadBuilderError>>>,
+    ),
+    /// Resolve the payload and return the payload
+    Resolve(
+        PayloadId,
+        /* kind: */ PayloadKind,
+        oneshot::Sender<Result<PayloadFuture<T::BuiltPayload>, PayloadResolveError>>,
+    ),
+    /// Resolve multiple payloads and return them in the same order
+    ResolveBatch(
+        Vec<(PayloadId, PayloadKind)>,
+        oneshot::Sender<Vec<Result<PayloadFuture<T::BuiltPayload>, PayloadResolveError>>>,
+    ),
+    /// Get the build statistics of the payload job
+    JobStats(PayloadId, oneshot::Sender<Option<PayloadJobStats>>),
+    /// Get how long the payload job has been alive
+    JobAge(PayloadId, oneshot::Sender<Option<Duration>>),
+    /// Get the identifiers of all active payload jobs
+    PayloadIds(oneshot::Sender<Vec<PayloadId>>),
+    /// Get the payload attributes of all active payload jobs
+    AllAttributes(oneshot::Sender<Vec<(PayloadId, T::PayloadBuilderAttributes)>>),
+    /// Get the identifiers of all active payload jobs that build on top of the given parent
+    JobsByParent(B256, oneshot::Sender<Vec<PayloadId>>),
+    /// Check whether a payload job with the given identifier is active
+    Contains(PayloadId, oneshot::Sender<bool>),
+    /// Cancel the payload job without resolving it
+    Cancel(PayloadId, oneshot::Sender<bool>),
+    /// Extend the deadline of the payload job by the given duration
+    ExtendDeadline(PayloadId, Duration, oneshot::Sender<bool>),
+    /// Payload service events
+    Subscribe(oneshot::Sender<broadcast::Receiver<Events<T>>>),
+    /// Payload service events, along with the recently built payloads
+    SubscribeBuiltPayloads(oneshot::Sender<(Vec<T::BuiltPayload>, broadcast::Receiver<Events<T>>)>),
+    /// Payload service events, if there's an active job for the given payload
+    SubscribeTo(PayloadId, oneshot::Sender<Option<broadcast::Receiver<Events<T>>>>),
+    /// Resolve all active jobs and terminate the service
+    Shutdown(oneshot::Sender<()>),
+}
+
+impl<T> fmt::Debug for PayloadServiceCommand<T>
+where
+    T: PayloadTypes,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BuildNewPayload(f0, f1) => {
+                f.debug_tuple("BuildNewPayload").field(&f0).field(&f1).finish()
+            }
+            Self::BuildNewPayloadForce(f0, f1) => {
+                f.debug_tuple("BuildNewPayloadForce").field(&f0).field(&f1).finish()
+            }
+            Self::BestPayload(f0, f1) => {
+                f.debug_tuple("BestPayload").field(&f0).field(&f1).finish()
+            }
+            Self::BestPayloadSummary(f0, f1) => {
+                f.debug_tuple("BestPayloadSummary").field(&f0).field(&f1).finish()
+            }
+            Self::PayloadTimestamp(f0, f1) => {
+                f.debug_tuple("PayloadTimestamp").field(&f0).field(&f1).finish()
+            }
+            Self::PayloadAttributes(f0, f1) => {
+                f.debug_tuple("PayloadAttributes").field(&f0).field(&f1).finish()
+            }
+            Self::Resolve(f0, f1, _f2) => f.debug_tuple("Resolve").field(&f0).field(&f1).finish(),
+            Self::ResolveBatch(f0, _f1) => f.debug_tuple("ResolveBatch").field(&f0).finish(),
+            Self::JobStats(f0, f1) => f.debug_tuple("JobStats").field(&f0).field(&f1).finish(),
+            Self::JobAge(f0, f1) => f.debug_tuple("JobAge").field(&f0).field(&f1).finish(),
+            Self::PayloadIds(f0) => f.debug_tuple("PayloadIds").field(&f0).finish(),
+            Self::AllAttributes(f0) => f.debug_tuple("AllAttributes").field(&f0).finish(),
+            Self::JobsByParent(f0, f1) => {
+                f.debug_tuple("JobsByParent").field(&f0).field(&f1).finish()
+            }
+            Self::Contains(f0, f1) => f.debug_tuple("Contains").field(&f0).field(&f1).finish(),
+            Self::Cancel(f0, f1) => f.debug_tuple("Cancel").field(&f0).field(&f1).finish(),
+            Self::ExtendDeadline(f0, f1, f2) => {
+                f.debug_tuple("ExtendDeadline").field(&f0).field(&f1).field(&f2).finish()
+            }
+            Self::Subscribe(f0) => f.debug_tuple("Subscribe").field(&f0).finish(),
+            Self::SubscribeBuiltPayloads(f0) => {
+                f.debug_tuple("SubscribeBuiltPayloads").field(&f0).finish()
+            }
+            Self::SubscribeTo(f0, f1) => {
+                f.debug_tuple("SubscribeTo").field(&f0).field(&f1).finish()
+            }
+            Self::Shutdown(f0) => f.debug_tuple("Shutdown").field(&f0).finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        spawn_test_payload_service, test_payload_service, TestPayloadJob, TestPayloadJobGenerator,
+    };
+    use alloy_primitives::Address;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use reth_ethereum_engine_primitives::{
+        EthBuiltPayload, EthPayloadBuilderAttributes, EthPayloadTypes,
+    };
+    use reth_execution_types::{Chain, ExecutionOutcome};
+    use reth_payload_builder_primitives::PayloadEvent;
+    use reth_primitives_traits::Block as _;
+    use reth_tasks::TaskManager;
+
+    fn test_attributes(seed: u8) -> EthPayloadBuilderAttributes {
+        EthPayloadBuilderAttributes { id: PayloadId::new([seed; 8]), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn cancel_payload_job() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let attr = test_attributes(1);
+        let id = handle.send_new_payload(attr).await.unwrap().unwrap();
+
+        assert!(handle.best_payload(id).await.is_some());
+        assert!(handle.cancel(id).await);
+        assert!(handle.best_payload(id).await.is_none());
+
+        // cancelling an unknown job is a no-op
+        assert!(!handle.cancel(id).await);
+    }
+
+    #[tokio::test]
+    async fn list_payload_ids() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        assert!(handle.payload_ids().await.is_empty());
+
+        let first = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let second = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+
+        let ids = handle.payload_ids().await;
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&first) && ids.contains(&second));
+
+        assert!(handle.contains(first).await);
+        assert!(handle.cancel(first).await);
+        assert!(!handle.contains(first).await);
+        assert_eq!(handle.payload_ids().await, vec![second]);
+    }
+
+    #[tokio::test]
+    async fn all_payload_attributes() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        assert!(handle.all_attributes().await.is_empty());
+
+        let mut expected = Vec::new();
+        for seed in 1..=3 {
+            let attr = test_attributes(seed);
+            let id = handle.send_new_payload(attr.clone()).await.unwrap().unwrap();
+            expected.push((id, attr));
+        }
+
+        let all = handle.all_attributes().await;
+        assert_eq!(all.len(), 3);
+        for pair in &expected {
+            assert!(all.contains(pair));
+        }
+    }
+
+    #[tokio::test]
+    async fn cap_fee_recipient_labels() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        let mut service = service.with_max_fee_recipient_labels(2);
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+
+        for seed in 1..=4 {
+            let attr = EthPayloadBuilderAttributes {
+                suggested_fee_recipient: Address::with_last_byte(seed % 3),
+                ..test_attributes(seed)
+            };
+            let rx = handle.send_new_payload(attr);
+            let _ = service.poll_unpin(&mut cx);
+            rx.await.unwrap().unwrap();
+        }
+
+        // the third distinct recipient is collapsed into the shared label
+        let recipients = &service.fee_recipient_metrics.recipients;
+        assert_eq!(recipients.len(), 2);
+        assert!(recipients.contains_key(&Address::with_last_byte(1)));
+        assert!(recipients.contains_key(&Address::with_last_byte(2)));
+        assert!(service.fee_recipient_metrics.other.is_some());
+    }
+
+    #[tokio::test]
+    async fn payload_ids_for_parent() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let parent = B256::with_last_byte(1);
+
+        let first = EthPayloadBuilderAttributes { parent, ..test_attributes(1) };
+        let second = EthPayloadBuilderAttributes { parent, ..test_attributes(2) };
+        let other =
+            EthPayloadBuilderAttributes { parent: B256::with_last_byte(2), ..test_attributes(3) };
+        let first = handle.send_new_payload(first).await.unwrap().unwrap();
+        let second = handle.send_new_payload(second).await.unwrap().unwrap();
+        let other = handle.send_new_payload(other).await.unwrap().unwrap();
+
+        let ids = handle.payload_ids_for_parent(parent).await;
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&first) && ids.contains(&second));
+        assert_eq!(handle.payload_ids_for_parent(B256::with_last_byte(2)).await, vec![other]);
+        assert!(handle.payload_ids_for_parent(B256::ZERO).await.is_empty());
+
+        assert!(handle.cancel(first).await);
+        assert_eq!(handle.payload_ids_for_parent(parent).await, vec![second]);
+    }
+
+    #[tokio::test]
+    async fn active_job_count() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        assert_eq!(handle.active_job_count().await, 0);
+
+        let mut expected = Vec::new();
+        for seed in 1..=3 {
+            expected.push(handle.send_new_payload(test_attributes(seed)).await.unwrap().unwrap());
+        }
+
+        let ids = handle.payload_ids().await;
+        assert_eq!(handle.active_job_count().await, 3);
+        assert!(expected.iter().all(|id| ids.contains(id)));
+    }
+
+    #[tokio::test]
+    async fn reject_jobs_over_limit() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        tokio::spawn(service.with_max_jobs(2));
+
+        let first = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let second = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+        let res = handle.send_new_payload(test_attributes(3)).await.unwrap();
+        assert!(matches!(res, Err(PayloadBuilderError::TooManyJobs)));
+
+        let ids = handle.payload_ids().await;
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&first) && ids.contains(&second));
+    }
+
+    #[tokio::test]
+    async fn evict_oldest_job_over_limit() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        tokio::spawn(
+            service.with_max_jobs(2).with_job_limit_policy(PayloadJobLimitPolicy::EvictOldest),
+        );
+
+        let first = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let second = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+        let third = handle.send_new_payload(test_attributes(3)).await.unwrap().unwrap();
+
+        let ids = handle.payload_ids().await;
+        assert_eq!(ids.len(), 2);
+        assert!(!ids.contains(&first));
+        assert!(ids.contains(&second) && ids.contains(&third));
+    }
+
+    #[tokio::test]
+    async fn contains_active_job_until_resolved() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        assert!(handle.contains(id).await);
+
+        // the job is not kept alive after it was resolved
+        handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+        assert!(!handle.contains(id).await);
+        assert!(!handle.contains(PayloadId::new([2; 8])).await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resolve_job_after_deadline() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        tokio::spawn(service.with_job_deadline(Duration::from_secs(12)));
+
+        let mut events = handle.subscribe().await.unwrap().into_built_payload_stream();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        assert!(handle.contains(id).await);
+
+        tokio::time::sleep(Duration::from_secs(13)).await;
+
+        let payload = events.next().await.unwrap();
+        assert_eq!(payload.id(), id);
+        assert!(!handle.contains(id).await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn extend_job_deadline() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        tokio::spawn(service.with_job_deadline(Duration::from_secs(12)));
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        assert!(handle.extend_deadline(id, Duration::from_secs(4)).await);
+
+        tokio::time::sleep(Duration::from_secs(13)).await;
+        assert!(handle.contains(id).await);
+
+        tokio::time::sleep(Duration::from_secs(4)).await;
+        assert!(!handle.contains(id).await);
+
+        // the job already completed
+        assert!(!handle.extend_deadline(id, Duration::from_secs(4)).await);
+    }
+
+    #[tokio::test]
+    async fn shutdown_resolves_active_jobs() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        let service = tokio::spawn(service);
+
+        let mut events = handle.subscribe().await.unwrap().into_built_payload_stream();
+        let first = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let second = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+
+        handle.shutdown().await;
+        service.await.unwrap();
+
+        let resolved = [events.next().await.unwrap().id(), events.next().await.unwrap().id()];
+        assert!(resolved.contains(&first) && resolved.contains(&second));
+        assert!(events.next().await.is_none());
+
+        // the service is gone
+        assert!(!handle.contains(first).await);
+    }
+
+    #[tokio::test]
+    async fn cache_payload_attributes() {
+        let (to_service, mut commands) = mpsc::unbounded_channel();
+        let store = PayloadStore::<EthPayloadTypes>::new(PayloadBuilderHandle::new(to_service));
+
+        let attr = test_attributes(1);
+        let id = attr.id;
+        let (events_tx, _) = broadcast::channel(PAYLOAD_EVENTS_BUFFER_SIZE);
+        let events = events_tx.clone();
+        let service = tokio::spawn(async move {
+            let mut lookups = 0;
+            while let Some(cmd) = commands.recv().await {
+                match cmd {
+                    PayloadServiceCommand::PayloadAttributes(_, tx) => {
+                        let _ = tx.send(Some(Ok(attr.clone())));
+                        lookups += 1;
+                    }
+                    PayloadServiceCommand::Subscribe(tx) => {
+                        let _ = tx.send(events.subscribe());
+                    }
+                    _ => {}
+                }
+            }
+            lookups
+        });
+
+        for _ in 0..2 {
+            assert_eq!(store.payload_attributes(id).await.unwrap().unwrap().id, id);
+        }
+
+        // replaced and dropped jobs are looked up again
+        events_tx.send(Events::AttributesReplaced(test_attributes(1))).unwrap();
+        for _ in 0..2 {
+            assert_eq!(store.payload_attributes(id).await.unwrap().unwrap().id, id);
+        }
+        events_tx.send(Events::Resolved(id)).unwrap();
+        assert_eq!(store.payload_attributes(id).await.unwrap().unwrap().id, id);
+
+        drop(store);
+        assert_eq!(service.await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn resolve_with_configured_kind() {
+        let (to_service, mut commands) = mpsc::unbounded_channel();
+        let handle = PayloadBuilderHandle::<EthPayloadTypes>::new(to_service);
+        let service = tokio::spawn(async move {
+            let mut kinds = Vec::new();
+            while let Some(cmd) = commands.recv().await {
+                if let PayloadServiceCommand::Resolve(_, kind, tx) = cmd {
+                    kinds.push(kind);
+                    let _ = tx.send(Err(PayloadResolveError::UnknownPayloadId));
+                }
+            }
+            kinds
+        });
+
+        let id = PayloadId::new([1; 8]);
+        let store = PayloadStore::new(handle.clone());
+        assert!(store.try_resolve(id).await.is_err());
+        assert!(store.try_resolve_latest(id).await.is_err());
+        let deadline_store =
+            PayloadStore::new(handle).with_resolve_kind(PayloadKind::WaitForDeadline);
+        assert!(deadline_store.try_resolve(id).await.is_err());
+
+        drop((store, deadline_store));
+        assert_eq!(
+            service.await.unwrap(),
+            vec![PayloadKind::Earliest, PayloadKind::WaitForDeadline, PayloadKind::WaitForDeadline]
+        );
+    }
+
+    #[tokio::test]
+    async fn track_active_jobs_on_enqueue() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let (service, handle) =
+            metrics::with_local_recorder(&recorder, test_payload_service::<EthPayloadTypes>);
+        tokio::spawn(service);
+
+        let metric = |name: &str| {
+            snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .find_map(|(key, _, _, value)| (key.key().name() == name).then_some(value))
+        };
+
+        for jobs in 1..=3u8 {
+            handle.send_new_payload(test_attributes(jobs)).await.unwrap().unwrap();
+            assert_eq!(
+                metric("payloads.active_jobs"),
+                Some(DebugValue::Gauge(f64::from(jobs).into()))
+            );
+            assert_eq!(
+                metric("payloads.payload_jobs_enqueued"),
+                Some(DebugValue::Histogram(vec![f64::from(jobs).into()]))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn standalone_service() {
+        let (service, handle) =
+            PayloadBuilderService::<_, _, EthPayloadTypes>::standalone(TestPayloadJobGenerator);
+        tokio::spawn(service);
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let payload = handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+        assert_eq!(payload.id(), id);
+        assert!(!handle.contains(id).await);
+    }
+
+    #[tokio::test]
+    async fn weak_handle_does_not_keep_channel_open() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        let weak = handle.downgrade();
+
+        let upgraded = weak.upgrade().unwrap();
+        let id = upgraded.send_new_payload(test_attributes(1));
+        drop(upgraded);
+
+        // the service itself does not keep the channel open
+        drop(handle);
+        assert!(weak.clone().upgrade().is_none());
+        assert!(service.handle().is_none());
+
+        drop(service);
+        assert!(id.await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn graceful_shutdown_resolves_active_jobs() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        let manager = TaskManager::current();
+        manager
+            .executor()
+            .spawn_critical_with_graceful_shutdown_signal("payload builder service", |shutdown| {
+                service.with_graceful_shutdown(shutdown)
+            });
+
+        let mut events = handle.subscribe().await.unwrap().into_built_payload_stream();
+        let first = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let second = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+
+        // returns once the service released its guard
+        manager.graceful_shutdown();
+
+        let resolved = [events.next().await.unwrap().id(), events.next().await.unwrap().id()];
+        assert!(resolved.contains(&first) && resolved.contains(&second));
+        assert!(events.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reject_new_jobs_on_shutdown() {
+        let (mut service, handle) = test_payload_service::<EthPayloadTypes>();
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+
+        let (tx, shutdown_rx) = oneshot::channel();
+        handle.to_service.send(PayloadServiceCommand::Shutdown(tx)).unwrap();
+        let rx = handle.send_new_payload(test_attributes(1));
+
+        assert!(service.poll_unpin(&mut cx).is_ready());
+        assert!(matches!(rx.await.unwrap(), Err(PayloadBuilderError::ShuttingDown)));
+        shutdown_rx.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn default_job_limit() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+
+        for seed in 0..DEFAULT_MAX_PAYLOAD_JOBS as u8 {
+            handle.send_new_payload(test_attributes(seed)).await.unwrap().unwrap();
+        }
+        assert_eq!(handle.active_job_count().await, DEFAULT_MAX_PAYLOAD_JOBS);
+
+        let res = handle.send_new_payload(test_attributes(u8::MAX)).await.unwrap();
+        assert!(matches!(res, Err(PayloadBuilderError::TooManyJobs)));
+    }
+
+    #[tokio::test]
+    async fn broadcast_better_payload() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let mut events = handle.subscribe().await.unwrap().receiver;
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        assert!(matches!(events.recv().await.unwrap(), Events::Attributes(attr) if attr.id == id));
+        assert!(
+            matches!(events.recv().await.unwrap(), Events::BetterPayload(payload) if payload.id() == id)
+        );
+
+        // the payload does not improve, so no further events are emitted until the job is
+        // cancelled
+        let _ = handle.payload_ids().await;
+        assert!(handle.cancel(id).await);
+        assert!(
+            matches!(events.recv().await.unwrap(), Events::Cancelled(cancelled) if cancelled == id)
+        );
+    }
+
+    /// A generator for jobs that fail on the first poll.
+    #[derive(Debug, Default)]
+    struct FailingPayloadJobGenerator;
+
+    impl PayloadJobGenerator for FailingPayloadJobGenerator {
+        type Job = FailingPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            Ok(FailingPayloadJob { attr })
+        }
+    }
+
+    #[derive(Debug)]
+    struct FailingPayloadJob {
+        attr: EthPayloadBuilderAttributes,
+    }
+
+    impl Future for FailingPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            Poll::Ready(Err(PayloadBuilderError::MissingParentBlock(self.attr.parent)))
+        }
+    }
+
+    impl PayloadJob for FailingPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture =
+            futures_util::future::Ready<Result<EthBuiltPayload, PayloadBuilderError>>;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            Err(PayloadBuilderError::MissingPayload)
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            Ok(self.attr.clone())
+        }
+
+        fn resolve_kind(
+            &mut self,
+            _kind: PayloadKind,
+        ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            (futures_util::future::ready(self.best_payload()), KeepPayloadJobAlive::No)
+        }
+    }
+
+    /// A generator for jobs that never build a payload besides the pre-built empty payload.
+    #[derive(Debug, Default)]
+    struct PreBuiltPayloadJobGenerator;
+
+    impl PayloadJobGenerator for PreBuiltPayloadJobGenerator {
+        type Job = PreBuiltPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            let block = alloy_consensus::Block::<_>::default().seal_slow();
+            let empty = EthBuiltPayload::new(attr.id, Arc::new(block), U256::ZERO, None);
+            Ok(PreBuiltPayloadJob { attr, empty })
+        }
+    }
+
+    #[derive(Debug)]
+    struct PreBuiltPayloadJob {
+        attr: EthPayloadBuilderAttributes,
+        empty: EthBuiltPayload,
+    }
+
+    impl Future for PreBuiltPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for PreBuiltPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture =
+            futures_util::future::Ready<Result<EthBuiltPayload, PayloadBuilderError>>;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            Err(PayloadBuilderError::MissingPayload)
+        }
+
+        fn pre_built_payload(&self) -> Option<EthBuiltPayload> {
+            Some(self.empty.clone())
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            Ok(self.attr.clone())
+        }
+
+        fn resolve_kind(
+            &mut self,
+            _kind: PayloadKind,
+        ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            (futures_util::future::ready(Ok(self.empty.clone())), KeepPayloadJobAlive::No)
+        }
+    }
+
+    #[tokio::test]
+    async fn best_payload_falls_back_to_pre_built_payload() {
+        let (service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            PreBuiltPayloadJobGenerator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        tokio::spawn(service);
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let payload = handle.best_payload(id).await.unwrap().unwrap();
+        assert_eq!(payload.id(), id);
+        assert!(payload.block().body().transactions.is_empty());
+    }
+
+    /// A generator for jobs whose resolve future takes a while and yields a new payload every time
+    /// the job is resolved.
+    #[derive(Debug, Default)]
+    struct DelayedResolvePayloadJobGenerator {
+        resolved: Arc<std::sync::atomic::AtomicU64>,
+    }
+
+    impl PayloadJobGenerator for DelayedResolvePayloadJobGenerator {
+        type Job = DelayedResolvePayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            Ok(DelayedResolvePayloadJob { attr, resolved: Arc::clone(&self.resolved) })
+        }
+    }
+
+    #[derive(Debug)]
+    struct DelayedResolvePayloadJob {
+        attr: EthPayloadBuilderAttributes,
+        resolved: Arc<std::sync::atomic::AtomicU64>,
+    }
+
+    impl DelayedResolvePayloadJob {
+        fn payload(&self, number: u64) -> EthBuiltPayload {
+            let header = alloy_consensus::Header { number, ..Default::default() };
+            let block = alloy_consensus::Block { header, body: Default::default() }.seal_slow();
+            EthBuiltPayload::new(self.attr.id, Arc::new(block), U256::ZERO, None)
+        }
+    }
+
+    impl Future for DelayedResolvePayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for DelayedResolvePayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture = PayloadFuture<EthBuiltPayload>;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            Ok(self.payload(0))
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            Ok(self.attr.clone())
+        }
+
+        fn resolve_kind(
+            &mut self,
+            _kind: PayloadKind,
+        ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            let number = self.resolved.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+            let payload = self.payload(number);
+            let fut = async move {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok(payload)
+            };
+            (Box::pin(fut), KeepPayloadJobAlive::No)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn share_concurrent_resolves() {
+        let generator = DelayedResolvePayloadJobGenerator::default();
+        let resolved = Arc::clone(&generator.resolved);
+        let (service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            generator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        tokio::spawn(service);
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let (first, second) = tokio::join!(
+            handle.try_resolve_kind(id, PayloadKind::Earliest),
+            handle.try_resolve_kind(id, PayloadKind::Earliest)
+        );
+        let (first, second) = (first.unwrap(), second.unwrap());
+
+        // both callers received the resolved payload rather than the best payload so far
+        assert_eq!(first.block().hash(), second.block().hash());
+        assert_eq!(first.block().number(), 1);
+        assert_eq!(resolved.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        // later requests are served from the resolved payloads
+        let third = handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+        assert_eq!(third.block().hash(), first.block().hash());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn prune_abandoned_resolves() {
+        let (mut service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            DelayedResolvePayloadJobGenerator::default(),
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+
+        let rx = handle.send_new_payload(test_attributes(1));
+        let _ = service.poll_unpin(&mut cx);
+        let id = rx.await.unwrap().unwrap();
+
+        let fut = service.resolve(id, PayloadKind::Earliest).unwrap();
+        let _ = service.poll_unpin(&mut cx);
+        assert!(service.resolving.contains_key(&id));
+
+        // the caller gave up before the payload was resolved
+        drop(fut);
+        let _ = service.poll_unpin(&mut cx);
+        assert!(service.resolving.is_empty());
+    }
+
+    /// Returns an interval that simulates the build iterations of a job, the first one completes a
+    /// second after the job was created.
+    fn build_interval() -> tokio::time::Interval {
+        let period = Duration::from_secs(1);
+        tokio::time::interval_at(Instant::now() + period, period)
+    }
+
+    /// Advances the paused clock by a second and lets the spawned jobs run.
+    async fn advance_jobs() {
+        tokio::time::advance(Duration::from_secs(1)).await;
+        tokio::task::yield_now().await;
+    }
+
+    /// A generator for jobs that complete a build iteration every second.
+    #[derive(Debug, Default)]
+    struct IteratingPayloadJobGenerator;
+
+    impl PayloadJobGenerator for IteratingPayloadJobGenerator {
+        type Job = IteratingPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            Ok(IteratingPayloadJob {
+                inner: TestPayloadJobGenerator.new_payload_job(attr)?,
+                interval: build_interval(),
+                iterations: 0,
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct IteratingPayloadJob {
+        inner: TestPayloadJob,
+        interval: tokio::time::Interval,
+        iterations: u64,
+    }
+
+    impl Future for IteratingPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let this = self.get_mut();
+            while this.interval.poll_tick(cx).is_ready() {
+                this.iterations += 1;
+            }
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for IteratingPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture = <TestPayloadJob as PayloadJob>::ResolvePayloadFuture;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            self.inner.best_payload()
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            self.inner.payload_attributes()
+        }
+
+        fn resolve_kind(
+            &mut self,
+            kind: PayloadKind,
+        ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            self.inner.resolve_kind(kind)
+        }
+
+        fn stats(&self) -> Option<PayloadJobStats> {
+            Some(PayloadJobStats { iterations: self.iterations, ..Default::default() })
+        }
+    }
+
+    /// A generator for jobs that are always ready to be polled again and count their polls.
+    #[derive(Debug, Default)]
+    struct BusyPayloadJobGenerator {
+        polls: Arc<std::sync::Mutex<HashMap<PayloadId, usize>>>,
+    }
+
+    impl PayloadJobGenerator for BusyPayloadJobGenerator {
+        type Job = BusyPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            Ok(BusyPayloadJob {
+                id: attr.payload_id(),
+                inner: TestPayloadJobGenerator.new_payload_job(attr)?,
+                polls: Arc::clone(&self.polls),
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct BusyPayloadJob {
+        id: PayloadId,
+        inner: TestPayloadJob,
+        polls: Arc<std::sync::Mutex<HashMap<PayloadId, usize>>>,
+    }
+
+    impl Future for BusyPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            *self.polls.lock().unwrap().entry(self.id).or_default() += 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for BusyPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture = <TestPayloadJob as PayloadJob>::ResolvePayloadFuture;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            self.inner.best_payload()
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            self.inner.payload_attributes()
+        }
+
+        fn resolve_kind(
+            &mut self,
+            kind: PayloadKind,
+        ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            self.inner.resolve_kind(kind)
+        }
+    }
+
+    #[tokio::test]
+    async fn check_newest_job_first() {
+        let (mut service, handle) = test_payload_service::<EthPayloadTypes>();
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+        let mut events = service.payload_events.subscribe();
+
+        let rxs =
+            (1..=4).map(|seed| handle.send_new_payload(test_attributes(seed))).collect::<Vec<_>>();
+
+        // cancelling a job retains the order of the remaining jobs
+        let (tx, rx) = oneshot::channel();
+        handle.to_service.send(PayloadServiceCommand::Cancel(test_attributes(2).id, tx)).unwrap();
+        let _ = service.poll_unpin(&mut cx);
+        assert!(rx.await.unwrap());
+
+        let mut ids = Vec::new();
+        for rx in rxs {
+            ids.push(rx.await.unwrap().unwrap());
+        }
+        ids.remove(1);
+        ids.reverse();
+
+        let mut broadcast = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let Events::BetterPayload(payload) = event {
+                broadcast.push(payload.id());
+            }
+        }
+        assert_eq!(broadcast, ids);
+    }
+
+    #[tokio::test]
+    async fn busy_job_does_not_starve_others() {
+        let generator = BusyPayloadJobGenerator::default();
+        let polls = Arc::clone(&generator.polls);
+        let (service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            generator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        tokio::spawn(service);
+
+        let first = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let second = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+
+        // both jobs keep getting polled, although each of them is always ready to be polled again
+        let progressed = |id| polls.lock().unwrap().get(&id).is_some_and(|polls| *polls > 3);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !progressed(first) || !progressed(second) {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    /// A generator for jobs whose first build iteration blocks until the other job is building as
+    /// well, which only succeeds if the jobs are polled concurrently.
+    #[derive(Debug, Default)]
+    struct RendezvousPayloadJobGenerator {
+        building: Arc<(std::sync::Mutex<usize>, std::sync::Condvar)>,
+    }
+
+    impl PayloadJobGenerator for RendezvousPayloadJobGenerator {
+        type Job = RendezvousPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            Ok(RendezvousPayloadJob {
+                inner: TestPayloadJobGenerator.new_payload_job(attr)?,
+                building: Arc::clone(&self.building),
+                built: false,
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct RendezvousPayloadJob {
+        inner: TestPayloadJob,
+        building: Arc<(std::sync::Mutex<usize>, std::sync::Condvar)>,
+        built: bool,
+    }
+
+    impl Future for RendezvousPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let this = self.get_mut();
+            if this.built {
+                return Poll::Pending
+            }
+
+            let (building, started) = &*this.building;
+            let mut building = building.lock().unwrap();
+            *building += 1;
+            started.notify_all();
+            let (_, res) = started
+                .wait_timeout_while(building, Duration::from_secs(5), |building| *building < 2)
+                .unwrap();
+            if res.timed_out() {
+                let err = std::io::Error::other("payload jobs were not built concurrently");
+                return Poll::Ready(Err(PayloadBuilderError::other(err)))
+            }
+
+            this.built = true;
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for RendezvousPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture = <TestPayloadJob as PayloadJob>::ResolvePayloadFuture;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            if !self.built {
+                return Err(PayloadBuilderError::MissingPayload)
+            }
+            self.inner.best_payload()
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            self.inner.payload_attributes()
+        }
+
+        fn resolve_kind(
+            &mut self,
+            kind: PayloadKind,
+        ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            self.inner.resolve_kind(kind)
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn jobs_progress_concurrently() {
+        let (service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            RendezvousPayloadJobGenerator::default(),
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        tokio::spawn(service);
+        let mut events = handle.subscribe().await.unwrap().receiver;
+
+        let first = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let second = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+
+        let mut built = Vec::new();
+        while built.len() < 2 {
+            match events.recv().await.unwrap() {
+                Events::BetterPayload(payload) => built.push(payload.id()),
+                Events::JobFailed { error, .. } => panic!("{error}"),
+                _ => {}
+            }
+        }
+        assert!(built.contains(&first) && built.contains(&second));
+    }
+
+    /// A generator for jobs that only build a payload a second after they were created.
+    #[derive(Debug, Default)]
+    struct DelayedBuildPayloadJobGenerator;
+
+    impl PayloadJobGenerator for DelayedBuildPayloadJobGenerator {
+        type Job = DelayedBuildPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            Ok(DelayedBuildPayloadJob {
+                inner: TestPayloadJobGenerator.new_payload_job(attr)?,
+                build: build_interval(),
+                created_at: Instant::now(),
+                time_to_first_build: None,
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct DelayedBuildPayloadJob {
+        inner: TestPayloadJob,
+        build: tokio::time::Interval,
+        created_at: Instant,
+        time_to_first_build: Option<Duration>,
+    }
+
+    impl Future for DelayedBuildPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let this = self.get_mut();
+            if this.time_to_first_build.is_none() && this.build.poll_tick(cx).is_ready() {
+                this.time_to_first_build = Some(this.created_at.elapsed());
+            }
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for DelayedBuildPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture = <TestPayloadJob as PayloadJob>::ResolvePayloadFuture;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            if self.time_to_first_build.is_none() {
+                return Err(PayloadBuilderError::MissingPayload)
+            }
+            self.inner.best_payload()
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            self.inner.payload_attributes()
+        }
+
+        fn resolve_kind(
+            &mut self,
+            kind: PayloadKind,
+        ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            self.inner.resolve_kind(kind)
+        }
+
+        fn stats(&self) -> Option<PayloadJobStats> {
+            Some(PayloadJobStats {
+                time_to_first_build: self.time_to_first_build,
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn track_time_to_first_build() {
+        let (mut service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            DelayedBuildPayloadJobGenerator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+
+        let rx = handle.send_new_payload(test_attributes(1));
+        let _ = service.poll_unpin(&mut cx);
+        rx.await.unwrap().unwrap();
+        assert_eq!(service.payload_jobs[0].time_to_first_build, None);
+
+        advance_jobs().await;
+        let _ = service.poll_unpin(&mut cx);
+        assert_eq!(service.payload_jobs[0].time_to_first_build, Some(Duration::from_secs(1)));
+
+        // only the first build is recorded
+        advance_jobs().await;
+        let _ = service.poll_unpin(&mut cx);
+        assert_eq!(service.payload_jobs[0].time_to_first_build, Some(Duration::from_secs(1)));
+        assert_eq!(service.payload_jobs[0].record_first_build(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn track_build_iterations() {
+        let (mut service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            IteratingPayloadJobGenerator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+
+        let rx = handle.send_new_payload(test_attributes(1));
+        let _ = service.poll_unpin(&mut cx);
+        let id = rx.await.unwrap().unwrap();
+        for _ in 0..3 {
+            advance_jobs().await;
+        }
+        let _ = service.poll_unpin(&mut cx);
+        assert_eq!(service.payload_jobs[0].id, id);
+        assert_eq!(service.payload_jobs[0].iterations, 3);
+
+        advance_jobs().await;
+        let _ = service.poll_unpin(&mut cx);
+        assert_eq!(service.payload_jobs[0].iterations, 4);
+    }
+
+    /// A generator for jobs whose best payload changes every second.
+    #[derive(Debug, Default)]
+    struct ImprovingPayloadJobGenerator;
+
+    impl PayloadJobGenerator for ImprovingPayloadJobGenerator {
+        type Job = ImprovingPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            Ok(ImprovingPayloadJob { attr, interval: build_interval(), iterations: 0 })
+        }
+    }
+
+    #[derive(Debug)]
+    struct ImprovingPayloadJob {
+        attr: EthPayloadBuilderAttributes,
+        interval: tokio::time::Interval,
+        iterations: usize,
+    }
+
+    impl ImprovingPayloadJob {
+        /// Fees of the best payload after each build iteration.
+        const FEES: [u64; 5] = [1, 1, 2, 1, 3];
+    }
+
+    impl Future for ImprovingPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let this = self.get_mut();
+            while this.interval.poll_tick(cx).is_ready() {
+                this.iterations = (this.iterations + 1).min(Self::FEES.len() - 1);
+            }
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for ImprovingPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture =
+            futures_util::future::Ready<Result<EthBuiltPayload, PayloadBuilderError>>;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            let block = alloy_consensus::Block::<_>::default().seal_slow();
+            let fees = U256::from(Self::FEES[self.iterations]);
+            Ok(EthBuiltPayload::new(self.attr.id, Arc::new(block), fees, None))
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            Ok(self.attr.clone())
+        }
+
+        fn resolve_kind(
+            &mut self,
+            _kind: PayloadKind,
+        ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            (futures_util::future::ready(self.best_payload()), KeepPayloadJobAlive::No)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn broadcast_only_improved_payloads() {
+        let (mut service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            ImprovingPayloadJobGenerator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+        let mut events = service.payload_events.subscribe();
+
+        let rx = handle.send_new_payload(test_attributes(1));
+        let _ = service.poll_unpin(&mut cx);
+        rx.await.unwrap().unwrap();
+        for _ in 1..ImprovingPayloadJob::FEES.len() {
+            advance_jobs().await;
+            let _ = service.poll_unpin(&mut cx);
+        }
+
+        let mut fees = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let Events::BetterPayload(payload) = event {
+                fees.push(payload.fees());
+            }
+        }
+        assert_eq!(fees, [1, 2, 3].map(U256::from));
+    }
+
+    #[tokio::test]
+    async fn broadcast_failed_job() {
+        let (service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            FailingPayloadJobGenerator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        tokio::spawn(service);
+        let mut events = handle.subscribe().await.unwrap().receiver;
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        assert!(matches!(events.recv().await.unwrap(), Events::Attributes(_)));
+        assert!(
+            matches!(events.recv().await.unwrap(), Events::JobFailed { id: failed, .. } if failed == id)
+        );
+        assert!(!handle.contains(id).await);
+    }
+
+    #[tokio::test]
+    async fn wait_for_payload() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let store = PayloadStore::new(handle.clone());
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let payload = store.wait_for_payload(id, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(payload.id(), id);
+
+        let unknown = PayloadId::new([2; 8]);
+        let err = store.wait_for_payload(unknown, Duration::from_secs(1)).await.unwrap_err();
+        assert!(matches!(err, WaitForPayloadError::UnknownPayload(id) if id == unknown));
+    }
+
+    #[tokio::test]
+    async fn job_stats() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        assert_eq!(handle.job_stats(id).await, Some(PayloadJobStats::default()));
+        assert!(handle.job_stats(PayloadId::new([2; 8])).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn best_payload_summary() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        let best = handle.best_payload(id).await.unwrap().unwrap();
+        let summary = handle.best_payload_summary(id).await.unwrap().unwrap();
+        assert_eq!(summary, PayloadSummary::new(&best));
+        assert_eq!(summary.block_hash, best.block().hash());
+        assert_eq!(summary.tx_count, 0);
+
+        assert!(handle.best_payload_summary(PayloadId::new([2; 8])).await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn job_age() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        assert_eq!(handle.job_age(id).await, Some(Duration::from_secs(3)));
+        assert!(handle.job_age(PayloadId::new([2; 8])).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn configurable_events_buffer() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        let service = service.with_events_buffer(PAYLOAD_EVENTS_BUFFER_SIZE * 4);
+        let events_tx = service.payload_events_handle();
+        tokio::spawn(service);
+
+        let mut events = handle.subscribe().await.unwrap().receiver;
+        let count = PAYLOAD_EVENTS_BUFFER_SIZE * 2;
+        for seed in 0..count {
+            events_tx.send(Events::Cancelled(PayloadId::new([seed as u8; 8]))).unwrap();
+        }
+
+        for seed in 0..count {
+            let event = events.recv().await.unwrap();
+            assert!(
+                matches!(event, Events::Cancelled(id) if id == PayloadId::new([seed as u8; 8]))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn best_payload_blocking() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        // the blocking accessor must be called outside of the async context
+        tokio::task::spawn_blocking(move || {
+            let payload = handle.best_payload_blocking(id).unwrap().unwrap();
+            assert_eq!(payload.id(), id);
+            assert!(handle.best_payload_blocking(PayloadId::new([2; 8])).is_none());
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn retain_resolved_payload() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        let resolved = handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+        assert!(!handle.contains(id).await);
+
+        let best = handle.best_payload(id).await.unwrap().unwrap();
+        assert_eq!(best.block().hash(), resolved.block().hash());
+        let resolved_again = handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+        assert_eq!(resolved_again.block().hash(), resolved.block().hash());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expire_resolved_payload() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        tokio::spawn(service.with_resolved_payloads_ttl(Duration::from_secs(1)));
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+        assert!(handle.best_payload(id).await.is_some());
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(handle.best_payload(id).await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resolve_error_reasons() {
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        tokio::spawn(service.with_resolved_payloads_ttl(Duration::from_secs(1)));
+
+        let unknown = handle.try_resolve_kind(PayloadId::new([2; 8]), PayloadKind::Earliest).await;
+        assert!(matches!(unknown, Err(PayloadResolveError::UnknownPayloadId)));
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+        let resolved_at = Instant::now();
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let res = handle.try_resolve_kind(id, PayloadKind::Earliest).await;
+        assert!(
+            matches!(res, Err(PayloadResolveError::JobAlreadyResolved { resolved_at: at }) if at <= resolved_at)
+        );
+
+        // the deprecated API collapses both cases
+        #[expect(deprecated)]
+        let res = handle.resolve_kind(id, PayloadKind::Earliest).await;
+        assert!(res.is_none());
+    }
+
+    #[tokio::test]
+    async fn best_payload_while_resolving() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        // resolve the job without awaiting the resolved payload
+        let (tx, rx) = oneshot::channel();
+        handle
+            .to_service
+            .send(PayloadServiceCommand::Resolve(id, PayloadKind::Earliest, tx))
+            .unwrap();
+        let fut = rx.await.unwrap().unwrap();
+
+        // a payload built before resolving isn't served in place of the resolved payload
+        assert!(!handle.contains(id).await);
+        assert!(handle.best_payload(id).await.is_none());
+
+        let resolved = fut.await.unwrap();
+        let best = handle.best_payload(id).await.unwrap().unwrap();
+        assert_eq!(best.block().hash(), resolved.block().hash());
+    }
+
+    #[tokio::test]
+    async fn replace_payload_job() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let mut events = handle.subscribe().await.unwrap().receiver;
+
+        let attr = test_attributes(1);
+        let id = handle.send_and_replace_payload(attr.clone()).await.unwrap().unwrap();
+        assert!(matches!(events.recv().await.unwrap(), Events::Attributes(_)));
+        assert!(matches!(events.recv().await.unwrap(), Events::BetterPayload(_)));
+
+        let replacement = EthPayloadBuilderAttributes { timestamp: 1, ..attr };
+        assert_eq!(handle.send_and_replace_payload(replacement).await.unwrap().unwrap(), id);
+        assert!(
+            matches!(events.recv().await.unwrap(), Events::AttributesReplaced(attr) if attr.timestamp == 1)
+        );
+        assert_eq!(handle.payload_ids().await, vec![id]);
+        assert_eq!(handle.payload_timestamp(id).await.unwrap().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn duplicate_payload_with_identical_attributes() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        assert_eq!(handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap(), id);
+        assert_eq!(handle.payload_ids().await, vec![id]);
+    }
+
+    #[tokio::test]
+    async fn duplicate_payload_with_conflicting_attributes() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+
+        let attr = test_attributes(1);
+        let id = handle.send_new_payload(attr.clone()).await.unwrap().unwrap();
+
+        let conflicting = EthPayloadBuilderAttributes { timestamp: 1, ..attr };
+        let res = handle.send_new_payload(conflicting).await.unwrap();
+        assert!(matches!(res, Err(PayloadBuilderError::DuplicatePayload(dup)) if dup == id));
+
+        // the existing job is left untouched
+        assert_eq!(handle.payload_ids().await, vec![id]);
+        assert_eq!(handle.payload_timestamp(id).await.unwrap().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn reject_attributes_by_validator() {
+        let disallowed = Address::repeat_byte(1);
+        let (service, handle) = test_payload_service::<EthPayloadTypes>();
+        tokio::spawn(service.with_attributes_validator(move |attr| {
+            if attr.suggested_fee_recipient == disallowed {
+                return Err(PayloadBuilderError::Other("disallowed fee recipient".into()))
+            }
+            Ok(())
+        }));
+
+        let attr = EthPayloadBuilderAttributes {
+            suggested_fee_recipient: disallowed,
+            ..test_attributes(1)
+        };
+        let res = handle.send_new_payload(attr.clone()).await.unwrap();
+        assert!(matches!(res, Err(PayloadBuilderError::Other(_))));
+        let res = handle.send_and_replace_payload(attr).await.unwrap();
+        assert!(matches!(res, Err(PayloadBuilderError::Other(_))));
+        assert!(handle.payload_ids().await.is_empty());
+
+        let id = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+        assert_eq!(handle.payload_ids().await, vec![id]);
+    }
+
+    #[tokio::test]
+    async fn modify_attributes_before_job_creation() {
+        let fee_recipient = Address::repeat_byte(1);
+        let (mut service, handle) = test_payload_service::<EthPayloadTypes>();
+        service = service.with_attributes_modifier(move |attr: EthPayloadBuilderAttributes| {
+            if attr.timestamp == u64::MAX {
+                return Err(PayloadBuilderError::Other("unsupported timestamp".into()))
+            }
+            Ok(EthPayloadBuilderAttributes { suggested_fee_recipient: fee_recipient, ..attr })
+        });
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+
+        let rx = handle.send_new_payload(test_attributes(1));
+        let _ = service.poll_unpin(&mut cx);
+        let id = rx.await.unwrap().unwrap();
+
+        let attr = service.payload_jobs[0].job.lock().payload_attributes().unwrap();
+        assert_eq!(attr.payload_id(), id);
+        assert_eq!(attr.suggested_fee_recipient, fee_recipient);
+
+        // jobs are rejected if the modifier fails
+        let rx = handle.send_new_payload(EthPayloadBuilderAttributes {
+            timestamp: u64::MAX,
+            ..test_attributes(2)
+        });
+        let _ = service.poll_unpin(&mut cx);
+        assert!(matches!(rx.await.unwrap(), Err(PayloadBuilderError::Other(_))));
+        assert_eq!(service.payload_ids(), vec![id]);
+    }
+
+    #[tokio::test]
+    async fn resolve_batch() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+
+        let mut ids = Vec::new();
+        for seed in 1..=3 {
+            ids.push(handle.send_new_payload(test_attributes(seed)).await.unwrap().unwrap());
+        }
+        let unknown = PayloadId::new([4; 8]);
+
+        let batch = [ids[2], unknown, ids[0], ids[1]]
+            .into_iter()
+            .map(|id| (id, PayloadKind::Earliest))
+            .collect();
+        let resolved = handle.resolve_batch(batch).await.unwrap();
+
+        assert_eq!(resolved.len(), 4);
+        assert_eq!(resolved[0].as_ref().unwrap().id(), ids[2]);
+        assert!(matches!(resolved[1], Err(PayloadResolveError::UnknownPayloadId)));
+        assert_eq!(resolved[2].as_ref().unwrap().id(), ids[0]);
+        assert_eq!(resolved[3].as_ref().unwrap().id(), ids[1]);
+
+        // the test jobs are not kept alive after being resolved
+        assert!(handle.payload_ids().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn subscribe_to_unknown_payload() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+
+        let mut events = handle.subscribe_to(PayloadId::new([1; 8])).await.unwrap();
+        assert!(events.is_terminated());
+        assert!(events.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn subscribe_to_terminates_when_resolved() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        let mut events = handle.subscribe_to(id).await.unwrap();
+        handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+
+        assert!(
+            matches!(events.next().await.unwrap(), Events::BuiltPayload(payload) if payload.id() == id)
+        );
+        assert!(
+            matches!(events.next().await.unwrap(), Events::Resolved(resolved) if resolved == id)
+        );
+        assert!(events.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn invalidate_jobs_on_reorged_parent() {
+        let (chain_tx, chain_rx) = mpsc::unbounded_channel();
+        let (service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            TestPayloadJobGenerator,
+            UnboundedReceiverStream::new(chain_rx),
+        );
+        tokio::spawn(service);
+        let mut events = handle.subscribe().await.unwrap().into_stream();
+
+        let block = |number| {
+            let block = alloy_consensus::Block {
+                header: alloy_consensus::Header { number, ..Default::default() },
+                body: Default::default(),
+            };
+            reth_primitives_traits::RecoveredBlock::new_sealed(block.seal_slow(), Vec::new())
+        };
+        let (reorged, canonical) = (block(1), block(2));
+
+        let reorged_attr =
+            EthPayloadBuilderAttributes { parent: reorged.hash(), ..test_attributes(1) };
+        let canonical_attr =
+            EthPayloadBuilderAttributes { parent: canonical.hash(), ..test_attributes(2) };
+        let reorged_id = handle.send_new_payload(reorged_attr).await.unwrap().unwrap();
+        let canonical_id = handle.send_new_payload(canonical_attr).await.unwrap().unwrap();
+        let unrelated_id = handle.send_new_payload(test_attributes(3)).await.unwrap().unwrap();
+
+        let old = Arc::new(Chain::new(vec![reorged], ExecutionOutcome::default(), None));
+        let new = Arc::new(Chain::new(vec![canonical], ExecutionOutcome::default(), None));
+        chain_tx.send(CanonStateNotification::Reorg { old, new }).unwrap();
+
+        loop {
+            if let Events::Invalidated(id) = events.next().await.unwrap().unwrap() {
+                assert_eq!(id, reorged_id);
+                break
+            }
+        }
+
+        let ids = handle.payload_ids().await;
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&canonical_id) && ids.contains(&unrelated_id));
+    }
+
+    #[tokio::test]
+    async fn subscribe_to_terminates_when_cancelled() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        let mut events = handle.subscribe_to(id).await.unwrap();
+        assert!(handle.cancel(id).await);
+
+        assert!(
+            matches!(events.next().await.unwrap(), Events::Cancelled(cancelled) if cancelled == id)
+        );
+        assert!(events.next().await.is_none());
+    }
+
+    /// A generator for jobs that are kept alive after being resolved.
+    #[derive(Debug, Default)]
+    struct KeepAlivePayloadJobGenerator;
+
+    impl PayloadJobGenerator for KeepAlivePayloadJobGenerator {
+        type Job = KeepAlivePayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            Ok(KeepAlivePayloadJob(TestPayloadJobGenerator.new_payload_job(attr)?))
+        }
+    }
+
+    #[derive(Debug)]
+    struct KeepAlivePayloadJob(TestPayloadJob);
+
+    impl Future for KeepAlivePayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for KeepAlivePayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture = <TestPayloadJob as PayloadJob>::ResolvePayloadFuture;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            self.0.best_payload()
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            self.0.payload_attributes()
+        }
+
+        fn resolve_kind(
+            &mut self,
+            kind: PayloadKind,
+        ) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            (self.0.resolve_kind(kind).0, KeepPayloadJobAlive::Yes)
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribe_to_outlives_kept_alive_resolve() {
+        let (service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            KeepAlivePayloadJobGenerator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        tokio::spawn(service);
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+
+        let mut events = handle.subscribe_to(id).await.unwrap();
+        handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+        assert!(matches!(events.next().await.unwrap(), Events::BuiltPayload(_)));
+
+        // the job is still active, so the subscription goes on until it's dropped
+        assert!(!events.is_terminated());
+        assert!(handle.cancel(id).await);
+        assert!(matches!(events.next().await.unwrap(), Events::Cancelled(_)));
+        assert!(events.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn subscribe_to_payload_id() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let first = PayloadId::new([1; 8]);
+        let mut events = handle.subscribe_id(first).await.unwrap();
+
+        handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let second = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+        assert!(handle.cancel(second).await);
+        assert!(handle.cancel(first).await);
+
+        assert!(matches!(
+            events.next().await.unwrap(),
+            PayloadEvent::Event(Events::Attributes(attr)) if attr.id == first
+        ));
+        assert!(matches!(
+            events.next().await.unwrap(),
+            PayloadEvent::Event(Events::BetterPayload(payload)) if payload.id() == first
+        ));
+        assert!(matches!(
+            events.next().await.unwrap(),
+            PayloadEvent::Event(Events::Cancelled(id)) if id == first
+        ));
+    }
+
+    /// A generator that records the jobs removed by the service.
+    #[derive(Debug, Default)]
+    struct RemovalTrackingPayloadJobGenerator<G> {
+        inner: G,
+        removed: Arc<Mutex<Vec<PayloadId>>>,
+    }
+
+    impl<G: PayloadJobGenerator> PayloadJobGenerator for RemovalTrackingPayloadJobGenerator<G> {
+        type Job = G::Job;
+
+        fn new_payload_job(
+            &self,
+            attr: <Self::Job as PayloadJob>::PayloadAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            self.inner.new_payload_job(attr)
+        }
+
+        fn on_job_removed(&mut self, id: PayloadId) {
+            self.removed.lock().push(id);
+        }
+    }
+
+    #[tokio::test]
+    async fn notify_generator_of_resolved_job() {
+        let generator =
+            RemovalTrackingPayloadJobGenerator::<PreBuiltPayloadJobGenerator>::default();
+        let removed = Arc::clone(&generator.removed);
+        let (service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            generator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        tokio::spawn(service);
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        assert!(removed.lock().is_empty());
+
+        // the job is resolved with `KeepPayloadJobAlive::No`
+        handle.try_resolve_kind(id, PayloadKind::Earliest).await.unwrap();
+        assert!(!handle.contains(id).await);
+        assert_eq!(*removed.lock(), vec![id]);
+    }
+
+    #[tokio::test]
+    async fn notify_generator_of_failed_job() {
+        let generator = RemovalTrackingPayloadJobGenerator::<FailingPayloadJobGenerator>::default();
+        let removed = Arc::clone(&generator.removed);
+        let (service, handle) = PayloadBuilderService::<_, _, EthPayloadTypes>::new(
+            generator,
+            futures_util::stream::empty::<CanonStateNotification>(),
+        );
+        tokio::spawn(service);
+        let mut events = handle.subscribe().await.unwrap().receiver;
+
+        let id = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        assert!(matches!(events.recv().await.unwrap(), Events::Attributes(_)));
+        assert!(matches!(events.recv().await.unwrap(), Events::JobFailed { .. }));
+        assert_eq!(*removed.lock(), vec![id]);
+    }
+
+    #[tokio::test]
+    async fn replay_built_payloads_to_late_subscribers() {
+        let handle = spawn_test_payload_service::<EthPayloadTypes>();
+        let first = handle.send_new_payload(test_attributes(1)).await.unwrap().unwrap();
+        let second = handle.send_new_payload(test_attributes(2)).await.unwrap().unwrap();
+
+        // built before subscribing
+        handle.try_resolve_kind(first, PayloadKind::Earliest).await.unwrap();
+
+        let mut payloads = handle.built_payloads_stream().await.unwrap();
+        handle.try_resolve_kind(second, PayloadKind::Earliest).await.unwrap();
+
+        assert_eq!(payloads.next().await.unwrap().id(), first);
+        assert_eq!(payloads.next().await.unwrap().id(), second);
     }
 }
@@ -2,7 +2,7 @@
 
 use crate::{
     traits::KeepPayloadJobAlive, EthBuiltPayload, EthPayloadBuilderAttributes,
-    PayloadBuilderHandle, PayloadBuilderService, PayloadJob, PayloadJobGenerator,
+    PayloadBuilderHandle, PayloadBuilderService, PayloadJob, PayloadJobGenerator, PayloadJobStats,
 };
 
 use alloy_consensus::Block;
@@ -109,4 +109,8 @@ impl PayloadJob for TestPayloadJob {
         let fut = futures_util::future::ready(self.best_payload());
         (fut, KeepPayloadJobAlive::No)
     }
+
+    fn stats(&self) -> Option<PayloadJobStats> {
+        Some(PayloadJobStats::default())
+    }
 }
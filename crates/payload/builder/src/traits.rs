@@ -1,10 +1,13 @@
 //! Trait abstractions used by the payload crate.
 
+use alloy_consensus::BlockHeader;
+use alloy_primitives::{B256, U256};
+use alloy_rpc_types::engine::PayloadId;
 use reth_chain_state::CanonStateNotification;
 use reth_payload_builder_primitives::PayloadBuilderError;
 use reth_payload_primitives::{BuiltPayload, PayloadBuilderAttributes, PayloadKind};
-use reth_primitives_traits::NodePrimitives;
-use std::future::Future;
+use reth_primitives_traits::{BlockBody, NodePrimitives};
+use std::{fmt, future::Future, time::Duration};
 
 /// A type that can build a payload.
 ///
@@ -16,6 +19,9 @@ use std::future::Future;
 /// is a valid payload to deliver to the CL, so it does not miss a slot, even if the payload is
 /// empty.
 ///
+/// The [`PayloadBuilderService`](crate::PayloadBuilderService) polls every job on its own task and
+/// queries it in between polls.
+///
 /// Note: A `PayloadJob` need to be cancel safe because it might be dropped after the CL has requested the payload via `engine_getPayloadV1` (see also [engine API docs](https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/paris.md#engine_getpayloadv1))
 pub trait PayloadJob: Future<Output = Result<(), PayloadBuilderError>> {
     /// Represents the payload attributes type that is used to spawn this payload job.
@@ -32,6 +38,27 @@ pub trait PayloadJob: Future<Output = Result<(), PayloadBuilderError>> {
     /// Note: This is never called by the CL.
     fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError>;
 
+    /// Returns a summary of the best payload that has been built so far.
+    ///
+    /// The default implementation derives the summary from [`PayloadJob::best_payload`], falling
+    /// back to [`PayloadJob::pre_built_payload`]. Jobs that keep their best payload around should
+    /// override this to avoid cloning it.
+    fn best_payload_summary(&self) -> Result<PayloadSummary, PayloadBuilderError> {
+        let payload = self.best_payload().or_else(|err| self.pre_built_payload().ok_or(err))?;
+        Ok(PayloadSummary::new(&payload))
+    }
+
+    /// Returns the payload that was pre-built when the job was created, if any.
+    ///
+    /// This is typically an empty payload on top of the parent that is built as soon as the job is
+    /// created, so a payload can be delivered even if [`PayloadJob::best_payload`] has no payload
+    /// yet. Returns `None` while it's still being built.
+    ///
+    /// The default implementation returns `None`.
+    fn pre_built_payload(&self) -> Option<Self::BuiltPayload> {
+        None
+    }
+
     /// Returns the payload attributes for the payload being built.
     fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError>;
 
@@ -67,7 +94,8 @@ pub trait PayloadJob: Future<Output = Result<(), PayloadBuilderError>> {
     /// `ResolvePayloadFuture`. [`PayloadKind::Earliest`] should return the earliest available
     /// payload (as fast as possible), e.g. racing an empty payload job against a pending job if
     /// there's no payload available yet. [`PayloadKind::WaitForPending`] is allowed to wait
-    /// until a built payload is available.
+    /// until a built payload is available. [`PayloadKind::WaitForDeadline`] waits until the
+    /// job's deadline is reached, which requires the job to be kept alive until then.
     fn resolve_kind(
         &mut self,
         kind: PayloadKind,
@@ -79,6 +107,68 @@ pub trait PayloadJob: Future<Output = Result<(), PayloadBuilderError>> {
     fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
         self.resolve_kind(PayloadKind::Earliest)
     }
+
+    /// Returns statistics about the build progress of this job.
+    ///
+    /// The default implementation returns `None`, meaning the job does not track any statistics.
+    fn stats(&self) -> Option<PayloadJobStats> {
+        None
+    }
+
+    /// Pushes back the deadline of this job by the given duration, so it keeps building for longer.
+    ///
+    /// Returns `false` if the job does not support extending its deadline or the deadline has
+    /// already been reached.
+    ///
+    /// The default implementation is a no-op.
+    fn extend_deadline(&mut self, extension: Duration) -> bool {
+        let _ = extension;
+        false
+    }
+}
+
+/// Build statistics of a [`PayloadJob`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PayloadJobStats {
+    /// Number of payload build iterations that have completed.
+    pub iterations: u64,
+    /// Fees of the best payload built so far.
+    pub best_fees: U256,
+    /// Gas used by the best payload built so far.
+    pub best_gas_used: u64,
+    /// Time elapsed since the job was created.
+    pub elapsed: Duration,
+    /// Time it took to build the first payload, `None` until a payload has been built.
+    pub time_to_first_build: Option<Duration>,
+}
+
+/// A lightweight summary of a built payload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PayloadSummary {
+    /// Hash of the built block.
+    pub block_hash: B256,
+    /// Number of the built block.
+    pub block_number: u64,
+    /// Fees collected by the built block.
+    pub fees: U256,
+    /// Gas used by the built block.
+    pub gas_used: u64,
+    /// Number of transactions in the built block.
+    pub tx_count: usize,
+}
+
+impl PayloadSummary {
+    /// Creates the summary of the given payload.
+    pub fn new<P: BuiltPayload>(payload: &P) -> Self {
+        let block = payload.block();
+        Self {
+            block_hash: block.hash(),
+            block_number: block.header().number(),
+            fees: payload.fees(),
+            gas_used: block.header().gas_used(),
+            tx_count: block.body().transactions().len(),
+        }
+    }
 }
 
 /// Whether the payload job should be kept alive or terminated after the payload was requested by
@@ -91,6 +181,32 @@ pub enum KeepPayloadJobAlive {
     No,
 }
 
+/// A type that modifies payload attributes before a payload job is created for them.
+///
+/// This can be used to apply a builder policy, e.g. overriding the fee recipient, without changing
+/// the [`PayloadJobGenerator`].
+pub trait PayloadAttributesModifier<Attributes>: Send {
+    /// Returns the modified attributes.
+    ///
+    /// If this returns an error, no payload job is created for the attributes.
+    fn modify(&self, attr: Attributes) -> Result<Attributes, PayloadBuilderError>;
+}
+
+impl<Attributes, F> PayloadAttributesModifier<Attributes> for F
+where
+    F: Fn(Attributes) -> Result<Attributes, PayloadBuilderError> + Send,
+{
+    fn modify(&self, attr: Attributes) -> Result<Attributes, PayloadBuilderError> {
+        self(attr)
+    }
+}
+
+impl<Attributes> fmt::Debug for dyn PayloadAttributesModifier<Attributes> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PayloadAttributesModifier").finish_non_exhaustive()
+    }
+}
+
 /// A type that knows how to create new jobs for creating payloads.
 pub trait PayloadJobGenerator {
     /// The type that manages the lifecycle of a payload.
@@ -118,4 +234,13 @@ pub trait PayloadJobGenerator {
     fn on_new_state<N: NodePrimitives>(&mut self, new_state: CanonStateNotification<N>) {
         let _ = new_state;
     }
+
+    /// Invoked after the service removed the job with the given [`PayloadId`].
+    ///
+    /// This is called whenever a job is dropped by the service, e.g. because it was resolved,
+    /// failed, cancelled or replaced, and can be used to clean up any per-job state kept by the
+    /// generator.
+    fn on_job_removed(&mut self, id: PayloadId) {
+        let _ = id;
+    }
 }
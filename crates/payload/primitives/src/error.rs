@@ -2,7 +2,7 @@
 
 use alloc::{boxed::Box, string::ToString};
 use alloy_primitives::B256;
-use alloy_rpc_types_engine::{ForkchoiceUpdateError, PayloadError, PayloadStatusEnum};
+use alloy_rpc_types_engine::{ForkchoiceUpdateError, PayloadError, PayloadId, PayloadStatusEnum};
 use core::error;
 use reth_errors::{BlockExecutionError, ProviderError, RethError};
 use tokio::sync::oneshot;
@@ -22,6 +22,16 @@ pub enum PayloadBuilderError {
     /// If there's no payload to resolve.
     #[error("missing payload")]
     MissingPayload,
+    /// Thrown when the maximum number of active payload jobs has been reached.
+    #[error("too many active payload jobs")]
+    TooManyJobs,
+    /// Thrown when a payload job with the same id but different attributes is already active.
+    #[error("payload job {0} already exists with different attributes")]
+    DuplicatePayload(PayloadId),
+    /// Thrown when a new payload job is requested while the payload builder service is shutting
+    /// down.
+    #[error("payload builder service is shutting down")]
+    ShuttingDown,
     /// Other internal error
     #[error(transparent)]
     Internal(#[from] RethError),
@@ -462,6 +462,13 @@ pub enum PayloadKind {
     /// already in progress one, and returns the best available built payload or awaits the job in
     /// progress.
     WaitForPending,
+    /// Waits until the deadline of the payload job is reached and returns the best payload built
+    /// until then.
+    ///
+    /// This gives the job as much time as possible to improve the payload, at the cost of delaying
+    /// the response. If the deadline has already elapsed, this behaves like
+    /// [`PayloadKind::Earliest`].
+    WaitForDeadline,
 }
 
 /// Validates that execution requests are valid according to Engine API specification.
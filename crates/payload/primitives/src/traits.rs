@@ -77,6 +77,14 @@ pub trait BuiltPayload: Send + Sync + fmt::Debug {
     /// The node's primitive types
     type Primitives: NodePrimitives;
 
+    /// Returns the identifier of the payload job that built this payload, if known.
+    ///
+    /// The default implementation returns `None`, so the payload isn't matched by streams that
+    /// filter payload events by their payload id.
+    fn payload_id(&self) -> Option<PayloadId> {
+        None
+    }
+
     /// Returns the built block in its sealed (hash-verified) form.
     fn block(&self) -> &SealedBlock<<Self::Primitives as NodePrimitives>::Block>;
 
@@ -1,6 +1,8 @@
 use alloy_rpc_types_admin::{NodeInfo, PeerInfo};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_network_api::DiscoveryTableEntry;
 use reth_network_peers::{AnyNode, NodeRecord};
+use std::net::IpAddr;
 
 /// Admin namespace rpc interface that gives access to several non-standard RPC methods.
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
@@ -27,6 +29,28 @@ pub trait AdminApi {
     #[method(name = "removeTrustedPeer")]
     fn remove_trusted_peer(&self, record: AnyNode) -> RpcResult<bool>;
 
+    /// Toggles whether only trusted peers are connected to or accepted.
+    ///
+    /// Enabling this disconnects all sessions with non-trusted peers.
+    ///
+    /// Returns true if the mode was successfully changed.
+    #[method(name = "setTrustedOnly")]
+    fn set_trusted_only(&self, trusted_only: bool) -> RpcResult<bool>;
+
+    /// Bans the given IP, indefinitely or for the given number of seconds, and disconnects all
+    /// sessions from it. Durations that are too large to represent are treated as an indefinite
+    /// ban.
+    ///
+    /// Returns true if the IP was successfully banned.
+    #[method(name = "banIp")]
+    fn ban_ip(&self, ip: IpAddr, duration_secs: Option<u64>) -> RpcResult<bool>;
+
+    /// Lifts the ban of the given IP.
+    ///
+    /// Returns true if the IP was successfully unbanned.
+    #[method(name = "unbanIp")]
+    fn unban_ip(&self, ip: IpAddr) -> RpcResult<bool>;
+
     /// The peers administrative property can be queried for all the information known about the
     /// connected remote nodes at the networking granularity. These include general information
     /// about the nodes themselves as participants of the devp2p P2P overlay protocol, as well as
@@ -46,6 +70,13 @@ pub trait AdminApi {
     #[method(name = "nodeInfo")]
     async fn node_info(&self) -> RpcResult<NodeInfo>;
 
+    /// Returns a snapshot of the nodes known to the discovery services of the node.
+    ///
+    /// This includes the discv4 and discv5 routing tables and the nodes resolved via DNS discovery,
+    /// and is intended for debugging peer discovery.
+    #[method(name = "discoveryTable")]
+    async fn discovery_table(&self) -> RpcResult<Vec<DiscoveryTableEntry>>;
+
     /// Clears all transactions from the transaction pool.
     /// Returns the number of transactions that were removed from the pool.
     #[method(name = "clearTxpool")]
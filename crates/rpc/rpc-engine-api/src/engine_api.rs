@@ -349,12 +349,7 @@ where
         &self,
         payload_id: PayloadId,
     ) -> EngineApiResult<EngineT::BuiltPayload> {
-        self.inner
-            .payload_store
-            .resolve(payload_id)
-            .await
-            .ok_or(EngineApiError::UnknownPayload)?
-            .map_err(|_| EngineApiError::UnknownPayload)
+        self.inner.payload_store.try_resolve(payload_id).await.map_err(Into::into)
     }
 
     /// Helper function for validating the payload timestamp and retrieving & converting the payload
@@ -1327,18 +1322,18 @@ mod tests {
 
         async fn network_status(&self) -> Result<NetworkStatus, NetworkError> {
             #[allow(deprecated)]
-            Ok(NetworkStatus {
-                client_version: "test".to_string(),
-                protocol_version: 5,
-                eth_protocol_info: EthProtocolInfo {
+            Ok(NetworkStatus::new(
+                "test".to_string(),
+                5,
+                EthProtocolInfo {
                     network: 1,
                     difficulty: None,
                     genesis: Default::default(),
                     config: Default::default(),
                     head: Default::default(),
                 },
-                capabilities: vec![],
-            })
+                vec![],
+            ))
         }
 
         fn chain_id(&self) -> u64 {
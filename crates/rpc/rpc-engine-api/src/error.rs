@@ -7,6 +7,7 @@ use jsonrpsee_types::error::{
     INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE, INVALID_PARAMS_MSG, SERVER_ERROR_MSG,
 };
 use reth_engine_primitives::{BeaconForkChoiceUpdateError, BeaconOnNewPayloadError};
+use reth_payload_builder::PayloadResolveError;
 use reth_payload_builder_primitives::PayloadBuilderError;
 use reth_payload_primitives::EngineObjectValidationError;
 use thiserror::Error;
@@ -39,6 +40,15 @@ pub enum EngineApiError {
     /// Payload does not exist / is not available.
     #[error("Unknown payload")]
     UnknownPayload,
+    /// Payload has already been resolved and is no longer available.
+    #[error("Unknown payload")]
+    PayloadAlreadyResolved,
+    /// The payload job failed to deliver a payload.
+    ///
+    /// Reported as an unknown payload, since there's no payload the CL could use, with the
+    /// builder error as error data.
+    #[error("Unknown payload")]
+    PayloadBuildFailed(PayloadBuilderError),
     /// The payload body request length is too large.
     #[error("requested count too large: {len}")]
     PayloadRequestTooLarge {
@@ -93,6 +103,16 @@ pub enum EngineApiError {
     Other(jsonrpsee_types::ErrorObject<'static>),
 }
 
+impl From<PayloadResolveError> for EngineApiError {
+    fn from(err: PayloadResolveError) -> Self {
+        match err {
+            PayloadResolveError::UnknownPayloadId => Self::UnknownPayload,
+            PayloadResolveError::JobAlreadyResolved { .. } => Self::PayloadAlreadyResolved,
+            PayloadResolveError::Builder(err) => Self::PayloadBuildFailed(err),
+        }
+    }
+}
+
 impl EngineApiError {
     /// Crates a new [`EngineApiError::Other`] variant.
     pub const fn other(err: jsonrpsee_types::ErrorObject<'static>) -> Self {
@@ -147,6 +167,18 @@ impl From<EngineApiError> for jsonrpsee_types::error::ErrorObject<'static> {
                 error.to_string(),
                 None::<()>,
             ),
+            EngineApiError::PayloadAlreadyResolved => jsonrpsee_types::error::ErrorObject::owned(
+                UNKNOWN_PAYLOAD_CODE,
+                error.to_string(),
+                Some(ErrorData::new("payload already resolved")),
+            ),
+            EngineApiError::PayloadBuildFailed(ref err) => {
+                jsonrpsee_types::error::ErrorObject::owned(
+                    UNKNOWN_PAYLOAD_CODE,
+                    error.to_string(),
+                    Some(ErrorData::new(err)),
+                )
+            }
             EngineApiError::PayloadRequestTooLarge { .. } |
             EngineApiError::BlobRequestTooLarge { .. } => {
                 jsonrpsee_types::error::ErrorObject::owned(
@@ -259,5 +291,39 @@ mod tests {
             "Unknown payload",
             EngineApiError::UnknownPayload,
         );
+
+        ensure_engine_rpc_error(
+            UNKNOWN_PAYLOAD_CODE,
+            "Unknown payload",
+            EngineApiError::PayloadAlreadyResolved,
+        );
+    }
+
+    #[test]
+    fn payload_resolve_error_to_engine_error() {
+        assert!(matches!(
+            EngineApiError::from(PayloadResolveError::UnknownPayloadId),
+            EngineApiError::UnknownPayload
+        ));
+        assert!(matches!(
+            EngineApiError::from(PayloadResolveError::JobAlreadyResolved {
+                resolved_at: tokio::time::Instant::now()
+            }),
+            EngineApiError::PayloadAlreadyResolved
+        ));
+
+        let err =
+            EngineApiError::from(PayloadResolveError::Builder(PayloadBuilderError::MissingPayload));
+        assert!(matches!(
+            err,
+            EngineApiError::PayloadBuildFailed(PayloadBuilderError::MissingPayload)
+        ));
+        let err = jsonrpsee_types::error::ErrorObject::from(err);
+        assert_eq!(err.code(), UNKNOWN_PAYLOAD_CODE);
+        assert_eq!(err.message(), "Unknown payload");
+        assert_eq!(
+            err.data().unwrap().get(),
+            format!(r#"{{"err":"{}"}}"#, PayloadBuilderError::MissingPayload)
+        );
     }
 }
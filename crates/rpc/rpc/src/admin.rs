@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{net::IpAddr, sync::Arc, time::Duration};
 
 use alloy_genesis::ChainConfig;
 use alloy_rpc_types_admin::{
@@ -8,7 +8,7 @@ use alloy_rpc_types_admin::{
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use reth_chainspec::{EthChainSpec, EthereumHardfork, EthereumHardforks, ForkCondition};
-use reth_network_api::{NetworkInfo, Peers};
+use reth_network_api::{DiscoveryTableEntry, NetworkInfo, Peers};
 use reth_network_peers::{id2pk, AnyNode, NodeRecord};
 use reth_network_types::PeerKind;
 use reth_rpc_api::AdminApiServer;
@@ -69,6 +69,24 @@ where
         Ok(true)
     }
 
+    /// Handler for `admin_setTrustedOnly`
+    fn set_trusted_only(&self, trusted_only: bool) -> RpcResult<bool> {
+        self.network.set_trusted_only(trusted_only);
+        Ok(true)
+    }
+
+    /// Handler for `admin_banIp`
+    fn ban_ip(&self, ip: IpAddr, duration_secs: Option<u64>) -> RpcResult<bool> {
+        self.network.ban_ip(ip, duration_secs.map(Duration::from_secs));
+        Ok(true)
+    }
+
+    /// Handler for `admin_unbanIp`
+    fn unban_ip(&self, ip: IpAddr) -> RpcResult<bool> {
+        self.network.unban_ip(ip);
+        Ok(true)
+    }
+
     /// Handler for `admin_peers`
     async fn peers(&self) -> RpcResult<Vec<PeerInfo>> {
         let peers = self.network.get_all_peers().await.to_rpc_result()?;
@@ -89,7 +107,7 @@ where
                     static_node: peer.kind.is_static(),
                 },
                 protocols: PeerProtocolInfo {
-                    eth: Some(EthPeerInfo::Info(EthInfo { version: peer.status.version as u64 })),
+                    eth: Some(EthPeerInfo::Info(EthInfo { version: peer.eth_version as u64 })),
                     snap: None,
                     other: Default::default(),
                 },
@@ -178,6 +196,11 @@ where
         })
     }
 
+    /// Handler for `admin_discoveryTable`
+    async fn discovery_table(&self) -> RpcResult<Vec<DiscoveryTableEntry>> {
+        self.network.discovery_nodes().await.to_rpc_result()
+    }
+
     /// Handler for `admin_peerEvents`
     async fn subscribe_peer_events(
         &self,
@@ -81,18 +81,18 @@ mod tests {
 
         async fn network_status(&self) -> Result<NetworkStatus, NetworkError> {
             #[allow(deprecated)]
-            Ok(NetworkStatus {
-                client_version: "test".to_string(),
-                protocol_version: 5,
-                eth_protocol_info: EthProtocolInfo {
+            Ok(NetworkStatus::new(
+                "test".to_string(),
+                5,
+                EthProtocolInfo {
                     network: 1,
                     difficulty: None,
                     genesis: Default::default(),
                     config: Default::default(),
                     head: Default::default(),
                 },
-                capabilities: vec![],
-            })
+                vec![],
+            ))
         }
 
         fn chain_id(&self) -> u64 {
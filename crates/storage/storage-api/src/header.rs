@@ -1,6 +1,7 @@
 use alloc::vec::Vec;
 use alloy_eips::BlockHashOrNumber;
 use alloy_primitives::{BlockHash, BlockNumber};
+use alloy_rlp::Encodable;
 use core::ops::RangeBounds;
 use reth_primitives_traits::{BlockHeader, SealedHeader};
 use reth_storage_errors::provider::ProviderResult;
@@ -50,6 +51,24 @@ pub trait HeaderProvider: Send {
         range: impl RangeBounds<BlockNumber>,
     ) -> ProviderResult<Vec<Self::Header>>;
 
+    /// Get headers in range of block numbers until their total encoded size exceeds `max_bytes`.
+    ///
+    /// The header that exceeds `max_bytes` is included, so at least one header is returned if the
+    /// range isn't empty.
+    fn headers_range_with_limit(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        max_bytes: usize,
+    ) -> ProviderResult<Vec<Self::Header>> {
+        let mut total_bytes = 0;
+        let headers = self.sealed_headers_while(range, |header| {
+            let within_limit = total_bytes <= max_bytes;
+            total_bytes += header.header().length();
+            within_limit
+        })?;
+        Ok(headers.into_iter().map(SealedHeader::unseal).collect())
+    }
+
     /// Get a single sealed header by block number.
     fn sealed_header(
         &self,
@@ -178,6 +178,10 @@ pub struct CustomBuiltPayload(pub OpBuiltPayload<CustomNodePrimitives>);
 impl BuiltPayload for CustomBuiltPayload {
     type Primitives = CustomNodePrimitives;
 
+    fn payload_id(&self) -> Option<alloy_rpc_types_engine::PayloadId> {
+        self.0.payload_id()
+    }
+
     fn block(&self) -> &SealedBlock<<Self::Primitives as NodePrimitives>::Block> {
         self.0.block()
     }
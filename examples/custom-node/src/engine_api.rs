@@ -107,9 +107,8 @@ impl CustomEngineApiServer for CustomEngineApi {
         Ok(self
             .inner
             .payload_store
-            .resolve(payload_id)
+            .try_resolve(payload_id)
             .await
-            .ok_or(EngineApiError::UnknownPayload)?
             .map_err(|_| EngineApiError::UnknownPayload)?
             .into())
     }